@@ -0,0 +1,283 @@
+use std::{fs, path::Path, process::ExitCode};
+
+use anyhow::{anyhow, bail, Context, Result};
+use common::{types::Rect, Rgba};
+use database::{LayerId, SceneUserContext, SvgExportOption};
+
+const USAGE: &str = "Usage: mvg <command> [options]
+
+Commands:
+  render <input> <output.png>  Render the document to a PNG image
+  convert <input> <output>     Convert between .mvg and .svg, by the file extensions
+  info <input>                 List the layers, the shape and coord counts and the bounds
+  validate <input>             Check the invariants of the paths
+
+Options:
+  --width <px>          Width of the PNG or SVG output, default to the document width
+  --height <px>         Height of the PNG or SVG output, default to the document height
+  --background <hex>    Color under the document in the PNG, default to transparent";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Mvg,
+    Svg,
+    Png,
+}
+
+impl Format {
+    fn of(path: &str) -> Result<Format> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("mvg") => Ok(Format::Mvg),
+            Some("svg") => Ok(Format::Svg),
+            Some("png") => Ok(Format::Png),
+            _ => bail!("{}: unknown format, expected .mvg, .svg or .png", path),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    command: String,
+    files: Vec<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    background: Rgba,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args> {
+    let command = args.next().ok_or(anyhow!("Missing command"))?;
+    let mut parsed = Args {
+        command,
+        files: Vec::new(),
+        width: None,
+        height: None,
+        background: Rgba::transparent(),
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or(anyhow!("Missing value for {}", name));
+        match arg.as_str() {
+            "--width" => parsed.width = Some(value("--width")?.parse().context("--width")?),
+            "--height" => parsed.height = Some(value("--height")?.parse().context("--height")?),
+            "--background" => {
+                parsed.background =
+                    Rgba::from_small_hex_safe(&value("--background")?).map_err(|e| anyhow!(e))?
+            }
+            option if option.starts_with("--") => bail!("Unknown option {}", option),
+            _ => parsed.files.push(arg),
+        }
+    }
+    Ok(parsed)
+}
+
+fn load(path: &str) -> Result<SceneUserContext> {
+    match Format::of(path)? {
+        Format::Mvg => {
+            let data = fs::read(path).with_context(|| format!("Can't read {}", path))?;
+            SceneUserContext::load(data).with_context(|| format!("Can't load {}", path))
+        }
+        Format::Svg => {
+            let text = fs::read_to_string(path).with_context(|| format!("Can't read {}", path))?;
+            let (context, warnings) = SceneUserContext::from_svg(&text)
+                .with_context(|| format!("Can't load {}", path))?;
+            for warning in warnings {
+                eprintln!("warning: {}: {}", path, warning);
+            }
+            Ok(context)
+        }
+        Format::Png => bail!("{}: PNG images can't be loaded", path),
+    }
+}
+
+/// Size of the output, the missing dimension follows the aspect ratio of the document.
+/// The PNG renderer also limits the number of pixels
+fn output_size(context: &SceneUserContext, args: &Args) -> Result<(u32, u32)> {
+    let base = context.camera.get_base_scale();
+    let (width, height) = match (args.width, args.height) {
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, (width as f32 * base.y / base.x).round() as u32),
+        (None, Some(height)) => ((height as f32 * base.x / base.y).round() as u32, height),
+        (None, None) => (base.x.round() as u32, base.y.round() as u32),
+    };
+    if width == 0 || height == 0 {
+        bail!("Invalid output size {}x{}", width, height);
+    }
+    Ok((width, height))
+}
+
+fn write(context: &SceneUserContext, args: &Args, path: &str, format: Format) -> Result<()> {
+    let (width, height) = output_size(context, args)?;
+    let data = match format {
+        Format::Mvg => context.save()?,
+        Format::Svg => context
+            .scene()
+            .to_svg(&SvgExportOption::new(width as f32, height as f32))
+            .into_bytes(),
+        Format::Png => context
+            .scene()
+            .render_png(width, height, args.background.clone())
+            .map_err(|e| anyhow!(e))?,
+    };
+    fs::write(path, data).with_context(|| format!("Can't write {}", path))
+}
+
+fn files<const N: usize>(args: &Args) -> Result<[&str; N]> {
+    let files: Vec<&str> = args.files.iter().map(|f| f.as_str()).collect();
+    files
+        .try_into()
+        .map_err(|_| anyhow!("{} expects {} file(s)\n\n{}", args.command, N, USAGE))
+}
+
+fn info(context: &SceneUserContext) {
+    let scene = context.scene();
+    let base = context.camera.get_base_scale();
+    let tree = scene.get_tree_view();
+    println!("size: {} x {}", base.x, base.y);
+    println!("layers: {}", tree.len());
+
+    let mut shapes = Vec::new();
+    for layer in &tree {
+        let id = LayerId::from(layer.layer_id);
+        let indent = "  ".repeat(layer.level + 1);
+        match scene.shape_select(id) {
+            Some(shape) => {
+                let coords: usize = shape.paths().map(|p| p.len()).sum();
+                let bounds = match shape.bounding_box() {
+                    Some(rect) => rect_string(&rect),
+                    None => "empty".to_string(),
+                };
+                println!(
+                    "{}{} {} \"{}\": {} paths, {} curves, {} coords, bounds {}",
+                    indent,
+                    layer.layer_id,
+                    layer.layer_type,
+                    layer.name,
+                    shape.paths().count(),
+                    shape.curves_len(),
+                    coords,
+                    bounds
+                );
+                shapes.push(id);
+            }
+            None => println!(
+                "{}{} {} \"{}\"",
+                indent, layer.layer_id, layer.layer_type, layer.name
+            ),
+        }
+    }
+
+    let coords: usize = shapes
+        .iter()
+        .filter_map(|id| scene.shape_select(*id))
+        .map(|shape| shape.paths().map(|p| p.len()).sum::<usize>())
+        .sum();
+    println!("shapes: {}, coords: {}", shapes.len(), coords);
+    if let Some(rect) = scene.shapes_bounding_box(&shapes) {
+        println!("bounds: {}", rect_string(&rect));
+    }
+}
+
+fn rect_string(rect: &Rect) -> String {
+    format!(
+        "({}, {}) to ({}, {})",
+        rect.top_left.x, rect.top_left.y, rect.bottom_right.x, rect.bottom_right.y
+    )
+}
+
+fn run(args: Args) -> Result<ExitCode> {
+    match args.command.as_str() {
+        "render" => {
+            let [input, output] = files(&args)?;
+            write(&load(input)?, &args, output, Format::Png)?;
+        }
+        "convert" => {
+            let [input, output] = files(&args)?;
+            write(&load(input)?, &args, output, Format::of(output)?)?;
+        }
+        "info" => {
+            let [input] = files(&args)?;
+            info(&load(input)?);
+        }
+        "validate" => {
+            let [input] = files(&args)?;
+            let issues = load(input)?.scene().validate();
+            for issue in &issues {
+                println!("{}", issue);
+            }
+            if !issues.is_empty() {
+                println!("{}: {} issue(s)", input, issues.len());
+                return Ok(ExitCode::FAILURE);
+            }
+            println!("{}: valid", input);
+        }
+        "help" | "--help" | "-h" => println!("{}", USAGE),
+        command => bail!("Unknown command {}\n\n{}", command, USAGE),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    match parse_args(std::env::args().skip(1)).and_then(run) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::Rgba;
+    use database::SceneUserContext;
+
+    use super::{parse_args, write, Format};
+
+    fn args(text: &str) -> impl Iterator<Item = String> + '_ {
+        text.split_whitespace().map(String::from)
+    }
+
+    #[test]
+    fn given_render_args_when_parse_then_files_and_options() {
+        let parsed =
+            parse_args(args("render a.mvg a.png --width 64 --background #ff0000")).unwrap();
+
+        assert_eq!(parsed.command, "render");
+        assert_eq!(parsed.files, vec!["a.mvg", "a.png"]);
+        assert_eq!(parsed.width, Some(64));
+        assert_eq!(parsed.height, None);
+        assert_eq!(parsed.background, Rgba::red());
+    }
+
+    #[test]
+    fn given_bad_options_when_parse_then_error() {
+        assert!(parse_args(args("render a.mvg --width")).is_err());
+        assert!(parse_args(args("render a.mvg --depth 2")).is_err());
+        assert!(parse_args(args("")).is_err());
+    }
+
+    #[test]
+    fn given_paths_when_format_then_from_extension() {
+        assert_eq!(Format::of("a/b.MVG").unwrap(), Format::Mvg);
+        assert_eq!(Format::of("b.svg").unwrap(), Format::Svg);
+        assert!(Format::of("b.txt").is_err());
+    }
+
+    #[test]
+    fn given_oversized_or_empty_size_when_render_then_error_without_output() {
+        let context = SceneUserContext::default();
+        let path = std::env::temp_dir().join("mvg-oversized-test.png");
+        let path = path.to_str().unwrap();
+
+        for size in ["--width 100000 --height 100000", "--width 0"] {
+            let parsed = parse_args(args(&format!("render a.mvg a.png {}", size))).unwrap();
+            let error = write(&context, &parsed, path, Format::Png).unwrap_err();
+            assert!(error.to_string().contains("size"));
+        }
+        assert!(!std::path::Path::new(path).exists());
+    }
+}
@@ -86,6 +86,11 @@ impl Affine {
         dist
     }
 
+    /// Factor by which the transformation scales areas, negative if it flips
+    pub fn determinant(&self) -> f32 {
+        self.m00 * self.m11 - self.m10 * self.m01
+    }
+
     pub fn get_translation(&self) -> Coord {
         Coord::new(self.m02, self.m12)
     }
@@ -244,6 +249,15 @@ mod test {
         assert_eq!(mat * inv, Affine::identity());
     }
 
+    #[test]
+    fn test_determinant() {
+        let mat = Affine::from_scale(Coord::new(2.0, 3.0))
+            .rotate(1.0)
+            .reflect_x();
+
+        assert_approx_eq!(f32, mat.determinant(), -6.0);
+    }
+
     #[test]
     fn test_rotation() {
         let mat = Affine::from_rotation(std::f32::consts::PI / 2.0);
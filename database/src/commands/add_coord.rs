@@ -35,7 +35,8 @@ impl Command for AddCoord {
         self.cp_to_undo = Some((changed_curve.cp0.clone(), changed_curve.cp1.clone()));
 
         let coord_ids = shape.curve_insert_smooth(self.curve, self.t);
-        self.coord_to_undo = Some(coord_ids.1);
+        let adopted = scene.shape_coords_adopt(self.shapes);
+        self.coord_to_undo = Some(adopted.get(&coord_ids.1).copied().unwrap_or(coord_ids.1));
         Ok(())
     }
 
@@ -79,8 +80,8 @@ mod test {
     fn given_square_when_add_coord_in_path() {
         let mut scene = Scene::new();
         let shape = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.5, 0.5));
-        let expected = shape.path.clone();
         let shape_id = scene.shape_insert(shape);
+        let expected = scene.shape_select(shape_id).unwrap().path.clone();
 
         let mut command = AddCoord::new(shape_id, 0, 0.3);
         command.execute(&mut scene).unwrap();
@@ -107,8 +108,8 @@ mod test {
             ],
             Affine::identity(),
         );
-        let expected = shape.path.clone();
         let shape_id = scene.shape_insert(shape);
+        let expected = scene.shape_select(shape_id).unwrap().path.clone();
 
         let mut command = AddCoord::new(shape_id, 0, 0.3);
         command.execute(&mut scene).unwrap();
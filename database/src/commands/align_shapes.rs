@@ -0,0 +1,389 @@
+use super::Command;
+use crate::{LayerId, Scene, Shape};
+use anyhow::{Context, Result};
+use common::{
+    pures::Affine,
+    types::{Coord, Rect},
+};
+use macros::boxed;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use tsify::Tsify;
+
+/// How the bounding boxes of the shapes are lined up
+#[derive(Tsify, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum Alignment {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    /// Centers on the same vertical line
+    CenterX,
+    /// Centers on the same horizontal line
+    CenterY,
+    /// Same distance between the centers from left to right, the outer shapes stay in place
+    DistributeCentersX,
+    /// Same distance between the centers from top to bottom, the outer shapes stay in place
+    DistributeCentersY,
+    /// Same gap between the shapes from left to right, the outer shapes stay in place
+    DistributeGapsX,
+    /// Same gap between the shapes from top to bottom, the outer shapes stay in place
+    DistributeGapsY,
+}
+
+/// Bounds the shapes are aligned to, the distributions are always in the selection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlignTarget {
+    /// Bounds around all the shapes
+    Selection,
+    /// Bounds of one of the layers, it stays in place with the layer containing it
+    Key(LayerId),
+    /// Rectangle of the scene
+    Scene,
+}
+
+/// Move the layers to line up their bounding boxes, see `Alignment`.
+/// The shapes of a folder move together, the bounds of a folder are around all its shapes
+#[derive(Clone, Debug)]
+pub struct AlignShapes {
+    layers: Vec<LayerId>,
+    alignment: Alignment,
+    target: AlignTarget,
+    shapes_to_undo: Vec<Shape>,
+}
+
+impl AlignShapes {
+    #[boxed]
+    pub fn new(layers: Vec<LayerId>, alignment: Alignment, target: AlignTarget) -> Self {
+        Self {
+            layers,
+            alignment,
+            target,
+            shapes_to_undo: Vec::new(),
+        }
+    }
+
+    /// Movement of each layer with bounds
+    fn movements(&self, scene: &Scene) -> Result<Vec<(LayerId, Coord)>> {
+        let mut boxes = Vec::with_capacity(self.layers.len());
+        for id in &self.layers {
+            if let Some(bounds) = layer_bounds(scene, *id)? {
+                boxes.push((*id, bounds));
+            }
+        }
+
+        let target = match self.target {
+            AlignTarget::Selection => boxes
+                .iter()
+                .map(|(_, b)| *b)
+                .reduce(|a, b| Rect::max(&a, &b)),
+            AlignTarget::Key(key) => layer_bounds(scene, key)?,
+            AlignTarget::Scene => Some(scene.max_rect()),
+        };
+        let Some(target) = target else {
+            return Ok(Vec::new());
+        };
+
+        let align = |offset: fn(&Rect, &Rect) -> Coord| -> Vec<(LayerId, Coord)> {
+            boxes
+                .iter()
+                .map(|(id, bounds)| (*id, offset(bounds, &target)))
+                .collect()
+        };
+        let movements = match self.alignment {
+            Alignment::Left => align(|b, t| Coord::new(t.top_left.x - b.top_left.x, 0.0)),
+            Alignment::Right => align(|b, t| Coord::new(t.bottom_right.x - b.bottom_right.x, 0.0)),
+            Alignment::Top => align(|b, t| Coord::new(0.0, t.top_left.y - b.top_left.y)),
+            Alignment::Bottom => align(|b, t| Coord::new(0.0, t.bottom_right.y - b.bottom_right.y)),
+            Alignment::CenterX => align(|b, t| Coord::new(t.center().x - b.center().x, 0.0)),
+            Alignment::CenterY => align(|b, t| Coord::new(0.0, t.center().y - b.center().y)),
+            Alignment::DistributeCentersX => distribute(&boxes, Axis::X, false),
+            Alignment::DistributeCentersY => distribute(&boxes, Axis::Y, false),
+            Alignment::DistributeGapsX => distribute(&boxes, Axis::X, true),
+            Alignment::DistributeGapsY => distribute(&boxes, Axis::Y, true),
+        };
+        Ok(movements)
+    }
+}
+
+impl Command for AlignShapes {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        let movements = self.movements(scene)?;
+        let mut shapes_to_undo = Vec::with_capacity(movements.len());
+        for (id, movement) in movements {
+            let shapes = scene.layer_shapes(id);
+            let is_key = match self.target {
+                AlignTarget::Key(key) => key == id || shapes.contains(&key),
+                _ => false,
+            };
+            if is_key || (movement.x == 0.0 && movement.y == 0.0) {
+                continue;
+            }
+            for shape_id in shapes {
+                let shape = scene
+                    .shape_select_mut(shape_id)
+                    .context("Shape not found")?;
+                shapes_to_undo.push(shape.clone());
+                shape.transform(&Affine::from_translate(movement));
+            }
+        }
+        self.shapes_to_undo = shapes_to_undo;
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        for old in self.shapes_to_undo.drain(..) {
+            let shape = scene.shape_select_mut(old.id).context("Shape not found")?;
+            shape.stroke = old.stroke.clone();
+            shape.fill = old.fill.clone();
+            shape.paths_replace(old);
+        }
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Bounds around the shapes of the layer, None for a layer without curves
+fn layer_bounds(scene: &Scene, id: LayerId) -> Result<Option<Rect>> {
+    scene.layer_select(id).context("Layer not found")?;
+    Ok(scene
+        .layer_shapes(id)
+        .iter()
+        .filter_map(|shape| scene.shape_select(*shape)?.bounding_box())
+        .reduce(|a, b| Rect::max(&a, &b)))
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+
+impl Axis {
+    /// Start, end and center of the bounds on the axis
+    fn span(&self, bounds: &Rect) -> (f32, f32, f32) {
+        match self {
+            Axis::X => (bounds.top_left.x, bounds.bottom_right.x, bounds.center().x),
+            Axis::Y => (bounds.top_left.y, bounds.bottom_right.y, bounds.center().y),
+        }
+    }
+
+    fn movement(&self, length: f32) -> Coord {
+        match self {
+            Axis::X => Coord::new(length, 0.0),
+            Axis::Y => Coord::new(0.0, length),
+        }
+    }
+}
+
+/// Space the centers or the gaps evenly between the first and the last shape on the axis
+fn distribute(boxes: &[(LayerId, Rect)], axis: Axis, gaps: bool) -> Vec<(LayerId, Coord)> {
+    if boxes.len() < 3 {
+        return Vec::new();
+    }
+    let mut boxes = boxes.to_vec();
+    boxes.sort_by(|(_, a), (_, b)| {
+        let key = |bounds: &Rect| match gaps {
+            true => axis.span(bounds).0,
+            false => axis.span(bounds).2,
+        };
+        key(a).total_cmp(&key(b))
+    });
+
+    let (first_start, _, first_center) = axis.span(&boxes[0].1);
+    let (_, last_end, last_center) = axis.span(&boxes[boxes.len() - 1].1);
+    let steps = (boxes.len() - 1) as f32;
+    let mut movements = Vec::with_capacity(boxes.len());
+    if gaps {
+        let lengths: f32 = boxes
+            .iter()
+            .map(|(_, b)| axis.span(b).1 - axis.span(b).0)
+            .sum();
+        let gap = (last_end - first_start - lengths) / steps;
+        let mut start = first_start;
+        for (id, bounds) in &boxes {
+            let (current, end, _) = axis.span(bounds);
+            movements.push((*id, axis.movement(start - current)));
+            start += end - current + gap;
+        }
+    } else {
+        let step = (last_center - first_center) / steps;
+        for (index, (id, bounds)) in boxes.iter().enumerate() {
+            let center = first_center + step * index as f32;
+            movements.push((*id, axis.movement(center - axis.span(bounds).2)));
+        }
+    }
+    movements
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::Affine,
+        types::{Coord, Rect},
+    };
+
+    use crate::{
+        commands::{CommandsHandler, Group},
+        user_context::user_selection::SelectedShape,
+        DbCoord, LayerId, Scene, SceneUserContext, Shape, UserSelection,
+    };
+
+    use super::{AlignShapes, AlignTarget, Alignment};
+
+    fn rect(scene: &mut Scene, x: f32, y: f32, width: f32, height: f32) -> LayerId {
+        scene.shape_insert(Shape::new_from_lines(
+            vec![
+                DbCoord::new(x, y),
+                DbCoord::new(x + width, y),
+                DbCoord::new(x + width, y + height),
+                DbCoord::new(x, y + height),
+            ],
+            Affine::identity(),
+        ))
+    }
+
+    fn bounds(handler: &CommandsHandler, id: LayerId) -> Rect {
+        handler
+            .scene()
+            .shape_select(id)
+            .unwrap()
+            .bounding_box()
+            .unwrap()
+    }
+
+    fn assert_near(a: Coord, b: Coord) {
+        assert!(
+            (a.x - b.x).abs() < 1e-5 && (a.y - b.y).abs() < 1e-5,
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn given_shapes_when_align_left_then_on_selection_bounds_and_undo() {
+        let mut scene = Scene::new();
+        let ids = vec![
+            rect(&mut scene, 0.1, 0.0, 0.2, 0.2),
+            rect(&mut scene, -0.3, 0.5, 0.1, 0.1),
+            rect(&mut scene, 0.4, -0.6, 0.3, 0.1),
+        ];
+        let expected = scene.clone();
+        let mut handler = CommandsHandler::from(scene);
+
+        handler
+            .execute(AlignShapes::boxed(
+                ids.clone(),
+                Alignment::Left,
+                AlignTarget::Selection,
+            ))
+            .unwrap();
+
+        assert_near(bounds(&handler, ids[0]).top_left, Coord::new(-0.3, 0.0));
+        assert_near(bounds(&handler, ids[1]).top_left, Coord::new(-0.3, 0.5));
+        assert_near(bounds(&handler, ids[2]).top_left, Coord::new(-0.3, -0.6));
+
+        handler.undo().unwrap();
+        assert_eq!(*handler.scene(), expected);
+    }
+
+    #[test]
+    fn given_key_or_scene_when_align_then_key_stays_and_shapes_on_target() {
+        let mut scene = Scene::new();
+        let key = rect(&mut scene, 0.1, 0.1, 0.2, 0.2);
+        let other = rect(&mut scene, -0.5, 0.5, 0.4, 0.4);
+        let mut handler = CommandsHandler::from(scene);
+
+        handler
+            .execute(AlignShapes::boxed(
+                vec![key, other],
+                Alignment::CenterY,
+                AlignTarget::Key(key),
+            ))
+            .unwrap();
+        assert_near(bounds(&handler, key).center(), Coord::new(0.2, 0.2));
+        assert_near(bounds(&handler, other).center(), Coord::new(-0.3, 0.2));
+
+        handler
+            .execute(AlignShapes::boxed(
+                vec![key, other],
+                Alignment::Bottom,
+                AlignTarget::Scene,
+            ))
+            .unwrap();
+        let border = handler.scene().max_rect().bottom_right.y;
+        assert!((bounds(&handler, key).bottom_right.y - border).abs() < 1e-5);
+        assert!((bounds(&handler, other).bottom_right.y - border).abs() < 1e-5);
+    }
+
+    #[test]
+    fn given_3_shapes_when_distribute_then_same_gaps_or_same_centers() {
+        let mut scene = Scene::new();
+        let ids = vec![
+            rect(&mut scene, 0.6, 0.0, 0.2, 0.1),
+            rect(&mut scene, -0.5, 0.0, 0.1, 0.1),
+            rect(&mut scene, 0.0, 0.0, 0.3, 0.1),
+        ];
+        let mut handler = CommandsHandler::from(scene);
+
+        handler
+            .execute(AlignShapes::boxed(
+                ids.clone(),
+                Alignment::DistributeGapsX,
+                AlignTarget::Selection,
+            ))
+            .unwrap();
+        // Gap of (1.3 - 0.6) / 2 between the shapes
+        assert_near(bounds(&handler, ids[1]).top_left, Coord::new(-0.5, 0.0));
+        assert_near(bounds(&handler, ids[2]).top_left, Coord::new(-0.05, 0.0));
+        assert_near(bounds(&handler, ids[0]).top_left, Coord::new(0.6, 0.0));
+
+        handler
+            .execute(AlignShapes::boxed(
+                ids.clone(),
+                Alignment::DistributeCentersX,
+                AlignTarget::Selection,
+            ))
+            .unwrap();
+        assert_near(bounds(&handler, ids[2]).center(), Coord::new(0.125, 0.05));
+    }
+
+    #[test]
+    fn given_selected_group_and_shape_when_align_left_then_group_moved_as_a_whole() {
+        let mut scene = Scene::new();
+        let pair = vec![
+            rect(&mut scene, 0.2, 0.0, 0.1, 0.1),
+            rect(&mut scene, 0.5, 0.3, 0.1, 0.1),
+        ];
+        let single = rect(&mut scene, -0.4, 0.6, 0.2, 0.2);
+        let mut handler = CommandsHandler::from(scene);
+        handler.execute(Group::boxed(pair.clone())).unwrap();
+        let mut context = SceneUserContext {
+            command_handler: handler,
+            ..SceneUserContext::new(500.0, 500.0)
+        };
+        // Selecting the group selects each of its shapes
+        let mut selected = UserSelection::default();
+        for id in pair.iter().chain([&single]) {
+            selected.shapes.push(SelectedShape::new(*id));
+        }
+
+        context
+            .align_shapes_of(&selected, Alignment::Left, AlignTarget::Selection)
+            .unwrap();
+
+        let handler = &context.command_handler;
+        assert_near(bounds(handler, pair[0]).top_left, Coord::new(-0.4, 0.0));
+        assert_near(bounds(handler, pair[1]).top_left, Coord::new(-0.1, 0.3));
+        assert_near(bounds(handler, single).top_left, Coord::new(-0.4, 0.6));
+    }
+}
@@ -0,0 +1,165 @@
+use super::Command;
+use crate::{LayerId, PathEnd};
+use anyhow::{Context, Error, Ok, Result};
+use common::types::Coord;
+use macros::boxed;
+use std::any::Any;
+
+/// Add an anchor at an end of an open subpath, the curve to the anchor uses the handles
+#[derive(Clone, Debug)]
+pub struct AppendAnchor {
+    shape: LayerId,
+    subpath: usize,
+    end: PathEnd,
+    handle_from: Option<Coord>,
+    handle_to: Option<Coord>,
+    anchor: Coord,
+}
+
+impl AppendAnchor {
+    /// handle_from is the handle of the current end toward the anchor,
+    /// handle_to is the handle of the anchor toward the current end.
+    /// A handle at None makes a straight segment on its side.
+    #[boxed]
+    pub fn new(
+        shape: LayerId,
+        subpath: usize,
+        end: PathEnd,
+        handle_from: Option<Coord>,
+        handle_to: Option<Coord>,
+        anchor: Coord,
+    ) -> Self {
+        Self {
+            shape,
+            subpath,
+            end,
+            handle_from,
+            handle_to,
+            anchor,
+        }
+    }
+}
+
+impl Command for AppendAnchor {
+    fn execute(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let shape = scene
+            .shape_select_mut(self.shape)
+            .context("Shape not found")?;
+        shape
+            .subpath_extend(
+                self.subpath,
+                self.end,
+                self.handle_from,
+                self.handle_to,
+                self.anchor,
+            )
+            .map_err(Error::msg)?;
+        scene.shape_coords_adopt(self.shape);
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let shape = scene
+            .shape_select_mut(self.shape)
+            .context("Shape not found")?;
+        shape
+            .subpath_retract(self.subpath, self.end)
+            .map_err(Error::msg)?;
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Affine, types::Coord};
+
+    use crate::{commands::Command, DbCoord, PathEnd, Scene, Shape};
+
+    use super::AppendAnchor;
+
+    fn open_path() -> Shape {
+        Shape::new_polyline(
+            vec![DbCoord::new(0.0, 0.0), DbCoord::new(1.0, 0.0)],
+            Affine::identity(),
+        )
+    }
+
+    #[test]
+    fn given_open_path_when_append_at_end_then_curve_to_anchor() {
+        let mut scene = Scene::new();
+        let shape_id = scene.shape_insert(open_path());
+        let expected = scene.clone();
+
+        let mut command = AppendAnchor::new(
+            shape_id,
+            0,
+            PathEnd::End,
+            Some(Coord::new(1.5, 0.0)),
+            None,
+            Coord::new(1.0, 1.0),
+        );
+        command.execute(&mut scene).unwrap();
+
+        let shape = scene.shape_select(shape_id).unwrap();
+        assert_eq!(shape.curves_len(), 2);
+        assert!(!shape.is_closed());
+        let curve = shape.curve_select(1).unwrap();
+        assert_eq!(curve.p0.coord(), Coord::new(1.0, 0.0));
+        assert_eq!(curve.cp0.coord(), Coord::new(1.5, 0.0));
+        assert_eq!(curve.cp1.id, curve.p1.id);
+        assert_eq!(curve.p1.coord(), Coord::new(1.0, 1.0));
+        assert!(shape.path.iter().all(|c| !c.id.is_provisional()));
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+    }
+
+    #[test]
+    fn given_open_path_when_append_at_start_then_anchor_is_first() {
+        let mut scene = Scene::new();
+        let shape_id = scene.shape_insert(open_path());
+        let expected = scene.clone();
+
+        let mut command = AppendAnchor::new(
+            shape_id,
+            0,
+            PathEnd::Start,
+            None,
+            Some(Coord::new(-1.0, 0.5)),
+            Coord::new(-1.0, 1.0),
+        );
+        command.execute(&mut scene).unwrap();
+
+        let shape = scene.shape_select(shape_id).unwrap();
+        assert_eq!(shape.curves_len(), 2);
+        let curve = shape.curve_select(0).unwrap();
+        assert_eq!(curve.p0.coord(), Coord::new(-1.0, 1.0));
+        assert_eq!(curve.cp0.coord(), Coord::new(-1.0, 0.5));
+        assert_eq!(curve.cp1.id, curve.p1.id);
+        assert_eq!(curve.p1.coord(), Coord::new(0.0, 0.0));
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+    }
+
+    #[test]
+    fn given_closed_path_when_append_then_error() {
+        let mut scene = Scene::new();
+        let mut shape = open_path();
+        shape.close();
+        let shape_id = scene.shape_insert(shape);
+
+        let mut command =
+            AppendAnchor::new(shape_id, 0, PathEnd::End, None, None, Coord::new(1.0, 1.0));
+
+        assert!(command.execute(&mut scene).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use super::Command;
-use crate::LayerId;
+use crate::{LayerId, Paint};
 use anyhow::{Ok, Result};
 use common::Rgba;
 use macros::boxed;
@@ -9,7 +9,7 @@ use std::any::Any;
 pub struct ChangeColor {
     shapes: Vec<LayerId>,
     color: Rgba,
-    old_colors: Option<Vec<Rgba>>,
+    old_colors: Option<Vec<Paint>>,
 }
 
 impl ChangeColor {
@@ -29,8 +29,8 @@ impl Command for ChangeColor {
             let mut old_colors = Vec::new();
             for shape in self.shapes.iter() {
                 if let Some(shape) = scene.shape_select_mut(*shape) {
-                    old_colors.push(shape.color.clone());
-                    shape.color = self.color.clone();
+                    old_colors.push(shape.fill.clone());
+                    shape.fill = Paint::Solid(self.color.clone());
                 }
             }
             self.old_colors = Some(old_colors);
@@ -38,7 +38,7 @@ impl Command for ChangeColor {
 
         for shape in self.shapes.iter() {
             if let Some(shape) = scene.shape_select_mut(*shape) {
-                shape.color = self.color.clone();
+                shape.fill = Paint::Solid(self.color.clone());
             }
         }
         Ok(())
@@ -48,7 +48,7 @@ impl Command for ChangeColor {
         if let Some(old_colors) = self.old_colors.take() {
             for (shape_index, old_color) in self.shapes.iter().zip(old_colors) {
                 if let Some(shape) = scene.shape_select_mut(*shape_index) {
-                    shape.color = old_color;
+                    shape.fill = old_color;
                 }
             }
             Ok(())
@@ -77,7 +77,7 @@ impl Command for ChangeColor {
 mod test {
     use common::Rgba;
 
-    use crate::{commands::CommandsHandler, Scene, Shape};
+    use crate::{commands::CommandsHandler, Paint, Scene, Shape};
 
     use super::ChangeColor;
 
@@ -85,7 +85,7 @@ mod test {
     fn given_shape_when_change_color() {
         let mut scene = Scene::new();
         let mut s = Shape::new();
-        s.color = Rgba::black();
+        s.fill = Rgba::black().into();
         let id = scene.shape_insert(s);
         let expected = scene.clone();
 
@@ -96,16 +96,16 @@ mod test {
 
         assert!(res.is_ok());
         assert_eq!(
-            command_handler.scene().shape_select(id).unwrap().color,
-            Rgba::white()
+            command_handler.scene().shape_select(id).unwrap().fill,
+            Paint::Solid(Rgba::white())
         );
 
         let res = command_handler.undo();
 
         assert!(res.is_ok());
         assert_eq!(
-            command_handler.scene().shape_select(id).unwrap().color,
-            Rgba::black()
+            command_handler.scene().shape_select(id).unwrap().fill,
+            Paint::Solid(Rgba::black())
         );
         assert_eq!(*command_handler.scene(), expected);
 
@@ -113,16 +113,16 @@ mod test {
 
         assert!(res.is_ok());
         assert_eq!(
-            command_handler.scene().shape_select(id).unwrap().color,
-            Rgba::white()
+            command_handler.scene().shape_select(id).unwrap().fill,
+            Paint::Solid(Rgba::white())
         );
 
         let res = command_handler.undo();
 
         assert!(res.is_ok());
         assert_eq!(
-            command_handler.scene().shape_select(id).unwrap().color,
-            Rgba::black()
+            command_handler.scene().shape_select(id).unwrap().fill,
+            Paint::Solid(Rgba::black())
         );
     }
 }
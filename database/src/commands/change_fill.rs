@@ -0,0 +1,162 @@
+use super::Command;
+use crate::{LayerId, Paint, Scene};
+use anyhow::{Context, Result};
+use macros::boxed;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use tsify::Tsify;
+
+/// Part of a shape that is colored by a paint
+#[derive(Tsify, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum PaintTarget {
+    Fill,
+    Stroke,
+}
+
+/// Replace the paint of the fill or of the stroke of the shapes, like with a gradient
+#[derive(Clone, Debug)]
+pub struct ChangeFill {
+    shapes: Vec<LayerId>,
+    target: PaintTarget,
+    paint: Paint,
+    old_paints: Vec<Paint>,
+}
+
+impl ChangeFill {
+    #[boxed]
+    pub fn new(shapes: Vec<LayerId>, target: PaintTarget, paint: Paint) -> Self {
+        Self {
+            shapes,
+            target,
+            paint,
+            old_paints: Vec::new(),
+        }
+    }
+
+    fn paint_mut<'a>(&self, scene: &'a mut Scene, id: LayerId) -> Result<&'a mut Paint> {
+        let shape = scene.shape_select_mut(id).context("Shape not found")?;
+        Ok(match self.target {
+            PaintTarget::Fill => &mut shape.fill,
+            PaintTarget::Stroke => &mut shape.stroke.paint,
+        })
+    }
+}
+
+impl Command for ChangeFill {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        let mut old_paints = Vec::with_capacity(self.shapes.len());
+        for id in &self.shapes {
+            let paint = self.paint_mut(scene, *id)?;
+            old_paints.push(std::mem::replace(paint, self.paint.clone()));
+        }
+        self.old_paints = old_paints;
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        let old_paints = std::mem::take(&mut self.old_paints);
+        for (id, old_paint) in self.shapes.iter().zip(old_paints) {
+            *self.paint_mut(scene, *id)? = old_paint;
+        }
+        Ok(())
+    }
+
+    fn merge(&self, futur: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        if let Some(other) = futur.as_any().downcast_ref::<ChangeFill>() {
+            if self.shapes == other.shapes && self.target == other.target {
+                let mut command = self.clone();
+                command.paint = other.paint.clone();
+                return Some(Ok(Box::new(command)));
+            }
+        }
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{types::Coord, Rgba};
+
+    use crate::{
+        commands::CommandsHandler, GradientStop, GradientUnits, LinearGradient, Paint, Scene, Shape,
+    };
+
+    use super::{ChangeFill, PaintTarget};
+
+    fn gradient(end: f32) -> Paint {
+        Paint::LinearGradient(LinearGradient {
+            start: Coord::new(0.0, 0.0),
+            end: Coord::new(end, 0.0),
+            stops: vec![
+                GradientStop::new(0.0, Rgba::red()),
+                GradientStop::new(1.0, Rgba::blue()),
+            ],
+            units: GradientUnits::Shape,
+        })
+    }
+
+    #[test]
+    fn given_shape_when_change_fill_then_undo_redo() {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new();
+        shape.fill = Rgba::black().into();
+        let id = scene.shape_insert(shape);
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        command_handler
+            .execute(ChangeFill::boxed(
+                vec![id],
+                PaintTarget::Fill,
+                gradient(1.0),
+            ))
+            .unwrap();
+        let shape = command_handler.scene().shape_select(id).unwrap();
+        assert_eq!(shape.fill, gradient(1.0));
+        assert_eq!(shape.stroke.paint, Paint::Solid(Rgba::black()));
+
+        command_handler.undo().unwrap();
+        assert_eq!(*command_handler.scene(), expected);
+
+        command_handler.redo().unwrap();
+        assert_eq!(
+            command_handler.scene().shape_select(id).unwrap().fill,
+            gradient(1.0)
+        );
+    }
+
+    #[test]
+    fn given_stroke_changes_when_merge_then_one_undo() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new());
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        for end in [0.5, 1.0] {
+            command_handler
+                .execute(ChangeFill::boxed(
+                    vec![id],
+                    PaintTarget::Stroke,
+                    gradient(end),
+                ))
+                .unwrap();
+        }
+        assert_eq!(
+            command_handler
+                .scene()
+                .shape_select(id)
+                .unwrap()
+                .stroke
+                .paint,
+            gradient(1.0)
+        );
+
+        command_handler.undo().unwrap();
+        assert_eq!(*command_handler.scene(), expected);
+    }
+}
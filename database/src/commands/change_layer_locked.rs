@@ -0,0 +1,87 @@
+use super::Command;
+use crate::{LayerId, Scene};
+use anyhow::{Context, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Lock or unlock a layer, a locked folder locks its layers
+#[derive(Clone, Debug)]
+pub struct ChangeLayerLocked {
+    layer: LayerId,
+    locked: bool,
+    old_locked: Option<bool>,
+}
+
+impl ChangeLayerLocked {
+    #[boxed]
+    pub fn new(layer: LayerId, locked: bool) -> Self {
+        Self {
+            layer,
+            locked,
+            old_locked: None,
+        }
+    }
+}
+
+impl Command for ChangeLayerLocked {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        let layer = scene.layer_mut(self.layer).context("Layer not found")?;
+        self.old_locked = Some(layer.locked);
+        layer.locked = self.locked;
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        let locked = self
+            .old_locked
+            .take()
+            .context("Undoing a command that was not executed")?;
+        let layer = scene.layer_mut(self.layer).context("Layer not found")?;
+        layer.locked = locked;
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, Length2d};
+
+    use crate::{
+        commands::{CommandsHandler, Group},
+        Scene, Shape,
+    };
+
+    use super::ChangeLayerLocked;
+
+    #[test]
+    fn given_locked_folder_when_hit_its_shape_then_not_hit_until_undo() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            Length2d::new(0.5, 0.5),
+        ));
+        let mut command_handler = CommandsHandler::from(scene);
+        command_handler.execute(Group::boxed(vec![id])).unwrap();
+        let folder = command_handler.scene().layer_parent(id).unwrap();
+
+        command_handler
+            .execute(ChangeLayerLocked::boxed(folder, true))
+            .unwrap();
+
+        let scene = command_handler.scene();
+        assert!(scene.get_tree_view()[0].locked);
+        assert!(scene.shape_select_hit(&Coord::new(0.0, 0.1), 0.0).is_none());
+
+        command_handler.undo().unwrap();
+        let scene = command_handler.scene();
+        assert!(scene.shape_select_hit(&Coord::new(0.0, 0.1), 0.0).is_some());
+    }
+}
@@ -0,0 +1,86 @@
+use super::Command;
+use crate::{LayerId, Scene};
+use anyhow::{Context, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Set the opacity of a layer between 0.0 and 1.0, it multiplies the opacity of its layers
+#[derive(Clone, Debug)]
+pub struct ChangeLayerOpacity {
+    layer: LayerId,
+    opacity: f32,
+    old_opacity: Option<f32>,
+}
+
+impl ChangeLayerOpacity {
+    #[boxed]
+    pub fn new(layer: LayerId, opacity: f32) -> Self {
+        Self {
+            layer,
+            opacity: opacity.clamp(0.0, 1.0),
+            old_opacity: None,
+        }
+    }
+}
+
+impl Command for ChangeLayerOpacity {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        let layer = scene.layer_mut(self.layer).context("Layer not found")?;
+        if self.old_opacity.is_none() {
+            self.old_opacity = Some(layer.opacity);
+        }
+        layer.opacity = self.opacity;
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        let opacity = self
+            .old_opacity
+            .take()
+            .context("Undoing a command that was not executed")?;
+        let layer = scene.layer_mut(self.layer).context("Layer not found")?;
+        layer.opacity = opacity;
+        Ok(())
+    }
+
+    fn merge(&self, other: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        if let Some(other) = other.as_any().downcast_ref::<ChangeLayerOpacity>() {
+            if self.layer == other.layer {
+                let mut command = self.clone();
+                command.opacity = other.opacity;
+                return Some(Ok(Box::new(command)));
+            }
+        }
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{commands::CommandsHandler, Scene, Shape};
+
+    use super::ChangeLayerOpacity;
+
+    #[test]
+    fn given_layer_when_change_opacity_twice_then_merge_in_one_undo() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new());
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        command_handler
+            .execute(ChangeLayerOpacity::boxed(id, 0.8))
+            .unwrap();
+        command_handler
+            .execute(ChangeLayerOpacity::boxed(id, 1.5))
+            .unwrap();
+        assert_eq!(command_handler.scene().get_tree_view()[0].opacity, 1.0);
+
+        command_handler.undo().unwrap();
+        assert_eq!(*command_handler.scene(), expected);
+    }
+}
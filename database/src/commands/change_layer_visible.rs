@@ -0,0 +1,82 @@
+use super::Command;
+use crate::{LayerId, Scene};
+use anyhow::{Context, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Show or hide a layer, a hidden folder hides its layers
+#[derive(Clone, Debug)]
+pub struct ChangeLayerVisible {
+    layer: LayerId,
+    visible: bool,
+    old_visible: Option<bool>,
+}
+
+impl ChangeLayerVisible {
+    #[boxed]
+    pub fn new(layer: LayerId, visible: bool) -> Self {
+        Self {
+            layer,
+            visible,
+            old_visible: None,
+        }
+    }
+}
+
+impl Command for ChangeLayerVisible {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        let layer = scene.layer_mut(self.layer).context("Layer not found")?;
+        self.old_visible = Some(layer.visible);
+        layer.visible = self.visible;
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        let visible = self
+            .old_visible
+            .take()
+            .context("Undoing a command that was not executed")?;
+        let layer = scene.layer_mut(self.layer).context("Layer not found")?;
+        layer.visible = visible;
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, Length2d};
+
+    use crate::{commands::CommandsHandler, Scene, Shape};
+
+    use super::ChangeLayerVisible;
+
+    #[test]
+    fn given_shape_when_hide_then_not_hit_and_undo_shows_it() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            Length2d::new(0.5, 0.5),
+        ));
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        command_handler
+            .execute(ChangeLayerVisible::boxed(id, false))
+            .unwrap();
+
+        let scene = command_handler.scene();
+        assert!(!scene.get_tree_view()[0].visible);
+        assert!(scene.shape_select_hit(&Coord::new(0.0, 0.1), 0.0).is_none());
+
+        command_handler.undo().unwrap();
+        assert_eq!(*command_handler.scene(), expected);
+    }
+}
@@ -0,0 +1,185 @@
+use super::Command;
+use crate::{scene::shape::Stroke, LayerId, LineCap, LineJoin, StrokeAlign};
+use anyhow::{Ok, Result};
+use macros::boxed;
+use std::{any::Any, mem::discriminant};
+
+/// Property of the stroke of a shape changed by [`ChangeStrokeProperty`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum StrokeProperty {
+    Join(LineJoin),
+    Cap(LineCap),
+    MiterLimit(f32),
+    /// Dash array and dash offset, no dash for a solid line
+    Dashes(Vec<f32>, f32),
+    Align(StrokeAlign),
+}
+
+impl StrokeProperty {
+    /// Value of the same property in the stroke
+    fn of(&self, stroke: &Stroke) -> Self {
+        match self {
+            StrokeProperty::Join(_) => StrokeProperty::Join(stroke.join),
+            StrokeProperty::Cap(_) => StrokeProperty::Cap(stroke.cap),
+            StrokeProperty::MiterLimit(_) => StrokeProperty::MiterLimit(stroke.miter_limit),
+            StrokeProperty::Dashes(_, _) => {
+                StrokeProperty::Dashes(stroke.dashes.clone(), stroke.dash_offset)
+            }
+            StrokeProperty::Align(_) => StrokeProperty::Align(stroke.align),
+        }
+    }
+
+    fn apply(&self, stroke: &mut Stroke) {
+        match self {
+            StrokeProperty::Join(join) => stroke.join = *join,
+            StrokeProperty::Cap(cap) => stroke.cap = *cap,
+            StrokeProperty::MiterLimit(miter_limit) => stroke.miter_limit = *miter_limit,
+            StrokeProperty::Dashes(dashes, offset) => {
+                stroke.dashes = dashes.clone();
+                stroke.dash_offset = *offset;
+            }
+            StrokeProperty::Align(align) => stroke.align = *align,
+        }
+    }
+}
+
+/// Set one property of the stroke of the shapes, like the join or the dashes
+#[derive(Clone, Debug)]
+pub struct ChangeStrokeProperty {
+    shapes: Vec<LayerId>,
+    value: StrokeProperty,
+    old_values: Option<Vec<(LayerId, StrokeProperty)>>,
+}
+
+impl ChangeStrokeProperty {
+    #[boxed]
+    pub fn new(shapes: Vec<LayerId>, value: StrokeProperty) -> Self {
+        Self {
+            shapes,
+            value,
+            old_values: None,
+        }
+    }
+}
+
+impl Command for ChangeStrokeProperty {
+    fn execute(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let mut old_values = Vec::with_capacity(self.shapes.len());
+        for id in self.shapes.iter() {
+            if let Some(shape) = scene.shape_select_mut(*id) {
+                old_values.push((*id, self.value.of(&shape.stroke)));
+                self.value.apply(&mut shape.stroke);
+            }
+        }
+        self.old_values = Some(old_values);
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        if let Some(old_values) = self.old_values.take() {
+            for (id, old_value) in old_values {
+                if let Some(shape) = scene.shape_select_mut(id) {
+                    old_value.apply(&mut shape.stroke);
+                }
+            }
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Undoing a command that was not executed"))
+        }
+    }
+
+    fn merge(&self, other: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        if let Some(other) = other.as_any().downcast_ref::<ChangeStrokeProperty>() {
+            if self.shapes == other.shapes
+                && discriminant(&self.value) == discriminant(&other.value)
+            {
+                let mut command = self.clone();
+                command.value = other.value.clone();
+                return Some(Ok(Box::new(command)));
+            }
+        }
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{commands::CommandsHandler, LayerId, LineCap, LineJoin, Scene, Shape, StrokeAlign};
+
+    use super::{ChangeStrokeProperty, StrokeProperty};
+
+    #[test]
+    fn given_shape_when_change_each_property_then_undo_redo() {
+        let values = vec![
+            StrokeProperty::Join(LineJoin::Round),
+            StrokeProperty::Cap(LineCap::Square),
+            StrokeProperty::MiterLimit(2.5),
+            StrokeProperty::Dashes(vec![0.1, 0.05], 0.02),
+            StrokeProperty::Align(StrokeAlign::Inside),
+        ];
+        for value in values {
+            let mut scene = Scene::new();
+            let id = scene.shape_insert(Shape::new());
+            let expected = scene.clone();
+            let mut command_handler = CommandsHandler::from(scene);
+
+            let res = command_handler.execute(ChangeStrokeProperty::boxed(vec![id], value.clone()));
+
+            assert!(res.is_ok());
+            let stroke = &command_handler.scene().shape_select(id).unwrap().stroke;
+            assert_eq!(value.of(stroke), value);
+
+            let res = command_handler.undo();
+
+            assert!(res.is_ok());
+            assert_eq!(*command_handler.scene(), expected);
+
+            let res = command_handler.redo();
+
+            assert!(res.is_ok());
+            let stroke = &command_handler.scene().shape_select(id).unwrap().stroke;
+            assert_eq!(value.of(stroke), value);
+        }
+    }
+
+    #[test]
+    fn given_missing_shape_when_undo_then_old_values_of_the_right_shapes() {
+        let mut scene = Scene::new();
+        let a = scene.shape_insert(Shape::new());
+        let b = scene.shape_insert(Shape::new());
+        scene.shape_select_mut(b).unwrap().stroke.cap = LineCap::Round;
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        let shapes = vec![LayerId::null(), a, b];
+        let command = ChangeStrokeProperty::boxed(shapes, StrokeProperty::Cap(LineCap::Square));
+        command_handler.execute(command).unwrap();
+        command_handler.undo().unwrap();
+
+        assert_eq!(*command_handler.scene(), expected);
+    }
+
+    #[test]
+    fn given_changes_of_same_property_when_execute_then_merged_in_one_undo() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new());
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        for limit in [2.0, 3.0, 5.0] {
+            let value = StrokeProperty::MiterLimit(limit);
+            command_handler
+                .execute(ChangeStrokeProperty::boxed(vec![id], value))
+                .unwrap();
+        }
+        let stroke = &command_handler.scene().shape_select(id).unwrap().stroke;
+        assert_eq!(stroke.miter_limit, 5.0);
+
+        command_handler.undo().unwrap();
+        assert_eq!(*command_handler.scene(), expected);
+    }
+}
@@ -84,7 +84,7 @@ mod test {
     fn given_shape_when_change_color() {
         let mut scene = Scene::new();
         let mut s = Shape::new();
-        s.color = Rgba::black();
+        s.fill = Rgba::black().into();
         let id = scene.shape_insert(s);
         let expected = scene.clone();
 
@@ -0,0 +1,104 @@
+use super::Command;
+use crate::{LayerId, PathEnd};
+use anyhow::{Context, Error, Ok, Result};
+use common::types::Coord;
+use macros::boxed;
+use std::any::Any;
+
+/// Close an open subpath with a curve from its last anchor to its first anchor
+#[derive(Clone, Debug)]
+pub struct ClosePath {
+    shape: LayerId,
+    subpath: usize,
+    handle_from: Option<Coord>,
+    handle_to: Option<Coord>,
+}
+
+impl ClosePath {
+    /// handle_from is the handle of the last anchor, handle_to is the handle of the first anchor.
+    /// A handle at None makes a straight segment on its side.
+    #[boxed]
+    pub fn new(
+        shape: LayerId,
+        subpath: usize,
+        handle_from: Option<Coord>,
+        handle_to: Option<Coord>,
+    ) -> Self {
+        Self {
+            shape,
+            subpath,
+            handle_from,
+            handle_to,
+        }
+    }
+}
+
+impl Command for ClosePath {
+    fn execute(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let shape = scene
+            .shape_select_mut(self.shape)
+            .context("Shape not found")?;
+        shape
+            .subpath_close(self.subpath, self.handle_from, self.handle_to)
+            .map_err(Error::msg)?;
+        scene.shape_coords_adopt(self.shape);
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let shape = scene
+            .shape_select_mut(self.shape)
+            .context("Shape not found")?;
+        shape
+            .subpath_retract(self.subpath, PathEnd::End)
+            .map_err(Error::msg)?;
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Affine, types::Coord};
+
+    use crate::{commands::Command, DbCoord, Scene, Shape};
+
+    use super::ClosePath;
+
+    #[test]
+    fn given_open_path_when_close_then_closed_and_filled() {
+        let mut scene = Scene::new();
+        let shape = Shape::new_polyline(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(1.0, 0.0),
+                DbCoord::new(1.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        let shape_id = scene.shape_insert(shape);
+        let expected = scene.clone();
+        assert!(!scene
+            .shape_select(shape_id)
+            .unwrap()
+            .contains(&Coord::new(0.8, 0.2)));
+
+        let mut command = ClosePath::new(shape_id, 0, None, None);
+        command.execute(&mut scene).unwrap();
+
+        let shape = scene.shape_select(shape_id).unwrap();
+        assert!(shape.is_closed());
+        assert_eq!(shape.curves_len(), 3);
+        assert!(shape.contains(&Coord::new(0.8, 0.2)));
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+    }
+}
@@ -0,0 +1,78 @@
+use super::Command;
+use crate::{
+    scene::{Layer, LayerPlace},
+    LayerId,
+};
+use anyhow::{Context, Error, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+#[derive(Clone, Debug)]
+pub struct DeleteLayer {
+    id: LayerId,
+    layer_to_undo: Option<(LayerPlace, Layer)>,
+}
+
+impl DeleteLayer {
+    #[boxed]
+    pub fn new(id: LayerId) -> Self {
+        Self {
+            id,
+            layer_to_undo: None,
+        }
+    }
+}
+
+impl Command for DeleteLayer {
+    fn execute(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        self.layer_to_undo = Some(scene.layer_remove(self.id).context("Layer not found")?);
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let (place, layer) = self.layer_to_undo.take().context("No layer to undo")?;
+        scene.layer_insert_at(place, layer).map_err(Error::msg)?;
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{commands::Command, Scene, Shape};
+
+    use super::DeleteLayer;
+
+    #[test]
+    fn given_3_layers_when_delete_middle_then_undo_at_same_position() {
+        let mut scene = Scene::new();
+        scene.shape_insert(Shape::new());
+        let id = scene.shape_insert(Shape::new());
+        scene.shape_insert(Shape::new());
+        let expected = scene.clone();
+
+        let mut command = DeleteLayer::new(id);
+        command.execute(&mut scene).unwrap();
+        assert_eq!(scene.get_tree_view().len(), 2);
+        assert!(scene.shape_select(id).is_none());
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+    }
+
+    #[test]
+    fn given_unknown_layer_when_delete_then_error() {
+        let mut scene = Scene::new();
+
+        let mut command = DeleteLayer::new(7.into());
+
+        assert!(command.execute(&mut scene).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use super::Command;
+use crate::{
+    scene::{Layer, LayerPlace},
+    LayerId, Scene,
+};
+use anyhow::{Context, Error, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Remove the layers in one step, a layer inside a removed folder is removed with it
+#[derive(Clone, Debug)]
+pub struct DeleteLayers {
+    layers: Vec<LayerId>,
+    /// Removed layers with their place, in the order they were removed
+    layers_to_undo: Vec<(LayerPlace, Layer)>,
+}
+
+impl DeleteLayers {
+    #[boxed]
+    pub fn new(layers: Vec<LayerId>) -> Self {
+        Self {
+            layers,
+            layers_to_undo: Vec::new(),
+        }
+    }
+}
+
+impl Command for DeleteLayers {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        self.layers_to_undo.clear();
+        for id in &self.layers {
+            if let Some(removed) = scene.layer_remove(*id) {
+                self.layers_to_undo.push(removed);
+            }
+        }
+        self.layers_to_undo.first().context("Layer not found")?;
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        for (place, layer) in self.layers_to_undo.drain(..).rev() {
+            scene.layer_insert_at(place, layer).map_err(Error::msg)?;
+        }
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{commands::Command, Scene, Shape};
+
+    use super::DeleteLayers;
+
+    #[test]
+    fn given_4_layers_when_delete_2_then_undo_at_same_positions() {
+        let mut scene = Scene::new();
+        let ids: Vec<_> = (0..4).map(|_| scene.shape_insert(Shape::new())).collect();
+        let expected = scene.clone();
+
+        let mut command = DeleteLayers::new(vec![ids[2], ids[0]]);
+        command.execute(&mut scene).unwrap();
+        assert_eq!(scene.get_tree_view().len(), 2);
+        assert!(scene.shape_select(ids[0]).is_none());
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+    }
+}
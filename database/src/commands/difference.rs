@@ -1,7 +1,6 @@
 use super::Command;
 use crate::{scene::shape::boolean::ShapeDifference, LayerId, Shape};
 use anyhow::{Ok, Result};
-use log::warn;
 use macros::boxed;
 use std::any::Any;
 
@@ -59,7 +58,8 @@ impl Command for Difference {
                 scene.layer_delete(self.b);
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = shapes.remove(0).path;
+                a_shape.paths_replace(shapes.remove(0));
+                scene.shape_coords_adopt(self.a);
 
                 if shapes.len() > 0 {
                     let mut new_shapes = Vec::new();
@@ -70,9 +70,6 @@ impl Command for Difference {
                     self.new_shapes = Some(new_shapes);
                 }
             }
-            ShapeDifference::AWithBHole => {
-                warn!("A with B hole not implemented");
-            }
         };
 
         Ok(())
@@ -109,14 +106,13 @@ impl Command for Difference {
                 }
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = a_shape_undo.path;
+                a_shape.paths_replace(a_shape_undo);
 
                 let id = scene.shape_insert(b_shape);
                 scene
                     .layer_move_at(id, self.b_shape_pos.unwrap())
                     .expect("not 404");
             }
-            ShapeDifference::AWithBHole => {}
         };
 
         Ok(())
@@ -0,0 +1,163 @@
+use super::Command;
+use crate::{
+    scene::{Layer, LayerPlace, LayerType},
+    LayerId, Scene,
+};
+use anyhow::{Context, Error, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Put the layers in a new folder, at the place of the layer the most in the foreground.
+/// A layer in the folder of another grouped layer stays in that folder
+#[derive(Clone, Debug)]
+pub struct Group {
+    layers: Vec<LayerId>,
+    folder: Option<LayerId>,
+    /// Places of the grouped layers, in the order they were removed
+    undo_places: Vec<(LayerId, LayerPlace)>,
+}
+
+impl Group {
+    #[boxed]
+    pub fn new(layers: Vec<LayerId>) -> Self {
+        Self {
+            layers,
+            folder: None,
+            undo_places: Vec::new(),
+        }
+    }
+
+    /// The grouped layers from the foreground, without the layers inside another grouped layer
+    fn grouped_layers(&self, scene: &Scene) -> Vec<LayerId> {
+        let layers: Vec<LayerId> = scene
+            .layers_iter()
+            .map(|layer| layer.id)
+            .filter(|id| self.layers.contains(id))
+            .collect();
+        layers
+            .iter()
+            .copied()
+            .filter(|id| {
+                !layers
+                    .iter()
+                    .any(|other| scene.layer_is_inside(*id, *other))
+            })
+            .collect()
+    }
+}
+
+impl Command for Group {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        let layers = self.grouped_layers(scene);
+        let first = *layers.first().context("No layer to group")?;
+        let place = scene.layer_place(first).context("Layer not found")?;
+        let folder = *self.folder.get_or_insert_with(|| scene.layer_id_reserve());
+
+        self.undo_places.clear();
+        let mut children = Vec::new();
+        for id in layers {
+            let (place, layer) = scene.layer_remove(id).context("Layer not found")?;
+            self.undo_places.push((id, place));
+            children.push(layer);
+        }
+
+        let name = format!("Group {}", folder.value());
+        let layer = Layer::new(folder, name, LayerType::Folder(children));
+        scene.layer_insert_at(place, layer).map_err(Error::msg)?;
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        let folder = self
+            .folder
+            .context("Undoing a command that was not executed")?;
+        let (_, layer) = scene.layer_remove(folder).context("Folder not found")?;
+        let LayerType::Folder(mut children) = layer.value else {
+            return Err(anyhow::anyhow!("Layer is not a folder"));
+        };
+
+        for (id, place) in self.undo_places.iter().rev() {
+            let position = children
+                .iter()
+                .position(|layer| layer.id == *id)
+                .context("Layer not found")?;
+            scene
+                .layer_insert_at(*place, children.remove(position))
+                .map_err(Error::msg)?;
+        }
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{commands::CommandsHandler, LayerId, Scene, Shape};
+
+    use super::Group;
+
+    #[test]
+    fn given_4_layers_when_group_2_then_folder_at_first_place_and_undo_redo() {
+        let mut scene = Scene::new();
+        let ids: Vec<LayerId> = (0..4).map(|_| scene.shape_insert(Shape::new())).collect();
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        command_handler
+            .execute(Group::boxed(vec![ids[3], ids[1]]))
+            .unwrap();
+
+        let scene = command_handler.scene();
+        let tree: Vec<(usize, usize)> = scene
+            .get_tree_view()
+            .iter()
+            .map(|layer| (layer.layer_id, layer.level))
+            .collect();
+        let folder = scene.layer_parent(ids[1]).unwrap();
+        assert_eq!(
+            tree,
+            vec![
+                (ids[0].value(), 0),
+                (folder.value(), 0),
+                (ids[1].value(), 1),
+                (ids[3].value(), 1),
+                (ids[2].value(), 0),
+            ]
+        );
+        let executed = scene.clone();
+
+        command_handler.undo().unwrap();
+        assert_eq!(*command_handler.scene(), expected);
+
+        command_handler.redo().unwrap();
+        assert_eq!(*command_handler.scene(), executed);
+    }
+
+    #[test]
+    fn given_group_and_its_layer_when_group_then_layer_stays_in_its_folder() {
+        let mut scene = Scene::new();
+        let id1 = scene.shape_insert(Shape::new());
+        let id2 = scene.shape_insert(Shape::new());
+        let mut command_handler = CommandsHandler::from(scene);
+        command_handler.execute(Group::boxed(vec![id1])).unwrap();
+        let folder = command_handler.scene().layer_parent(id1).unwrap();
+
+        command_handler
+            .execute(Group::boxed(vec![folder, id1, id2]))
+            .unwrap();
+
+        let scene = command_handler.scene();
+        assert_eq!(scene.layer_parent(id1), Some(folder));
+        let outer = scene.layer_parent(folder).unwrap();
+        assert_eq!(scene.layer_parent(id2), Some(outer));
+        assert_eq!(scene.layer_shapes(outer), vec![id1, id2]);
+        assert!(command_handler.execute(Group::boxed(vec![])).is_err());
+    }
+}
@@ -0,0 +1,76 @@
+use super::Command;
+use crate::Shape;
+use anyhow::{Context, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Insert a shape on top of the layers
+#[derive(Clone, Debug)]
+pub struct InsertShape {
+    shape: Shape,
+}
+
+impl InsertShape {
+    /// The shape keeps its id if it is not null, see `CommandsHandler::layer_id_reserve`
+    #[boxed]
+    pub fn new(shape: Shape) -> Self {
+        Self { shape }
+    }
+}
+
+impl Command for InsertShape {
+    fn execute(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let id = scene.shape_insert(self.shape.clone());
+        scene.layer_move_top(id);
+        // Keep the ids of the scene to have the same layer and coords on redo
+        self.shape = scene.shape_select(id).context("Shape not found")?.clone();
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        scene
+            .shape_select(self.shape.id)
+            .context("Shape not found")?;
+        scene.layer_delete(self.shape.id);
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, Length2d};
+
+    use crate::{commands::Command, Scene, Shape};
+
+    use super::InsertShape;
+
+    #[test]
+    fn given_shape_when_insert_undo_redo_then_same_ids() {
+        let mut scene = Scene::new();
+        scene.shape_insert(Shape::new());
+        let expected = scene.clone();
+
+        let mut command = InsertShape::new(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            Length2d::new(0.5, 0.5),
+        ));
+        command.execute(&mut scene).unwrap();
+        let inserted = scene.clone();
+        assert_eq!(scene.get_tree_view().len(), 2);
+        assert_eq!(scene.layer_position(command.shape.id), Some(0));
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+
+        command.execute(&mut scene).unwrap();
+        assert_eq!(scene, inserted);
+    }
+}
@@ -53,13 +53,15 @@ impl Command for Intersection {
                 scene.layer_delete(self.b);
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = self.b_shape.as_ref().unwrap().path.clone();
+                a_shape.paths_replace(self.b_shape.as_ref().unwrap().clone());
+                scene.shape_coords_adopt(self.a);
             }
             ShapeIntersection::New(mut shapes) => {
                 scene.layer_delete(self.b);
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = shapes.remove(0).path;
+                a_shape.paths_replace(shapes.remove(0));
+                scene.shape_coords_adopt(self.a);
 
                 if shapes.len() > 0 {
                     let mut new_shapes = Vec::new();
@@ -98,7 +100,7 @@ impl Command for Intersection {
                     .expect("not 404");
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = a_shape_undo.path;
+                a_shape.paths_replace(a_shape_undo);
             }
             ShapeIntersection::New(_) => {
                 if let Some(new_shapes) = self.new_shapes.take() {
@@ -108,7 +110,7 @@ impl Command for Intersection {
                 }
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = a_shape_undo.path;
+                a_shape.paths_replace(a_shape_undo);
 
                 let id = scene.shape_insert(b_shape);
                 scene
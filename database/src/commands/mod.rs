@@ -1,9 +1,10 @@
 use std::{any::Any, fmt::Debug};
 
 use anyhow::Result;
+use common::types::Rect;
 use serde::{Deserialize, Serialize};
 
-use crate::Scene;
+use crate::{LayerId, Scene};
 mod add_coord;
 mod change_color;
 mod move_coords;
@@ -15,11 +16,30 @@ mod change_stroke_size;
 mod union;
 mod difference;
 mod intersection;
+mod start_path;
+mod append_anchor;
+mod close_path;
+mod insert_shape;
+mod delete_layer;
+mod replace_shape_path;
+mod transform_shapes;
+mod change_fill;
+mod change_stroke_property;
+mod outline_stroke;
+mod group;
+mod ungroup;
+mod change_layer_visible;
+mod change_layer_locked;
+mod change_layer_opacity;
+mod rename_layer;
+mod align_shapes;
+mod delete_layers;
+mod paste_layers;
 
 pub use add_coord::AddCoord;
 pub use change_color::ChangeColor;
 pub use move_coords::MoveCoords;
-pub use move_layer::MoveLayer;
+pub use move_layer::{LayerDestination, MoveLayer};
 pub use remove_coord::RemoveCoord;
 pub use toggle_handle::ToggleHandle;
 pub use change_stroke_color::ChangeStrokeColor;
@@ -27,6 +47,25 @@ pub use change_stroke_size::ChangeStrokeSize;
 pub use union::Union;
 pub use difference::Difference;
 pub use intersection::Intersection;
+pub use start_path::StartPath;
+pub use append_anchor::AppendAnchor;
+pub use close_path::ClosePath;
+pub use insert_shape::InsertShape;
+pub use delete_layer::DeleteLayer;
+pub use replace_shape_path::ReplaceShapePath;
+pub use transform_shapes::{Pivot, TransformShapes};
+pub use change_fill::{ChangeFill, PaintTarget};
+pub use change_stroke_property::{ChangeStrokeProperty, StrokeProperty};
+pub use outline_stroke::OutlineStroke;
+pub use group::Group;
+pub use ungroup::Ungroup;
+pub use change_layer_visible::ChangeLayerVisible;
+pub use change_layer_locked::ChangeLayerLocked;
+pub use change_layer_opacity::ChangeLayerOpacity;
+pub use rename_layer::RenameLayer;
+pub use align_shapes::{AlignShapes, AlignTarget, Alignment};
+pub use delete_layers::DeleteLayers;
+pub(crate) use paste_layers::PasteLayers;
 
 
 pub trait Command: Any + Debug {
@@ -50,7 +89,8 @@ pub struct CommandsHandler {
 }
 
 impl From<Scene> for CommandsHandler {
-    fn from(scene: Scene) -> Self {
+    fn from(mut scene: Scene) -> Self {
+        scene.index_refresh();
         Self {
             scene,
             stack: Vec::new(),
@@ -64,12 +104,27 @@ impl CommandsHandler {
         &self.scene
     }
 
-    pub fn unsafe_scene_mut(&mut self) -> &mut Scene {
+    /// Mutate the scene without a command, only to load it. The undo stack doesn't follow.
+    pub(crate) fn unsafe_scene_mut(&mut self) -> &mut Scene {
         &mut self.scene
     }
 
+    /// Take a layer id for a layer inserted later by a command.
+    /// The allocator is not part of the document, the undo stack stays valid.
+    pub fn layer_id_reserve(&mut self) -> LayerId {
+        self.scene.layer_id_reserve()
+    }
+
+    /// Boxes of the scene changed by the commands, the undos and the redos since the last
+    /// take, to draw again only these regions. None when the whole scene must be drawn again
+    pub fn dirty_regions_take(&mut self) -> Option<Vec<Rect>> {
+        self.scene.dirty_regions_take()
+    }
+
     pub fn execute(&mut self, mut command: Box<dyn Command>) -> Result<()> {
-        command.execute(&mut self.scene)?;
+        let result = command.execute(&mut self.scene);
+        self.scene.index_refresh();
+        result?;
         self.stack.truncate(self.index);
         if let Some(&prev_command) = self.stack.last().as_ref() {
             if let Some(merged) = prev_command.merge(command.as_ref()) {
@@ -91,7 +146,9 @@ impl CommandsHandler {
             return Ok(());
         }
 
-        self.stack[self.index - 1].undo(&mut self.scene)?;
+        let result = self.stack[self.index - 1].undo(&mut self.scene);
+        self.scene.index_refresh();
+        result?;
         self.index -= 1;
         Ok(())
     }
@@ -101,7 +158,9 @@ impl CommandsHandler {
             return Ok(());
         }
 
-        self.stack[self.index].execute(&mut self.scene)?;
+        let result = self.stack[self.index].execute(&mut self.scene);
+        self.scene.index_refresh();
+        result?;
         self.index += 1;
         Ok(())
     }
@@ -121,7 +180,7 @@ mod tests{
     fn when_2_commands_then_merge_simpler(){
         let mut scene = Scene::new();
         let mut s = Shape::new();
-        s.color = Rgba::black();
+        s.fill = Rgba::black().into();
         let id = scene.shape_insert(s);
        
 
@@ -1,49 +1,67 @@
 use super::Command;
-use crate::LayerId;
+use crate::{scene::LayerPlace, LayerId};
 use anyhow::{Context, Error, Ok, Result};
 use macros::boxed;
 use std::any::Any;
 
+/// Where a layer is moved
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerDestination {
+    /// At the place of the layer, in the folder of the layer
+    Before(LayerId),
+    /// On top of the layers of the folder
+    Into(LayerId),
+}
+
 #[derive(Clone, Debug)]
 pub struct MoveLayer {
     layer: LayerId,
-    move_to: LayerId,
-    undo_pos: Option<usize>,
+    move_to: LayerDestination,
+    undo_place: Option<LayerPlace>,
 }
 
 impl MoveLayer {
     #[boxed]
-    pub fn new(layer: LayerId, move_to: LayerId) -> Self {
+    pub fn new(layer: LayerId, move_to: LayerDestination) -> Self {
         Self {
             layer,
             move_to,
-            undo_pos: None,
+            undo_place: None,
         }
     }
 }
 
 impl Command for MoveLayer {
     fn execute(&mut self, scene: &mut crate::Scene) -> Result<()> {
-        let before_pos = scene
-            .layer_position(self.layer)
-            .context("layer not found")?;
-        scene
-            .layer_position(self.move_to)
-            .context("layer not found")?;
-        scene
-            .layer_move_before(self.layer, self.move_to)
-            .map_err(Error::msg)?;
+        let before_place = scene.layer_place(self.layer).context("layer not found")?;
+        match self.move_to {
+            LayerDestination::Before(move_to) => {
+                scene.layer_position(move_to).context("layer not found")?;
+                scene
+                    .layer_move_before(self.layer, move_to)
+                    .map_err(Error::msg)?;
+            }
+            LayerDestination::Into(folder) => {
+                let place = LayerPlace {
+                    parent: Some(folder),
+                    index: 0,
+                };
+                scene
+                    .layer_move_into(self.layer, place)
+                    .map_err(Error::msg)?;
+            }
+        }
 
-        self.undo_pos = Some(before_pos);
+        self.undo_place = Some(before_place);
 
         Ok(())
     }
 
     fn undo(&mut self, scene: &mut crate::Scene) -> Result<()> {
-        let before_pos = self.undo_pos.context("no position to undo")?;
+        let before_place = self.undo_place.context("no position to undo")?;
 
         scene
-            .layer_move_at(self.layer, before_pos)
+            .layer_move_into(self.layer, before_place)
             .map_err(Error::msg)?;
         Ok(())
     }
@@ -61,7 +79,10 @@ impl Command for MoveLayer {
 mod test {
     use common::types::{Coord, Length2d};
 
-    use crate::{commands::{Command, MoveLayer}, Scene, Shape};
+    use crate::{
+        commands::{Command, Group, LayerDestination, MoveLayer},
+        Scene, Shape,
+    };
 
 
     #[test]
@@ -70,7 +91,7 @@ mod test {
         let layer1 = scene.shape_insert(Shape::new_circle(Coord::new(0.0,0.0), Length2d::new(0.5,0.5)));
         let layer2 = scene.shape_insert(Shape::new_circle(Coord::new(0.0,0.0), Length2d::new(0.5,0.5)));
     
-        let mut move_layer = MoveLayer::new(layer2, layer1);
+        let mut move_layer = MoveLayer::new(layer2, LayerDestination::Before(layer1));
         move_layer.execute(&mut scene).unwrap();
         assert_eq!(scene.layer_position(layer2).unwrap(), 0);
         assert_eq!(scene.layer_position(layer1).unwrap(), 1);
@@ -80,4 +101,33 @@ mod test {
         assert_eq!(scene.layer_position(layer1).unwrap(), 0);
     }
 
+    #[test]
+    fn given_layer_and_group_when_move_into_group_then_reparented_and_undo() {
+        let mut scene = Scene::new();
+        let layer1 = scene.shape_insert(Shape::new());
+        let layer2 = scene.shape_insert(Shape::new());
+        let layer3 = scene.shape_insert(Shape::new());
+        let mut group = Group::new(vec![layer2, layer3]);
+        group.execute(&mut scene).unwrap();
+        let folder = scene.layer_parent(layer2).unwrap();
+        let expected = scene.clone();
+
+        let mut move_layer = MoveLayer::new(layer1, LayerDestination::Into(folder));
+        move_layer.execute(&mut scene).unwrap();
+        assert_eq!(scene.layer_parent(layer1), Some(folder));
+        assert_eq!(scene.layer_position(layer1), Some(0));
+        assert_eq!(scene.layer_position(folder), Some(0));
+
+        move_layer.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+
+        let mut move_layer = MoveLayer::new(layer3, LayerDestination::Before(layer1));
+        move_layer.execute(&mut scene).unwrap();
+        assert_eq!(scene.layer_parent(layer3), None);
+        assert_eq!(scene.layer_position(layer3), Some(0));
+
+        let mut move_layer = MoveLayer::new(folder, LayerDestination::Into(folder));
+        assert!(move_layer.execute(&mut scene).is_err());
+    }
+
 }
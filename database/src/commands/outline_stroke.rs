@@ -0,0 +1,160 @@
+use super::Command;
+use crate::{LayerId, Scene, Shape};
+use anyhow::{Context, Error, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Replace the stroke of the shapes by a filled shape of its outline, inserted above the shape.
+/// A shape without fill becomes the outline of its stroke
+#[derive(Clone, Debug)]
+pub struct OutlineStroke {
+    shapes: Vec<LayerId>,
+    old_shapes: Vec<Shape>,
+    /// The shapes without their stroke and their outlines, kept to have the same ids on redo
+    new_shapes: Vec<(Shape, Option<Shape>)>,
+}
+
+impl OutlineStroke {
+    #[boxed]
+    pub fn new(shapes: Vec<LayerId>) -> Self {
+        Self {
+            shapes,
+            old_shapes: Vec::new(),
+            new_shapes: Vec::new(),
+        }
+    }
+
+    fn outline(&mut self, scene: &Scene) -> Result<()> {
+        for id in &self.shapes {
+            let shape = scene.shape_select(*id).context("Shape not found")?;
+            let Some(outline) = shape.outline_stroke() else {
+                continue;
+            };
+            self.old_shapes.push(shape.clone());
+
+            let mut shape = shape.clone();
+            if shape.fill.is_transparent() {
+                shape.paths_replace(outline.clone());
+                shape.fill = outline.fill;
+                shape.stroke = outline.stroke;
+                self.new_shapes.push((shape, None));
+            } else {
+                shape.stroke.size = 0.0;
+                self.new_shapes.push((shape, Some(outline)));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Command for OutlineStroke {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        if self.old_shapes.is_empty() {
+            self.outline(scene)?;
+        }
+
+        for (shape, outline) in self.new_shapes.iter_mut() {
+            let position = scene.layer_position(shape.id).context("Shape not found")?;
+            scene.shape_put(shape.clone());
+            *shape = scene
+                .shape_select(shape.id)
+                .context("Shape not found")?
+                .clone();
+
+            if let Some(outline) = outline {
+                let id = scene.shape_insert(outline.clone());
+                scene.layer_move_at(id, position).map_err(Error::msg)?;
+                *outline = scene.shape_select(id).context("Shape not found")?.clone();
+            }
+        }
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        for (_, outline) in &self.new_shapes {
+            if let Some(outline) = outline {
+                scene.layer_delete(outline.id);
+            }
+        }
+        for shape in &self.old_shapes {
+            scene.shape_put(shape.clone());
+        }
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        types::{Coord, Length2d},
+        Rgba,
+    };
+
+    use crate::{commands::CommandsHandler, scene::shape::Stroke, Paint, Scene, Shape};
+
+    use super::OutlineStroke;
+
+    fn stroked_circle(fill: Rgba) -> Shape {
+        let mut shape = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.5, 0.5));
+        shape.fill = fill.into();
+        shape.stroke = Stroke::new(0.1, Rgba::red());
+        shape
+    }
+
+    #[test]
+    fn given_filled_shape_when_outline_stroke_then_outline_above_and_undo_redo() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(stroked_circle(Rgba::black()));
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        command_handler
+            .execute(OutlineStroke::boxed(vec![id]))
+            .unwrap();
+
+        let scene = command_handler.scene();
+        let tree = scene.get_tree_view();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(scene.layer_position(id), Some(1));
+        assert_eq!(scene.shape_select(id).unwrap().stroke.size, 0.0);
+        let outline = scene.shape_select(tree[0].layer_id.into()).unwrap();
+        assert_eq!(outline.fill, Paint::Solid(Rgba::red()));
+        assert!(outline.contains(&Coord::new(0.53, 0.1)));
+        assert!(outline.contains(&Coord::new(0.45, 0.1)));
+        assert!(!outline.contains(&Coord::new(0.0, 0.1)));
+        assert!(!outline.contains(&Coord::new(0.55, 0.1)));
+        let executed = scene.clone();
+
+        command_handler.undo().unwrap();
+        assert_eq!(*command_handler.scene(), expected);
+
+        command_handler.redo().unwrap();
+        assert_eq!(*command_handler.scene(), executed);
+    }
+
+    #[test]
+    fn given_shape_without_fill_when_outline_stroke_then_shape_is_outline() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(stroked_circle(Rgba::transparent()));
+        let mut command_handler = CommandsHandler::from(scene);
+
+        command_handler
+            .execute(OutlineStroke::boxed(vec![id]))
+            .unwrap();
+
+        let scene = command_handler.scene();
+        assert_eq!(scene.get_tree_view().len(), 1);
+        let shape = scene.shape_select(id).unwrap();
+        assert_eq!(shape.fill, Paint::Solid(Rgba::red()));
+        assert!(shape.contains(&Coord::new(0.49, 0.1)));
+        assert_eq!(scene.validate(), vec![]);
+    }
+}
@@ -0,0 +1,136 @@
+use super::Command;
+use crate::{
+    scene::{Layer, LayerPlace, LayerType},
+    Scene,
+};
+use anyhow::{Context, Error, Ok, Result};
+use common::{pures::Affine, types::Coord};
+use macros::boxed;
+use std::any::Any;
+
+/// Insert copies of layers at a place, with new ids and moved by the offset
+#[derive(Clone, Debug)]
+pub struct PasteLayers {
+    /// From the foreground, the first one is inserted at the place
+    layers: Vec<Layer>,
+    place: LayerPlace,
+    offset: Coord,
+    /// The layers have the ids of the scene, kept to have the same layers on redo
+    adopted: bool,
+}
+
+impl PasteLayers {
+    #[boxed]
+    pub(crate) fn new(layers: Vec<Layer>, place: LayerPlace, offset: Coord) -> Self {
+        Self {
+            layers,
+            place,
+            offset,
+            adopted: false,
+        }
+    }
+}
+
+impl Command for PasteLayers {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        if !self.adopted {
+            let translate = Affine::from_translate(self.offset);
+            for layer in self.layers.iter_mut() {
+                scene.layer_ids_renew(layer);
+                shapes_transform(layer, &translate);
+            }
+            self.adopted = true;
+        }
+
+        for (index, layer) in self.layers.iter().enumerate() {
+            let place = LayerPlace {
+                parent: self.place.parent,
+                index: self.place.index + index,
+            };
+            scene
+                .layer_insert_at(place, layer.clone())
+                .map_err(Error::msg)?;
+        }
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        for layer in &self.layers {
+            scene.layer_remove(layer.id).context("Layer not found")?;
+        }
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn shapes_transform(layer: &mut Layer, transform: &Affine) {
+    match &mut layer.value {
+        LayerType::Shape(shape) => shape.transform(transform),
+        LayerType::Folder(children) => {
+            for child in children {
+                shapes_transform(child, transform);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, Length2d};
+
+    use crate::{commands::CommandsHandler, scene::LayerPlace, Scene, Shape};
+
+    use super::PasteLayers;
+
+    #[test]
+    fn given_copied_layer_when_paste_twice_then_new_ids_and_undo_redo() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            Length2d::new(0.5, 0.5),
+        ));
+        scene.shape_insert(Shape::new());
+        let copied = vec![scene.layers_iter().next().unwrap().clone()];
+        let mut handler = CommandsHandler::from(scene);
+        let expected = handler.scene().clone();
+        let top = LayerPlace {
+            parent: None,
+            index: 0,
+        };
+
+        let offset = Coord::new(0.1, 0.0);
+        handler
+            .execute(PasteLayers::boxed(copied.clone(), top, offset))
+            .unwrap();
+        handler
+            .execute(PasteLayers::boxed(copied, top, offset))
+            .unwrap();
+
+        let scene = handler.scene();
+        let second = scene.layer_at(top).unwrap();
+        let first = scene.layer_at(LayerPlace { index: 1, ..top }).unwrap();
+        assert!(first != id && second != id && first != second);
+        let original = scene.shape_select(id).unwrap();
+        let pasted = scene.shape_select(first).unwrap();
+        assert_eq!(pasted.path[0].coord(), original.path[0].coord() + offset);
+        assert!(pasted
+            .paths()
+            .flatten()
+            .all(|c| original.paths().flatten().all(|o| o.id != c.id)));
+        let pasted = scene.clone();
+
+        handler.undo().unwrap();
+        handler.undo().unwrap();
+        assert_eq!(*handler.scene(), expected);
+        handler.redo().unwrap();
+        handler.redo().unwrap();
+        assert_eq!(*handler.scene(), pasted);
+    }
+}
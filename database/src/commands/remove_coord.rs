@@ -80,12 +80,13 @@ mod test {
             let mut scene = Scene::new();
             let shape = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.5, 0.5));
 
-            let coord_id = shape.path[i].id;
-            let coord = shape.path[i].coord();
-
             let shape_id = scene.shape_insert(shape);
             let expected = scene.clone();
 
+            let inserted = scene.shape_select(shape_id).unwrap();
+            let coord_id = inserted.path[i].id;
+            let coord = inserted.path[i].coord();
+
             let mut command = RemoveCoord::new(shape_id, coord_id);
             command.execute(&mut scene).unwrap();
 
@@ -0,0 +1,72 @@
+use super::Command;
+use crate::{LayerId, Scene};
+use anyhow::{Context, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+#[derive(Clone, Debug)]
+pub struct RenameLayer {
+    layer: LayerId,
+    name: String,
+    old_name: Option<String>,
+}
+
+impl RenameLayer {
+    #[boxed]
+    pub fn new(layer: LayerId, name: String) -> Self {
+        Self {
+            layer,
+            name,
+            old_name: None,
+        }
+    }
+}
+
+impl Command for RenameLayer {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        let layer = scene.layer_mut(self.layer).context("Layer not found")?;
+        self.old_name = Some(std::mem::replace(&mut layer.name, self.name.clone()));
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        let name = self
+            .old_name
+            .take()
+            .context("Undoing a command that was not executed")?;
+        let layer = scene.layer_mut(self.layer).context("Layer not found")?;
+        layer.name = name;
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{commands::CommandsHandler, Scene, Shape};
+
+    use super::RenameLayer;
+
+    #[test]
+    fn given_layer_when_rename_then_name_in_tree_view_and_undo() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new());
+        let mut command_handler = CommandsHandler::from(scene);
+
+        command_handler
+            .execute(RenameLayer::boxed(id, "Petal".to_string()))
+            .unwrap();
+        assert_eq!(command_handler.scene().get_tree_view()[0].name, "Petal");
+
+        command_handler.undo().unwrap();
+        let name = &command_handler.scene().get_tree_view()[0].name;
+        assert_eq!(*name, format!("Shape {}", id.value()));
+    }
+}
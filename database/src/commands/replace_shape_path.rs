@@ -0,0 +1,97 @@
+use super::Command;
+use crate::{LayerId, Shape};
+use anyhow::{Context, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Replace the subpaths and the fill rule of a shape, the style of the shape is kept
+#[derive(Clone, Debug)]
+pub struct ReplaceShapePath {
+    shape: LayerId,
+    paths: Shape,
+    paths_to_undo: Option<Shape>,
+}
+
+impl ReplaceShapePath {
+    #[boxed]
+    pub fn new(shape: LayerId, paths: Shape) -> Self {
+        Self {
+            shape,
+            paths,
+            paths_to_undo: None,
+        }
+    }
+}
+
+impl Command for ReplaceShapePath {
+    fn execute(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let shape = scene
+            .shape_select_mut(self.shape)
+            .context("Shape not found")?;
+        self.paths_to_undo = Some(shape.clone());
+        shape.paths_replace(self.paths.clone());
+        scene.shape_coords_adopt(self.shape);
+
+        // Keep the ids of the scene to have the same coords on redo
+        self.paths = scene
+            .shape_select(self.shape)
+            .context("Shape not found")?
+            .clone();
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let shape = scene
+            .shape_select_mut(self.shape)
+            .context("Shape not found")?;
+        shape.paths_replace(self.paths_to_undo.take().context("No path to undo")?);
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        types::{Coord, Length2d},
+        Rgba,
+    };
+
+    use crate::{commands::Command, Scene, Shape};
+
+    use super::ReplaceShapePath;
+
+    #[test]
+    fn given_shape_when_replace_path_then_style_kept_and_undo() {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.5, 0.5));
+        shape.fill = Rgba::red().into();
+        let shape_id = scene.shape_insert(shape);
+        let expected = scene.clone();
+
+        let mut paths = Shape::new_circle(Coord::new(0.2, 0.0), Length2d::new(0.1, 0.1));
+        paths.fill = Rgba::blue().into();
+        let mut command = ReplaceShapePath::new(shape_id, paths);
+        command.execute(&mut scene).unwrap();
+
+        let shape = scene.shape_select(shape_id).unwrap();
+        assert_eq!(shape.fill, Rgba::red().into());
+        assert!(shape.contains(&Coord::new(0.2, 0.03)));
+        assert!(!shape.contains(&Coord::new(-0.3, 0.03)));
+        assert!(shape.path.iter().all(|c| !c.id.is_provisional()));
+        let replaced = scene.clone();
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+
+        command.execute(&mut scene).unwrap();
+        assert_eq!(scene, replaced);
+    }
+}
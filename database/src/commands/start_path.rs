@@ -0,0 +1,83 @@
+use super::Command;
+use crate::{scene::shape::Stroke, DbCoord, LayerId, Shape};
+use anyhow::{Context, Ok, Result};
+use common::{types::Coord, Rgba};
+use macros::boxed;
+use std::any::Any;
+
+/// Create an open path with a single anchor on top of the layers
+#[derive(Clone, Debug)]
+pub struct StartPath {
+    shape: Shape,
+}
+
+impl StartPath {
+    #[boxed]
+    pub fn new(shape_id: LayerId, anchor: Coord, color: Rgba, stroke: Stroke) -> Self {
+        let mut shape = Shape::new();
+        shape.id = shape_id;
+        shape.path.push(DbCoord::from(anchor));
+        shape.fill = color.into();
+        shape.stroke = stroke;
+        Self { shape }
+    }
+}
+
+impl Command for StartPath {
+    fn execute(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        let id = scene.shape_insert(self.shape.clone());
+        scene.layer_move_top(id);
+        // Keep the ids of the scene to have the same coords on redo
+        self.shape = scene.shape_select(id).context("Shape not found")?.clone();
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut crate::Scene) -> Result<()> {
+        scene
+            .shape_select(self.shape.id)
+            .context("Shape not found")?;
+        scene.layer_delete(self.shape.id);
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{types::Coord, Rgba};
+
+    use crate::{commands::Command, scene::shape::Stroke, LayerId, Scene};
+
+    use super::StartPath;
+
+    #[test]
+    fn given_scene_when_start_path_then_open_shape_on_top() {
+        let mut scene = Scene::new();
+        let expected = scene.clone();
+        let id = LayerId::from(7);
+
+        let mut command = StartPath::new(
+            id,
+            Coord::new(0.5, 0.5),
+            Rgba::transparent(),
+            Stroke::new(0.01, Rgba::black()),
+        );
+        command.execute(&mut scene).unwrap();
+
+        let shape = scene.shape_select(id).unwrap();
+        assert_eq!(shape.path.len(), 1);
+        assert!(!shape.is_closed());
+        assert!(!shape.path[0].id.is_provisional());
+        assert_eq!(scene.layer_position(id), Some(0));
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+    }
+}
@@ -1,5 +1,5 @@
 use super::Command;
-use crate::{CoordId, DbCoord, LayerId};
+use crate::{scene::shape::cubic_path::path_curves_len, CoordId, DbCoord, LayerId};
 use anyhow::{Context, Ok, Result};
 use macros::boxed;
 use std::any::Any;
@@ -8,7 +8,7 @@ use std::any::Any;
 pub struct ToggleHandle {
     shapes: LayerId,
     coord: CoordId,
-    cp_to_undo: Option<(DbCoord, DbCoord)>,
+    cp_to_undo: Option<(DbCoord, Option<DbCoord>)>,
 }
 
 impl ToggleHandle {
@@ -31,12 +31,15 @@ impl Command for ToggleHandle {
             .curve_select_of_coord_id(self.coord)
             .context("Curve not found")?;
         let curve = shape.curve_select(curve_index).context("Curve not found")?;
-        let curve_after = shape
-            .curve_select((curve_index + 1) % shape.curves_len())
-            .context("Curve not found")?;
-        self.cp_to_undo = Some((curve.cp1.clone(), curve_after.cp0.clone()));
+        // The end of an open subpath has no curve after
+        let cp_after = match shape.curve_next(curve_index) {
+            Some(next) => Some(*shape.curve_select(next).context("Curve not found")?.cp0),
+            None => None,
+        };
+        self.cp_to_undo = Some((curve.cp1.clone(), cp_after));
 
         shape.toggle_separate_join_handle(curve_index);
+        scene.shape_coords_adopt(self.shapes);
 
         Ok(())
     }
@@ -49,12 +52,15 @@ impl Command for ToggleHandle {
         let curve_index = shape
             .curve_select_of_coord_id(self.coord)
             .context("Curve not found")?;
-        let path_len = shape.path.len();
-        let curves_len = shape.curves_len();
-        shape.path[(curve_index * 3 + 2) % path_len] =
-            self.cp_to_undo.as_ref().context("No coord to undo")?.0;
-        shape.path[((curve_index + 1) % curves_len * 3 + 1) % path_len] =
-            self.cp_to_undo.as_ref().context("No coord to undo")?.1;
+        let (subpath, curve_index) = shape.curve_locate(curve_index).context("Curve not found")?;
+        let path = shape.subpath_mut(subpath);
+        let path_len = path.len();
+        let curves_len = path_curves_len(path);
+        let (cp_before, cp_after) = self.cp_to_undo.context("No coord to undo")?;
+        path[(curve_index * 3 + 2) % path_len] = cp_before;
+        if let Some(cp_after) = cp_after {
+            path[((curve_index + 1) % curves_len * 3 + 1) % path_len] = cp_after;
+        }
 
         Ok(())
     }
@@ -84,9 +90,9 @@ mod test {
     fn given_circle_when_toggle_coord() {
         let mut scene = Scene::new();
         let shape = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.5, 0.5));
-        let coord_id = shape.path[0].id;
-        let expected = shape.path.clone();
         let shape_id = scene.shape_insert(shape);
+        let expected = scene.shape_select(shape_id).unwrap().path.clone();
+        let coord_id = expected[0].id;
 
         let mut command = ToggleHandle::new(shape_id, coord_id);
         command.execute(&mut scene).unwrap();
@@ -113,9 +119,9 @@ mod test {
             ],
             Affine::identity(),
         );
-        let expected = shape.path.clone();
-        let coord_id = shape.path[0].id;
         let shape_id = scene.shape_insert(shape);
+        let expected = scene.shape_select(shape_id).unwrap().path.clone();
+        let coord_id = expected[0].id;
 
         let mut command = ToggleHandle::new(shape_id, coord_id);
         command.execute(&mut scene).unwrap();
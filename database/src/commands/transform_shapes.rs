@@ -0,0 +1,236 @@
+use super::Command;
+use crate::{LayerId, Scene, Shape};
+use anyhow::{Context, Result};
+use common::{
+    pures::Affine,
+    types::{Coord, Rect},
+};
+use macros::boxed;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use tsify::Tsify;
+
+/// Point that stays in place when the transform is applied
+#[derive(Tsify, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum Pivot {
+    /// Centre of the bounding box of the shapes
+    Center,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Custom(Coord),
+}
+
+impl Pivot {
+    pub fn resolve(&self, bounds: &Rect) -> Coord {
+        match self {
+            Pivot::Center => bounds.center(),
+            Pivot::TopLeft => bounds.top_left,
+            Pivot::TopRight => Coord::new(bounds.bottom_right.x, bounds.top_left.y),
+            Pivot::BottomLeft => Coord::new(bounds.top_left.x, bounds.bottom_right.y),
+            Pivot::BottomRight => bounds.bottom_right,
+            Pivot::Custom(coord) => *coord,
+        }
+    }
+}
+
+/// Apply an affine transform to every coord of the shapes, around a pivot
+#[derive(Clone, Debug)]
+pub struct TransformShapes {
+    shapes: Vec<LayerId>,
+    transform: Affine,
+    pivot: Pivot,
+    scale_stroke: bool,
+    /// Transform with the pivot of the last execute
+    applied: Affine,
+    shapes_to_undo: Vec<Shape>,
+}
+
+impl TransformShapes {
+    /// With scale_stroke, the stroke size follows the change of area of the transform
+    #[boxed]
+    pub fn new(shapes: Vec<LayerId>, transform: Affine, pivot: Pivot, scale_stroke: bool) -> Self {
+        Self {
+            shapes,
+            transform,
+            pivot,
+            scale_stroke,
+            applied: Affine::identity(),
+            shapes_to_undo: Vec::new(),
+        }
+    }
+}
+
+impl Command for TransformShapes {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        let pivot = match self.pivot {
+            Pivot::Custom(coord) => coord,
+            pivot => match scene.shapes_bounding_box(&self.shapes) {
+                Some(bounds) => pivot.resolve(&bounds),
+                None => return Ok(()),
+            },
+        };
+        let applied = (self.transform * Affine::from_translate(pivot * -1.0)).translate(pivot);
+        let stroke_factor = applied.determinant().abs().sqrt();
+
+        let mut shapes_to_undo = Vec::with_capacity(self.shapes.len());
+        for id in &self.shapes {
+            let shape = scene.shape_select_mut(*id).context("Shape not found")?;
+            shapes_to_undo.push(shape.clone());
+            shape.transform(&applied);
+            if self.scale_stroke {
+                shape.stroke.size *= stroke_factor;
+            }
+        }
+        self.applied = applied;
+        self.shapes_to_undo = shapes_to_undo;
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        for old in self.shapes_to_undo.drain(..) {
+            let shape = scene.shape_select_mut(old.id).context("Shape not found")?;
+            shape.stroke = old.stroke.clone();
+            shape.fill = old.fill.clone();
+            shape.paths_replace(old);
+        }
+        Ok(())
+    }
+
+    fn merge(&self, futur: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        if let Some(other) = futur.as_any().downcast_ref::<TransformShapes>() {
+            if self.shapes == other.shapes && self.scale_stroke == other.scale_stroke {
+                // The pivots are already in the applied transforms
+                let new_self = TransformShapes {
+                    shapes: self.shapes.clone(),
+                    transform: other.applied * self.applied,
+                    pivot: Pivot::Custom(Coord::new(0.0, 0.0)),
+                    scale_stroke: self.scale_stroke,
+                    applied: other.applied * self.applied,
+                    shapes_to_undo: self.shapes_to_undo.clone(),
+                };
+                return Some(Ok(Box::new(new_self)));
+            }
+        }
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::{Affine, Vec2},
+        types::{Coord, Length2d},
+        Rgba,
+    };
+
+    use crate::{
+        commands::{Command, CommandsHandler},
+        scene::shape::Stroke,
+        Scene, Shape,
+    };
+
+    use super::{Pivot, TransformShapes};
+
+    fn assert_near(a: Coord, b: Coord) {
+        assert!(a.distance(&b) < 0.001, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn given_circle_when_scale_at_center_then_center_kept_and_undo() {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new_circle(Coord::new(1.0, 1.0), Length2d::new(0.5, 0.5));
+        shape.stroke = Stroke::new(0.1, Rgba::black());
+        let shape_id = scene.shape_insert(shape);
+        let expected = scene.clone();
+
+        let transform = Affine::from_scale(Coord::new(2.0, 2.0));
+        let mut command = TransformShapes::new(vec![shape_id], transform, Pivot::Center, true);
+        command.execute(&mut scene).unwrap();
+
+        let shape = scene.shape_select(shape_id).unwrap();
+        let rect = shape.bounding_box().unwrap();
+        assert_near(rect.center(), Coord::new(1.0, 1.0));
+        assert!((rect.width() - 2.0).abs() < 0.001);
+        assert!((shape.stroke.size - 0.2).abs() < 0.0001);
+
+        command.undo(&mut scene).unwrap();
+        assert_eq!(scene, expected);
+    }
+
+    #[test]
+    fn given_square_when_rotate_at_corner_then_corner_kept() {
+        let mut scene = Scene::new();
+        let shape = Shape::quick_from_string(
+            "M 0 0 C 0 0 1 0 1 0 C 1 0 1 1 1 1 C 1 1 0 1 0 1 C 0 1 0 0 0 0 Z",
+        );
+        let shape_id = scene.shape_insert(shape);
+
+        let transform = Affine::from_rotation(std::f32::consts::FRAC_PI_2);
+        let mut command =
+            TransformShapes::new(vec![shape_id], transform, Pivot::BottomRight, false);
+        command.execute(&mut scene).unwrap();
+
+        let shape = scene.shape_select(shape_id).unwrap();
+        assert_near(shape.path[0].coord(), Coord::new(2.0, 0.0));
+        assert_near(shape.path[6].coord(), Coord::new(1.0, 1.0));
+        assert_eq!(shape.stroke.size, 0.0);
+    }
+
+    #[test]
+    fn given_drag_when_execute_twice_then_one_undo_step() {
+        let mut scene = Scene::new();
+        let shape_id = scene.shape_insert(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            Length2d::new(0.5, 0.5),
+        ));
+        let expected = scene.clone();
+        let mut handler = CommandsHandler::from(scene);
+
+        let scale = Affine::from_scale(Coord::new(2.0, 1.0));
+        let translate = Affine::from_translate(Coord::new(0.5, 0.0));
+        handler
+            .execute(TransformShapes::boxed(
+                vec![shape_id],
+                scale,
+                Pivot::TopLeft,
+                false,
+            ))
+            .unwrap();
+        handler
+            .execute(TransformShapes::boxed(
+                vec![shape_id],
+                translate,
+                Pivot::TopLeft,
+                false,
+            ))
+            .unwrap();
+        let transformed = handler.scene().clone();
+        let rect = transformed
+            .shape_select(shape_id)
+            .unwrap()
+            .bounding_box()
+            .unwrap();
+        assert_near(rect.top_left, Coord::new(0.0, -0.5));
+        assert_near(rect.bottom_right, Coord::new(2.0, 0.5));
+
+        handler.undo().unwrap();
+        assert_eq!(handler.scene(), &expected);
+
+        handler.redo().unwrap();
+        let rect = handler
+            .scene()
+            .shape_select(shape_id)
+            .unwrap()
+            .bounding_box()
+            .unwrap();
+        assert_near(rect.top_left, Coord::new(0.0, -0.5));
+        assert_near(rect.bottom_right, Coord::new(2.0, 0.5));
+    }
+}
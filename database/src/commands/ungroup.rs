@@ -0,0 +1,127 @@
+use super::Command;
+use crate::{
+    scene::{Layer, LayerPlace, LayerType},
+    LayerId, Scene,
+};
+use anyhow::{Context, Error, Ok, Result};
+use macros::boxed;
+use std::any::Any;
+
+/// Replace the folders by their layers, at the place of the folder
+#[derive(Clone, Debug)]
+pub struct Ungroup {
+    folders: Vec<LayerId>,
+    /// The removed folders with their places, in the order they were removed
+    undo_folders: Vec<(LayerPlace, Layer)>,
+}
+
+impl Ungroup {
+    #[boxed]
+    pub fn new(folders: Vec<LayerId>) -> Self {
+        Self {
+            folders,
+            undo_folders: Vec::new(),
+        }
+    }
+}
+
+impl Command for Ungroup {
+    fn execute(&mut self, scene: &mut Scene) -> Result<()> {
+        for folder in &self.folders {
+            match scene.layer_select(*folder) {
+                Some(LayerType::Folder(_)) => {}
+                Some(LayerType::Shape(_)) => return Err(anyhow::anyhow!("Layer is not a folder")),
+                None => return Err(anyhow::anyhow!("Folder not found")),
+            }
+        }
+
+        self.undo_folders.clear();
+        for folder in &self.folders {
+            let (place, layer) = scene.layer_remove(*folder).context("Folder not found")?;
+            if let LayerType::Folder(children) = &layer.value {
+                for (index, child) in children.iter().enumerate() {
+                    let child_place = LayerPlace {
+                        parent: place.parent,
+                        index: place.index + index,
+                    };
+                    scene
+                        .layer_insert_at(child_place, child.clone())
+                        .map_err(Error::msg)?;
+                }
+            }
+            self.undo_folders.push((place, layer));
+        }
+        Ok(())
+    }
+
+    fn undo(&mut self, scene: &mut Scene) -> Result<()> {
+        for (place, layer) in self.undo_folders.iter().rev() {
+            if let LayerType::Folder(children) = &layer.value {
+                for child in children {
+                    scene.layer_delete(child.id);
+                }
+            }
+            scene
+                .layer_insert_at(*place, layer.clone())
+                .map_err(Error::msg)?;
+        }
+        Ok(())
+    }
+
+    fn merge(&self, _: &dyn Command) -> Option<Result<Box<dyn Command>>> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        commands::{CommandsHandler, Group},
+        LayerId, Scene, Shape,
+    };
+
+    use super::Ungroup;
+
+    #[test]
+    fn given_group_when_ungroup_then_layers_at_folder_place_and_undo() {
+        let mut scene = Scene::new();
+        let ids: Vec<LayerId> = (0..4).map(|_| scene.shape_insert(Shape::new())).collect();
+        let mut command_handler = CommandsHandler::from(scene);
+        command_handler
+            .execute(Group::boxed(vec![ids[1], ids[2]]))
+            .unwrap();
+        let grouped = command_handler.scene().clone();
+        let folder = grouped.layer_parent(ids[1]).unwrap();
+
+        command_handler
+            .execute(Ungroup::boxed(vec![folder]))
+            .unwrap();
+
+        let scene = command_handler.scene();
+        let tree: Vec<(usize, usize)> = scene
+            .get_tree_view()
+            .iter()
+            .map(|layer| (layer.layer_id, layer.level))
+            .collect();
+        let expected: Vec<(usize, usize)> = ids.iter().map(|id| (id.value(), 0)).collect();
+        assert_eq!(tree, expected);
+
+        command_handler.undo().unwrap();
+        assert_eq!(*command_handler.scene(), grouped);
+    }
+
+    #[test]
+    fn given_shape_when_ungroup_then_error_and_scene_unchanged() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new());
+        let expected = scene.clone();
+        let mut command_handler = CommandsHandler::from(scene);
+
+        assert!(command_handler.execute(Ungroup::boxed(vec![id])).is_err());
+        assert_eq!(*command_handler.scene(), expected);
+    }
+}
@@ -49,13 +49,15 @@ impl Command for Union {
                 scene.layer_delete(self.b);
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = self.b_shape.as_ref().unwrap().path.clone();
+                a_shape.paths_replace(self.b_shape.as_ref().unwrap().clone());
+                scene.shape_coords_adopt(self.a);
             }
             ShapeUnion::New(shape) => {
                 scene.layer_delete(self.b);
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = shape.path;
+                a_shape.paths_replace(shape);
+                scene.shape_coords_adopt(self.a);
             }
             ShapeUnion::None => {
                 self.b_shape_pos = None;
@@ -84,7 +86,7 @@ impl Command for Union {
                     .expect("not 404");
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = a_shape_undo.path;
+                a_shape.paths_replace(a_shape_undo);
             }
             ShapeUnion::New(_) => {
                 let id = scene.shape_insert(b_shape);
@@ -93,7 +95,7 @@ impl Command for Union {
                     .expect("not 404");
 
                 let a_shape = scene.shape_select_mut(self.a).expect("not 404");
-                a_shape.path = a_shape_undo.path;
+                a_shape.paths_replace(a_shape_undo);
             }
             ShapeUnion::None => {}
         };
@@ -0,0 +1,91 @@
+/*
+Migration chain of the payload.
+Each step decodes the payload with the model of its version, converts it to the model
+of the next version and encodes it again. The last version is decoded in the current model.
+The models of the older versions are frozen copies kept only for the migration.
+*/
+
+use std::borrow::Cow;
+
+use crate::SceneUserContext;
+
+use super::{
+    v1::SceneUserContextV1, v2::SceneUserContextV2, v3::SceneUserContextV3, v4::SceneUserContextV4,
+    v5::SceneUserContextV5, v6::SceneUserContextV6, FileFormatError, CURRENT_VERSION,
+};
+
+/// Upgrade the payload of a version to the payload of the next version
+type Migration = fn(&[u8]) -> Result<Vec<u8>, FileFormatError>;
+
+/// `MIGRATIONS[n]` upgrades a payload of the version `n` to the version `n + 1`
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [
+    v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7,
+];
+
+pub fn migrate(version: u16, payload: &[u8]) -> Result<SceneUserContext, FileFormatError> {
+    let mut payload = Cow::Borrowed(payload);
+    for migration in MIGRATIONS.iter().skip(version as usize) {
+        payload = Cow::Owned(migration(&payload)?);
+    }
+
+    let mut context = postcard::from_bytes::<SceneUserContext>(&payload)
+        .map_err(|e| FileFormatError::CorruptPayload(e.to_string()))?;
+    let scene = context.command_handler.unsafe_scene_mut();
+    scene.ids_reseed();
+    scene.index_refresh();
+    Ok(context)
+}
+
+/// The version 0 is the payload saved without header, the model is the same
+fn v0_to_v1(payload: &[u8]) -> Result<Vec<u8>, FileFormatError> {
+    Ok(payload.to_vec())
+}
+
+/// The version 2 adds the id allocator of the scene
+fn v1_to_v2(payload: &[u8]) -> Result<Vec<u8>, FileFormatError> {
+    let context = postcard::from_bytes::<SceneUserContextV1>(payload)
+        .map_err(|e| FileFormatError::CorruptPayload(e.to_string()))?;
+    postcard::to_allocvec(&SceneUserContextV2::from(context))
+        .map_err(|e| FileFormatError::Serialization(e.to_string()))
+}
+
+/// The version 3 adds the subpaths and the fill rule of the shapes
+fn v2_to_v3(payload: &[u8]) -> Result<Vec<u8>, FileFormatError> {
+    let context = postcard::from_bytes::<SceneUserContextV2>(payload)
+        .map_err(|e| FileFormatError::CorruptPayload(e.to_string()))?;
+    postcard::to_allocvec(&SceneUserContextV3::from(context))
+        .map_err(|e| FileFormatError::Serialization(e.to_string()))
+}
+
+/// The version 4 replaces the colors of the fill and the stroke by paints with gradients
+fn v3_to_v4(payload: &[u8]) -> Result<Vec<u8>, FileFormatError> {
+    let context = postcard::from_bytes::<SceneUserContextV3>(payload)
+        .map_err(|e| FileFormatError::CorruptPayload(e.to_string()))?;
+    postcard::to_allocvec(&SceneUserContextV4::from(context))
+        .map_err(|e| FileFormatError::Serialization(e.to_string()))
+}
+
+/// The version 5 adds the join, the cap, the miter limit, the dashes and the alignment of the
+/// strokes
+fn v4_to_v5(payload: &[u8]) -> Result<Vec<u8>, FileFormatError> {
+    let context = postcard::from_bytes::<SceneUserContextV4>(payload)
+        .map_err(|e| FileFormatError::CorruptPayload(e.to_string()))?;
+    postcard::to_allocvec(&SceneUserContextV5::from(context))
+        .map_err(|e| FileFormatError::Serialization(e.to_string()))
+}
+
+/// The version 6 makes the layers a tree, a folder has its own layers
+fn v5_to_v6(payload: &[u8]) -> Result<Vec<u8>, FileFormatError> {
+    let context = postcard::from_bytes::<SceneUserContextV5>(payload)
+        .map_err(|e| FileFormatError::CorruptPayload(e.to_string()))?;
+    postcard::to_allocvec(&SceneUserContextV6::from(context))
+        .map_err(|e| FileFormatError::Serialization(e.to_string()))
+}
+
+/// The version 7 adds the visibility, the lock and the opacity of the layers
+fn v6_to_v7(payload: &[u8]) -> Result<Vec<u8>, FileFormatError> {
+    let context = postcard::from_bytes::<SceneUserContextV6>(payload)
+        .map_err(|e| FileFormatError::CorruptPayload(e.to_string()))?;
+    postcard::to_allocvec(&SceneUserContext::from(context))
+        .map_err(|e| FileFormatError::Serialization(e.to_string()))
+}
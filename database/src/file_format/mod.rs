@@ -0,0 +1,381 @@
+/*
+The .mvg file format.
+
+A file is a header followed by the postcard payload of a SceneUserContext.
+    magic: 4 bytes, "MVG\0"
+    version: u16 little endian
+    checksum: u32 little endian, CRC-32 of the payload
+    payload
+
+The payload of an older version is upgraded to the current model with the migration chain.
+The files saved before the header existed are read as the version 0.
+*/
+
+use std::fmt::Display;
+
+use crate::SceneUserContext;
+
+mod migration;
+mod v1;
+mod v2;
+mod v3;
+mod v4;
+mod v5;
+mod v6;
+
+pub const MAGIC: [u8; 4] = *b"MVG\0";
+pub const CURRENT_VERSION: u16 = 7;
+const HEADER_LEN: usize = MAGIC.len() + 2 + 4;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileFormatError {
+    /// The data is not a .mvg file
+    BadMagic,
+    /// The file was saved by a newer version of the application
+    UnsupportedVersion { found: u16, supported: u16 },
+    /// The payload does not match the checksum of the header
+    ChecksumMismatch { expected: u32, found: u32 },
+    /// The payload can't be decoded in the model of its version
+    CorruptPayload(String),
+    /// The scene can't be encoded
+    Serialization(String),
+}
+
+impl Display for FileFormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileFormatError::BadMagic => write!(f, "Not a .mvg file"),
+            FileFormatError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "Unsupported file version {}, the latest supported version is {}",
+                found, supported
+            ),
+            FileFormatError::ChecksumMismatch { expected, found } => write!(
+                f,
+                "Checksum mismatch, expected {:08x} but found {:08x}",
+                expected, found
+            ),
+            FileFormatError::CorruptPayload(message) => write!(f, "Corrupt payload: {}", message),
+            FileFormatError::Serialization(message) => {
+                write!(f, "Serialization failed: {}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FileFormatError {}
+
+/// Encode the scene context with the header of the current version
+pub fn encode(context: &SceneUserContext) -> Result<Vec<u8>, FileFormatError> {
+    let payload = postcard::to_allocvec(context)
+        .map_err(|e| FileFormatError::Serialization(e.to_string()))?;
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(&MAGIC);
+    bytes.extend_from_slice(&CURRENT_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Decode a file of any supported version in the current model
+pub fn decode(bytes: &[u8]) -> Result<SceneUserContext, FileFormatError> {
+    let Some(rest) = bytes.strip_prefix(&MAGIC) else {
+        // Files without header are from version 0 or not .mvg files at all
+        return migration::migrate(0, bytes).map_err(|_| FileFormatError::BadMagic);
+    };
+
+    if rest.len() < HEADER_LEN - MAGIC.len() {
+        return Err(FileFormatError::CorruptPayload(
+            "Header is truncated".to_string(),
+        ));
+    }
+    let version = u16::from_le_bytes([rest[0], rest[1]]);
+    let expected = u32::from_le_bytes([rest[2], rest[3], rest[4], rest[5]]);
+    let payload = &rest[6..];
+
+    if version > CURRENT_VERSION {
+        return Err(FileFormatError::UnsupportedVersion {
+            found: version,
+            supported: CURRENT_VERSION,
+        });
+    }
+
+    let found = crc32fast::hash(payload);
+    if found != expected {
+        return Err(FileFormatError::ChecksumMismatch { expected, found });
+    }
+
+    migration::migrate(version, payload)
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        types::{Coord, ScreenLength2d},
+        Rgba,
+    };
+
+    use crate::{
+        user_context::clipboard::Clipboard, CoordId, GradientUnits, LayerId, LineJoin, Paint,
+        SceneUserContext, Shape, UserSelection,
+    };
+
+    use super::{
+        decode, encode,
+        v1::{CameraV1, RenderOptionV1},
+        v2::{DbCoordV2, IdAllocatorV2},
+        v3::FillRuleV3,
+        v4::{GradientStopV4, GradientUnitsV4, LinearGradientV4, PaintV4},
+        v5::{LineCapV5, LineJoinV5, ShapeV5, StrokeAlignV5, StrokeV5},
+        v6::{CommandsHandlerV6, LayerTypeV6, LayerV6, SceneUserContextV6, SceneV6},
+        FileFormatError, CURRENT_VERSION, MAGIC,
+    };
+
+    fn version_6_file(layers: Vec<LayerV6>) -> Vec<u8> {
+        let context = SceneUserContextV6 {
+            command_handler: CommandsHandlerV6 {
+                scene: SceneV6 {
+                    background: Rgba::white(),
+                    layers,
+                    ids: IdAllocatorV2 {
+                        next_layer: 1,
+                        next_coord: 1,
+                    },
+                },
+            },
+            render_options: RenderOptionV1 {
+                to_layer: None,
+                skip_layers: Vec::new(),
+                only_layers: Vec::new(),
+            },
+            camera: CameraV1 {
+                position: Coord::new(0.0, 0.0),
+                scaling: 1.0,
+                rotation: 0.0,
+                reflect_x: false,
+                reflect_y: false,
+                base_scale: ScreenLength2d::new(500.0, 500.0),
+            },
+        };
+        let payload = postcard::to_allocvec(&context).unwrap();
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&6u16.to_le_bytes());
+        bytes.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        bytes.extend_from_slice(&payload);
+        bytes
+    }
+
+    #[test]
+    fn given_scene_when_encode_decode_then_same_scene() {
+        let context = SceneUserContext::default();
+
+        let bytes = encode(&context).unwrap();
+        assert!(bytes.starts_with(&MAGIC));
+
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.scene(), context.scene());
+    }
+
+    #[test]
+    fn given_legacy_file_without_header_when_decode_then_loaded() {
+        let bytes = include_bytes!("../../../Flower-demo.mvg");
+
+        let context = decode(bytes).unwrap();
+
+        assert_eq!(context.scene().get_tree_view().len(), 6);
+    }
+
+    #[test]
+    fn given_legacy_file_when_decode_then_colors_are_solid_paints() {
+        let bytes = include_bytes!("../../../Flower-demo.mvg");
+
+        let context = decode(bytes).unwrap();
+
+        let scene = context.scene();
+        let shapes: Vec<&Shape> = scene
+            .get_tree_view()
+            .iter()
+            .filter_map(|layer| scene.shape_select(LayerId::from(layer.layer_id)))
+            .collect();
+        assert!(!shapes.is_empty());
+        for shape in shapes {
+            assert!(matches!(shape.fill, Paint::Solid(_)));
+            assert!(matches!(shape.stroke.paint, Paint::Solid(_)));
+        }
+    }
+
+    #[test]
+    fn given_legacy_file_when_insert_shape_then_new_layer_id() {
+        let bytes = include_bytes!("../../../Flower-demo.mvg");
+        let mut context = decode(bytes).unwrap();
+        let max_id = context
+            .scene()
+            .get_tree_view()
+            .iter()
+            .map(|layer| layer.layer_id)
+            .max()
+            .unwrap();
+
+        let id = context
+            .command_handler
+            .unsafe_scene_mut()
+            .shape_insert(Shape::new());
+
+        assert!(id.value() > max_id);
+    }
+
+    #[test]
+    fn given_version_6_folders_when_decode_then_visible_nested_layers() {
+        let folder = |id: usize, layers| LayerV6 {
+            id: LayerId::from(id),
+            name: format!("Folder {}", id),
+            value: LayerTypeV6::Folder(layers),
+        };
+        let bytes = version_6_file(vec![folder(1, vec![folder(2, Vec::new())])]);
+
+        let decoded = decode(&bytes).unwrap();
+
+        let tree = decoded.scene().get_tree_view();
+        assert_eq!(tree.len(), 2);
+        assert_eq!((tree[1].layer_id, tree[1].level), (2, 1));
+        assert!(tree.iter().all(|layer| layer.visible && !layer.locked));
+        assert!(tree.iter().all(|layer| layer.opacity == 1.0));
+    }
+
+    #[test]
+    fn given_clipboard_of_version_6_when_paste_then_migrated_layers() {
+        let folder = |id: usize, layers| LayerV6 {
+            id: LayerId::from(id),
+            name: format!("Folder {}", id),
+            value: LayerTypeV6::Folder(layers),
+        };
+        let clipboard = Clipboard {
+            data: version_6_file(vec![folder(1, vec![folder(2, Vec::new())])]),
+            // Without data, the empty document would be an error
+            svg: String::new(),
+        };
+        let mut context = SceneUserContext::new(500.0, 500.0);
+
+        context
+            .paste(
+                &mut UserSelection::default(),
+                &clipboard,
+                Coord::new(0.0, 0.0),
+            )
+            .unwrap();
+
+        let tree = context.scene().get_tree_view();
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[1].level, 1);
+    }
+
+    #[test]
+    fn given_version_6_gradient_and_round_join_when_decode_then_same_paint_and_stroke() {
+        let coord = |id: usize, x: f32, y: f32| DbCoordV2 {
+            id: CoordId::from(id),
+            coord: Coord::new(x, y),
+        };
+        let stops = vec![
+            GradientStopV4 {
+                offset: 0.0,
+                color: Rgba::black(),
+            },
+            GradientStopV4 {
+                offset: 1.0,
+                color: Rgba::white(),
+            },
+        ];
+        let shape = ShapeV5 {
+            id: LayerId::from(1),
+            path: vec![coord(1, 0.0, 0.0), coord(2, 0.5, 0.0), coord(3, 0.0, 0.5)],
+            subpaths: Vec::new(),
+            fill_rule: FillRuleV3::NonZero,
+            fill: PaintV4::LinearGradient(LinearGradientV4 {
+                start: Coord::new(0.0, 0.0),
+                end: Coord::new(1.0, 1.0),
+                stops,
+                units: GradientUnitsV4::Scene,
+            }),
+            stroke: StrokeV5 {
+                size: 0.1,
+                paint: PaintV4::Solid(Rgba::black()),
+                join: LineJoinV5::Round,
+                cap: LineCapV5::Butt,
+                miter_limit: 4.0,
+                dashes: vec![0.1, 0.2],
+                dash_offset: 0.0,
+                align: StrokeAlignV5::Center,
+            },
+        };
+        let bytes = version_6_file(vec![LayerV6 {
+            id: LayerId::from(1),
+            name: "Shape 1".to_string(),
+            value: LayerTypeV6::Shape(shape),
+        }]);
+
+        let decoded = decode(&bytes).unwrap();
+
+        let shape = decoded.scene().shape_select(LayerId::from(1)).unwrap();
+        assert_eq!(shape.path[1].id, CoordId::from(2));
+        assert_eq!(shape.path[1].coord(), Coord::new(0.5, 0.0));
+        let Paint::LinearGradient(gradient) = &shape.fill else {
+            panic!("The fill is not a linear gradient");
+        };
+        assert_eq!(gradient.units, GradientUnits::Scene);
+        assert_eq!(gradient.stops[1].color, Rgba::white());
+        assert_eq!(shape.stroke.join, LineJoin::Round);
+        assert_eq!(shape.stroke.dashes, vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn given_random_bytes_when_decode_then_bad_magic() {
+        assert!(matches!(decode(&[]), Err(FileFormatError::BadMagic)));
+        assert!(matches!(
+            decode(b"<svg></svg>"),
+            Err(FileFormatError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn given_newer_version_when_decode_then_unsupported_version() {
+        let mut bytes = encode(&SceneUserContext::default()).unwrap();
+        bytes[4..6].copy_from_slice(&(CURRENT_VERSION + 1).to_le_bytes());
+
+        assert!(matches!(
+            decode(&bytes),
+            Err(FileFormatError::UnsupportedVersion { found, supported })
+                if found == CURRENT_VERSION + 1 && supported == CURRENT_VERSION
+        ));
+    }
+
+    #[test]
+    fn given_modified_payload_when_decode_then_checksum_mismatch() {
+        let mut bytes = encode(&SceneUserContext::default()).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+
+        assert!(matches!(
+            decode(&bytes),
+            Err(FileFormatError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn given_truncated_file_when_decode_then_corrupt_payload() {
+        let bytes = encode(&SceneUserContext::default()).unwrap();
+
+        assert!(matches!(
+            decode(&bytes[..8]),
+            Err(FileFormatError::CorruptPayload(_))
+        ));
+
+        let mut truncated = bytes[..bytes.len() / 2].to_vec();
+        let checksum = crc32fast::hash(&truncated[10..]);
+        truncated[6..10].copy_from_slice(&checksum.to_le_bytes());
+        assert!(matches!(
+            decode(&truncated),
+            Err(FileFormatError::CorruptPayload(_))
+        ));
+    }
+}
@@ -0,0 +1,93 @@
+/*
+Model of the version 1, the scene has no id allocator.
+The render options and the camera are the same until the version 6.
+*/
+
+use common::{
+    types::{Coord, ScreenLength2d},
+    Rgba,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{user_context::camera::Camera, LayerId, RenderOption};
+
+use super::v2::{CommandsHandlerV2, IdAllocatorV2, LayerV2, SceneUserContextV2, SceneV2};
+
+#[derive(Deserialize)]
+pub struct SceneUserContextV1 {
+    command_handler: CommandsHandlerV1,
+    render_options: RenderOptionV1,
+    camera: CameraV1,
+}
+
+#[derive(Deserialize)]
+struct CommandsHandlerV1 {
+    scene: SceneV1,
+}
+
+#[derive(Deserialize)]
+struct SceneV1 {
+    background: Rgba,
+    layers: Vec<LayerV2>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct RenderOptionV1 {
+    pub(super) to_layer: Option<LayerId>,
+    pub(super) skip_layers: Vec<LayerId>,
+    pub(super) only_layers: Vec<LayerId>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CameraV1 {
+    pub(super) position: Coord,
+    pub(super) scaling: f32,
+    pub(super) rotation: f32,
+    pub(super) reflect_x: bool,
+    pub(super) reflect_y: bool,
+    pub(super) base_scale: ScreenLength2d,
+}
+
+impl From<SceneUserContextV1> for SceneUserContextV2 {
+    fn from(value: SceneUserContextV1) -> Self {
+        let scene = value.command_handler.scene;
+        SceneUserContextV2 {
+            command_handler: CommandsHandlerV2 {
+                scene: SceneV2 {
+                    background: scene.background,
+                    layers: scene.layers,
+                    // Reseeded from the layers when the current model is loaded
+                    ids: IdAllocatorV2 {
+                        next_layer: 1,
+                        next_coord: 1,
+                    },
+                },
+            },
+            render_options: value.render_options,
+            camera: value.camera,
+        }
+    }
+}
+
+impl From<RenderOptionV1> for RenderOption {
+    fn from(value: RenderOptionV1) -> Self {
+        RenderOption {
+            to_layer: value.to_layer,
+            skip_layers: value.skip_layers,
+            only_layers: value.only_layers,
+            view: None,
+        }
+    }
+}
+
+impl From<CameraV1> for Camera {
+    fn from(value: CameraV1) -> Self {
+        Camera::from_saved(
+            value.position,
+            value.scaling,
+            value.rotation,
+            (value.reflect_x, value.reflect_y),
+            value.base_scale,
+        )
+    }
+}
@@ -0,0 +1,120 @@
+/*
+Model of the version 2, a shape has a single path.
+The id allocator and the coords are the same until the version 6.
+*/
+
+use common::{types::Coord, Rgba};
+use serde::{Deserialize, Serialize};
+
+use crate::{scene::id::IdAllocator, CoordId, DbCoord, LayerId};
+
+use super::{
+    v1::{CameraV1, RenderOptionV1},
+    v3::{
+        CommandsHandlerV3, FillRuleV3, LayerTypeV3, LayerV3, SceneUserContextV3, SceneV3, ShapeV3,
+        StrokeV3,
+    },
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct SceneUserContextV2 {
+    pub(super) command_handler: CommandsHandlerV2,
+    pub(super) render_options: RenderOptionV1,
+    pub(super) camera: CameraV1,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CommandsHandlerV2 {
+    pub(super) scene: SceneV2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SceneV2 {
+    pub(super) background: Rgba,
+    pub(super) layers: Vec<LayerV2>,
+    pub(super) ids: IdAllocatorV2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct IdAllocatorV2 {
+    pub(super) next_layer: usize,
+    pub(super) next_coord: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct LayerV2 {
+    id: LayerId,
+    name: String,
+    value: LayerTypeV2,
+}
+
+#[derive(Serialize, Deserialize)]
+enum LayerTypeV2 {
+    Shape(ShapeV2),
+    Folder,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ShapeV2 {
+    id: LayerId,
+    path: Vec<DbCoordV2>,
+    color: Rgba,
+    stroke: StrokeV3,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct DbCoordV2 {
+    pub(super) id: CoordId,
+    pub(super) coord: Coord,
+}
+
+impl From<LayerV2> for LayerV3 {
+    fn from(value: LayerV2) -> Self {
+        LayerV3 {
+            id: value.id,
+            name: value.name,
+            value: match value.value {
+                LayerTypeV2::Shape(shape) => LayerTypeV3::Shape(ShapeV3 {
+                    id: shape.id,
+                    path: shape.path,
+                    subpaths: Vec::new(),
+                    fill_rule: FillRuleV3::EvenOdd,
+                    color: shape.color,
+                    stroke: shape.stroke,
+                }),
+                LayerTypeV2::Folder => LayerTypeV3::Folder,
+            },
+        }
+    }
+}
+
+impl From<SceneUserContextV2> for SceneUserContextV3 {
+    fn from(value: SceneUserContextV2) -> Self {
+        let scene = value.command_handler.scene;
+        SceneUserContextV3 {
+            command_handler: CommandsHandlerV3 {
+                scene: SceneV3 {
+                    background: scene.background,
+                    layers: scene.layers.into_iter().map(LayerV3::from).collect(),
+                    ids: scene.ids,
+                },
+            },
+            render_options: value.render_options,
+            camera: value.camera,
+        }
+    }
+}
+
+impl From<IdAllocatorV2> for IdAllocator {
+    fn from(value: IdAllocatorV2) -> Self {
+        IdAllocator::starting_at(value.next_layer, value.next_coord)
+    }
+}
+
+impl From<DbCoordV2> for DbCoord {
+    fn from(value: DbCoordV2) -> Self {
+        let mut coord = DbCoord::new(value.coord.x, value.coord.y);
+        coord.id = value.id;
+        coord
+    }
+}
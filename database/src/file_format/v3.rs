@@ -0,0 +1,121 @@
+/*
+Model of the version 3, the fill and the stroke of a shape are a single color.
+The fill rule is the same until the version 6.
+*/
+
+use common::Rgba;
+use serde::{Deserialize, Serialize};
+
+use crate::{FillRule, LayerId};
+
+use super::{
+    v1::{CameraV1, RenderOptionV1},
+    v2::{DbCoordV2, IdAllocatorV2},
+    v4::{
+        CommandsHandlerV4, LayerTypeV4, LayerV4, PaintV4, SceneUserContextV4, SceneV4, ShapeV4,
+        StrokeV4,
+    },
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct SceneUserContextV3 {
+    pub(super) command_handler: CommandsHandlerV3,
+    pub(super) render_options: RenderOptionV1,
+    pub(super) camera: CameraV1,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CommandsHandlerV3 {
+    pub(super) scene: SceneV3,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SceneV3 {
+    pub(super) background: Rgba,
+    pub(super) layers: Vec<LayerV3>,
+    pub(super) ids: IdAllocatorV2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct LayerV3 {
+    pub(super) id: LayerId,
+    pub(super) name: String,
+    pub(super) value: LayerTypeV3,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum LayerTypeV3 {
+    Shape(ShapeV3),
+    Folder,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct ShapeV3 {
+    pub(super) id: LayerId,
+    pub(super) path: Vec<DbCoordV2>,
+    pub(super) subpaths: Vec<Vec<DbCoordV2>>,
+    pub(super) fill_rule: FillRuleV3,
+    pub(super) color: Rgba,
+    pub(super) stroke: StrokeV3,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum FillRuleV3 {
+    NonZero,
+    EvenOdd,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct StrokeV3 {
+    pub(super) size: f32,
+    pub(super) color: Rgba,
+}
+
+impl From<LayerV3> for LayerV4 {
+    fn from(value: LayerV3) -> Self {
+        LayerV4 {
+            id: value.id,
+            name: value.name,
+            value: match value.value {
+                LayerTypeV3::Shape(shape) => LayerTypeV4::Shape(ShapeV4 {
+                    id: shape.id,
+                    path: shape.path,
+                    subpaths: shape.subpaths,
+                    fill_rule: shape.fill_rule,
+                    fill: PaintV4::Solid(shape.color),
+                    stroke: StrokeV4 {
+                        size: shape.stroke.size,
+                        paint: PaintV4::Solid(shape.stroke.color),
+                    },
+                }),
+                LayerTypeV3::Folder => LayerTypeV4::Folder,
+            },
+        }
+    }
+}
+
+impl From<SceneUserContextV3> for SceneUserContextV4 {
+    fn from(value: SceneUserContextV3) -> Self {
+        let scene = value.command_handler.scene;
+        SceneUserContextV4 {
+            command_handler: CommandsHandlerV4 {
+                scene: SceneV4 {
+                    background: scene.background,
+                    layers: scene.layers.into_iter().map(LayerV4::from).collect(),
+                    ids: scene.ids,
+                },
+            },
+            render_options: value.render_options,
+            camera: value.camera,
+        }
+    }
+}
+
+impl From<FillRuleV3> for FillRule {
+    fn from(value: FillRuleV3) -> Self {
+        match value {
+            FillRuleV3::NonZero => FillRule::NonZero,
+            FillRuleV3::EvenOdd => FillRule::EvenOdd,
+        }
+    }
+}
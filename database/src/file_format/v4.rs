@@ -0,0 +1,183 @@
+/*
+Model of the version 4, the stroke of a shape is a size and a paint.
+The paints are the same until the version 6.
+*/
+
+use common::{types::Coord, Rgba};
+use serde::{Deserialize, Serialize};
+
+use crate::{GradientStop, GradientUnits, LayerId, LinearGradient, Paint, RadialGradient};
+
+use super::{
+    v1::{CameraV1, RenderOptionV1},
+    v2::{DbCoordV2, IdAllocatorV2},
+    v3::FillRuleV3,
+    v5::{
+        CommandsHandlerV5, LayerTypeV5, LayerV5, LineCapV5, LineJoinV5, SceneUserContextV5,
+        SceneV5, ShapeV5, StrokeAlignV5, StrokeV5,
+    },
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct SceneUserContextV4 {
+    pub(super) command_handler: CommandsHandlerV4,
+    pub(super) render_options: RenderOptionV1,
+    pub(super) camera: CameraV1,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CommandsHandlerV4 {
+    pub(super) scene: SceneV4,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SceneV4 {
+    pub(super) background: Rgba,
+    pub(super) layers: Vec<LayerV4>,
+    pub(super) ids: IdAllocatorV2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct LayerV4 {
+    pub(super) id: LayerId,
+    pub(super) name: String,
+    pub(super) value: LayerTypeV4,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum LayerTypeV4 {
+    Shape(ShapeV4),
+    Folder,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct ShapeV4 {
+    pub(super) id: LayerId,
+    pub(super) path: Vec<DbCoordV2>,
+    pub(super) subpaths: Vec<Vec<DbCoordV2>>,
+    pub(super) fill_rule: FillRuleV3,
+    pub(super) fill: PaintV4,
+    pub(super) stroke: StrokeV4,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct StrokeV4 {
+    pub(super) size: f32,
+    pub(super) paint: PaintV4,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum PaintV4 {
+    Solid(Rgba),
+    LinearGradient(LinearGradientV4),
+    RadialGradient(RadialGradientV4),
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct LinearGradientV4 {
+    pub(super) start: Coord,
+    pub(super) end: Coord,
+    pub(super) stops: Vec<GradientStopV4>,
+    pub(super) units: GradientUnitsV4,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct RadialGradientV4 {
+    pub(super) center: Coord,
+    pub(super) radius: f32,
+    pub(super) stops: Vec<GradientStopV4>,
+    pub(super) units: GradientUnitsV4,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct GradientStopV4 {
+    pub(super) offset: f32,
+    pub(super) color: Rgba,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum GradientUnitsV4 {
+    Shape,
+    Scene,
+}
+
+impl From<LayerV4> for LayerV5 {
+    fn from(value: LayerV4) -> Self {
+        LayerV5 {
+            id: value.id,
+            name: value.name,
+            value: match value.value {
+                LayerTypeV4::Shape(shape) => LayerTypeV5::Shape(ShapeV5 {
+                    id: shape.id,
+                    path: shape.path,
+                    subpaths: shape.subpaths,
+                    fill_rule: shape.fill_rule,
+                    fill: shape.fill,
+                    stroke: StrokeV5 {
+                        size: shape.stroke.size,
+                        paint: shape.stroke.paint,
+                        join: LineJoinV5::Miter,
+                        cap: LineCapV5::Butt,
+                        miter_limit: 4.0,
+                        dashes: Vec::new(),
+                        dash_offset: 0.0,
+                        align: StrokeAlignV5::Center,
+                    },
+                }),
+                LayerTypeV4::Folder => LayerTypeV5::Folder,
+            },
+        }
+    }
+}
+
+impl From<SceneUserContextV4> for SceneUserContextV5 {
+    fn from(value: SceneUserContextV4) -> Self {
+        let scene = value.command_handler.scene;
+        SceneUserContextV5 {
+            command_handler: CommandsHandlerV5 {
+                scene: SceneV5 {
+                    background: scene.background,
+                    layers: scene.layers.into_iter().map(LayerV5::from).collect(),
+                    ids: scene.ids,
+                },
+            },
+            render_options: value.render_options,
+            camera: value.camera,
+        }
+    }
+}
+
+impl From<PaintV4> for Paint {
+    fn from(value: PaintV4) -> Self {
+        match value {
+            PaintV4::Solid(color) => Paint::Solid(color),
+            PaintV4::LinearGradient(gradient) => Paint::LinearGradient(LinearGradient {
+                start: gradient.start,
+                end: gradient.end,
+                stops: gradient.stops.into_iter().map(GradientStop::from).collect(),
+                units: gradient.units.into(),
+            }),
+            PaintV4::RadialGradient(gradient) => Paint::RadialGradient(RadialGradient {
+                center: gradient.center,
+                radius: gradient.radius,
+                stops: gradient.stops.into_iter().map(GradientStop::from).collect(),
+                units: gradient.units.into(),
+            }),
+        }
+    }
+}
+
+impl From<GradientStopV4> for GradientStop {
+    fn from(value: GradientStopV4) -> Self {
+        GradientStop::new(value.offset, value.color)
+    }
+}
+
+impl From<GradientUnitsV4> for GradientUnits {
+    fn from(value: GradientUnitsV4) -> Self {
+        match value {
+            GradientUnitsV4::Shape => GradientUnits::Shape,
+            GradientUnitsV4::Scene => GradientUnits::Scene,
+        }
+    }
+}
@@ -0,0 +1,152 @@
+/*
+Model of the version 5, the layers are a flat list and a folder has no layer.
+The shapes are the same until the version 6.
+*/
+
+use common::Rgba;
+use serde::{Deserialize, Serialize};
+
+use crate::{LayerId, LineCap, LineJoin, StrokeAlign};
+
+use super::{
+    v1::{CameraV1, RenderOptionV1},
+    v2::{DbCoordV2, IdAllocatorV2},
+    v3::FillRuleV3,
+    v4::PaintV4,
+    v6::{CommandsHandlerV6, LayerTypeV6, LayerV6, SceneUserContextV6, SceneV6},
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct SceneUserContextV5 {
+    pub(super) command_handler: CommandsHandlerV5,
+    pub(super) render_options: RenderOptionV1,
+    pub(super) camera: CameraV1,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CommandsHandlerV5 {
+    pub(super) scene: SceneV5,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SceneV5 {
+    pub(super) background: Rgba,
+    pub(super) layers: Vec<LayerV5>,
+    pub(super) ids: IdAllocatorV2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct LayerV5 {
+    pub(super) id: LayerId,
+    pub(super) name: String,
+    pub(super) value: LayerTypeV5,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum LayerTypeV5 {
+    Shape(ShapeV5),
+    Folder,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct ShapeV5 {
+    pub(super) id: LayerId,
+    pub(super) path: Vec<DbCoordV2>,
+    pub(super) subpaths: Vec<Vec<DbCoordV2>>,
+    pub(super) fill_rule: FillRuleV3,
+    pub(super) fill: PaintV4,
+    pub(super) stroke: StrokeV5,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct StrokeV5 {
+    pub(super) size: f32,
+    pub(super) paint: PaintV4,
+    pub(super) join: LineJoinV5,
+    pub(super) cap: LineCapV5,
+    pub(super) miter_limit: f32,
+    pub(super) dashes: Vec<f32>,
+    pub(super) dash_offset: f32,
+    pub(super) align: StrokeAlignV5,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum LineJoinV5 {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum LineCapV5 {
+    Butt,
+    Round,
+    Square,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum StrokeAlignV5 {
+    Center,
+    Inside,
+    Outside,
+}
+
+impl From<LayerV5> for LayerV6 {
+    fn from(value: LayerV5) -> Self {
+        LayerV6 {
+            id: value.id,
+            name: value.name,
+            value: match value.value {
+                LayerTypeV5::Shape(shape) => LayerTypeV6::Shape(shape),
+                LayerTypeV5::Folder => LayerTypeV6::Folder(Vec::new()),
+            },
+        }
+    }
+}
+
+impl From<SceneUserContextV5> for SceneUserContextV6 {
+    fn from(value: SceneUserContextV5) -> Self {
+        let scene = value.command_handler.scene;
+        SceneUserContextV6 {
+            command_handler: CommandsHandlerV6 {
+                scene: SceneV6 {
+                    background: scene.background,
+                    layers: scene.layers.into_iter().map(LayerV6::from).collect(),
+                    ids: scene.ids,
+                },
+            },
+            render_options: value.render_options,
+            camera: value.camera,
+        }
+    }
+}
+
+impl From<LineJoinV5> for LineJoin {
+    fn from(value: LineJoinV5) -> Self {
+        match value {
+            LineJoinV5::Miter => LineJoin::Miter,
+            LineJoinV5::Round => LineJoin::Round,
+            LineJoinV5::Bevel => LineJoin::Bevel,
+        }
+    }
+}
+
+impl From<LineCapV5> for LineCap {
+    fn from(value: LineCapV5) -> Self {
+        match value {
+            LineCapV5::Butt => LineCap::Butt,
+            LineCapV5::Round => LineCap::Round,
+            LineCapV5::Square => LineCap::Square,
+        }
+    }
+}
+
+impl From<StrokeAlignV5> for StrokeAlign {
+    fn from(value: StrokeAlignV5) -> Self {
+        match value {
+            StrokeAlignV5::Center => StrokeAlign::Center,
+            StrokeAlignV5::Inside => StrokeAlign::Inside,
+            StrokeAlignV5::Outside => StrokeAlign::Outside,
+        }
+    }
+}
@@ -0,0 +1,100 @@
+/*
+Model of the version 6, the layers are a tree without visibility, lock or opacity.
+The shapes are the same as in the version 5.
+*/
+
+use common::Rgba;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::CommandsHandler,
+    scene::{shape::Stroke, Layer, LayerType},
+    DbCoord, LayerId, Scene, Shape,
+};
+
+use super::{
+    v1::{CameraV1, RenderOptionV1},
+    v2::IdAllocatorV2,
+    v5::ShapeV5,
+};
+
+#[derive(Serialize, Deserialize)]
+pub struct SceneUserContextV6 {
+    pub(super) command_handler: CommandsHandlerV6,
+    pub(super) render_options: RenderOptionV1,
+    pub(super) camera: CameraV1,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct CommandsHandlerV6 {
+    pub(super) scene: SceneV6,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct SceneV6 {
+    pub(super) background: Rgba,
+    pub(super) layers: Vec<LayerV6>,
+    pub(super) ids: IdAllocatorV2,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) struct LayerV6 {
+    pub(super) id: LayerId,
+    pub(super) name: String,
+    pub(super) value: LayerTypeV6,
+}
+
+#[derive(Serialize, Deserialize)]
+pub(super) enum LayerTypeV6 {
+    Shape(ShapeV5),
+    Folder(Vec<LayerV6>),
+}
+
+impl From<LayerV6> for Layer {
+    fn from(value: LayerV6) -> Self {
+        let layer_type = match value.value {
+            LayerTypeV6::Shape(shape) => {
+                let mut current = Shape::new();
+                current.id = shape.id;
+                current.path = shape.path.into_iter().map(DbCoord::from).collect();
+                current.subpaths = shape
+                    .subpaths
+                    .into_iter()
+                    .map(|path| path.into_iter().map(DbCoord::from).collect())
+                    .collect();
+                current.fill_rule = shape.fill_rule.into();
+                current.fill = shape.fill.into();
+                current.stroke = Stroke {
+                    size: shape.stroke.size,
+                    paint: shape.stroke.paint.into(),
+                    join: shape.stroke.join.into(),
+                    cap: shape.stroke.cap.into(),
+                    miter_limit: shape.stroke.miter_limit,
+                    dashes: shape.stroke.dashes,
+                    dash_offset: shape.stroke.dash_offset,
+                    align: shape.stroke.align.into(),
+                };
+                LayerType::Shape(current)
+            }
+            LayerTypeV6::Folder(layers) => {
+                LayerType::Folder(layers.into_iter().map(Layer::from).collect())
+            }
+        };
+        Layer::new(value.id, value.name, layer_type)
+    }
+}
+
+impl From<SceneUserContextV6> for crate::SceneUserContext {
+    fn from(value: SceneUserContextV6) -> Self {
+        let scene = value.command_handler.scene;
+        crate::SceneUserContext {
+            command_handler: CommandsHandler::from(Scene::from_layers(
+                scene.background,
+                scene.layers.into_iter().map(Layer::from).collect(),
+                scene.ids.into(),
+            )),
+            render_options: value.render_options.into(),
+            camera: value.camera.into(),
+        }
+    }
+}
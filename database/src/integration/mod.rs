@@ -12,7 +12,7 @@ fn test_shape_insert() {
     let inserted_id = db.shape_insert(shape);
     let shape = db.shape_select(inserted_id).unwrap();
     assert_eq!(shape.id, inserted_id);
-    assert_eq!(shape.color, Rgba::new(0, 0, 0, 0));
+    assert_eq!(shape.fill, Rgba::new(0, 0, 0, 0).into());
 }
 
 #[test]
@@ -1,3 +1,4 @@
+mod file_format;
 mod math;
 mod scene;
 mod user_context;
@@ -6,17 +7,38 @@ pub mod commands;
 #[cfg(test)]
 mod integration;
 
+pub use file_format::FileFormatError;
 pub use scene::id::CoordId;
 pub use scene::id::LayerId;
 pub use scene::render::DrawingContext;
 pub use scene::render::RenderOption;
+pub use scene::render::RenderStats;
+pub use scene::render::StrokeStyle;
+#[cfg(feature = "tiny-skia-renderer")]
+pub use scene::render::TinySkiaRenderer;
 pub use scene::shape::coord::DbCoord;
 pub use scene::shape::curve::Curve;
+pub use scene::shape::paint::{GradientStop, GradientUnits, LinearGradient, Paint, RadialGradient};
 pub use scene::shape::Shape;
+pub use scene::shape::{FillRule, LineCap, LineJoin, PathEnd, StrokeAlign};
+pub use scene::svg::SvgExportOption;
+pub use scene::svg::SvgImportError;
+pub use scene::svg::SvgImportWarning;
 pub use scene::tree_view::TreeViewModel;
+pub use scene::validate::PathIssue;
 pub use scene::Scene;
 
+pub use user_context::clipboard::Clipboard;
+pub use user_context::guides::Guide;
+pub use user_context::guides::GuideAxis;
+pub use user_context::guides::GuideLabel;
+pub use user_context::primitive::Primitive;
+pub use user_context::snap::Snap;
+pub use user_context::snap::SnapReason;
+pub use user_context::snap::SnapSettings;
+pub use user_context::user_selection::MarqueeMode;
 pub use user_context::user_selection::SelectedLevel;
+pub use user_context::user_selection::SelectionModifier;
 pub use user_context::user_selection::UserSelection;
 pub use user_context::SceneUserContext;
 
@@ -10,7 +10,6 @@ use crate::math::curve::{add_smooth_result, cubic_bezier};
 
 use super::line_intersection::line_intersection;
 
-#[allow(dead_code)]
 pub fn bounding_box(p0: &Coord, cp0: &Coord, cp1: &Coord, p1: &Coord) -> Rect {
     let extremities = extremites(p0, cp0, cp1, p1);
 
@@ -3,3 +3,4 @@ pub mod curve2;
 pub mod curve3;
 pub mod line_intersection;
 pub mod line_different_side;
+pub mod offset;
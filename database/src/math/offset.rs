@@ -0,0 +1,657 @@
+use std::f32::consts::PI;
+
+use common::{
+    pures::Vec2,
+    types::{Coord, Vector},
+};
+
+use crate::scene::shape::{LineCap, LineJoin};
+
+use super::{
+    curve::{add_smooth_result, cubic_bezier, is_line, tangent_vector},
+    curve2::{intersection, IntersectionResult},
+};
+
+/// Largest distance between an offset curve and the exact offset, relative to the distance
+const TOLERANCE: f32 = 0.002;
+/// Smallest tolerance, in the units of the path
+const MIN_TOLERANCE: f32 = 0.00001;
+/// Maximum number of times a curve is split in half to follow its offset
+const MAX_DEPTH: u32 = 8;
+/// Number of chords used to measure the length of a curve
+const LENGTH_STEPS: usize = 16;
+
+type Cubic = [Coord; 4];
+
+/// How the offsets of the curves are connected at the corners and at the ends of an open path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffsetStyle {
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Maximum ratio of the distance of the miter tip to the offset distance, like SVG
+    pub miter_limit: f32,
+}
+
+impl Default for OffsetStyle {
+    fn default() -> Self {
+        OffsetStyle {
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+        }
+    }
+}
+
+/// Path at a signed distance of a path, on the side of the normal (-y, x) of the direction
+/// for a positive distance.
+///
+/// The paths have the layout of a shape: the start then cp0, cp1 and p1 for each curve,
+/// a closed path ends on its start. The outer corners are connected by the join of the style
+/// and the inner corners are trimmed where the offset curves cross.
+pub fn offset_path(path: &[Coord], closed: bool, distance: f32, style: &OffsetStyle) -> Vec<Coord> {
+    if distance == 0.0 {
+        return path.to_vec();
+    }
+    to_path(&offset_cubics(path, closed, distance, style, false))
+}
+
+/// Outline of the stroke of a path, the area to fill with the nonzero rule.
+///
+/// A closed path gives its two offsets at half the width, the inner one reversed.
+/// An open path gives a single closed path around it with the caps of the style.
+pub fn stroke_outline(
+    path: &[Coord],
+    closed: bool,
+    width: f32,
+    style: &OffsetStyle,
+) -> Vec<Vec<Coord>> {
+    let cubics = cubics(path);
+    if cubics.is_empty() || width <= 0.0 {
+        return Vec::new();
+    }
+    let half = width / 2.0;
+    let left = offset_cubics(path, closed, half, style, true);
+    let right = offset_cubics(path, closed, -half, style, true);
+
+    if closed {
+        return vec![to_path(&left), reverse(&to_path(&right))];
+    }
+
+    let first = &cubics[0];
+    let last = &cubics[cubics.len() - 1];
+    let end_direction = tangent_vector(1.0, &last[0], &last[1], &last[2], &last[3]);
+    let start_direction = -tangent_vector(0.0, &first[0], &first[1], &first[2], &first[3]);
+
+    let mut outline = left;
+    outline.extend(cap(last[3], end_direction, half, style.cap));
+    outline.extend(reverse_cubics(&right));
+    outline.extend(cap(first[0], start_direction, half, style.cap));
+    vec![to_path(&outline)]
+}
+
+/// Split a path in the open paths of its dashes, the lengths alternate between a dash and a gap.
+/// An odd number of lengths is repeated to have as many dashes as gaps.
+/// None when the lengths draw a solid line
+pub fn dash_path(
+    path: &[Coord],
+    closed: bool,
+    dashes: &[f32],
+    offset: f32,
+) -> Option<Vec<Vec<Coord>>> {
+    let mut pattern = dashes.to_vec();
+    if pattern.len() % 2 == 1 {
+        pattern.extend_from_slice(dashes);
+    }
+    let total: f32 = pattern.iter().sum();
+    if pattern.is_empty() || total <= 0.0 || pattern.iter().any(|length| *length < 0.0) {
+        return None;
+    }
+
+    // Position in the pattern at the start of the path
+    let mut index = 0;
+    let mut remaining = pattern[0];
+    let mut skip = offset.rem_euclid(total);
+    while skip > 0.0 {
+        if skip >= remaining {
+            skip -= remaining;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        } else {
+            remaining -= skip;
+            skip = 0.0;
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut dash: Vec<Cubic> = Vec::new();
+    for cubic in cubics(path) {
+        let lengths = arc_lengths(&cubic);
+        let length = lengths[LENGTH_STEPS];
+        let mut position = 0.0;
+        loop {
+            let on = index % 2 == 0;
+            if remaining >= length - position {
+                if on {
+                    dash.push(sub_cubic(&cubic, t_at_length(&lengths, position), 1.0));
+                }
+                remaining -= length - position;
+                break;
+            }
+            let end = position + remaining;
+            if on {
+                let (t0, t1) = (t_at_length(&lengths, position), t_at_length(&lengths, end));
+                dash.push(sub_cubic(&cubic, t0, t1));
+                result.push(to_path(&std::mem::take(&mut dash)));
+            }
+            position = end;
+            index = (index + 1) % pattern.len();
+            remaining = pattern[index];
+        }
+    }
+    if !dash.is_empty() {
+        result.push(to_path(&dash));
+    }
+    // The path is closed when the pattern is solid at the end and at the start
+    if closed && result.len() > 1 && index % 2 == 0 && offset.rem_euclid(total) < pattern[0] {
+        let first = result.remove(0);
+        let last = result.last_mut().expect("More than one dash");
+        last.extend_from_slice(&first[1..]);
+    }
+    Some(result)
+}
+
+/// Curves of the path, without the curves of length 0
+fn cubics(path: &[Coord]) -> Vec<Cubic> {
+    if path.len() < 4 {
+        return Vec::new();
+    }
+    (0..(path.len() - 1) / 3)
+        .map(|i| {
+            [
+                path[i * 3],
+                path[i * 3 + 1],
+                path[i * 3 + 2],
+                path[i * 3 + 3],
+            ]
+        })
+        .filter(|[p0, cp0, cp1, p1]| !(p0 == p1 && p0 == cp0 && p0 == cp1))
+        .collect()
+}
+
+/// Offset of every curve of the path connected at the corners.
+/// With pivot, the inner corners that can't be trimmed go through the corner of the path,
+/// the loop is inside a stroke filled with the nonzero rule.
+fn offset_cubics(
+    path: &[Coord],
+    closed: bool,
+    distance: f32,
+    style: &OffsetStyle,
+    pivot: bool,
+) -> Vec<Cubic> {
+    let curves = cubics(path);
+    let tolerance = (distance.abs() * TOLERANCE).max(MIN_TOLERANCE);
+    let mut pieces: Vec<Vec<Cubic>> = curves
+        .iter()
+        .map(|cubic| {
+            let mut offsets = Vec::new();
+            offset_cubic(cubic, distance, tolerance, 0, &mut offsets);
+            offsets
+        })
+        .collect();
+
+    let count = curves.len();
+    let mut joins: Vec<Vec<Cubic>> = vec![Vec::new(); count];
+    let corners = if closed { 0..count } else { 1..count };
+    for next in corners {
+        let previous = (next + count - 1) % count;
+        let incoming = &curves[previous];
+        let outgoing = &curves[next];
+        let corner = Corner {
+            point: outgoing[0],
+            incoming: tangent_vector(1.0, &incoming[0], &incoming[1], &incoming[2], &incoming[3]),
+            outgoing: tangent_vector(0.0, &outgoing[0], &outgoing[1], &outgoing[2], &outgoing[3]),
+        };
+        let end = pieces[previous].len() - 1;
+        let mut before = pieces[previous][end];
+        let mut after = pieces[next][0];
+        joins[next] = corner.join(&mut before, &mut after, distance, style, pivot);
+        pieces[previous][end] = before;
+        pieces[next][0] = after;
+    }
+
+    joins
+        .into_iter()
+        .zip(pieces)
+        .flat_map(|(join, piece)| join.into_iter().chain(piece))
+        .collect()
+}
+
+/// Append the offset of a curve, split in half until the approximation is in the tolerance
+fn offset_cubic(cubic: &Cubic, distance: f32, tolerance: f32, depth: u32, out: &mut Vec<Cubic>) {
+    let [p0, cp0, cp1, p1] = cubic;
+    if is_line(p0, cp0, cp1, p1) {
+        let shift = normal(Vector::from(p1 - p0).normal()) * distance;
+        out.push(cubic.map(|coord| coord + shift));
+        return;
+    }
+
+    let start = tangent_vector(0.0, p0, cp0, cp1, p1);
+    let end = tangent_vector(1.0, p0, cp0, cp1, p1);
+    let q0 = p0 + normal(start) * distance;
+    let q3 = p1 + normal(end) * distance;
+    let (start, end): (Coord, Coord) = (start.into(), end.into());
+
+    // Handle lengths for the middle of the approximation on the middle of the offset
+    let middle = exact_offset(cubic, 0.5, distance) - (q0 + q3) * 0.5;
+    let determinant = end.x * start.y - start.x * end.y;
+    let (a, b) = if determinant.abs() > 0.001 {
+        let x = middle.x / 0.375;
+        let y = middle.y / 0.375;
+        (
+            (end.x * y - end.y * x) / determinant,
+            (start.x * y - start.y * x) / determinant,
+        )
+    } else {
+        let scale = match p0.distance(p1) {
+            length if length > 0.0 => q0.distance(&q3) / length,
+            _ => 1.0,
+        };
+        (p0.distance(cp0) * scale, p1.distance(cp1) * scale)
+    };
+    let candidate = [q0, q0 + start * a, q3 - end * b, q3];
+
+    let close = [0.25, 0.5, 0.75].iter().all(|t| {
+        let coord = cubic_bezier(
+            *t,
+            &candidate[0],
+            &candidate[1],
+            &candidate[2],
+            &candidate[3],
+        );
+        coord.distance(&exact_offset(cubic, *t, distance)) <= tolerance
+    });
+    if (a >= 0.0 && b >= 0.0 && close) || depth >= MAX_DEPTH {
+        out.push(candidate);
+    } else {
+        let (first, second) = split(cubic, 0.5);
+        offset_cubic(&first, distance, tolerance, depth + 1, out);
+        offset_cubic(&second, distance, tolerance, depth + 1, out);
+    }
+}
+
+fn exact_offset(cubic: &Cubic, t: f32, distance: f32) -> Coord {
+    let [p0, cp0, cp1, p1] = cubic;
+    cubic_bezier(t, p0, cp0, cp1, p1) + normal(tangent_vector(t, p0, cp0, cp1, p1)) * distance
+}
+
+/// Normal of a direction, the side of a positive offset distance
+fn normal(direction: Vector) -> Coord {
+    Coord::new(-direction.y, direction.x)
+}
+
+/// Corner of a path between two curves
+struct Corner {
+    point: Coord,
+    incoming: Vector,
+    outgoing: Vector,
+}
+
+impl Corner {
+    /// Curves from the end of the offset before the corner to the start of the offset after it
+    fn join(
+        &self,
+        before: &mut Cubic,
+        after: &mut Cubic,
+        distance: f32,
+        style: &OffsetStyle,
+        pivot: bool,
+    ) -> Vec<Cubic> {
+        let (from, to) = (before[3], after[0]);
+        if from == to {
+            return Vec::new();
+        }
+        let cross = self.incoming.x * self.outgoing.y - self.incoming.y * self.outgoing.x;
+        let dot = self.incoming.dot(&self.outgoing);
+        if cross.abs() < 0.0001 && dot > 0.0 {
+            return vec![line(from, to)];
+        }
+
+        let outer = cross * distance < 0.0 || cross.abs() < 0.0001;
+        if !outer {
+            if let Some((trimmed_before, trimmed_after)) = trim(before, after) {
+                *before = trimmed_before;
+                *after = trimmed_after;
+                return Vec::new();
+            }
+            return match pivot {
+                true => vec![line(from, self.point), line(self.point, to)],
+                false => vec![line(from, to)],
+            };
+        }
+
+        match style.join {
+            LineJoin::Bevel => vec![line(from, to)],
+            LineJoin::Round => {
+                let sweep = if cross.abs() < 0.0001 {
+                    -PI * distance.signum()
+                } else {
+                    let (u, v) = (from - self.point, to - self.point);
+                    (u.x * v.y - u.y * v.x).atan2(u.dot(&v))
+                };
+                arc(self.point, from, sweep)
+            }
+            LineJoin::Miter => {
+                let (n0, n1) = (normal(self.incoming), normal(self.outgoing));
+                let cos = n0.dot(&n1);
+                if 1.0 + cos > 0.0001 && (2.0 / (1.0 + cos)).sqrt() <= style.miter_limit {
+                    let tip = self.point + (n0 + n1) * (distance / (1.0 + cos));
+                    vec![line(from, tip), line(tip, to)]
+                } else {
+                    vec![line(from, to)]
+                }
+            }
+        }
+    }
+}
+
+/// Cut the ends of two offset curves that cross at an inner corner
+fn trim(before: &Cubic, after: &Cubic) -> Option<(Cubic, Cubic)> {
+    let [a0, a1, a2, a3] = before;
+    let [b0, b1, b2, b3] = after;
+    let IntersectionResult::Pts(points) = intersection(a0, a1, a2, a3, b0, b1, b2, b3) else {
+        return None;
+    };
+    let point = points
+        .iter()
+        .max_by(|a, b| (a.t1 - a.t2).total_cmp(&(b.t1 - b.t2)))?;
+    // The t of a straight curve is along the line, only its end moves
+    let trimmed_before = match is_straight(before) {
+        true => line(before[0], point.coord),
+        false => split(before, point.t1).0,
+    };
+    let trimmed_after = match is_straight(after) {
+        true => line(point.coord, after[3]),
+        false => split(after, point.t2).1,
+    };
+    Some((trimmed_before, trimmed_after))
+}
+
+/// End of an open path, direction points out of the path
+fn cap(center: Coord, direction: Vector, half: f32, cap: LineCap) -> Vec<Cubic> {
+    let side = normal(direction) * half;
+    let (from, to) = (center + side, center - side);
+    let forward: Coord = Coord::from(direction) * half;
+    match cap {
+        LineCap::Butt => vec![line(from, to)],
+        LineCap::Square => vec![
+            line(from, from + forward),
+            line(from + forward, to + forward),
+            line(to + forward, to),
+        ],
+        LineCap::Round => arc(center, from, -PI),
+    }
+}
+
+/// Circular arc around center from a point, at most a quarter of turn per curve
+fn arc(center: Coord, from: Coord, sweep: f32) -> Vec<Cubic> {
+    let radius = center.distance(&from);
+    let start = (from.y - center.y).atan2(from.x - center.x);
+    let count = ((sweep.abs() / (PI / 2.0)) - 0.001).ceil().max(1.0) as usize;
+    let step = sweep / count as f32;
+    let handle = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+
+    let point = |angle: f32| center + Coord::new(angle.cos(), angle.sin()) * radius;
+    let tangent = |angle: f32| Coord::new(-angle.sin(), angle.cos()) * handle;
+    (0..count)
+        .map(|i| {
+            let a0 = start + step * i as f32;
+            let a1 = a0 + step;
+            let p0 = if i == 0 { from } else { point(a0) };
+            let p1 = point(a1);
+            [p0, p0 + tangent(a0), p1 - tangent(a1), p1]
+        })
+        .collect()
+}
+
+/// Straight curve, its handles are on its points
+fn line(from: Coord, to: Coord) -> Cubic {
+    [from, from, to, to]
+}
+
+fn is_straight(cubic: &Cubic) -> bool {
+    cubic[0] == cubic[1] && cubic[2] == cubic[3]
+}
+
+/// The two halves of a curve at t, a straight curve keeps its handles on its points
+fn split(cubic: &Cubic, t: f32) -> (Cubic, Cubic) {
+    let [p0, cp0, cp1, p1] = *cubic;
+    if is_straight(cubic) {
+        let middle = cubic_bezier(t, &p0, &cp0, &cp1, &p1);
+        return (line(p0, middle), line(middle, p1));
+    }
+    let (cp0_left, cp1_left, middle, cp0_right, cp1_right) =
+        add_smooth_result(&p0, &cp0, &cp1, &p1, t);
+    (
+        [p0, cp0_left, cp1_left, middle],
+        [middle, cp0_right, cp1_right, p1],
+    )
+}
+
+/// Part of a curve between t0 and t1
+fn sub_cubic(cubic: &Cubic, t0: f32, t1: f32) -> Cubic {
+    if is_straight(cubic) {
+        let [p0, cp0, cp1, p1] = cubic;
+        let start = cubic_bezier(t0, p0, cp0, cp1, p1);
+        return line(start, cubic_bezier(t1, p0, cp0, cp1, p1));
+    }
+    let cubic = if t1 < 1.0 { split(cubic, t1).0 } else { *cubic };
+    if t0 > 0.0 && t1 > 0.0 {
+        split(&cubic, t0 / t1).1
+    } else {
+        cubic
+    }
+}
+
+/// Length of the curve at each of the steps of t
+fn arc_lengths(cubic: &Cubic) -> [f32; LENGTH_STEPS + 1] {
+    let [p0, cp0, cp1, p1] = cubic;
+    let mut lengths = [0.0; LENGTH_STEPS + 1];
+    let mut previous = *p0;
+    for (i, length) in lengths.iter_mut().enumerate().skip(1) {
+        let coord = cubic_bezier(i as f32 / LENGTH_STEPS as f32, p0, cp0, cp1, p1);
+        *length = previous.distance(&coord);
+        previous = coord;
+    }
+    for i in 1..lengths.len() {
+        lengths[i] += lengths[i - 1];
+    }
+    lengths
+}
+
+fn t_at_length(lengths: &[f32; LENGTH_STEPS + 1], length: f32) -> f32 {
+    let step = lengths
+        .partition_point(|l| *l < length)
+        .clamp(1, LENGTH_STEPS);
+    let (start, end) = (lengths[step - 1], lengths[step]);
+    let ratio = if end > start {
+        (length - start) / (end - start)
+    } else {
+        0.0
+    };
+    ((step - 1) as f32 + ratio.clamp(0.0, 1.0)) / LENGTH_STEPS as f32
+}
+
+fn to_path(cubics: &[Cubic]) -> Vec<Coord> {
+    let mut path = Vec::with_capacity(cubics.len() * 3 + 1);
+    if let Some(first) = cubics.first() {
+        path.push(first[0]);
+    }
+    for [_, cp0, cp1, p1] in cubics {
+        path.extend([*cp0, *cp1, *p1]);
+    }
+    path
+}
+
+fn reverse(path: &[Coord]) -> Vec<Coord> {
+    path.iter().rev().copied().collect()
+}
+
+fn reverse_cubics(cubics: &[Cubic]) -> Vec<Cubic> {
+    cubics
+        .iter()
+        .rev()
+        .map(|[p0, cp0, cp1, p1]| [*p1, *cp1, *cp0, *p0])
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Vec2, types::Coord};
+    use float_cmp::assert_approx_eq;
+
+    use crate::scene::shape::{LineCap, LineJoin};
+
+    use super::{dash_path, offset_path, stroke_outline, OffsetStyle};
+
+    /// Signed area of a path from its anchors and handles, positive when it turns from x to y
+    fn path_area(path: &[Coord]) -> f32 {
+        let mut area = 0.0;
+        for i in 0..path.len() {
+            let a = path[i];
+            let b = path[(i + 1) % path.len()];
+            area += a.x * b.y - b.x * a.y;
+        }
+        area / 2.0
+    }
+
+    fn square() -> Vec<Coord> {
+        let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (0.0, 0.0)];
+        let mut path = vec![Coord::new(0.0, 0.0)];
+        for pair in corners.windows(2) {
+            let (a, b) = (
+                Coord::new(pair[0].0, pair[0].1),
+                Coord::new(pair[1].0, pair[1].1),
+            );
+            path.extend([a, b, b]);
+        }
+        path
+    }
+
+    fn circle(radius: f32) -> Vec<Coord> {
+        let k = 0.552_284_8 * radius;
+        vec![
+            Coord::new(radius, 0.0),
+            Coord::new(radius, k),
+            Coord::new(k, radius),
+            Coord::new(0.0, radius),
+            Coord::new(-k, radius),
+            Coord::new(-radius, k),
+            Coord::new(-radius, 0.0),
+            Coord::new(-radius, -k),
+            Coord::new(-k, -radius),
+            Coord::new(0.0, -radius),
+            Coord::new(k, -radius),
+            Coord::new(radius, -k),
+            Coord::new(radius, 0.0),
+        ]
+    }
+
+    #[test]
+    fn given_square_when_offset_outside_with_miter_then_larger_square() {
+        // The square turns from x to y, the outside is on the side of -normal
+        let path = offset_path(&square(), true, -0.1, &OffsetStyle::default());
+
+        assert_eq!(path.first(), path.last());
+        assert!(path.contains(&Coord::new(-0.1, -0.1)));
+        assert!(path.contains(&Coord::new(1.1, 1.1)));
+        assert_approx_eq!(f32, path_area(&path), 1.44, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn given_square_when_offset_inside_then_corners_trimmed() {
+        let path = offset_path(&square(), true, 0.1, &OffsetStyle::default());
+
+        assert!(path.contains(&Coord::new(0.1, 0.1)));
+        assert!(path.contains(&Coord::new(0.9, 0.9)));
+        assert_approx_eq!(f32, path_area(&path), 0.64, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn given_circle_when_offset_then_every_point_at_new_radius() {
+        let style = OffsetStyle {
+            join: LineJoin::Round,
+            ..OffsetStyle::default()
+        };
+
+        let path = offset_path(&circle(1.0), true, -0.5, &style);
+
+        for i in 0..(path.len() - 1) / 3 {
+            let curve = &path[i * 3..i * 3 + 4];
+            for t in [0.0, 0.3, 0.5, 0.8] {
+                let coord =
+                    crate::math::curve::cubic_bezier(t, &curve[0], &curve[1], &curve[2], &curve[3]);
+                assert_approx_eq!(f32, coord.norm(), 1.5, epsilon = 0.005);
+            }
+        }
+    }
+
+    #[test]
+    fn given_open_line_when_stroke_outline_then_caps_around_ends() {
+        let line = vec![
+            Coord::new(0.0, 0.0),
+            Coord::new(0.0, 0.0),
+            Coord::new(1.0, 0.0),
+            Coord::new(1.0, 0.0),
+        ];
+        let outline = |cap| {
+            let style = OffsetStyle {
+                cap,
+                ..OffsetStyle::default()
+            };
+            stroke_outline(&line, false, 0.2, &style)
+        };
+
+        let butt = outline(LineCap::Butt);
+        assert_eq!(butt.len(), 1);
+        assert_eq!(butt[0].first(), butt[0].last());
+        assert_approx_eq!(f32, path_area(&butt[0]).abs(), 0.2, epsilon = 0.0001);
+
+        let square = outline(LineCap::Square);
+        assert!(square[0].contains(&Coord::new(1.1, 0.1)));
+        assert_approx_eq!(f32, path_area(&square[0]).abs(), 0.24, epsilon = 0.0001);
+
+        let round = outline(LineCap::Round);
+        assert!(round[0].contains(&Coord::new(1.1, 0.0)));
+        assert!(round[0].contains(&Coord::new(-0.1, 0.0)));
+    }
+
+    #[test]
+    fn given_closed_path_when_stroke_outline_then_outer_and_reversed_inner() {
+        let outline = stroke_outline(&square(), true, 0.2, &OffsetStyle::default());
+
+        assert_eq!(outline.len(), 2);
+        assert_approx_eq!(f32, path_area(&outline[0]), 0.64, epsilon = 0.0001);
+        assert_approx_eq!(f32, path_area(&outline[1]), -1.44, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn given_dashes_when_dash_path_then_pieces_of_dash_length() {
+        let line = vec![
+            Coord::new(0.0, 0.0),
+            Coord::new(0.0, 0.0),
+            Coord::new(1.0, 0.0),
+            Coord::new(1.0, 0.0),
+        ];
+
+        let dashes = dash_path(&line, false, &[0.2, 0.1], 0.1).unwrap();
+
+        // The lengths are measured on chords of the curve
+        assert_eq!(dashes.len(), 4);
+        assert_eq!(dashes[0][0], Coord::new(0.0, 0.0));
+        assert_approx_eq!(f32, dashes[0][3].x, 0.1, epsilon = 0.002);
+        assert_approx_eq!(f32, dashes[1][0].x, 0.2, epsilon = 0.002);
+        assert_approx_eq!(f32, dashes[1][3].x, 0.4, epsilon = 0.002);
+        assert_eq!(dashes[3][3], Coord::new(1.0, 0.0));
+        assert_eq!(dash_path(&line, false, &[], 0.0), None);
+    }
+}
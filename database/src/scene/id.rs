@@ -1,7 +1,11 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+use super::{layer_find_mut, shape::coord::DbCoord, Layer, LayerIter, LayerType, Scene};
+
 macro_rules! create_struct_id {
-    ($name:ident, $id_conter:ident) => {
+    ($name:ident) => {
         #[derive(
             Debug,
             Clone,
@@ -19,24 +23,11 @@ macro_rules! create_struct_id {
             id: usize,
         }
 
-        static $id_conter: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(1);
-
         impl $name {
             pub fn null() -> Self {
                 $name { id: 0 }
             }
 
-            #[allow(dead_code)]
-            pub(crate) fn new() -> Self {
-                $name {
-                    id: $id_conter.fetch_add(1, std::sync::atomic::Ordering::SeqCst),
-                }
-            }
-
-            pub(crate) fn update(&mut self) {
-                self.id = $id_conter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
-            }
-
             pub fn value(&self) -> usize {
                 self.id
             }
@@ -50,5 +41,243 @@ macro_rules! create_struct_id {
     };
 }
 
-create_struct_id!(CoordId, COORD_ID_COUNTER);
-create_struct_id!(LayerId, LAYER_ID_COUNTER);
+create_struct_id!(CoordId);
+create_struct_id!(LayerId);
+
+/// Coords created outside of a scene get a provisional id in the upper half of the range.
+/// The scene replaces them by its own ids when the coords are added to it.
+const PROVISIONAL_START: usize = usize::MAX / 2 + 1;
+static PROVISIONAL_COORD_ID_COUNTER: std::sync::atomic::AtomicUsize =
+    std::sync::atomic::AtomicUsize::new(PROVISIONAL_START);
+
+impl CoordId {
+    pub(crate) fn new() -> Self {
+        CoordId {
+            id: PROVISIONAL_COORD_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::SeqCst),
+        }
+    }
+
+    pub(crate) fn update(&mut self) {
+        *self = CoordId::new();
+    }
+
+    pub fn is_provisional(&self) -> bool {
+        self.id >= PROVISIONAL_START
+    }
+}
+
+/// Allocate the ids of a scene. The ids are never reused, even after a delete.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdAllocator {
+    next_layer: usize,
+    next_coord: usize,
+}
+
+impl Default for IdAllocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdAllocator {
+    pub fn new() -> Self {
+        Self {
+            next_layer: 1,
+            next_coord: 1,
+        }
+    }
+
+    /// Allocator giving the ids from these ones, like the allocator of a saved scene
+    pub(crate) fn starting_at(next_layer: usize, next_coord: usize) -> Self {
+        Self {
+            next_layer,
+            next_coord,
+        }
+    }
+
+    pub fn layer_next(&mut self) -> LayerId {
+        let id = LayerId::from(self.next_layer);
+        self.next_layer += 1;
+        id
+    }
+
+    pub fn coord_next(&mut self) -> CoordId {
+        let id = CoordId::from(self.next_coord);
+        self.next_coord += 1;
+        id
+    }
+
+    /// Replace the null and provisional ids of the coords by ids of the scene.
+    /// Coords sharing a provisional id still share the same id after.
+    /// Return the provisional ids with their replacement.
+    pub fn coords_adopt<'a>(
+        &mut self,
+        coords: impl Iterator<Item = &'a mut DbCoord>,
+    ) -> HashMap<CoordId, CoordId> {
+        let mut replaced = HashMap::new();
+        for coord in coords {
+            if coord.id == CoordId::null() {
+                coord.id = self.coord_next();
+            } else if coord.id.is_provisional() {
+                coord.id = *replaced
+                    .entry(coord.id)
+                    .or_insert_with(|| self.coord_next());
+            } else {
+                self.next_coord = self.next_coord.max(coord.id.value() + 1);
+            }
+        }
+        replaced
+    }
+
+    /// Make sure the next ids are after the given ids
+    pub fn reserve(&mut self, layer: LayerId, coord: CoordId) {
+        self.next_layer = self.next_layer.max(layer.value() + 1);
+        if !coord.is_provisional() {
+            self.next_coord = self.next_coord.max(coord.value() + 1);
+        }
+    }
+}
+
+impl Scene {
+    /// Give ids of the scene to the new coords of a shape modified in place
+    pub(crate) fn shape_coords_adopt(&mut self, index: LayerId) -> HashMap<CoordId, CoordId> {
+        let ids = &mut self.ids;
+        match layer_find_mut(&mut self.layers, index) {
+            Some(layer) => match &mut layer.value {
+                LayerType::Shape(shape) => {
+                    ids.coords_adopt(shape.paths_mut().flat_map(|p| p.iter_mut()))
+                }
+                LayerType::Folder(_) => HashMap::new(),
+            },
+            None => HashMap::new(),
+        }
+    }
+
+    /// Take a layer id for a layer inserted later, like by a command
+    pub(crate) fn layer_id_reserve(&mut self) -> LayerId {
+        self.ids.layer_next()
+    }
+
+    /// Give new ids of the scene to the layer, the layers of its folder and their coords, like
+    /// for a copy. Coords sharing an id still share the same id after. A layer without name is
+    /// named after its new id
+    pub(crate) fn layer_ids_renew(&mut self, layer: &mut Layer) {
+        layer.id = self.ids.layer_next();
+        match &mut layer.value {
+            LayerType::Shape(shape) => {
+                shape.id = layer.id;
+                let mut replaced = HashMap::new();
+                for coord in shape.paths_mut().flat_map(|p| p.iter_mut()) {
+                    coord.id = *replaced
+                        .entry(coord.id)
+                        .or_insert_with(|| self.ids.coord_next());
+                }
+            }
+            LayerType::Folder(children) => {
+                for child in children {
+                    self.layer_ids_renew(child);
+                }
+            }
+        }
+        if layer.name.is_empty() {
+            layer.name = format!("{} {}", layer.value.type_string(), layer.id.value());
+        }
+    }
+
+    /// Move the allocator past every id of the scene and replace the provisional ids.
+    /// Used after loading, where the allocator can be behind the ids in the layers.
+    pub(crate) fn ids_reseed(&mut self) {
+        for (_, layer) in LayerIter::new(&self.layers) {
+            self.ids.reserve(layer.id, CoordId::null());
+            if let LayerType::Shape(shape) = &layer.value {
+                self.ids.reserve(shape.id, CoordId::null());
+                for coord in shape.paths().flatten() {
+                    self.ids.reserve(LayerId::null(), coord.id);
+                }
+            }
+        }
+        let shapes: Vec<LayerId> = self
+            .layers_iter()
+            .filter_map(|layer| match &layer.value {
+                LayerType::Shape(shape) => Some(shape.id),
+                LayerType::Folder(_) => None,
+            })
+            .collect();
+        for shape in shapes {
+            self.shape_coords_adopt(shape);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Affine, types::Coord};
+
+    use crate::{CoordId, DbCoord, LayerId, Scene, Shape};
+
+    #[test]
+    fn given_new_shape_when_insert_then_ids_from_scene() {
+        let mut scene = Scene::new();
+        let shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(1.0, 0.0),
+                DbCoord::new(1.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        assert!(shape.path.iter().all(|c| c.id.is_provisional()));
+
+        let id = scene.shape_insert(shape);
+
+        assert_eq!(id, LayerId::from(1));
+        let shape = scene.shape_select(id).unwrap();
+        assert!(shape.path.iter().all(|c| !c.id.is_provisional()));
+        assert!(shape.is_closed());
+        assert!(shape.curves().all(|c| c.is_straight()));
+        assert_eq!(shape.path[0].id, CoordId::from(1));
+    }
+
+    #[test]
+    fn given_two_scenes_when_insert_then_ids_independent() {
+        let mut scene_a = Scene::new();
+        let mut scene_b = Scene::new();
+
+        scene_a.shape_insert(Shape::new());
+        let id_a = scene_a.shape_insert(Shape::new());
+        let id_b = scene_b.shape_insert(Shape::new());
+
+        assert_eq!(id_a, LayerId::from(2));
+        assert_eq!(id_b, LayerId::from(1));
+    }
+
+    #[test]
+    fn given_loaded_scene_when_insert_then_no_collision() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            common::types::Length2d::new(0.5, 0.5),
+        ));
+        let max_coord = scene
+            .shape_select(id)
+            .unwrap()
+            .path
+            .iter()
+            .map(|c| c.id)
+            .max()
+            .unwrap();
+
+        // An old file has ids but no allocator
+        let mut loaded = scene.clone();
+        loaded.ids = super::IdAllocator::new();
+        loaded.ids_reseed();
+
+        let new_id = loaded.shape_insert(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            common::types::Length2d::new(0.2, 0.2),
+        ));
+        assert!(new_id > id);
+        let new_shape = loaded.shape_select(new_id).unwrap();
+        assert!(new_shape.path.iter().all(|c| c.id > max_coord));
+    }
+}
@@ -1,48 +1,156 @@
 use common::{types::Rect, Rgba};
-use id::LayerId;
+use id::{IdAllocator, LayerId};
 use serde::{Deserialize, Serialize};
+use shape::Shape;
+use spatial_index::SpatialIndex;
 
 pub mod id;
 pub mod render;
 #[macro_use]
 pub mod shape;
+mod spatial_index;
+pub mod svg;
 pub mod tree_view;
+pub mod validate;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-enum LayerType {
+pub(crate) enum LayerType {
     Shape(shape::Shape),
-    Folder,
+    /// Group of layers, index 0 is the foreground of the group
+    Folder(Vec<Layer>),
 }
 
 impl LayerType {
-    pub fn render(&self, renderer: &mut dyn render::DrawingContext) -> Result<(), String> {
-        match self {
-            LayerType::Shape(shape) => shape.render(renderer),
-            LayerType::Folder => Ok(()),
-        }
-    }
-
     pub fn type_string(&self) -> String {
         match self {
             LayerType::Shape(_) => "Shape".to_string(),
-            LayerType::Folder => "Folder".to_string(),
+            LayerType::Folder(_) => "Folder".to_string(),
         }
     }
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
-struct Layer {
+pub(crate) struct Layer {
     pub id: LayerId,
     pub name: String,
     pub value: LayerType,
+    /// A hidden layer is not rendered and can't be selected, like the layers of a hidden folder
+    pub visible: bool,
+    /// A locked layer is rendered but can't be selected
+    pub locked: bool,
+    /// Between 0.0 and 1.0, multiplied with the opacity of the folders containing the layer
+    pub opacity: f32,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+impl Layer {
+    pub fn new(id: LayerId, name: String, value: LayerType) -> Self {
+        Self {
+            id,
+            name,
+            value,
+            visible: true,
+            locked: false,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Place of a layer in the tree, the folder containing it and its index in the folder.
+/// No folder for a layer at the root of the scene
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct LayerPlace {
+    pub parent: Option<LayerId>,
+    pub index: usize,
+}
+
+/// Depth first iterator over the layers and the layers of their folders, from the foreground.
+/// Each layer comes with its level, 0 at the root
+pub(crate) struct LayerIter<'a> {
+    stack: Vec<std::slice::Iter<'a, Layer>>,
+}
+
+impl<'a> LayerIter<'a> {
+    pub fn new(layers: &'a [Layer]) -> Self {
+        Self {
+            stack: vec![layers.iter()],
+        }
+    }
+}
+
+impl<'a> Iterator for LayerIter<'a> {
+    type Item = (usize, &'a Layer);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(iter) = self.stack.last_mut() {
+            match iter.next() {
+                Some(layer) => {
+                    let level = self.stack.len() - 1;
+                    if let LayerType::Folder(children) = &layer.value {
+                        self.stack.push(children.iter());
+                    }
+                    return Some((level, layer));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+fn layer_find_mut(layers: &mut [Layer], index: LayerId) -> Option<&mut Layer> {
+    for layer in layers {
+        if layer.id == index {
+            return Some(layer);
+        }
+        if let LayerType::Folder(children) = &mut layer.value {
+            if let Some(layer) = layer_find_mut(children, index) {
+                return Some(layer);
+            }
+        }
+    }
+    None
+}
+
+fn layer_find_place(
+    layers: &[Layer],
+    parent: Option<LayerId>,
+    index: LayerId,
+) -> Option<LayerPlace> {
+    layers.iter().enumerate().find_map(|(position, layer)| {
+        if layer.id == index {
+            return Some(LayerPlace {
+                parent,
+                index: position,
+            });
+        }
+        match &layer.value {
+            LayerType::Folder(children) => layer_find_place(children, Some(layer.id), index),
+            LayerType::Shape(_) => None,
+        }
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Scene {
     pub background: Rgba,
 
     //Index 0 is the foreground
     layers: Vec<Layer>,
+
+    ids: IdAllocator,
+
+    #[serde(skip)]
+    index: SpatialIndex,
+}
+
+/// The allocator and the index are not part of the content, a scene after an undo is equal to
+/// the scene before
+impl PartialEq for Scene {
+    fn eq(&self, other: &Self) -> bool {
+        self.background == other.background && self.layers == other.layers
+    }
 }
 
 impl Default for Scene {
@@ -56,79 +164,256 @@ impl Scene {
         Scene {
             background: Rgba::new(255, 255, 255, 0),
             layers: Vec::new(),
+            ids: IdAllocator::new(),
+            index: SpatialIndex::default(),
         }
     }
 
-    fn layer_select(&self, index: LayerId) -> Option<&LayerType> {
-        let find_result = self.layers.iter().find(|l| l.id == index);
-        if let Some(layer) = find_result {
-            return Some(&layer.value);
-        }
+    /// Scene of loaded layers, the allocator is moved past the ids of the layers
+    pub(crate) fn from_layers(background: Rgba, layers: Vec<Layer>, ids: IdAllocator) -> Self {
+        let mut scene = Scene {
+            background,
+            layers,
+            ids,
+            index: SpatialIndex::default(),
+        };
+        scene.ids_reseed();
+        scene
+    }
 
-        None
+    /// Layers at the root of the scene with their folders, the opposite of `from_layers`
+    pub(crate) fn into_layers(self) -> Vec<Layer> {
+        self.layers
+    }
+
+    /// Every layer of the scene with the layers of the folders, from the foreground
+    pub(crate) fn layers_iter(&self) -> impl Iterator<Item = &Layer> {
+        LayerIter::new(&self.layers).map(|(_, layer)| layer)
+    }
+
+    pub(crate) fn layer_select(&self, index: LayerId) -> Option<&LayerType> {
+        self.layers_iter()
+            .find(|l| l.id == index)
+            .map(|layer| &layer.value)
     }
 
     fn layer_select_mut(&mut self, index: LayerId) -> Option<&mut LayerType> {
-        let find_result = self.layers.iter_mut().find(|l| l.id == index);
-        if let Some(layer) = find_result {
-            return Some(&mut layer.value);
+        self.index.mark(index);
+        layer_find_mut(&mut self.layers, index).map(|layer| &mut layer.value)
+    }
+
+    pub(crate) fn layer_mut(&mut self, index: LayerId) -> Option<&mut Layer> {
+        self.index.mark(index);
+        layer_find_mut(&mut self.layers, index)
+    }
+
+    /// Shapes of the visible layers from the foreground, with their opacity times the opacity
+    /// of their folders. Without the locked layers if `unlocked_only`
+    pub(crate) fn shapes_visible(&self, unlocked_only: bool) -> Vec<(&Shape, f32)> {
+        fn collect<'a>(
+            layers: &'a [Layer],
+            opacity: f32,
+            unlocked_only: bool,
+            shapes: &mut Vec<(&'a Shape, f32)>,
+        ) {
+            for layer in layers {
+                if !layer.visible || (unlocked_only && layer.locked) {
+                    continue;
+                }
+                let opacity = opacity * layer.opacity;
+                match &layer.value {
+                    LayerType::Shape(shape) => shapes.push((shape, opacity)),
+                    LayerType::Folder(children) => {
+                        collect(children, opacity, unlocked_only, shapes)
+                    }
+                }
+            }
         }
 
-        None
+        let mut shapes = Vec::new();
+        collect(&self.layers, 1.0, unlocked_only, &mut shapes);
+        shapes
+    }
+
+    /// Layers in the folder, the root of the scene for no folder
+    fn layer_children(&self, parent: Option<LayerId>) -> Option<&Vec<Layer>> {
+        match parent {
+            None => Some(&self.layers),
+            Some(parent) => match self.layer_select(parent)? {
+                LayerType::Folder(children) => Some(children),
+                LayerType::Shape(_) => None,
+            },
+        }
+    }
+
+    fn layer_children_mut(&mut self, parent: Option<LayerId>) -> Option<&mut Vec<Layer>> {
+        match parent {
+            None => Some(&mut self.layers),
+            Some(parent) => match self.layer_select_mut(parent)? {
+                LayerType::Folder(children) => Some(children),
+                LayerType::Shape(_) => None,
+            },
+        }
+    }
+
+    /// Layer at the place, None if the folder has no layer at this index
+    pub(crate) fn layer_at(&self, place: LayerPlace) -> Option<LayerId> {
+        let layers = self.layer_children(place.parent)?;
+        layers.get(place.index).map(|layer| layer.id)
     }
 
+    pub(crate) fn layer_place(&self, index: LayerId) -> Option<LayerPlace> {
+        layer_find_place(&self.layers, None, index)
+    }
+
+    /// Position of the layer in its folder
     pub fn layer_position(&self, index: LayerId) -> Option<usize> {
-        self.layers.iter().position(|l| l.id == index)
+        self.layer_place(index).map(|place| place.index)
+    }
+
+    /// Folder containing the layer, None for a layer at the root of the scene
+    pub fn layer_parent(&self, index: LayerId) -> Option<LayerId> {
+        self.layer_place(index)?.parent
+    }
+
+    /// True if the layer is in the folder or in one of its folders
+    pub fn layer_is_inside(&self, index: LayerId, folder: LayerId) -> bool {
+        match self.layer_select(folder) {
+            Some(LayerType::Folder(children)) => {
+                LayerIter::new(children).any(|(_, layer)| layer.id == index)
+            }
+            _ => false,
+        }
+    }
+
+    /// Layer at the root of the scene containing the layer, the layer itself if it is at the root
+    pub fn layer_root(&self, index: LayerId) -> Option<LayerId> {
+        let mut index = index;
+        let mut place = self.layer_place(index)?;
+        while let Some(parent) = place.parent {
+            index = parent;
+            place = self.layer_place(parent)?;
+        }
+        Some(index)
+    }
+
+    /// Shapes of the layer, the shapes in a folder and in its folders
+    pub fn layer_shapes(&self, index: LayerId) -> Vec<LayerId> {
+        match self.layer_select(index) {
+            Some(LayerType::Shape(shape)) => vec![shape.id],
+            Some(LayerType::Folder(children)) => LayerIter::new(children)
+                .filter_map(|(_, layer)| match &layer.value {
+                    LayerType::Shape(shape) => Some(shape.id),
+                    LayerType::Folder(_) => None,
+                })
+                .collect(),
+            None => Vec::new(),
+        }
     }
 
     pub fn layer_delete(&mut self, index: LayerId) {
-        self.layers.retain(|l| l.id != index);
+        self.layer_remove(index);
+    }
+
+    /// Remove the layer and return it with its place, to put it back with `layer_insert_at`
+    pub(crate) fn layer_remove(&mut self, index: LayerId) -> Option<(LayerPlace, Layer)> {
+        let place = self.layer_place(index)?;
+        let layers = self.layer_children_mut(place.parent)?;
+        let layer = layers.remove(place.index);
+        self.index_mark_layer(&layer);
+        Some((place, layer))
+    }
+
+    pub(crate) fn layer_insert_at(
+        &mut self,
+        place: LayerPlace,
+        layer: Layer,
+    ) -> Result<(), String> {
+        self.index_mark_layer(&layer);
+        let layers = self
+            .layer_children_mut(place.parent)
+            .ok_or("folder not found")?;
+        let position = place.index.min(layers.len());
+        layers.insert(position, layer);
+        Ok(())
+    }
+
+    /// Mark the layer and the layers of its folder as changed in the index
+    fn index_mark_layer(&mut self, layer: &Layer) {
+        for (_, layer) in LayerIter::new(std::slice::from_ref(layer)) {
+            self.index.mark(layer.id);
+        }
     }
 
     pub fn layer_move_up(&mut self, index: LayerId) {
-        let index = self.layers.iter().position(|l| l.id == index).unwrap();
-        if index > 0 {
-            self.layers.swap(index, index - 1);
+        self.index.mark(index);
+        let place = self.layer_place(index).unwrap();
+        let layers = self.layer_children_mut(place.parent).unwrap();
+        if place.index > 0 {
+            layers.swap(place.index, place.index - 1);
         }
     }
 
     pub fn layer_move_top(&mut self, index: LayerId) {
-        let index = self.layers.iter().position(|l| l.id == index).unwrap();
-        if index > 0 {
-            self.layers[0..(index + 1)].rotate_right(1);
+        self.index.mark(index);
+        let place = self.layer_place(index).unwrap();
+        let layers = self.layer_children_mut(place.parent).unwrap();
+        if place.index > 0 {
+            layers[0..(place.index + 1)].rotate_right(1);
         }
     }
 
     pub fn layer_move_down(&mut self, index: LayerId) {
-        let index = self.layers.iter().position(|l| l.id == index).unwrap();
-        if index < self.layers.len() - 1 {
-            self.layers.swap(index, index + 1);
+        self.index.mark(index);
+        let place = self.layer_place(index).unwrap();
+        let layers = self.layer_children_mut(place.parent).unwrap();
+        if place.index < layers.len() - 1 {
+            layers.swap(place.index, place.index + 1);
         }
     }
 
+    /// Move the layer at the place of the other layer, in the folder of the other layer
     pub fn layer_move_before(
         &mut self,
         layer: LayerId,
         layer_after: LayerId,
     ) -> Result<(), String> {
-        let index_position = self
-            .layers
-            .iter()
-            .position(|l| l.id == layer_after)
+        let place = self
+            .layer_place(layer_after)
             .ok_or("id_position not found")?;
-        self.layer_move_at(layer, index_position)?;
-        Ok(())
+        self.layer_move_into(layer, place)
     }
 
+    /// Move the layer in its folder
     pub fn layer_move_at(&mut self, id_to_move: LayerId, index: usize) -> Result<(), String> {
-        let index_to_move = self
-            .layers
-            .iter()
-            .position(|l| l.id == id_to_move)
+        let place = self.layer_place(id_to_move).ok_or("id_to_move not found")?;
+        self.layer_move_into(
+            id_to_move,
+            LayerPlace {
+                parent: place.parent,
+                index,
+            },
+        )
+    }
+
+    /// Move the layer at the place, the folder of the place can be another folder
+    pub(crate) fn layer_move_into(
+        &mut self,
+        id_to_move: LayerId,
+        place: LayerPlace,
+    ) -> Result<(), String> {
+        if let Some(parent) = place.parent {
+            if parent == id_to_move || self.layer_is_inside(parent, id_to_move) {
+                return Err("A folder can't be moved inside itself".to_string());
+            }
+        }
+        self.layer_children(place.parent)
+            .ok_or("folder not found")?;
+
+        let (_, layer) = self
+            .layer_remove(id_to_move)
             .ok_or("id_to_move not found")?;
-        let layer = self.layers.remove(index_to_move);
-        self.layers.insert(index, layer);
-        Ok(())
+        self.layer_insert_at(place, layer)
     }
 
     pub fn max_rect(&self) -> Rect {
@@ -137,13 +422,13 @@ impl Scene {
 
     pub fn debug_string(&self) -> String {
         let mut result = String::new();
-        for layer in &self.layers {
+        for layer in self.layers_iter() {
             match &layer.value {
                 LayerType::Shape(shape) => {
                     let path = format!("{}\n", shape.path());
                     result.push_str(&path);
                 }
-                LayerType::Folder => {
+                LayerType::Folder(_) => {
                     result.push_str("Folder\n");
                 }
             }
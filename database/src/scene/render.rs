@@ -1,14 +1,21 @@
+use std::collections::HashSet;
+
 use common::{
     pures::Affine,
-    types::{Coord, ScreenRect},
+    types::{Coord, Rect, ScreenRect},
     Rgba,
 };
 use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{FillRule, GradientStop, LayerId, LineCap, LineJoin, Scene, StrokeAlign};
 
-use crate::{LayerId, Scene};
+use super::{Layer, LayerType};
 
 #[cfg(feature = "tiny-skia-renderer")]
 mod tiny_skia;
+#[cfg(feature = "tiny-skia-renderer")]
+pub use tiny_skia::TinySkiaRenderer;
 
 mod transparent_grid;
 
@@ -23,62 +30,226 @@ pub trait DrawingContext {
 
     fn set_fill(&mut self, color: &Rgba) -> Result<(), String>;
 
-    fn set_stroke(&mut self, color: &Rgba, size: f64) -> Result<(), String>;
+    fn set_stroke(&mut self, color: &Rgba, size: f64, style: &StrokeStyle) -> Result<(), String>;
+
+    /// Fill with a gradient along the line from start to end, the coords are in screen space
+    fn set_fill_linear_gradient(
+        &mut self,
+        start: &Coord,
+        end: &Coord,
+        stops: &[GradientStop],
+    ) -> Result<(), String>;
+
+    /// Fill with a gradient from the center to the circle of the radius, in screen space
+    fn set_fill_radial_gradient(
+        &mut self,
+        center: &Coord,
+        radius: f32,
+        stops: &[GradientStop],
+    ) -> Result<(), String>;
+
+    fn set_stroke_linear_gradient(
+        &mut self,
+        start: &Coord,
+        end: &Coord,
+        stops: &[GradientStop],
+        size: f64,
+        style: &StrokeStyle,
+    ) -> Result<(), String>;
+
+    fn set_stroke_radial_gradient(
+        &mut self,
+        center: &Coord,
+        radius: f32,
+        stops: &[GradientStop],
+        size: f64,
+        style: &StrokeStyle,
+    ) -> Result<(), String>;
+
+    fn set_fill_rule(&mut self, fill_rule: FillRule) -> Result<(), String>;
 
     fn start_shape(&mut self, start_point: &Coord) -> Result<(), String>;
 
+    /// Start the next subpath of the current shape, the previous subpath is left as it is
+    fn start_subpath(&mut self, start_point: &Coord) -> Result<(), String>;
+
     fn move_curve(&mut self, cp0: &Coord, cp1: &Coord, p1: &Coord) -> Result<(), String>;
 
     fn move_line(&mut self, p: &Coord) -> Result<(), String>;
 
+    /// Close the current subpath with a line to its start point
+    fn close_subpath(&mut self) -> Result<(), String>;
+
     fn close_shape(&mut self) -> Result<(), String>;
 
+    /// Fill and stroke the current shape without closing its last subpath
+    fn end_shape(&mut self) -> Result<(), String>;
+
+    /// Fill the text with the fill color, the position is the start of its baseline in screen space
+    fn fill_text(&mut self, text: &str, position: &Coord) -> Result<(), String>;
+
+    /// Draw the next shapes in a layer of their own until `end_group`. The layer is then drawn
+    /// with the opacity, the shapes of the group do not show through each other
+    fn begin_group(&mut self, opacity: f32) -> Result<(), String>;
+
+    fn end_group(&mut self) -> Result<(), String>;
+
     fn end(&mut self) -> Result<(), String>;
 }
 
+/// How a stroke is drawn, the lengths are in screen space
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrokeStyle {
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+    /// Lengths of the dashes and the gaps in alternance, a solid line if empty
+    pub dashes: Vec<f64>,
+    pub dash_offset: f64,
+    /// Inside and outside draw the stroke with twice the size, clipped by the shape
+    pub align: StrokeAlign,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+            align: StrokeAlign::default(),
+        }
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct RenderOption {
     /// If set, only render the layers up to this layer not included
     pub to_layer: Option<LayerId>,
-    /// If set, skip the layers with these ids
+    /// If set, skip the layers with these ids, a skipped folder skips its layers.
+    /// The hidden layers are always skipped
     pub skip_layers: Vec<LayerId>,
-    /// If set, only render the layers with these ids
+    /// If set, only render the layers with these ids, a folder renders all its layers
     pub only_layers: Vec<LayerId>,
+    /// If set, skip the shapes with bounds outside this rectangle of the scene.
+    /// The view of the camera, or a dirty region to draw again only what changed. Not saved
+    #[serde(skip)]
+    pub view: Option<Rect>,
+}
+
+/// Count of the shapes of a render
+#[derive(Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderStats {
+    pub rendered: usize,
+    /// Shapes skipped as they are outside of the view
+    pub culled: usize,
 }
 
 impl Scene {
     pub fn render<T: DrawingContext>(&self, renderer: &mut T) -> Result<(), String> {
-        self.render_with_options(renderer, RenderOption::default())
+        self.render_with_options(renderer, RenderOption::default())?;
+        Ok(())
     }
 
     pub fn render_with_options(
         &self,
         renderer: &mut impl DrawingContext,
         options: RenderOption,
-    ) -> Result<(), String> {
+    ) -> Result<RenderStats, String> {
         renderer.create()?;
         renderer.fill_background(&self.background)?;
         //use transparent_grid::render_transparent_grid;
         //render_transparent_grid(renderer)?;
 
-        for layer in self.layers.iter().rev() {
-            if let Some(to_layer) = options.to_layer {
-                if layer.id == to_layer {
-                    break;
-                }
-            }
+        let in_view = options.view.map(|view| self.shapes_in_view(&view));
+        let mut stats = RenderStats::default();
+        render_layers(
+            &self.layers,
+            renderer,
+            &options,
+            &in_view,
+            &mut stats,
+            false,
+        )?;
+
+        Ok(stats)
+    }
 
-            if options.skip_layers.contains(&layer.id) {
-                continue;
-            }
+    /// Shapes that can be in the view, from the index when it is built
+    fn shapes_in_view(&self, view: &Rect) -> HashSet<LayerId> {
+        match self.index.shapes_candidates(view) {
+            Some(candidates) => candidates,
+            None => self
+                .layers_iter()
+                .filter_map(|layer| match &layer.value {
+                    LayerType::Shape(shape) => shape
+                        .bounding_box_with_stroke()
+                        .filter(|bounds| bounds.intersect(view))
+                        .map(|_| shape.id),
+                    LayerType::Folder(_) => None,
+                })
+                .collect(),
+        }
+    }
+}
 
-            if options.only_layers.is_empty() || options.only_layers.contains(&layer.id) {
-                layer.value.render(renderer)?;
+/// Render the layers from the background, the folders with their layers.
+/// A layer of `only_layers` renders all its layers. Return false once `to_layer` is reached.
+/// The opacity of a shape is applied to its paints, a folder with an opacity is drawn in a group.
+/// The shapes not in `in_view` are counted as culled
+fn render_layers(
+    layers: &[Layer],
+    renderer: &mut impl DrawingContext,
+    options: &RenderOption,
+    in_view: &Option<HashSet<LayerId>>,
+    stats: &mut RenderStats,
+    included: bool,
+) -> Result<bool, String> {
+    for layer in layers.iter().rev() {
+        if let Some(to_layer) = options.to_layer {
+            if layer.id == to_layer {
+                return Ok(false);
             }
         }
 
-        Ok(())
+        if !layer.visible || options.skip_layers.contains(&layer.id) {
+            continue;
+        }
+
+        let included =
+            included || options.only_layers.is_empty() || options.only_layers.contains(&layer.id);
+        let opacity = layer.opacity;
+        match &layer.value {
+            LayerType::Shape(_) if !included => {}
+            LayerType::Shape(shape) if in_view.as_ref().is_some_and(|s| !s.contains(&shape.id)) => {
+                stats.culled += 1;
+            }
+            LayerType::Shape(shape) => {
+                match opacity < 1.0 {
+                    true => shape.with_opacity(opacity).render(renderer)?,
+                    false => shape.render(renderer)?,
+                }
+                stats.rendered += 1;
+            }
+            LayerType::Folder(children) if opacity < 1.0 => {
+                renderer.begin_group(opacity)?;
+                let more = render_layers(children, renderer, options, in_view, stats, included)?;
+                renderer.end_group()?;
+                if !more {
+                    return Ok(false);
+                }
+            }
+            LayerType::Folder(children) => {
+                if !render_layers(children, renderer, options, in_view, stats, included)? {
+                    return Ok(false);
+                }
+            }
+        }
     }
+    Ok(true)
 }
 
 #[cfg(test)]
@@ -123,7 +294,56 @@ impl DrawingContext for MockDrawingContext {
         Ok(())
     }
 
-    fn set_stroke(&mut self, _color: &Rgba, _size: f64) -> Result<(), String> {
+    fn set_stroke(
+        &mut self,
+        _color: &Rgba,
+        _size: f64,
+        _style: &StrokeStyle,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn set_fill_linear_gradient(
+        &mut self,
+        _start: &Coord,
+        _end: &Coord,
+        _stops: &[GradientStop],
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn set_fill_radial_gradient(
+        &mut self,
+        _center: &Coord,
+        _radius: f32,
+        _stops: &[GradientStop],
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn set_stroke_linear_gradient(
+        &mut self,
+        _start: &Coord,
+        _end: &Coord,
+        _stops: &[GradientStop],
+        _size: f64,
+        _style: &StrokeStyle,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn set_stroke_radial_gradient(
+        &mut self,
+        _center: &Coord,
+        _radius: f32,
+        _stops: &[GradientStop],
+        _size: f64,
+        _style: &StrokeStyle,
+    ) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn set_fill_rule(&mut self, _fill_rule: FillRule) -> Result<(), String> {
         Ok(())
     }
 
@@ -131,14 +351,38 @@ impl DrawingContext for MockDrawingContext {
         Ok(())
     }
 
+    fn start_subpath(&mut self, _start_point: &Coord) -> Result<(), String> {
+        Ok(())
+    }
+
     fn move_curve(&mut self, _cp0: &Coord, _cp1: &Coord, _p1: &Coord) -> Result<(), String> {
         Ok(())
     }
 
+    fn close_subpath(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
     fn close_shape(&mut self) -> Result<(), String> {
         Ok(())
     }
 
+    fn end_shape(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn fill_text(&mut self, _: &str, _: &Coord) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn begin_group(&mut self, _: f32) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn end_group(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
     fn end(&mut self) -> Result<(), String> {
         Ok(())
     }
@@ -147,3 +391,41 @@ impl DrawingContext for MockDrawingContext {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, Length2d, Rect};
+
+    use crate::{commands::CommandsHandler, Scene, Shape};
+
+    use super::{MockDrawingContext, RenderOption, RenderStats};
+
+    #[test]
+    fn given_shapes_outside_view_when_render_then_culled_with_or_without_index() {
+        let mut scene = Scene::new();
+        for i in 0..10 {
+            let center = Coord::new(i as f32, 0.0);
+            scene.shape_insert(Shape::new_circle(center, Length2d::new(0.1, 0.1)));
+        }
+        let options = RenderOption {
+            view: Some(Rect::new(-0.5, -0.5, 1.5, 0.5)),
+            ..Default::default()
+        };
+        let expected = RenderStats {
+            rendered: 2,
+            culled: 8,
+        };
+
+        let stats = scene
+            .render_with_options(&mut MockDrawingContext::default(), options.clone())
+            .unwrap();
+        assert_eq!(stats, expected);
+
+        let command_handler = CommandsHandler::from(scene);
+        let stats = command_handler
+            .scene()
+            .render_with_options(&mut MockDrawingContext::default(), options)
+            .unwrap();
+        assert_eq!(stats, expected);
+    }
+}
@@ -1,47 +1,228 @@
-use tiny_skia::{Paint, PathBuilder, Pixmap};
+use common::{
+    pures::Affine,
+    types::{Coord, Rect, ScreenRect},
+    Rgba,
+};
+use tiny_skia::{
+    Mask, Paint, PathBuilder, Pixmap, Point, Shader, SpreadMode, Stroke, StrokeDash, Transform,
+};
 
-#[derive(Default)]
-pub struct TinySkiaRenderer<'a> {
+use crate::{FillRule, GradientStop, LineCap, LineJoin, Scene, StrokeAlign};
+
+use super::{DrawingContext, StrokeStyle};
+
+/// Renderer to a pixmap in memory, without a browser canvas
+pub struct TinySkiaRenderer {
     transform: Affine,
+    width: u32,
+    height: u32,
     pixmap: Option<Pixmap>,
-    paint: Option<Paint<'a>>,
+    fill: Paint<'static>,
+    stroke: Paint<'static>,
+    stroke_style: Stroke,
+    stroke_align: StrokeAlign,
+    fill_rule: tiny_skia::FillRule,
     current_path: Option<PathBuilder>,
+    /// Pixmaps under the open groups with the opacity of the groups
+    groups: Vec<(Pixmap, f32)>,
 }
 
-impl<'a> TinySkiaRenderer<'a> {
-    pub fn new(width: f32, height: f32) -> Self {
-        let mut rtn = Self::default();
-        rtn.transform = Affine::from_translate(Vec2::new(1.0, 1.0))
-            .scale(Vec2::new(0.5, 0.5))
-            .scale(Vec2::new(width, height));
-        rtn
+impl TinySkiaRenderer {
+    /// The transform goes from the scene coordinates to the pixels of the image
+    pub fn new(width: u32, height: u32, transform: Affine) -> Self {
+        Self {
+            transform,
+            width,
+            height,
+            pixmap: None,
+            fill: paint(&Rgba::transparent()),
+            stroke: paint(&Rgba::transparent()),
+            stroke_style: Stroke {
+                width: 0.0,
+                ..Stroke::default()
+            },
+            stroke_align: StrokeAlign::Center,
+            fill_rule: tiny_skia::FillRule::EvenOdd,
+            current_path: None,
+            groups: Vec::new(),
+        }
+    }
+
+    pub fn pixmap(&self) -> Option<&Pixmap> {
+        self.pixmap.as_ref()
+    }
+
+    pub fn take_pixmap(&mut self) -> Option<Pixmap> {
+        self.pixmap.take()
+    }
+
+    fn pixmap_mut(&mut self) -> Result<&mut Pixmap, String> {
+        self.pixmap.as_mut().ok_or("Pixmap not created".to_string())
+    }
+
+    fn path_mut(&mut self) -> Result<&mut PathBuilder, String> {
+        self.current_path
+            .as_mut()
+            .ok_or("No shape started".to_string())
     }
 
-    pub fn get_rgba(self) -> Option<Vec<u8>> {
-        match self.pixmap {
-            Some(pixmap) => Some(pixmap.take()),
-            None => None,
+    /// Fill and stroke the current path
+    fn draw_path(&mut self) -> Result<(), String> {
+        let builder = self.current_path.take().ok_or("No shape started")?;
+        let Some(path) = builder.finish() else {
+            return Ok(());
+        };
+
+        let pixmap = self.pixmap.as_mut().ok_or("Pixmap not created")?;
+        if !is_transparent(&self.fill) {
+            pixmap.fill_path(
+                &path,
+                &self.fill,
+                self.fill_rule,
+                Transform::identity(),
+                None,
+            );
         }
+        if self.stroke_style.width > 0.0 && !is_transparent(&self.stroke) {
+            // The inside and outside strokes are centered strokes of twice the size, clipped
+            let mut stroke = self.stroke_style.clone();
+            let mask = match self.stroke_align {
+                StrokeAlign::Center => None,
+                align => {
+                    stroke.width *= 2.0;
+                    let mut mask = Mask::new(self.width, self.height).ok_or("Invalid mask size")?;
+                    mask.fill_path(&path, self.fill_rule, true, Transform::identity());
+                    if align == StrokeAlign::Outside {
+                        mask.invert();
+                    }
+                    Some(mask)
+                }
+            };
+            pixmap.stroke_path(
+                &path,
+                &self.stroke,
+                &stroke,
+                Transform::identity(),
+                mask.as_ref(),
+            );
+        }
+        Ok(())
+    }
+
+    fn set_stroke_style(&mut self, size: f64, style: &StrokeStyle) {
+        // An odd dash array is repeated to have dashes and gaps in pairs, like the canvas
+        let mut dashes: Vec<f32> = style.dashes.iter().map(|dash| *dash as f32).collect();
+        if dashes.len() % 2 == 1 {
+            dashes.extend_from_within(..);
+        }
+        self.stroke_style = Stroke {
+            width: size as f32,
+            miter_limit: style.miter_limit,
+            line_cap: match style.cap {
+                LineCap::Butt => tiny_skia::LineCap::Butt,
+                LineCap::Round => tiny_skia::LineCap::Round,
+                LineCap::Square => tiny_skia::LineCap::Square,
+            },
+            line_join: match style.join {
+                LineJoin::Miter => tiny_skia::LineJoin::Miter,
+                LineJoin::Round => tiny_skia::LineJoin::Round,
+                LineJoin::Bevel => tiny_skia::LineJoin::Bevel,
+            },
+            dash: StrokeDash::new(dashes, style.dash_offset as f32),
+        };
+        self.stroke_align = style.align;
+    }
+}
+
+fn paint(color: &Rgba) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(color.r, color.g, color.b, color.a);
+    paint.anti_alias = true;
+    paint
+}
+
+fn linear_gradient(start: &Coord, end: &Coord, stops: &[GradientStop]) -> Paint<'static> {
+    let shader = tiny_skia::LinearGradient::new(
+        Point::from_xy(start.x, start.y),
+        Point::from_xy(end.x, end.y),
+        gradient_stops(stops),
+        SpreadMode::Pad,
+        Transform::identity(),
+    );
+    gradient_paint(shader, stops)
+}
+
+fn radial_gradient(center: &Coord, radius: f32, stops: &[GradientStop]) -> Paint<'static> {
+    let center = Point::from_xy(center.x, center.y);
+    let shader = tiny_skia::RadialGradient::new(
+        center,
+        center,
+        radius,
+        gradient_stops(stops),
+        SpreadMode::Pad,
+        Transform::identity(),
+    );
+    gradient_paint(shader, stops)
+}
+
+fn gradient_stops(stops: &[GradientStop]) -> Vec<tiny_skia::GradientStop> {
+    stops
+        .iter()
+        .map(|stop| {
+            let color = &stop.color;
+            let color = tiny_skia::Color::from_rgba8(color.r, color.g, color.b, color.a);
+            tiny_skia::GradientStop::new(stop.offset, color)
+        })
+        .collect()
+}
+
+/// A degenerate gradient, like an empty line, is painted with its last color
+fn gradient_paint(shader: Option<Shader<'static>>, stops: &[GradientStop]) -> Paint<'static> {
+    match shader {
+        Some(shader) => Paint {
+            shader,
+            anti_alias: true,
+            ..Paint::default()
+        },
+        None => paint(
+            &stops
+                .last()
+                .map(|stop| stop.color.clone())
+                .unwrap_or(Rgba::transparent()),
+        ),
+    }
+}
+
+fn is_transparent(paint: &Paint) -> bool {
+    match &paint.shader {
+        tiny_skia::Shader::SolidColor(color) => color.alpha() == 0.0,
+        _ => false,
     }
 }
 
-impl<'a> VgcRenderer for TinySkiaRenderer<'a> {
+impl DrawingContext for TinySkiaRenderer {
     fn create(&mut self) -> Result<(), String> {
         self.pixmap = Some(
-            Pixmap::new(
-                (self.transform.get_scale().x * 2.0) as u32,
-                (self.transform.get_scale().y * 2.0) as u32,
-            )
-            .expect("Valid Size"),
+            Pixmap::new(self.width, self.height)
+                .ok_or(format!("Invalid image size {}x{}", self.width, self.height))?,
         );
         Ok(())
     }
 
     fn fill_background(&mut self, color: &Rgba) -> Result<(), String> {
-        let pixmap = self.pixmap.as_mut().expect("Valid Pixmap");
-        pixmap.fill(tiny_skia::Color::from_rgba8(
-            color.r, color.g, color.b, color.a,
-        ));
+        let corner0 = self.transform * Coord::new(-1.0, -1.0);
+        let corner1 = self.transform * Coord::new(1.0, 1.0);
+        let rect = tiny_skia::Rect::from_ltrb(
+            corner0.x.min(corner1.x),
+            corner0.y.min(corner1.y),
+            corner0.x.max(corner1.x),
+            corner0.y.max(corner1.y),
+        )
+        .ok_or("Invalid background rectangle")?;
+
+        let paint = paint(color);
+        self.pixmap_mut()?
+            .fill_rect(rect, &paint, Transform::identity(), None);
         Ok(())
     }
 
@@ -49,41 +230,144 @@ impl<'a> VgcRenderer for TinySkiaRenderer<'a> {
         Ok(self.transform)
     }
 
+    fn get_max_view(&self) -> Result<ScreenRect, String> {
+        Ok(ScreenRect::new(
+            0.0,
+            0.0,
+            self.width as f32,
+            self.height as f32,
+        ))
+    }
+
     fn set_fill(&mut self, color: &Rgba) -> Result<(), String> {
-        let mut paint = Paint::default();
-        paint.set_color_rgba8(color.r, color.g, color.b, color.a);
-        paint.anti_alias = true;
-        self.paint = Some(paint);
+        self.fill = paint(color);
+        Ok(())
+    }
+
+    fn set_stroke(&mut self, color: &Rgba, size: f64, style: &StrokeStyle) -> Result<(), String> {
+        self.stroke = paint(color);
+        self.set_stroke_style(size, style);
+        Ok(())
+    }
+
+    fn set_fill_linear_gradient(
+        &mut self,
+        start: &Coord,
+        end: &Coord,
+        stops: &[GradientStop],
+    ) -> Result<(), String> {
+        self.fill = linear_gradient(start, end, stops);
+        Ok(())
+    }
+
+    fn set_fill_radial_gradient(
+        &mut self,
+        center: &Coord,
+        radius: f32,
+        stops: &[GradientStop],
+    ) -> Result<(), String> {
+        self.fill = radial_gradient(center, radius, stops);
+        Ok(())
+    }
+
+    fn set_stroke_linear_gradient(
+        &mut self,
+        start: &Coord,
+        end: &Coord,
+        stops: &[GradientStop],
+        size: f64,
+        style: &StrokeStyle,
+    ) -> Result<(), String> {
+        self.stroke = linear_gradient(start, end, stops);
+        self.set_stroke_style(size, style);
+        Ok(())
+    }
+
+    fn set_stroke_radial_gradient(
+        &mut self,
+        center: &Coord,
+        radius: f32,
+        stops: &[GradientStop],
+        size: f64,
+        style: &StrokeStyle,
+    ) -> Result<(), String> {
+        self.stroke = radial_gradient(center, radius, stops);
+        self.set_stroke_style(size, style);
+        Ok(())
+    }
+
+    fn set_fill_rule(&mut self, fill_rule: FillRule) -> Result<(), String> {
+        self.fill_rule = match fill_rule {
+            FillRule::NonZero => tiny_skia::FillRule::Winding,
+            FillRule::EvenOdd => tiny_skia::FillRule::EvenOdd,
+        };
         Ok(())
     }
 
     fn start_shape(&mut self, start_point: &Coord) -> Result<(), String> {
-        let mut pb = PathBuilder::new();
-        pb.move_to(start_point.x(), start_point.y());
-        self.current_path = Some(pb);
+        let mut builder = PathBuilder::new();
+        builder.move_to(start_point.x, start_point.y);
+        self.current_path = Some(builder);
+        Ok(())
+    }
+
+    fn start_subpath(&mut self, start_point: &Coord) -> Result<(), String> {
+        self.path_mut()?.move_to(start_point.x, start_point.y);
         Ok(())
     }
 
     fn move_curve(&mut self, cp0: &Coord, cp1: &Coord, p1: &Coord) -> Result<(), String> {
-        let pb = self.current_path.as_mut().expect("Valid PathBuilder");
-        pb.cubic_to(cp0.x(), cp0.y(), cp1.x(), cp1.y(), p1.x(), p1.y());
+        self.path_mut()?
+            .cubic_to(cp0.x, cp0.y, cp1.x, cp1.y, p1.x, p1.y);
+        Ok(())
+    }
+
+    fn move_line(&mut self, p: &Coord) -> Result<(), String> {
+        self.path_mut()?.line_to(p.x, p.y);
+        Ok(())
+    }
+
+    fn close_subpath(&mut self) -> Result<(), String> {
+        self.path_mut()?.close();
         Ok(())
     }
 
     fn close_shape(&mut self) -> Result<(), String> {
-        let pb = self.current_path.take().expect("Valid PathBuilder");
-        let path = pb.finish().expect("Valid Path");
+        self.path_mut()?.close();
+        self.draw_path()
+    }
+
+    fn end_shape(&mut self) -> Result<(), String> {
+        self.draw_path()
+    }
+
+    fn fill_text(&mut self, _: &str, _: &Coord) -> Result<(), String> {
+        // tiny-skia has no text, the exports have no overlay
+        Ok(())
+    }
 
-        let pixmap = self.pixmap.as_mut().expect("Valid Pixmap");
-        let paint = self.paint.as_ref().expect("Valid Paint");
+    fn begin_group(&mut self, opacity: f32) -> Result<(), String> {
+        let group = Pixmap::new(self.width, self.height).ok_or("Invalid group size")?;
+        let under = self.pixmap.replace(group).ok_or("Pixmap not created")?;
+        self.groups.push((under, opacity));
+        Ok(())
+    }
 
-        pixmap.fill_path(
-            &path,
-            &paint,
-            tiny_skia::FillRule::Winding,
-            tiny_skia::Transform::identity(),
+    fn end_group(&mut self) -> Result<(), String> {
+        let (mut under, opacity) = self.groups.pop().ok_or("No group started")?;
+        let group = self.pixmap.take().ok_or("Pixmap not created")?;
+        under.draw_pixmap(
+            0,
+            0,
+            group.as_ref(),
+            &tiny_skia::PixmapPaint {
+                opacity,
+                ..tiny_skia::PixmapPaint::default()
+            },
+            Transform::identity(),
             None,
         );
+        self.pixmap = Some(under);
         Ok(())
     }
 
@@ -92,45 +376,280 @@ impl<'a> VgcRenderer for TinySkiaRenderer<'a> {
     }
 }
 
+/// Largest number of pixels of a PNG, the pixmap of this size is 256 MiB
+const PNG_MAX_PIXELS: u64 = 8192 * 8192;
+
+impl Scene {
+    /// Render the scene to a PNG image of the size, the scene range -1.0 to 1.0 fills the image.
+    /// The background color is under the background of the scene.
+    /// An empty image or an image over `PNG_MAX_PIXELS` is an error
+    pub fn render_png(&self, width: u32, height: u32, background: Rgba) -> Result<Vec<u8>, String> {
+        if width == 0 || height == 0 || width as u64 * height as u64 > PNG_MAX_PIXELS {
+            return Err(format!(
+                "Invalid image size {}x{}, the image must have 1 to {} pixels",
+                width, height, PNG_MAX_PIXELS
+            ));
+        }
+        let transform = Rect::new(0.0, 0.0, width as f32, height as f32)
+            .affine_to_normal()
+            .inverse();
+        let mut renderer = TinySkiaRenderer::new(width, height, transform);
+        self.render(&mut renderer)?;
+
+        let pixmap = renderer.take_pixmap().ok_or("Pixmap not created")?;
+        let mut image = Pixmap::new(width, height).ok_or("Invalid image size")?;
+        image.fill(tiny_skia::Color::from_rgba8(
+            background.r,
+            background.g,
+            background.b,
+            background.a,
+        ));
+        image.draw_pixmap(
+            0,
+            0,
+            pixmap.as_ref(),
+            &tiny_skia::PixmapPaint::default(),
+            Transform::identity(),
+            None,
+        );
+
+        image.encode_png().map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
 mod test {
+    use common::{
+        pures::Affine,
+        types::{Coord, Rect},
+        Rgba,
+    };
+
+    use crate::{
+        commands::{ChangeLayerOpacity, CommandsHandler, Group, InsertShape},
+        scene::shape::Stroke,
+        DbCoord, GradientStop, GradientUnits, LayerId, LinearGradient, Paint, RenderOption, Scene,
+        Shape, StrokeAlign,
+    };
+
+    use super::TinySkiaRenderer;
+
+    fn scene_with_square() -> Scene {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(-0.5, -0.5),
+                DbCoord::new(0.5, -0.5),
+                DbCoord::new(0.5, 0.5),
+                DbCoord::new(-0.5, 0.5),
+            ],
+            Affine::identity(),
+        );
+        shape.fill = Rgba::black().into();
+        shape.stroke = Stroke::new(0.1, Rgba::red());
+        scene.shape_insert(shape);
+        scene
+    }
 
     #[test]
-    fn test_tiny_skia_renderer() {
-        use super::*;
-        use crate::generate_from_push;
-        use common::types::Coord;
-
-        let vgc = generate_from_push(vec![vec![
-            Coord::new(0.43 * 2.0 - 1.0, 0.27 * 2.0 - 1.0),
-            Coord::new(0.06577811 * 2.0 - 1.0, 0.2938202 * 2.0 - 1.0),
-            Coord::new(0.0 * 2.0 - 1.0, 1.0),
-            Coord::new(0.0 * 2.0 - 1.0, 1.0),
-            Coord::new(0.0 * 2.0 - 1.0, 1.0),
-            Coord::new(1.0, 1.0),
-            Coord::new(1.0, 1.0),
-            Coord::new(1.0, 1.0),
-            Coord::new(0.7942219 * 2.0 - 1.0, 0.24617982 * 2.0 - 1.0),
-            Coord::new(0.43 * 2.0 - 1.0, 0.27 * 2.0 - 1.0),
-        ]]);
-
-        let renderer = &mut TinySkiaRenderer::new(512.0, 512.0);
-
-        let res = vgc.render(renderer);
-        assert!(res.is_ok());
-
-        let pixmap = renderer.pixmap.take().expect("Valid Pixmap");
+    fn given_square_when_render_then_fill_and_stroke_pixels() {
+        let scene = scene_with_square();
+        let transform = Rect::new(0.0, 0.0, 100.0, 100.0)
+            .affine_to_normal()
+            .inverse();
+        let mut renderer = TinySkiaRenderer::new(100, 100, transform);
+
+        scene.render(&mut renderer).unwrap();
 
+        let pixmap = renderer.pixmap().unwrap();
+        let pixel = |x, y| pixmap.pixel(x, y).unwrap().demultiply();
         let black = tiny_skia::ColorU8::from_rgba(0, 0, 0, 255);
-        let white = tiny_skia::ColorU8::from_rgba(255, 255, 255, 255);
+        let red = tiny_skia::ColorU8::from_rgba(255, 0, 0, 255);
+        assert_eq!(pixel(50, 50), black);
+        assert_eq!(pixel(25, 50), red);
+        assert_eq!(pixel(5, 5).alpha(), 0);
+    }
+
+    #[test]
+    fn given_grouped_square_when_render_with_options_then_folder_filters_its_layers() {
+        let mut command_handler = CommandsHandler::from(scene_with_square());
+        let square = LayerId::from(command_handler.scene().get_tree_view()[0].layer_id);
+        command_handler.execute(Group::boxed(vec![square])).unwrap();
+        let scene = command_handler.scene();
+        let folder = scene.layer_parent(square).unwrap();
+        let transform = Rect::new(0.0, 0.0, 100.0, 100.0)
+            .affine_to_normal()
+            .inverse();
+        let center_alpha = |options: RenderOption| {
+            let mut renderer = TinySkiaRenderer::new(100, 100, transform);
+            scene.render_with_options(&mut renderer, options).unwrap();
+            let pixmap = renderer.take_pixmap().unwrap();
+            pixmap.pixel(50, 50).unwrap().alpha()
+        };
+        let mut options = RenderOption::default();
+        assert_eq!(center_alpha(options.clone()), 255);
 
-        assert!(pixmap.pixel(511, 511).is_some());
-        assert!(pixmap.pixel(514, 514).is_none());
+        options.skip_layers = vec![folder];
+        assert_eq!(center_alpha(options.clone()), 0);
 
-        assert!(pixmap.pixel(107, 190).unwrap().demultiply() == white);
-        assert!(pixmap.pixel(118, 200).unwrap().demultiply() == black);
-        assert!(pixmap.pixel(482, 484).unwrap().demultiply() == black);
-        assert!(pixmap.pixel(511, 494).unwrap().demultiply() == white);
+        options.skip_layers.clear();
+        options.only_layers = vec![folder];
+        assert_eq!(center_alpha(options.clone()), 255);
 
-        assert!(pixmap.encode_png().is_ok());
+        options.to_layer = Some(square);
+        assert_eq!(center_alpha(options), 0);
+    }
+
+    #[test]
+    fn given_half_opaque_folder_when_render_then_square_half_transparent() {
+        let mut command_handler = CommandsHandler::from(scene_with_square());
+        let square = LayerId::from(command_handler.scene().get_tree_view()[0].layer_id);
+        command_handler.execute(Group::boxed(vec![square])).unwrap();
+        let folder = command_handler.scene().layer_parent(square).unwrap();
+        command_handler
+            .execute(ChangeLayerOpacity::boxed(folder, 0.5))
+            .unwrap();
+        let transform = Rect::new(0.0, 0.0, 100.0, 100.0)
+            .affine_to_normal()
+            .inverse();
+        let mut renderer = TinySkiaRenderer::new(100, 100, transform);
+
+        command_handler.scene().render(&mut renderer).unwrap();
+
+        let pixmap = renderer.take_pixmap().unwrap();
+        assert_eq!(pixmap.pixel(50, 50).unwrap().alpha(), 128);
+    }
+
+    #[test]
+    fn given_half_opaque_folder_of_overlapping_squares_when_render_then_group_uniform() {
+        let mut command_handler = CommandsHandler::from(scene_with_square());
+        let mut shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(1.0, 0.0),
+                DbCoord::new(1.0, 1.0),
+                DbCoord::new(0.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        shape.fill = Rgba::black().into();
+        command_handler.execute(InsertShape::boxed(shape)).unwrap();
+        let shapes = command_handler
+            .scene()
+            .get_tree_view()
+            .iter()
+            .map(|layer| LayerId::from(layer.layer_id))
+            .collect();
+        command_handler.execute(Group::boxed(shapes)).unwrap();
+        let folder = LayerId::from(command_handler.scene().get_tree_view()[0].layer_id);
+        command_handler
+            .execute(ChangeLayerOpacity::boxed(folder, 0.5))
+            .unwrap();
+        let transform = Rect::new(0.0, 0.0, 100.0, 100.0)
+            .affine_to_normal()
+            .inverse();
+        let mut renderer = TinySkiaRenderer::new(100, 100, transform);
+
+        command_handler.scene().render(&mut renderer).unwrap();
+
+        // The overlap is as transparent as each square alone, not seen through the other square
+        let pixmap = renderer.take_pixmap().unwrap();
+        assert_eq!(pixmap.pixel(40, 40).unwrap().alpha(), 128);
+        assert_eq!(pixmap.pixel(60, 60).unwrap().alpha(), 128);
+        assert_eq!(pixmap.pixel(90, 90).unwrap().alpha(), 128);
+    }
+
+    #[test]
+    fn given_open_path_when_render_then_only_stroke() {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new_polyline(
+            vec![
+                DbCoord::new(-0.5, 0.0),
+                DbCoord::new(0.5, 0.0),
+                DbCoord::new(0.5, 0.5),
+            ],
+            Affine::identity(),
+        );
+        shape.fill = Rgba::black().into();
+        shape.stroke = Stroke::new(0.1, Rgba::red());
+        scene.shape_insert(shape);
+        let transform = Rect::new(0.0, 0.0, 100.0, 100.0)
+            .affine_to_normal()
+            .inverse();
+        let mut renderer = TinySkiaRenderer::new(100, 100, transform);
+
+        scene.render(&mut renderer).unwrap();
+
+        let pixmap = renderer.pixmap().unwrap();
+        assert_eq!(pixmap.pixel(50, 50).unwrap().alpha(), 255);
+        assert_eq!(pixmap.pixel(60, 60).unwrap().alpha(), 0);
+    }
+
+    #[test]
+    fn given_linear_gradient_when_render_then_colors_follow_the_shape() {
+        let mut scene = scene_with_square();
+        let id = LayerId::from(scene.get_tree_view()[0].layer_id);
+        let shape = scene.shape_select_mut(id).unwrap();
+        shape.stroke = Stroke::new(0.0, Rgba::black());
+        shape.fill = Paint::LinearGradient(LinearGradient {
+            start: Coord::new(0.0, 0.0),
+            end: Coord::new(1.0, 0.0),
+            stops: vec![
+                GradientStop::new(0.0, Rgba::red()),
+                GradientStop::new(1.0, Rgba::blue()),
+            ],
+            units: GradientUnits::Shape,
+        });
+        let transform = Rect::new(0.0, 0.0, 100.0, 100.0)
+            .affine_to_normal()
+            .inverse();
+        let mut renderer = TinySkiaRenderer::new(100, 100, transform);
+
+        scene.render(&mut renderer).unwrap();
+
+        let pixmap = renderer.pixmap().unwrap();
+        let left = pixmap.pixel(27, 50).unwrap().demultiply();
+        let right = pixmap.pixel(73, 50).unwrap().demultiply();
+        assert!(left.red() > 200 && left.blue() < 50);
+        assert!(right.blue() > 200 && right.red() < 50);
+    }
+
+    #[test]
+    fn given_aligned_strokes_when_render_then_stroke_on_one_side_of_the_path() {
+        let render = |align| {
+            let mut scene = scene_with_square();
+            let id = LayerId::from(scene.get_tree_view()[0].layer_id);
+            scene.shape_select_mut(id).unwrap().stroke.align = align;
+            let transform = Rect::new(0.0, 0.0, 100.0, 100.0)
+                .affine_to_normal()
+                .inverse();
+            let mut renderer = TinySkiaRenderer::new(100, 100, transform);
+            scene.render(&mut renderer).unwrap();
+            renderer.take_pixmap().unwrap()
+        };
+        let red = tiny_skia::ColorU8::from_rgba(255, 0, 0, 255);
+
+        let inside = render(StrokeAlign::Inside);
+        assert_eq!(inside.pixel(28, 50).unwrap().demultiply(), red);
+        assert_eq!(inside.pixel(22, 50).unwrap().alpha(), 0);
+
+        let outside = render(StrokeAlign::Outside);
+        assert_eq!(outside.pixel(22, 50).unwrap().demultiply(), red);
+        assert_eq!(outside.pixel(28, 50).unwrap().red(), 0);
+    }
+
+    #[test]
+    fn given_scene_when_render_png_then_png_of_size() {
+        let scene = scene_with_square();
+
+        let png = scene.render_png(64, 32, Rgba::white()).unwrap();
+
+        assert_eq!(&png[1..4], b"PNG");
+        let pixmap = tiny_skia::Pixmap::decode_png(&png).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (64, 32));
+        let white = tiny_skia::ColorU8::from_rgba(255, 255, 255, 255);
+        assert_eq!(pixmap.pixel(1, 1).unwrap().demultiply(), white);
+        assert!(scene.render_png(0, 32, Rgba::white()).is_err());
+        assert!(scene.render_png(100_000, 100_000, Rgba::white()).is_err());
     }
 }
@@ -64,7 +64,6 @@ pub fn verify_difference(res: ShapeDifference, a: Shape, b: Shape) {
             ShapeDifference::A => vec![a.clone()],
             ShapeDifference::EraseA => vec![],
             ShapeDifference::New(merged) => merged,
-            ShapeDifference::AWithBHole => unimplemented!(),
         }
     };
 
@@ -329,6 +328,7 @@ mod ovelapping_ovals {
 }
 
 mod enveloping_circles {
+    use super::verify_difference;
     use crate::{
         scene::shape::boolean::{
             difference::shape_difference, intersection::shape_intersection, union::shape_union,
@@ -375,9 +375,14 @@ mod enveloping_circles {
         let res = shape_difference(&a, &b);
 
         match &res {
-            ShapeDifference::AWithBHole => {}
+            ShapeDifference::New(merged) => {
+                assert_eq!(merged.len(), 1);
+                assert_eq!(merged[0].subpaths.len(), 1);
+            }
             _ => panic!("Unexpected result"),
         }
+
+        verify_difference(res, a, b);
     }
 }
 
@@ -690,3 +695,107 @@ mod squares_touching_outside {
         }
     }
 }
+
+mod donut_and_circle {
+    use common::types::{Coord, Length2d};
+
+    use super::{verify_difference, verify_intersection, verify_union};
+    use crate::{
+        scene::shape::boolean::{ShapeDifference, ShapeIntersection, ShapeUnion},
+        FillRule, Shape,
+    };
+
+    fn donut() -> Shape {
+        let a = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.8, 0.8));
+        let b = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.4, 0.4));
+        match a.difference(&b) {
+            ShapeDifference::New(mut shapes) => shapes.remove(0),
+            _ => panic!("Unexpected result"),
+        }
+    }
+
+    #[test]
+    fn contains() {
+        let mut a = donut();
+
+        assert!(a.contains(&Coord::new(0.6, 0.01)));
+        assert!(!a.contains(&Coord::new(0.01, 0.01)));
+
+        a.fill_rule = FillRule::NonZero;
+        assert!(a.contains(&Coord::new(0.6, 0.01)));
+        assert!(!a.contains(&Coord::new(0.01, 0.01)));
+    }
+
+    #[test]
+    fn union_fill_hole() {
+        let a = donut();
+        let b = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.5, 0.5));
+
+        let res = a.union(&b);
+
+        match &res {
+            ShapeUnion::New(merged) => assert!(merged.subpaths.is_empty()),
+            _ => panic!("Unexpected result"),
+        }
+
+        verify_union(res, a, b);
+    }
+
+    #[test]
+    fn union() {
+        let a = donut();
+        let b = Shape::new_circle(Coord::new(0.6, 0.0), Length2d::new(0.3, 0.3));
+
+        let res = a.union(&b);
+
+        match &res {
+            ShapeUnion::New(merged) => assert_eq!(merged.subpaths.len(), 1),
+            _ => panic!("Unexpected result"),
+        }
+
+        verify_union(res, a, b);
+    }
+
+    #[test]
+    fn intersection() {
+        let a = donut();
+        let b = Shape::new_circle(Coord::new(0.6, 0.0), Length2d::new(0.3, 0.3));
+
+        let res = a.intersection(&b);
+
+        match &res {
+            ShapeIntersection::New(_) => {}
+            _ => panic!("Unexpected result"),
+        }
+
+        verify_intersection(res, a, b);
+    }
+
+    #[test]
+    fn difference() {
+        let a = donut();
+        let b = Shape::new_circle(Coord::new(0.6, 0.0), Length2d::new(0.3, 0.3));
+
+        let res = a.difference(&b);
+
+        match &res {
+            ShapeDifference::New(merged) => assert_eq!(merged.len(), 1),
+            _ => panic!("Unexpected result"),
+        }
+
+        verify_difference(res, a, b);
+    }
+
+    #[test]
+    fn difference_in_hole() {
+        let a = donut();
+        let b = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.2, 0.2));
+
+        let res = a.difference(&b);
+
+        match &res {
+            ShapeDifference::A => {}
+            _ => panic!("Unexpected result"),
+        }
+    }
+}
@@ -0,0 +1,294 @@
+/*
+Boolean operations on shapes with several subpaths.
+A shape is split in islands, an outer contour with its holes. The operations on the islands are
+done with the boolean operations on single contours.
+    A - B = for each island of B: (A cut outer of B) + (A AND each hole of B)
+    A AND B = for each pair of islands: (outer of A AND outer of B) cut each hole of A and B
+    A OR B = merged outers cut each part of a hole that is not filled by the other shape
+*/
+
+use common::types::Coord;
+
+use super::{
+    difference::{shape_difference, ShapeDifference},
+    intersection::{shape_intersection, ShapeIntersection},
+    union::{shape_union, ShapeUnion},
+};
+use crate::{
+    scene::shape::{cubic_path::path_curves_len, FillRule},
+    DbCoord, Shape,
+};
+
+#[derive(Debug, Clone)]
+struct Island {
+    outer: Vec<DbCoord>,
+    holes: Vec<Vec<DbCoord>>,
+}
+
+impl Island {
+    fn new(outer: Vec<DbCoord>) -> Self {
+        Self {
+            outer,
+            holes: Vec::new(),
+        }
+    }
+
+    /// Add a hole inside the outer, it is merged with the holes it overlaps
+    fn hole_add(&mut self, hole: Vec<DbCoord>) {
+        contour_merge_into(&mut self.holes, hole);
+    }
+}
+
+pub fn compound_union(a: &Shape, b: &Shape) -> ShapeUnion {
+    let islands_a = islands(a);
+    let islands_b = islands(b);
+    if region_intersection(&islands_a, &islands_b).is_empty() {
+        return ShapeUnion::None;
+    }
+
+    let mut outers = Vec::new();
+    for island in islands_a.iter().chain(islands_b.iter()) {
+        contour_merge_into(&mut outers, island.outer.clone());
+    }
+
+    // Parts of the holes of one shape not filled by the other shape stay empty
+    let mut empty = Vec::new();
+    for hole in islands_a.iter().flat_map(|island| island.holes.iter()) {
+        empty.extend(region_difference(
+            vec![Island::new(hole.clone())],
+            &islands_b,
+        ));
+    }
+    for hole in islands_b.iter().flat_map(|island| island.holes.iter()) {
+        empty.extend(region_difference(
+            vec![Island::new(hole.clone())],
+            &islands_a,
+        ));
+    }
+
+    let region = region_difference(outers.into_iter().map(Island::new).collect(), &empty);
+    ShapeUnion::New(shape_from_islands(a, region))
+}
+
+pub fn compound_intersection(a: &Shape, b: &Shape) -> ShapeIntersection {
+    let region = region_intersection(&islands(a), &islands(b));
+    if region.is_empty() {
+        return ShapeIntersection::None;
+    }
+    ShapeIntersection::New(vec![shape_from_islands(a, region)])
+}
+
+pub fn compound_difference(a: &Shape, b: &Shape) -> ShapeDifference {
+    let islands_a = islands(a);
+    let islands_b = islands(b);
+    if region_intersection(&islands_a, &islands_b).is_empty() {
+        return ShapeDifference::A;
+    }
+
+    let region = region_difference(islands_a, &islands_b);
+    if region.is_empty() {
+        return ShapeDifference::EraseA;
+    }
+    ShapeDifference::New(vec![shape_from_islands(a, region)])
+}
+
+/// Shape a with the contour hole as a hole, used when the hole is fully inside a
+pub fn shape_with_hole(a: &Shape, hole: &[DbCoord]) -> Shape {
+    let mut island = Island::new(a.path.clone());
+    island.hole_add(hole.to_vec());
+    shape_from_islands(a, vec![island])
+}
+
+/// Region of each island minus each island of other
+fn region_difference(region: Vec<Island>, other: &[Island]) -> Vec<Island> {
+    let mut region = region;
+    for island in other {
+        let mut next: Vec<Island> = region
+            .iter()
+            .flat_map(|r| island_cut(r.clone(), &island.outer))
+            .collect();
+        for hole in &island.holes {
+            next.extend(region.iter().flat_map(|r| island_intersect(r, hole)));
+        }
+        region = next;
+    }
+    region
+}
+
+fn region_intersection(a: &[Island], b: &[Island]) -> Vec<Island> {
+    let mut region = Vec::new();
+    for island_a in a {
+        for island_b in b {
+            for piece in island_intersect(island_a, &island_b.outer) {
+                region.extend(island_holes_cut(piece, &island_b.holes));
+            }
+        }
+    }
+    region
+}
+
+/// Remove the contour from the island
+fn island_cut(island: Island, cut: &[DbCoord]) -> Vec<Island> {
+    match shape_difference(&contour(&island.outer), &contour(cut)) {
+        ShapeDifference::A => vec![island],
+        ShapeDifference::EraseA => vec![],
+        ShapeDifference::New(shapes) => shapes
+            .into_iter()
+            .flat_map(|shape| {
+                // A piece of the outer, with the cut as a hole when the cut is inside
+                let mut piece = Island::new(shape.path);
+                for hole in shape.subpaths {
+                    piece.hole_add(hole);
+                }
+                island_holes_cut(piece, &island.holes)
+            })
+            .collect(),
+    }
+}
+
+/// Part of the island inside the contour
+fn island_intersect(island: &Island, other: &[DbCoord]) -> Vec<Island> {
+    match shape_intersection(&contour(&island.outer), &contour(other)) {
+        ShapeIntersection::A => vec![island.clone()],
+        ShapeIntersection::B => island_holes_cut(Island::new(other.to_vec()), &island.holes),
+        ShapeIntersection::New(shapes) => shapes
+            .into_iter()
+            .flat_map(|shape| island_holes_cut(Island::new(shape.path), &island.holes))
+            .collect(),
+        ShapeIntersection::None => vec![],
+    }
+}
+
+fn island_holes_cut(island: Island, holes: &[Vec<DbCoord>]) -> Vec<Island> {
+    let mut islands = vec![island];
+    for hole in holes {
+        islands = islands
+            .into_iter()
+            .flat_map(|island| island_cut(island, hole))
+            .collect();
+    }
+    islands
+}
+
+/// Add the contour to the list, the contours it overlaps are merged with it
+fn contour_merge_into(contours: &mut Vec<Vec<DbCoord>>, contour_new: Vec<DbCoord>) {
+    let mut contour_new = contour_new;
+    let mut i = 0;
+    while i < contours.len() {
+        match shape_union(&contour(&contour_new), &contour(&contours[i])) {
+            ShapeUnion::A => {
+                contours.remove(i);
+            }
+            ShapeUnion::B => return,
+            ShapeUnion::New(merged) => {
+                contour_new = merged.path;
+                contours.remove(i);
+                // The merged contour can overlap the contours already visited
+                i = 0;
+            }
+            ShapeUnion::None => i += 1,
+        }
+    }
+    contours.push(contour_new);
+}
+
+/// Split the shape in islands according to its fill rule
+fn islands(shape: &Shape) -> Vec<Island> {
+    let paths: Vec<&Vec<DbCoord>> = shape
+        .paths()
+        .filter(|path| path_curves_len(path) > 0)
+        .collect();
+    let contours: Vec<Shape> = paths.iter().map(|path| contour(path)).collect();
+    let signs: Vec<i32> = paths
+        .iter()
+        .map(|path| if path_area(path) >= 0.0 { 1 } else { -1 })
+        .collect();
+    let is_filled = |count: i32, winding: i32| match shape.fill_rule {
+        FillRule::EvenOdd => count % 2 == 1,
+        FillRule::NonZero => winding != 0,
+    };
+
+    let mut outers = Vec::new();
+    let mut holes = Vec::new();
+    for (i, path) in paths.iter().enumerate() {
+        let point = path[0].coord;
+        let containers: Vec<usize> = (0..paths.len())
+            .filter(|j| *j != i && contours[*j].contains(&point))
+            .collect();
+        let count = containers.len() as i32;
+        let winding = containers.iter().map(|j| signs[*j]).sum::<i32>();
+
+        let filled_outside = is_filled(count, winding);
+        let filled_inside = is_filled(count + 1, winding + signs[i]);
+        if !filled_outside && filled_inside {
+            outers.push((i, containers));
+        } else if filled_outside && !filled_inside {
+            holes.push((i, containers));
+        }
+    }
+
+    let mut islands: Vec<Island> = outers
+        .iter()
+        .map(|(i, _)| Island::new(paths[*i].clone()))
+        .collect();
+    for (i, containers) in holes {
+        // The hole belongs to the deepest outer around it
+        let parent = outers
+            .iter()
+            .enumerate()
+            .filter(|(_, (outer, _))| containers.contains(outer))
+            .max_by_key(|(_, (_, outer_containers))| outer_containers.len());
+        if let Some((index, _)) = parent {
+            islands[index].hole_add(paths[i].clone());
+        }
+    }
+    islands
+}
+
+/// Shape with the style of from and the islands as subpaths.
+/// The holes turn in the opposite direction of the outers to work with both fill rules.
+fn shape_from_islands(from: &Shape, islands: Vec<Island>) -> Shape {
+    let oriented = |mut path: Vec<DbCoord>, positive: bool| {
+        if (path_area(&path) >= 0.0) != positive {
+            path.reverse();
+        }
+        path
+    };
+
+    let mut paths = islands.into_iter().flat_map(|island| {
+        std::iter::once(oriented(island.outer, true)).chain(
+            island
+                .holes
+                .into_iter()
+                .map(move |hole| oriented(hole, false)),
+        )
+    });
+
+    let mut shape = from.clone();
+    shape.path = paths.next().unwrap_or_default();
+    shape.subpaths = paths.collect();
+    shape
+}
+
+/// Shape of a single contour
+fn contour(path: &[DbCoord]) -> Shape {
+    let mut shape = Shape::new();
+    shape.path = path.to_vec();
+    shape
+}
+
+/// Signed area of a contour, the sign gives its direction
+fn path_area(path: &[DbCoord]) -> f32 {
+    let shape = contour(path);
+    let points: Vec<Coord> = shape
+        .curves()
+        .flat_map(|curve| (0..4).map(move |i| curve.cubic_bezier(i as f32 / 4.0)))
+        .collect();
+
+    let mut area = 0.0;
+    for (i, p0) in points.iter().enumerate() {
+        let p1 = points[(i + 1) % points.len()];
+        area += p0.x * p1.y - p1.x * p0.y;
+    }
+    area / 2.0
+}
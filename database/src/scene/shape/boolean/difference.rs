@@ -3,7 +3,8 @@ use std::ptr;
 use anyhow::Error;
 
 use super::{
-    create_shape, find_intersecions, mark_entry_exit_points, GreinerShape, IntersectionType,
+    compound::shape_with_hole, create_shape, find_intersecions, mark_entry_exit_points,
+    GreinerShape, IntersectionType,
 };
 use crate::scene::shape::Shape;
 
@@ -17,10 +18,8 @@ pub enum ShapeDifference {
     /// A and B do not fully contain each other
     /// New shapes are created.
     /// Multiple shapes can be created exemple shapes like a l and o. The o could be in the middle of the l. The result would be 2 shapes.
+    /// When B is inside A, the result is A with a hole the shape of B in it.
     New(Vec<Shape>),
-
-    /// A has a hole the shaoe of B in it.
-    AWithBHole,
 }
 
 #[allow(dead_code)]
@@ -44,7 +43,7 @@ fn try_shape_difference(a: &Shape, b: &Shape) -> Result<ShapeDifference, Error>
 
     if intersections_a.is_empty() && intersections_b.is_empty() {
         if a.contains(&b.path[0].coord) {
-            return Ok(ShapeDifference::AWithBHole);
+            return Ok(ShapeDifference::New(vec![shape_with_hole(a, &b.path)]));
         } else if b.contains(&a.path[0].coord) {
             return Ok(ShapeDifference::EraseA);
         } else {
@@ -57,7 +56,7 @@ fn try_shape_difference(a: &Shape, b: &Shape) -> Result<ShapeDifference, Error>
 
     mark_entry_exit_points(&mut ag, a, &mut bg, b)?;
 
-    if let Some(result) = handle_touching_shape(&ag, &bg)? {
+    if let Some(result) = handle_touching_shape(&ag, &bg, a, b)? {
         return Ok(result);
     }
 
@@ -68,6 +67,8 @@ fn try_shape_difference(a: &Shape, b: &Shape) -> Result<ShapeDifference, Error>
 fn handle_touching_shape(
     ag: &GreinerShape,
     bg: &GreinerShape,
+    a: &Shape,
+    b: &Shape,
 ) -> Result<Option<ShapeDifference>, Error> {
     let mut count_intersections = 0;
     for i in 0..ag.intersections_len {
@@ -93,8 +94,9 @@ fn handle_touching_shape(
         if ag.data[0].entry && bg.data[0].entry {
             return Ok(Some(ShapeDifference::A));
         } else if ag.data[0].entry && !bg.data[0].entry {
-            //We could compute that
-            return Ok(Some(ShapeDifference::AWithBHole));
+            return Ok(Some(ShapeDifference::New(vec![shape_with_hole(
+                a, &b.path,
+            )])));
         } else if !ag.data[0].entry && bg.data[0].entry {
             return Ok(Some(ShapeDifference::EraseA));
         } else {
@@ -277,6 +279,9 @@ fn do_difference(ag: &GreinerShape, bg: &GreinerShape, a: &Shape, _b: &Shape) ->
                 panic!("Infinite loop detected");
             }
         }
+        // The path ends on the first intersection, it is closed by sharing its id
+        let len_last = merged.path.len() - 1;
+        merged.path[len_last] = merged.path[0];
         shapes.push(merged);
     }
 
@@ -278,6 +278,9 @@ fn do_intersection(ag: &GreinerShape, bg: &GreinerShape, a: &Shape, _b: &Shape)
                 panic!("Infinite loop detected");
             }
         }
+        // The path ends on the first intersection, it is closed by sharing its id
+        let len_last = merged.path.len() - 1;
+        merged.path[len_last] = merged.path[0];
         shapes.push(merged);
     }
 
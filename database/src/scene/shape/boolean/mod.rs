@@ -5,6 +5,7 @@ Intersection : A AND B
 Difference : A NOT B
 */
 
+mod compound;
 mod difference;
 mod intersection;
 mod union;
@@ -28,16 +29,30 @@ pub use self::{difference::ShapeDifference, intersection::ShapeIntersection, uni
 
 impl Shape {
     pub fn union(&self, other: &Shape) -> ShapeUnion {
+        if self.is_compound() || other.is_compound() {
+            return compound::compound_union(self, other);
+        }
         union::shape_union(self, other)
     }
 
     pub fn intersection(&self, other: &Shape) -> ShapeIntersection {
+        if self.is_compound() || other.is_compound() {
+            return compound::compound_intersection(self, other);
+        }
         intersection::shape_intersection(self, other)
     }
 
     pub fn difference(&self, other: &Shape) -> ShapeDifference {
+        if self.is_compound() || other.is_compound() {
+            return compound::compound_difference(self, other);
+        }
         difference::shape_difference(self, other)
     }
+
+    /// The operations on a single contour are done directly, the others are split in islands
+    fn is_compound(&self) -> bool {
+        !self.subpaths.is_empty()
+    }
 }
 
 /// When calculating the union of two shapes, we need to find all the intersection points between the two shapes.
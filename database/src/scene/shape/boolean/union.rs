@@ -85,7 +85,8 @@ fn handle_touching_shape(
 
     if count_intersections == 0 {
         if ag.data[0].entry && bg.data[0].entry {
-            unimplemented!("We should compute the shape of the intersection");
+            // Touching from outside, the shapes stay separated
+            return Ok(Some(ShapeUnion::None));
         } else if ag.data[0].entry && !bg.data[0].entry {
             return Ok(Some(ShapeUnion::A));
         } else if !ag.data[0].entry && bg.data[0].entry {
@@ -1,4 +1,4 @@
-use super::Shape;
+use super::{curve::path_is_closed, Shape};
 use crate::scene::id::CoordId;
 use common::pures::Affine;
 use common::types::Coord;
@@ -14,106 +14,111 @@ pub enum CoordType {
 
 impl Shape {
     pub fn coord_select(&self, index: CoordId) -> Option<&DbCoord> {
-        self.path.iter().find(|c| c.id == index)
+        self.paths().flatten().find(|c| c.id == index)
     }
 
     pub fn coord_select_mut(&mut self, index: CoordId) -> Option<&mut DbCoord> {
-        self.path.iter_mut().find(|c| c.id == index)
+        self.paths_mut()
+            .flat_map(|p| p.iter_mut())
+            .find(|c| c.id == index)
     }
 
     pub fn coord_set(&mut self, index: CoordId, coord: Coord) {
-        self.path
-            .iter_mut()
+        self.paths_mut()
+            .flat_map(|p| p.iter_mut())
             .filter(|c| c.id == index)
             .for_each(|c| c.coord = coord);
     }
 
-    pub(crate) fn coord_index_select(&self, index: CoordId) -> Option<(usize, CoordType)> {
-        for (i, c) in self.path.iter().enumerate() {
-            if c.id == index {
-                if i == 0 {
-                    return Some((i, CoordType::P0));
-                } else if i % 3 == 1 {
-                    return Some((i, CoordType::CP0));
-                } else if i % 3 == 2 {
-                    if self.path[i + 1].id == index {
-                        return Some((i + 1, CoordType::P1));
+    /// Return the subpath of the coord, its index in the subpath and its type
+    pub(crate) fn coord_index_select(&self, index: CoordId) -> Option<(usize, usize, CoordType)> {
+        for (subpath, path) in self.paths().enumerate() {
+            for (i, c) in path.iter().enumerate() {
+                if c.id == index {
+                    if i == 0 {
+                        return Some((subpath, i, CoordType::P0));
+                    } else if i % 3 == 1 {
+                        return Some((subpath, i, CoordType::CP0));
+                    } else if i % 3 == 2 {
+                        if path[i + 1].id == index {
+                            return Some((subpath, i + 1, CoordType::P1));
+                        }
+                        return Some((subpath, i, CoordType::CP1));
+                    } else {
+                        return Some((subpath, i, CoordType::P1));
                     }
-                    return Some((i, CoordType::CP1));
-                } else {
-                    return Some((i, CoordType::P1));
                 }
             }
         }
         None
     }
 
+    /// Delete the coord, a subpath left without coord is removed
     pub fn coord_delete(&mut self, index: CoordId) -> Result<(), String> {
-        let (index, coord_type) = self.coord_index_select(index).ok_or("Coord not found")?;
-        match coord_type {
-            CoordType::P0 => {
-                assert_eq!(index, 0);
-                if self.is_closed() {
-                    let len = self.path.len();
-                    if len == 4 {
-                        self.path.clear();
-                        return Ok(());
-                    }
-                    self.path.swap(len - 2, 2); // keep CPl of P1
-
-                    self.path.remove(index); // P0
-                    self.path.remove(index); // CPr
-                    self.path.remove(index); // CPl of P0
-                    let len = self.path.len();
-                    self.path[len - 1] = self.path[0]; //Change to the first P0
-                } else {
-                    self.path.remove(index); //P0
-                    self.path.remove(index); //CPr
-                    self.path.remove(index); //CPl of P1
-                }
-            }
-            CoordType::CP0 => {
-                self.path[index] = self.path[index + 1];
-            }
-            CoordType::CP1 => {
-                self.path[index] = self.path[index - 1];
-            }
-            CoordType::P1 => {
-                if self.path.len() - 1 == index {
-                    if !self.is_closed() {
-                        self.path.remove(index - 2); //cp0
-                        self.path.remove(index - 2); //cp1
-                        self.path.remove(index - 2); //p1
-                    } else {
-                        let len = self.path.len();
-                        if len == 4 {
-                            self.path.clear();
-                            return Ok(());
-                        }
-                        let index = if index == 0 { len - 1 } else { index };
-                        self.path.swap(len - 1, index); // keep CPl of P1
-
-                        self.path.remove(index); // P0
-                        self.path.remove(index); // CPr
-                        self.path.remove(index); // CPl of P0
-                    }
-                } else {
-                    self.path.remove(index); //P1
-                    self.path.remove(index); //CPl
-                    self.path.remove(index - 1); //CPr
-                }
-            }
+        let (subpath, index, coord_type) =
+            self.coord_index_select(index).ok_or("Coord not found")?;
+        path_coord_delete(self.subpath_mut(subpath), index, coord_type);
+        if self.subpath(subpath).is_empty() {
+            self.subpath_remove(subpath);
         }
-
         Ok(())
     }
 }
 
-impl Shape {
-    pub fn curves_path_update_id(&mut self) {
-        for coord in &mut self.path {
-            if coord.id == CoordId::null() {
-                coord.id.update();
+fn path_coord_delete(path: &mut Vec<DbCoord>, index: usize, coord_type: CoordType) {
+    match coord_type {
+        CoordType::P0 => {
+            assert_eq!(index, 0);
+            if path_is_closed(path) {
+                let len = path.len();
+                if len == 4 {
+                    path.clear();
+                    return;
+                }
+                path.swap(len - 2, 2); // keep CPl of P1
+
+                path.remove(index); // P0
+                path.remove(index); // CPr
+                path.remove(index); // CPl of P0
+                let len = path.len();
+                path[len - 1] = path[0]; //Change to the first P0
+            } else if path.len() == 1 {
+                path.clear(); // Single anchor of an open path
+            } else {
+                path.remove(index); //P0
+                path.remove(index); //CPr
+                path.remove(index); //CPl of P1
+            }
+        }
+        CoordType::CP0 => {
+            path[index] = path[index + 1];
+        }
+        CoordType::CP1 => {
+            path[index] = path[index - 1];
+        }
+        CoordType::P1 => {
+            if path.len() - 1 == index {
+                if !path_is_closed(path) {
+                    path.remove(index - 2); //cp0
+                    path.remove(index - 2); //cp1
+                    path.remove(index - 2); //p1
+                } else {
+                    let len = path.len();
+                    if len == 4 {
+                        path.clear();
+                        return;
+                    }
+                    let index = if index == 0 { len - 1 } else { index };
+                    path.swap(len - 1, index); // keep CPl of P1
+
+                    path.remove(index); // P0
+                    path.remove(index); // CPr
+                    path.remove(index); // CPl of P0
+                }
+            } else {
+                path.remove(index); //P1
+                path.remove(index); //CPl
+                path.remove(index - 1); //CPr
             }
         }
     }
@@ -266,4 +271,55 @@ mod tests {
         assert!(shape.path.iter().all(|c| c.id != id2));
         assert!(shape.path.iter().all(|c| c.id != id4));
     }
+
+    #[test]
+    fn given_compound_shape_when_delete_last_coords_of_subpath_then_subpath_removed() {
+        let mut shape = Shape::new();
+        shape.path = vec![
+            DbCoord::new(0.0, 0.0),
+            DbCoord::new(0.0, 0.0),
+            DbCoord::new(0.0, 0.0),
+            DbCoord::new(0.0, 0.0),
+        ];
+        shape.path[3].id = shape.path[0].id;
+        let mut subpath = vec![
+            DbCoord::new(0.5, 0.5),
+            DbCoord::new(0.5, 0.5),
+            DbCoord::new(0.5, 0.5),
+            DbCoord::new(0.5, 0.5),
+        ];
+        subpath[3].id = subpath[0].id;
+        shape.subpaths.push(subpath.clone());
+
+        shape.coord_delete(shape.path[0].id).unwrap();
+
+        assert_eq!(shape.path, subpath);
+        assert!(shape.subpaths.is_empty());
+    }
+
+    #[test]
+    fn given_open_path_when_delete_ends_then_stays_open() {
+        let mut shape = Shape::new_polyline(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(1.0, 0.0),
+                DbCoord::new(1.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        let first = shape.path[0].id;
+        let last = shape.path[6].id;
+
+        shape.coord_delete(first).unwrap();
+        assert_eq!(shape.path.len(), 4);
+        assert!(!shape.is_closed());
+        assert!(shape.path.iter().all(|c| c.id != first));
+
+        shape.coord_delete(last).unwrap();
+        assert_eq!(shape.path.len(), 1);
+        assert_eq!(shape.path[0].coord(), Coord::new(1.0, 0.0));
+
+        shape.coord_delete(shape.path[0].id).unwrap();
+        assert!(shape.is_empty());
+    }
 }
@@ -1,43 +1,79 @@
 use common::types::Coord;
 
-use crate::{CoordId, Curve, DbCoord, Shape};
+use crate::{CoordId, Curve, DbCoord, PathEnd, Shape};
 
 use super::{
     coord::CoordType,
-    curve::{self},
+    curve::{self, path_is_closed},
 };
 
 impl Shape {
+    /// Number of curves of all the subpaths
     pub fn curves_len(&self) -> usize {
-        (self.path.len() - 1) / 3
+        self.paths().map(|path| path_curves_len(path)).sum()
     }
 
     pub fn curve_select_of_coord_id(&self, id: CoordId) -> Option<usize> {
         match self.coord_index_select(id) {
-            Some((index, coord_type)) => match coord_type {
-                CoordType::P1 => Some((index - 1) / 3),
-                CoordType::P0 => Some(self.curves_len() - 1),
-                _ => None,
-            },
+            Some((subpath, index, coord_type)) => {
+                let offset = self.curve_offset(subpath);
+                match coord_type {
+                    CoordType::P1 => Some(offset + (index - 1) / 3),
+                    // The first anchor of an open subpath is the end of no curve
+                    CoordType::P0 if path_is_closed(self.subpath(subpath)) => {
+                        Some(offset + path_curves_len(self.subpath(subpath)) - 1)
+                    }
+                    _ => None,
+                }
+            }
             None => None,
         }
     }
 
     pub fn curves(&self) -> impl Iterator<Item = Curve> + '_ {
-        (0..self.curves_len()).map(|index| self.curve_select(index).expect("Curve should exist"))
+        self.paths()
+            .flat_map(|path| (0..path_curves_len(path)).map(|index| path_curve_select(path, index)))
     }
 
+    /// Select a curve by its index in all the subpaths
     pub fn curve_select(&self, index: usize) -> Option<Curve> {
-        if index < self.curves_len() {
-            let p0 = &self.path[index * 3];
-            let cp0 = &self.path[(index * 3 + 1) % self.path.len()];
-            let cp1 = &self.path[(index * 3 + 2) % self.path.len()];
-            let p1 = &self.path[(index * 3 + 3) % self.path.len()];
-            return Some(Curve { p0, cp0, cp1, p1 });
+        let (subpath, index) = self.curve_locate(index)?;
+        Some(path_curve_select(self.subpath(subpath), index))
+    }
+
+    /// Return the subpath of a curve and the index of the curve in this subpath
+    pub(crate) fn curve_locate(&self, index: usize) -> Option<(usize, usize)> {
+        let mut index = index;
+        for (subpath, path) in self.paths().enumerate() {
+            let len = path_curves_len(path);
+            if index < len {
+                return Some((subpath, index));
+            }
+            index -= len;
         }
         None
     }
 
+    /// Index of the first curve of a subpath
+    pub(crate) fn curve_offset(&self, subpath: usize) -> usize {
+        self.paths()
+            .take(subpath)
+            .map(|path| path_curves_len(path))
+            .sum()
+    }
+
+    /// Index of the curve after in the same subpath, the last curve is followed by the first.
+    /// The last curve of an open subpath has no curve after.
+    pub(crate) fn curve_next(&self, index: usize) -> Option<usize> {
+        let (subpath, local) = self.curve_locate(index)?;
+        let path = self.subpath(subpath);
+        let len = path_curves_len(path);
+        if local + 1 == len && !path_is_closed(path) {
+            return None;
+        }
+        Some(self.curve_offset(subpath) + (local + 1) % len)
+    }
+
     pub fn toggle_separate_join_handle(&mut self, index: usize) {
         if self.is_handles_joined(index) {
             self.handle_separate(index);
@@ -48,16 +84,24 @@ impl Shape {
 
     fn is_handles_joined(&self, index: usize) -> bool {
         let curve = self.curve_select(index).expect("Curve should exist");
-        let curve_next = self
-            .curve_select((index + 1) % self.curves_len())
-            .expect("Curve should exist");
-        curve.cp1.id == curve.p1.id && curve_next.cp0.id == curve.p1.id
+        let next_joined = match self.curve_next(index) {
+            Some(next) => {
+                let curve_next = self.curve_select(next).expect("Curve should exist");
+                curve_next.cp0.id == curve.p1.id
+            }
+            None => true,
+        };
+        curve.cp1.id == curve.p1.id && next_joined
     }
 
     pub fn handle_join(&mut self, curve_index_p1: usize) {
-        let index_p1 = (curve_index_p1 * 3 + 3) % self.path.len();
-        let len = self.path.len();
-        if self.is_closed() {
+        let (subpath, curve_index_p1) = self
+            .curve_locate(curve_index_p1)
+            .expect("Curve should exist");
+        let path = self.subpath_mut(subpath);
+        let index_p1 = (curve_index_p1 * 3 + 3) % path.len();
+        let len = path.len();
+        if path_is_closed(path) {
             
             let index_cpl = {
                 if (index_p1 - 1) % len == len - 1 {
@@ -66,7 +110,7 @@ impl Shape {
                     index_p1 - 1
                 }
             };
-            self.path[index_cpl] = self.path[index_p1];
+            path[index_cpl] = path[index_p1];
             
             let index_cpr ={
                 if (index_p1 + 1) % len == 0 {
@@ -75,7 +119,7 @@ impl Shape {
                     index_p1 + 1
                 }
             };
-            self.path[index_cpr] = self.path[index_p1];
+            path[index_cpr] = path[index_p1];
         }else {
             let index_cpl:Option<usize> ={
                 if index_p1 == 0 {
@@ -85,7 +129,7 @@ impl Shape {
                 }
             };
             if let Some(index) = index_cpl {
-                self.path[index] = self.path[index_p1];
+                path[index] = path[index_p1];
             }
 
             let index_cpr:Option<usize> ={
@@ -96,32 +140,45 @@ impl Shape {
                 }
             };
             if let Some(index) = index_cpr {
-                self.path[index] = self.path[index_p1];
+                path[index] = path[index_p1];
             }
         }
     }
 
     pub fn handle_separate(&mut self, curve_index_p1: usize) {
+        let curve_index_next = self.curve_next(curve_index_p1);
         let (cp1_left, cp0_right) = {
             let curve_a = self
                 .curve_select(curve_index_p1)
                 .expect("Curve should exist");
-            let curve_b = self
-                .curve_select((curve_index_p1 + 1) % self.curves_len())
-                .expect("Curve should exist");
-
-            curve::tangent_cornor_pts(curve_a, curve_b)
+            match curve_index_next {
+                Some(next) => {
+                    let curve_b = self.curve_select(next).expect("Curve should exist");
+                    curve::tangent_cornor_pts(curve_a, curve_b)
+                }
+                // The end of an open subpath has a single handle toward the previous anchor
+                None => {
+                    let p1 = curve_a.p1.coord;
+                    (p1 + (curve_a.p0.coord - p1) * (1.0 / 3.0), p1)
+                }
+            }
         };
 
-        let len = self.path.len();
-        let curve_len = self.curves_len();
-        let cpl = &mut self.path[(curve_index_p1 * 3 + 2) % len];
+        let (subpath, curve_index_p1) = self
+            .curve_locate(curve_index_p1)
+            .expect("Curve should exist");
+        let path = self.subpath_mut(subpath);
+        let len = path.len();
+        let curve_len = path_curves_len(path);
+        let cpl = &mut path[(curve_index_p1 * 3 + 2) % len];
         cpl.coord = cp1_left;
         cpl.id.update();
 
-        let cpr = &mut self.path[(((curve_index_p1 + 1) % curve_len) * 3 + 1) % len];
-        cpr.coord = cp0_right;
-        cpr.id.update();
+        if curve_index_next.is_some() {
+            let cpr = &mut path[(((curve_index_p1 + 1) % curve_len) * 3 + 1) % len];
+            cpr.coord = cp0_right;
+            cpr.id.update();
+        }
     }
 
     /// Cut curve_index at t without chnaging the curve by replacing the handles
@@ -140,27 +197,29 @@ impl Shape {
         let cp1r = cp1r.into();
         let cp2 = cp2;
 
-        let index_cp1 = (curve_index * 3 + 2) % self.path.len();
-
         let is_straight = curve.is_straight();
         let left_handle_free = curve.left_handle_free();
         let right_handle_free = curve.right_handle_free();
 
+        let (subpath, curve_index) = self.curve_locate(curve_index).expect("Curve should exist");
+        let path = self.subpath_mut(subpath);
+        let index_cp1 = (curve_index * 3 + 2) % path.len();
+
         let new_coords = vec![p1, p1, p1];
-        self.path.splice(index_cp1..index_cp1, new_coords);
+        path.splice(index_cp1..index_cp1, new_coords);
 
         //for a straight line no handle
         if !(is_straight) {
-            self.path[index_cp1] = cp1l;
-            self.path[index_cp1 + 2] = cp1r;
+            path[index_cp1] = cp1l;
+            path[index_cp1 + 2] = cp1r;
         }
         //left has separate handle
         if left_handle_free {
-            self.path[index_cp1 - 1].coord = cp0;
+            path[index_cp1 - 1].coord = cp0;
         }
         //right has separate handle
         if right_handle_free {
-            self.path[index_cp1 + 3].coord = cp2;
+            path[index_cp1 + 3].coord = cp2;
         }
 
         (cp1l.id, p1.id, cp1r.id)
@@ -169,11 +228,109 @@ impl Shape {
     pub fn curve_insert_line(&mut self, curve_index: usize, coord: Coord) {
         let p1: DbCoord = coord.into();
         let new_coords = vec![p1, p1, p1];
-        let index_cp1 = (curve_index * 3 + 2) % self.path.len();
-        self.path.splice(index_cp1..index_cp1, new_coords);
+        let (subpath, curve_index) = self.curve_locate(curve_index).expect("Curve should exist");
+        let path = self.subpath_mut(subpath);
+        let index_cp1 = (curve_index * 3 + 2) % path.len();
+        path.splice(index_cp1..index_cp1, new_coords);
     }
 }
 
+impl Shape {
+    /// Add a curve at an end of an open subpath, the anchor is the new end.
+    /// handle_from is the handle of the current end toward the anchor, handle_to is the handle
+    /// of the anchor toward the current end. A handle at None is on its anchor, like a line.
+    pub fn subpath_extend(
+        &mut self,
+        subpath: usize,
+        end: PathEnd,
+        handle_from: Option<Coord>,
+        handle_to: Option<Coord>,
+        anchor: Coord,
+    ) -> Result<(), String> {
+        let path = self.subpath_open_mut(subpath)?;
+        let anchor = DbCoord::from(anchor);
+        let handle_to = handle_to.map(DbCoord::from).unwrap_or(anchor);
+        match end {
+            PathEnd::End => {
+                let last = path[path.len() - 1];
+                path.push(handle_from.map(DbCoord::from).unwrap_or(last));
+                path.push(handle_to);
+                path.push(anchor);
+            }
+            PathEnd::Start => {
+                let first = path[0];
+                let handle_from = handle_from.map(DbCoord::from).unwrap_or(first);
+                path.splice(0..0, [anchor, handle_to, handle_from]);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the curve at an end of a subpath.
+    /// A closed subpath is opened by removing its last curve.
+    pub fn subpath_retract(&mut self, subpath: usize, end: PathEnd) -> Result<(), String> {
+        if subpath > self.subpaths.len() {
+            return Err("Subpath not found".to_string());
+        }
+        let path = self.subpath_mut(subpath);
+        if path_curves_len(path) == 0 {
+            return Err("Subpath has no curve".to_string());
+        }
+        match end {
+            PathEnd::End => path.truncate(path.len() - 3),
+            PathEnd::Start => {
+                path.drain(0..3);
+            }
+        }
+        Ok(())
+    }
+
+    /// Close an open subpath with a curve from its last anchor to its first anchor
+    pub fn subpath_close(
+        &mut self,
+        subpath: usize,
+        handle_from: Option<Coord>,
+        handle_to: Option<Coord>,
+    ) -> Result<(), String> {
+        let path = self.subpath_open_mut(subpath)?;
+        if path_curves_len(path) == 0 {
+            return Err("Subpath has no curve".to_string());
+        }
+        let first = path[0];
+        let last = path[path.len() - 1];
+        path.push(handle_from.map(DbCoord::from).unwrap_or(last));
+        path.push(handle_to.map(DbCoord::from).unwrap_or(first));
+        path.push(first);
+        Ok(())
+    }
+
+    fn subpath_open_mut(&mut self, subpath: usize) -> Result<&mut Vec<DbCoord>, String> {
+        if subpath > self.subpaths.len() {
+            return Err("Subpath not found".to_string());
+        }
+        let path = self.subpath_mut(subpath);
+        if path.is_empty() {
+            return Err("Subpath is empty".to_string());
+        }
+        if path_is_closed(path) {
+            return Err("Subpath is closed".to_string());
+        }
+        Ok(path)
+    }
+}
+
+pub(crate) fn path_curves_len(path: &[DbCoord]) -> usize {
+    path.len().saturating_sub(1) / 3
+}
+
+pub(crate) fn path_curve_select(path: &[DbCoord], index: usize) -> Curve<'_> {
+    let p0 = &path[index * 3];
+    let cp0 = &path[(index * 3 + 1) % path.len()];
+    let cp1 = &path[(index * 3 + 2) % path.len()];
+    let p1 = &path[(index * 3 + 3) % path.len()];
+    Curve { p0, cp0, cp1, p1 }
+}
+
 #[cfg(test)]
 mod tests {
     use common::pures::Affine;
@@ -248,4 +405,66 @@ mod tests {
         assert_eq!(shape.path[2].coord, Coord::new(1.0, 1.0));
         assert_ne!(shape.path[0].coord, Coord::new(1.0, 1.0));
     }
+
+    #[test]
+    fn given_compound_shape_when_toggle_handle_last_of_subpath_then_next_is_first_of_subpath() {
+        let mut shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(0.0, 1.0),
+                DbCoord::new(1.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        let hole = Shape::new_from_lines(
+            vec![
+                DbCoord::new(0.2, 0.4),
+                DbCoord::new(0.2, 0.8),
+                DbCoord::new(0.6, 0.8),
+            ],
+            Affine::identity(),
+        );
+        shape.subpaths.push(hole.path.clone());
+        assert_eq!(shape.curves_len(), 6);
+
+        let curve = shape
+            .curve_select_of_coord_id(hole.path[0].id)
+            .expect("Not 404");
+        assert_eq!(curve, 5);
+        assert_eq!(shape.curve_next(curve), Some(3));
+
+        let path = shape.path.clone();
+        shape.toggle_separate_join_handle(curve);
+
+        assert_eq!(shape.path, path);
+        assert_ne!(shape.subpaths[0][1].coord, Coord::new(0.2, 0.4));
+        assert_ne!(shape.subpaths[0][8].coord, Coord::new(0.2, 0.4));
+    }
+
+    #[test]
+    fn given_open_path_when_separate_last_anchor_then_single_handle() {
+        let mut shape = Shape::new_polyline(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(0.0, 1.0),
+                DbCoord::new(1.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        let curve = shape
+            .curve_select_of_coord_id(shape.path[6].id)
+            .expect("Not 404");
+        assert_eq!(shape.curve_next(curve), None);
+        assert_eq!(shape.curve_select_of_coord_id(shape.path[0].id), None);
+
+        shape.toggle_separate_join_handle(curve);
+
+        assert_ne!(shape.path[5].id, shape.path[6].id);
+        assert_eq!(shape.path[5].coord, Coord::new(2.0 / 3.0, 1.0));
+        assert_eq!(shape.path.len(), 7);
+
+        shape.toggle_separate_join_handle(curve);
+
+        assert_eq!(shape.path[5].id, shape.path[6].id);
+    }
 }
@@ -1,5 +1,5 @@
 use super::{coord::DbCoord, Shape};
-use common::types::Coord;
+use common::types::{Coord, Rect, Vector};
 
 use crate::math;
 
@@ -34,8 +34,7 @@ impl Shape {
         let mut min_t = 0.0;
         let mut min_coord = Coord::new(-1000.0, -1000.0);
 
-        for curve_index in 0..self.curves_len() {
-            let curve = self.curve_select(curve_index).expect("Curve should exist");
+        for (curve_index, curve) in self.curves().enumerate() {
             let (t_min, distance, coord_closest) = curve.t_closest(coord);
 
             if distance < min_distance {
@@ -48,27 +47,40 @@ impl Shape {
         (min_index, min_t, min_distance, min_coord)
     }
 
+    /// Return true if every subpath is closed
     pub fn is_closed(&self) -> bool {
-        self.path.first().map_or(false, |first| {
-            self.path.last().map_or(false, |last| first.id == last.id)
-        })
+        self.paths().all(|path| path_is_closed(path))
     }
 
+    /// Close every open subpath with a line to its first coord
     pub fn close(&mut self) {
-        if !self.is_closed() {
-            if let Some(first) = self.path.first() {
-                let first_coord = *first;
-                if let Some(last) = self.path.last() {
-                    let last_coord = *last;
-                    self.path.push(last_coord);
-                    self.path.push(first_coord);
-                    self.path.push(first_coord);
+        for path in self.paths_mut() {
+            if !path_is_closed(path) {
+                if let Some(first) = path.first() {
+                    let first_coord = *first;
+                    if let Some(last) = path.last() {
+                        let last_coord = *last;
+                        path.push(last_coord);
+                        path.push(first_coord);
+                        path.push(first_coord);
+                    }
                 }
             }
         }
     }
 }
 
+/// A subpath is closed when its last coord is its first coord, a single anchor is open
+pub(crate) fn path_is_closed(path: &[DbCoord]) -> bool {
+    path.len() > 1 && path[0].id == path[path.len() - 1].id
+}
+
+/// A subpath encloses an area when it is closed or when it ends on the position of its start.
+/// Only the area of these subpaths is filled.
+pub(crate) fn path_encloses(path: &[DbCoord]) -> bool {
+    path_is_closed(path) || (path.len() > 1 && path[0].coord == path[path.len() - 1].coord)
+}
+
 impl Curve<'_> {
     /// Find the closest point on a curve defined by p0, cp0, cp1, p1
     /// It return the t value of the curve, the distance and the closest point
@@ -92,6 +104,26 @@ impl Curve<'_> {
         )
     }
 
+    pub fn tangent_vector(&self, t: f32) -> Vector {
+        math::curve::tangent_vector(
+            t,
+            &self.p0.coord,
+            &self.cp0.coord,
+            &self.cp1.coord,
+            &self.p1.coord,
+        )
+    }
+
+    /// Rectangle around the anchors and the handles, the curve is inside
+    pub fn quick_bounding_box(&self) -> Rect {
+        math::curve2::quick_bounding_box(
+            &self.p0.coord,
+            &self.cp0.coord,
+            &self.cp1.coord,
+            &self.p1.coord,
+        )
+    }
+
     pub fn intersection_with_y(&self, y: f32) -> Vec<f32> {
         math::curve2::intersection_with_y(
             &self.p0.coord,
@@ -1,14 +1,20 @@
 use common::{
+    math::point_in_radius,
     pures::Affine,
-    types::{Coord, Length2d},
+    types::{Coord, Length2d, Rect},
     Rgba,
 };
 use coord::DbCoord;
+use cubic_path::{path_curve_select, path_curves_len};
+use curve::path_encloses;
+use paint::Paint;
 use serde::{Deserialize, Serialize};
+use tsify::Tsify;
 
 use crate::{
+    math::curve2::{bounding_box, quick_bounding_box},
     scene::{Layer, LayerId, Scene},
-    DrawingContext,
+    CoordId, DrawingContext, StrokeStyle, UserSelection,
 };
 
 use super::LayerType;
@@ -17,66 +23,288 @@ pub mod boolean;
 pub mod coord;
 pub mod cubic_path;
 pub mod curve;
+mod outline;
+pub mod paint;
+mod primitives;
+
+/// Shape of the stroke where two segments meet
+#[derive(Tsify, Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum LineJoin {
+    /// Sharp corner, a bevel when the miter limit is exceeded
+    #[default]
+    Miter,
+    Round,
+    Bevel,
+}
+
+/// Shape of the stroke at the ends of an open subpath and of the dashes
+#[derive(Tsify, Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum LineCap {
+    /// Stop at the end
+    #[default]
+    Butt,
+    Round,
+    /// Continue half of the size after the end
+    Square,
+}
+
+/// Position of the stroke around the path, open subpaths are always stroked on the center
+#[derive(Tsify, Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum StrokeAlign {
+    #[default]
+    Center,
+    Inside,
+    Outside,
+}
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Stroke {
     pub size: f32,
-    pub color: Rgba,
+    pub paint: Paint,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Maximum ratio of the miter length to the size before a miter join becomes a bevel
+    pub miter_limit: f32,
+    /// Lengths of the dashes and the gaps in alternance, a solid line if empty
+    pub dashes: Vec<f32>,
+    /// Distance into the dash pattern where the stroke starts
+    pub dash_offset: f32,
+    pub align: StrokeAlign,
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Stroke::new(0.0, Rgba::default())
+    }
 }
 
 impl Stroke {
     pub fn new(size: f32, color: Rgba) -> Self {
-        Stroke { size, color }
+        Stroke {
+            size,
+            paint: Paint::Solid(color),
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+            align: StrokeAlign::default(),
+        }
+    }
+
+    /// Style of the stroke for a renderer, the lengths are scaled by the transform
+    pub fn style(&self, transform: &Affine) -> StrokeStyle {
+        StrokeStyle {
+            join: self.join,
+            cap: self.cap,
+            miter_limit: self.miter_limit,
+            dashes: self
+                .dashes
+                .iter()
+                .map(|dash| transform.scale_length(*dash) as f64)
+                .collect(),
+            dash_offset: transform.scale_length(self.dash_offset) as f64,
+            align: self.align,
+        }
     }
 }
 
+/// One of the two ends of an open subpath
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PathEnd {
+    /// The first anchor of the subpath
+    Start,
+    /// The last anchor of the subpath
+    End,
+}
+
+/// Rule to decide which parts of a shape with several subpaths are inside
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside when the subpaths around the point don't cancel each other by their direction
+    NonZero,
+    /// Inside when the point is surrounded by an odd number of subpaths
+    #[default]
+    EvenOdd,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Shape {
     pub id: LayerId,
+    /// First subpath of the shape
     pub path: Vec<DbCoord>,
-    pub color: Rgba,
+    /// Other subpaths of the shape, they make the holes or the other parts of a compound shape
+    pub subpaths: Vec<Vec<DbCoord>>,
+    pub fill_rule: FillRule,
+    pub fill: Paint,
     pub stroke: Stroke,
 }
 
 impl Shape {
     pub fn render(&self, renderer: &mut dyn DrawingContext) -> Result<(), String> {
         let transform = renderer.get_transform()?;
-        let coords: Vec<Coord> = self.path.iter().map(|c| transform * c.coord).collect();
-        renderer.set_fill(&self.color)?;
-        if self.stroke.size == 0.0 {
-            renderer.set_stroke(&Rgba::transparent(), 0.0)?;
+        // Only the gradients are placed on the bounding box
+        let bounds = match (&self.fill, &self.stroke.paint) {
+            (Paint::Solid(_), Paint::Solid(_)) => None,
+            _ => self.bounding_box(),
+        };
+        let mut style = self.stroke.style(&transform);
+        let set_stroke = |renderer: &mut dyn DrawingContext, style: &StrokeStyle| {
+            if self.stroke.size == 0.0 {
+                renderer.set_stroke(&Rgba::transparent(), 0.0, style)
+            } else {
+                self.stroke.paint.render_stroke(
+                    renderer,
+                    &transform,
+                    bounds,
+                    transform.scale_length(self.stroke.size) as f64,
+                    style,
+                )
+            }
+        };
+        self.fill.render_fill(renderer, &transform, bounds)?;
+        renderer.set_fill_rule(self.fill_rule)?;
+        set_stroke(renderer, &style)?;
+
+        let (closed, open): (Vec<&Vec<DbCoord>>, Vec<&Vec<DbCoord>>) = self
+            .paths()
+            .filter(|p| path_curves_len(p) > 0)
+            .partition(|p| path_encloses(p));
+        if !closed.is_empty() {
+            render_paths(renderer, &transform, &closed, true)?;
+        }
+        if !open.is_empty() {
+            // An open subpath has no inside, it is only stroked on its center
+            renderer.set_fill(&Rgba::transparent())?;
+            if style.align != StrokeAlign::Center {
+                style.align = StrokeAlign::Center;
+                set_stroke(renderer, &style)?;
+            }
+            render_paths(renderer, &transform, &open, false)?;
+        }
+        renderer.end()?;
+        Ok(())
+    }
+
+    /// Iterate over the subpaths, the first one is `path`
+    pub fn paths(&self) -> impl Iterator<Item = &Vec<DbCoord>> + '_ {
+        std::iter::once(&self.path).chain(self.subpaths.iter())
+    }
+
+    pub fn paths_mut(&mut self) -> impl Iterator<Item = &mut Vec<DbCoord>> + '_ {
+        std::iter::once(&mut self.path).chain(self.subpaths.iter_mut())
+    }
+
+    pub(crate) fn subpath(&self, index: usize) -> &Vec<DbCoord> {
+        match index {
+            0 => &self.path,
+            i => &self.subpaths[i - 1],
+        }
+    }
+
+    pub(crate) fn subpath_mut(&mut self, index: usize) -> &mut Vec<DbCoord> {
+        match index {
+            0 => &mut self.path,
+            i => &mut self.subpaths[i - 1],
+        }
+    }
+
+    /// Remove the subpath at index, the next subpath replaces `path` if needed
+    pub(crate) fn subpath_remove(&mut self, index: usize) {
+        if index > 0 {
+            self.subpaths.remove(index - 1);
+        } else if self.subpaths.is_empty() {
+            self.path.clear();
         } else {
-            renderer.set_stroke(
-                &self.stroke.color,
-                transform.scale_length(self.stroke.size) as f64,
-            )?;
+            self.path = self.subpaths.remove(0);
         }
+    }
+
+    /// Shape with its fill and its stroke faded by the opacity, between 0.0 and 1.0
+    pub fn with_opacity(&self, opacity: f32) -> Shape {
+        let mut shape = self.clone();
+        shape.fill = self.fill.with_opacity(opacity);
+        shape.stroke.paint = self.stroke.paint.with_opacity(opacity);
+        shape
+    }
+
+    /// Replace the subpaths and the fill rule by the ones of other, keep the id and the style
+    pub fn paths_replace(&mut self, other: Shape) {
+        self.path = other.path;
+        self.subpaths = other.subpaths;
+        self.fill_rule = other.fill_rule;
+    }
+}
+
+/// Path data of the subpaths, only the closed subpaths end with Z
+pub(crate) fn paths_to_string<'a>(
+    paths: impl Iterator<Item = &'a Vec<DbCoord>>,
+    transform: &Affine,
+) -> String {
+    use std::fmt::Write;
+
+    let mut path = String::new();
+    for (index, subpath) in paths.enumerate() {
+        if index > 0 {
+            write!(&mut path, " ").expect("Write should be ok");
+        }
+        for (i, db_coord) in subpath.iter().enumerate() {
+            let coord = *transform * db_coord.coord;
+            if i == 0 {
+                write!(&mut path, "M {} {} ", coord.x, coord.y).expect("Write should be ok");
+            } else if (i - 1) % 3 == 0 {
+                write!(&mut path, "C {} {} ", coord.x, coord.y).expect("Write should be ok");
+            } else {
+                write!(&mut path, "{} {} ", coord.x, coord.y).expect("Write should be ok");
+            }
+        }
+        if path_encloses(subpath) {
+            write!(&mut path, "Z").expect("Write should be ok");
+        }
+    }
+    path
+}
 
-        renderer.start_shape(&coords[0])?;
+fn render_paths(
+    renderer: &mut dyn DrawingContext,
+    transform: &Affine,
+    paths: &[&Vec<DbCoord>],
+    closed: bool,
+) -> Result<(), String> {
+    for (index, path) in paths.iter().enumerate() {
+        let coords: Vec<Coord> = path.iter().map(|c| *transform * c.coord).collect();
+        if index == 0 {
+            renderer.start_shape(&coords[0])?;
+        } else {
+            renderer.start_subpath(&coords[0])?;
+        }
         for i in (1..(coords.len() - 1)).step_by(3) {
-            let cp0 = coords[i];
-            let cp1 = coords[i + 1];
-            let p1 = coords[i + 2];
-            renderer.move_curve(&cp0, &cp1, &p1)?;
+            renderer.move_curve(&coords[i], &coords[i + 1], &coords[i + 2])?;
+        }
+        if closed {
+            renderer.close_subpath()?;
         }
-        renderer.close_shape()?;
-        renderer.end()?;
-        Ok(())
     }
+    renderer.end_shape()
 }
 
 impl Scene {
     pub fn shape_insert(&mut self, mut shape: Shape) -> LayerId {
         if shape.id == LayerId::null() {
-            shape.id.update();
+            shape.id = self.ids.layer_next();
+        } else {
+            self.ids.reserve(shape.id, CoordId::null());
         }
         let id = shape.id;
-        shape.curves_path_update_id();
-        self.layers.push(Layer {
-            id: shape.id,
-            value: LayerType::Shape(shape),
-            name: format!("Shape {}", id.value()),
-        });
+        self.ids
+            .coords_adopt(shape.paths_mut().flat_map(|p| p.iter_mut()));
+        self.index.mark(id);
+        let name = format!("Shape {}", id.value());
+        self.layers
+            .push(Layer::new(id, name, LayerType::Shape(shape)));
 
         id
     }
@@ -97,51 +325,87 @@ impl Scene {
         }
     }
 
-    pub fn shape_put(&mut self, shape: Shape) {
-        let index = self
-            .layers
-            .iter()
-            .position(|l| l.id == shape.id)
-            .expect("Valid shape id");
-        self.layers[index].value = LayerType::Shape(shape);
+    pub fn shape_put(&mut self, mut shape: Shape) {
+        self.ids
+            .coords_adopt(shape.paths_mut().flat_map(|p| p.iter_mut()));
+        let layer = self.layer_select_mut(shape.id).expect("Valid shape id");
+        *layer = LayerType::Shape(shape);
     }
 
     pub fn shape_select_contains(&self, coord: &Coord) -> Option<&Shape> {
-        let find_result = self.layers.iter().find(|l| {
-            if let LayerType::Shape(shape) = &l.value {
-                if shape.contains(coord) {
-                    return true;
-                }
-            }
-            false
-        });
-        find_result.and_then(|l| {
-            if let LayerType::Shape(shape) = &l.value {
+        let candidates = self
+            .index
+            .shapes_candidates(&Rect::new(coord.x, coord.y, coord.x, coord.y));
+        self.layers_iter().find_map(|l| match &l.value {
+            LayerType::Shape(shape)
+                if candidates.as_ref().is_none_or(|c| c.contains(&shape.id))
+                    && shape.contains(coord) =>
+            {
                 Some(shape)
-            } else {
-                None
             }
+            _ => None,
         })
     }
 
-    pub fn shape_select_contains_mut(&mut self, coord: &Coord) -> Option<&mut Shape> {
-        let find_result = self.layers.iter_mut().find(|l| {
-            if let LayerType::Shape(shape) = &l.value {
-                if shape.contains(coord) {
-                    return true;
+    /// Bounding box around all the shapes, None if none of them has a coord
+    pub fn shapes_bounding_box(&self, shapes: &[LayerId]) -> Option<Rect> {
+        shapes
+            .iter()
+            .filter_map(|id| self.shape_select(*id)?.bounding_box())
+            .reduce(|a, b| Rect::max(&a, &b))
+    }
+
+    /// Bounding box around the selected shapes
+    pub fn selection_bounds(&self, selected: &UserSelection) -> Option<Rect> {
+        let shapes: Vec<LayerId> = selected.shapes.iter().map(|s| s.shape_id).collect();
+        self.shapes_bounding_box(&shapes)
+    }
+
+    /// First shape hit by the coord, the open subpaths are hit within tolerance of their stroke.
+    /// The hidden and the locked layers can't be hit
+    pub fn shape_select_hit(&self, coord: &Coord, tolerance: f32) -> Option<&Shape> {
+        let area = Rect::new(
+            coord.x - tolerance,
+            coord.y - tolerance,
+            coord.x + tolerance,
+            coord.y + tolerance,
+        );
+        self.shapes_visible_in(true, &area)
+            .into_iter()
+            .map(|(shape, _)| shape)
+            .find(|shape| shape.hit(coord, tolerance))
+    }
+
+    /// Find the end anchor of an open subpath in the radius of the coord
+    /// Return the shape, the subpath and the end of the subpath
+    pub fn shape_select_open_end(
+        &self,
+        coord: &Coord,
+        radius: Length2d,
+    ) -> Option<(LayerId, usize, PathEnd)> {
+        self.layers_iter().find_map(|l| match &l.value {
+            LayerType::Shape(shape) => shape.paths().enumerate().find_map(|(subpath, path)| {
+                if path.is_empty() || path_encloses(path) {
+                    return None;
                 }
-            }
-            false
-        });
-        find_result.and_then(|l| {
-            if let LayerType::Shape(shape) = &mut l.value {
-                Some(shape)
-            } else {
-                None
-            }
+                let last = path[path.len() - 1];
+                if point_in_radius(*coord, last.coord, radius) {
+                    Some((shape.id, subpath, PathEnd::End))
+                } else if point_in_radius(*coord, path[0].coord, radius) {
+                    Some((shape.id, subpath, PathEnd::Start))
+                } else {
+                    None
+                }
+            }),
+            LayerType::Folder(_) => None,
         })
     }
 
+    pub fn shape_select_contains_mut(&mut self, coord: &Coord) -> Option<&mut Shape> {
+        let id = self.shape_select_contains(coord)?.id;
+        self.shape_select_mut(id)
+    }
+
     // pub fn shapes_closest(&self, coord: &Coord) -> Vec<(usize, usize, f32, Coord)> {
     //     let mut vec = Vec::new();
     //     for (shape_index, shape) in self.shapes.iter().enumerate() {
@@ -169,7 +433,9 @@ impl Shape {
         Shape {
             id: LayerId::null(),
             path: Vec::new(),
-            color: Rgba::transparent(),
+            subpaths: Vec::new(),
+            fill_rule: FillRule::default(),
+            fill: Paint::Solid(Rgba::transparent()),
             stroke: Stroke::new(0.0, Rgba::black()),
         }
     }
@@ -188,23 +454,24 @@ impl Shape {
 
     ///List of coordinates of lines. It will close the shape.
     pub fn new_from_lines(coords: Vec<coord::DbCoord>, transform: Affine) -> Self {
+        let mut shape = Shape::new_polyline(coords, transform);
+        shape.close();
+        shape
+    }
+
+    ///List of coordinates of lines. The path is left open.
+    pub fn new_polyline(coords: Vec<coord::DbCoord>, transform: Affine) -> Self {
         let mut shape = Shape::new();
         if coords.is_empty() {
             return shape;
         }
 
         shape.path.push(coords[0].transform(&transform));
-        shape.path.push(coords[0].transform(&transform));
-        for coord in coords.iter().skip(1) {
-            shape.path.push(coord.transform(&transform));
-            shape.path.push(coord.transform(&transform));
-            shape.path.push(coord.transform(&transform));
+        for pair in coords.windows(2) {
+            shape.path.push(pair[0].transform(&transform));
+            shape.path.push(pair[1].transform(&transform));
+            shape.path.push(pair[1].transform(&transform));
         }
-
-        shape.path.push(coords[0].transform(&transform));
-        shape.path.push(coords[0].transform(&transform));
-
-        shape.close();
         shape
     }
 
@@ -238,45 +505,125 @@ impl Shape {
     }
 
     pub fn path(&self) -> String {
-        use std::fmt::Write;
+        self.path_with_transform(&Affine::identity())
+    }
 
-        let mut path = String::new();
-        for i in 0..self.path.len() {
-            let coord = self.path[i].coord;
-            if i == 0 {
-                write!(&mut path, "M {} {} ", coord.x, coord.y).expect("Write should be ok");
-            } else if (i - 1) % 3 == 0 {
-                write!(&mut path, "C {} {} ", coord.x, coord.y).expect("Write should be ok");
-            } else {
-                write!(&mut path, "{} {} ", coord.x, coord.y).expect("Write should be ok");
-            }
-        }
-        write!(&mut path, "Z").expect("Write should be ok");
-        path
+    /// Same as `path` but each coordinate is transformed before being written
+    pub fn path_with_transform(&self, transform: &Affine) -> String {
+        paths_to_string(self.paths(), transform)
     }
 
     /// Return true if the coord is inside the shape
-    /// Use the even-odd rule
+    /// Use the fill rule of the shape to combine the subpaths, the open subpaths have no inside
     pub fn contains(&self, coord: &Coord) -> bool {
         let mut count = 0;
-        for curve_index in 0..self.curves_len() {
-            let curve = self.curve_select(curve_index).expect("Curve should exist");
-
+        let mut winding = 0;
+        let closed_curves = self
+            .paths()
+            .filter(|path| path_encloses(path))
+            .flat_map(|path| (0..path_curves_len(path)).map(|i| path_curve_select(path, i)));
+        for curve in closed_curves {
             let t_intersections = curve.intersection_with_y(coord.y);
             for t in t_intersections {
                 let x = curve.cubic_bezier(t).x;
                 if x > coord.x {
                     count += 1;
+                    if curve.tangent_vector(t).y > 0.0 {
+                        winding += 1;
+                    } else {
+                        winding -= 1;
+                    }
                 }
             }
         }
-        count % 2 == 1
+        match self.fill_rule {
+            FillRule::EvenOdd => count % 2 == 1,
+            FillRule::NonZero => winding != 0,
+        }
+    }
+
+    /// Return true if the coord is on the stroke of the shape, within tolerance
+    pub fn stroke_contains(&self, coord: &Coord, tolerance: f32) -> bool {
+        if self.curves_len() == 0 {
+            return false;
+        }
+        let (_, _, distance, _) = self.closest_curve(coord);
+        distance <= self.stroke.size / 2.0 + tolerance
+    }
+
+    /// Return true if the coord selects the shape.
+    /// Inside the closed subpaths or on the stroke of an open subpath
+    pub fn hit(&self, coord: &Coord, tolerance: f32) -> bool {
+        let has_open = self.paths().any(|path| !path_encloses(path));
+        self.contains(coord) || (has_open && self.stroke_contains(coord, tolerance))
     }
 
     pub fn is_empty(&self) -> bool {
         self.path.is_empty()
     }
 
+    /// Smallest rectangle around the curves of the shape, None if the shape has no coord
+    pub fn bounding_box(&self) -> Option<Rect> {
+        self.paths_bounding_box(bounding_box)
+    }
+
+    /// Bounding box grown by the farthest the stroke can paint from the curves, with its joins
+    /// and its caps. None if the shape has no coord
+    pub fn bounding_box_with_stroke(&self) -> Option<Rect> {
+        let rect = self.bounding_box()?;
+        let width = match self.stroke.align {
+            StrokeAlign::Center => self.stroke.size / 2.0,
+            StrokeAlign::Inside | StrokeAlign::Outside => self.stroke.size,
+        };
+        let margin = width.max(0.0) * self.stroke.miter_limit.max(std::f32::consts::SQRT_2);
+        Some(Rect::new(
+            rect.top_left.x - margin,
+            rect.top_left.y - margin,
+            rect.bottom_right.x + margin,
+            rect.bottom_right.y + margin,
+        ))
+    }
+
+    /// Rectangle around the anchors and the handles of the shape, None if the shape has no coord.
+    /// Faster than `bounding_box` but it can be larger than the curves
+    pub fn quick_bounding_box(&self) -> Option<Rect> {
+        self.paths_bounding_box(quick_bounding_box)
+    }
+
+    fn paths_bounding_box(
+        &self,
+        curve_box: fn(&Coord, &Coord, &Coord, &Coord) -> Rect,
+    ) -> Option<Rect> {
+        self.paths()
+            .filter_map(|path| {
+                let first = path.first()?.coord();
+                let start = Rect {
+                    top_left: first,
+                    bottom_right: first,
+                };
+                Some((0..path_curves_len(path)).fold(start, |rect, i| {
+                    let curve = path_curve_select(path, i);
+                    let curve_rect = curve_box(
+                        &curve.p0.coord(),
+                        &curve.cp0.coord(),
+                        &curve.cp1.coord(),
+                        &curve.p1.coord(),
+                    );
+                    Rect::max(&rect, &curve_rect)
+                }))
+            })
+            .reduce(|a, b| Rect::max(&a, &b))
+    }
+
+    /// Apply the transform to every coord and to the gradients of the shape, the ids are kept
+    pub fn transform(&mut self, transform: &Affine) {
+        for coord in self.paths_mut().flat_map(|path| path.iter_mut()) {
+            *coord = coord.transform(transform);
+        }
+        self.fill.transform(transform);
+        self.stroke.paint.transform(transform);
+    }
+
     ///Creates a new shape from a string of path coordinates.
     /// # Example
     ///
@@ -337,4 +684,59 @@ mod test {
         let coord = Coord::new(0.0, 0.0);
         assert!(!shape.contains(&coord));
     }
+
+    #[test]
+    fn given_open_path_when_hit_then_only_near_stroke() {
+        let mut shape = Shape::new_polyline(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(1.0, 0.0),
+                DbCoord::new(1.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        shape.stroke = Stroke::new(0.1, Rgba::black());
+
+        assert!(!shape.is_closed());
+        assert!(!shape.contains(&Coord::new(0.9, 0.1)));
+        assert!(!shape.hit(&Coord::new(0.5, 0.5), 0.01));
+        assert!(shape.hit(&Coord::new(0.5, 0.04), 0.01));
+        assert!(shape.hit(&Coord::new(1.05, 0.5), 0.01));
+    }
+
+    #[test]
+    fn given_open_path_when_render_then_ok() {
+        let shape = Shape::new_polyline(
+            vec![DbCoord::new(0.0, 0.0), DbCoord::new(1.0, 0.0)],
+            Affine::identity(),
+        );
+        let mut renderer = MockDrawingContext::default();
+
+        shape.render(&mut renderer).expect("Render should be ok");
+        assert_eq!(shape.path(), "M 0 0 C 0 0 1 0 1 0 ");
+    }
+
+    #[test]
+    fn given_circle_when_bounding_box_then_touch_extremities() {
+        let shape = Shape::new_circle(Coord::new(0.5, 0.0), Length2d::new(1.0, 2.0));
+
+        let rect = shape.bounding_box().unwrap();
+
+        assert!((rect.top_left.x + 0.5).abs() < 0.001);
+        assert!((rect.top_left.y + 2.0).abs() < 0.001);
+        assert!((rect.bottom_right.x - 1.5).abs() < 0.001);
+        assert!((rect.bottom_right.y - 2.0).abs() < 0.001);
+        assert_eq!(Shape::new().bounding_box(), None);
+    }
+
+    #[test]
+    fn given_curve_when_quick_bounding_box_then_contains_handles() {
+        let shape = Shape::quick_from_string("M 0 0 C 0 2 1 2 1 0");
+
+        let rect = shape.bounding_box().unwrap();
+        let quick = shape.quick_bounding_box().unwrap();
+
+        assert!((rect.bottom_right.y - 1.5).abs() < 0.001);
+        assert_eq!(quick, Rect::new(0.0, 0.0, 1.0, 2.0));
+    }
 }
@@ -0,0 +1,237 @@
+use common::{
+    pures::Vec2,
+    types::{Coord, Vector},
+};
+
+use crate::math::{
+    curve::{cubic_bezier, tangent_vector},
+    offset::{dash_path, offset_path, stroke_outline, OffsetStyle},
+};
+
+use super::{coord::DbCoord, curve::path_encloses, FillRule, LineJoin, Shape, StrokeAlign};
+
+impl Shape {
+    /// Filled shape of the area painted by the stroke, with its joins, caps, dashes and alignment.
+    /// The dashes are outlined on the center of the path.
+    /// None when the stroke has no size or the shape no curve
+    pub fn outline_stroke(&self) -> Option<Shape> {
+        let stroke = &self.stroke;
+        if stroke.size <= 0.0 {
+            return None;
+        }
+        let style = OffsetStyle {
+            join: stroke.join,
+            cap: stroke.cap,
+            miter_limit: stroke.miter_limit,
+        };
+
+        let mut contours = Vec::new();
+        for path in self.paths() {
+            let coords: Vec<Coord> = path.iter().map(|c| c.coord).collect();
+            let closed = path_encloses(path);
+            if let Some(dashes) = dash_path(&coords, closed, &stroke.dashes, stroke.dash_offset) {
+                for dash in dashes {
+                    contours.extend(stroke_outline(&dash, false, stroke.size, &style));
+                }
+                continue;
+            }
+
+            let outward = match closed {
+                true => self.outward(&coords),
+                false => None,
+            };
+            match (stroke.align, outward) {
+                (StrokeAlign::Inside | StrokeAlign::Outside, Some(outward)) => {
+                    // The stroke is between the path and its offset by the whole size
+                    let distance = match stroke.align {
+                        StrokeAlign::Inside => -outward * stroke.size,
+                        _ => outward * stroke.size,
+                    };
+                    let offset = offset_path(&coords, true, distance, &style);
+                    contours.push(coords);
+                    contours.push(offset.into_iter().rev().collect());
+                }
+                _ => contours.extend(stroke_outline(&coords, closed, stroke.size, &style)),
+            }
+        }
+        if contours.is_empty() {
+            return None;
+        }
+
+        let mut outline = Shape::new();
+        outline.fill = stroke.paint.clone();
+        outline.fill_rule = FillRule::NonZero;
+        let mut paths = contours.iter().map(|contour| coords_to_path(contour, true));
+        outline.path = paths.next().unwrap_or_default();
+        outline.subpaths = paths.collect();
+        Some(outline)
+    }
+
+    /// Shape grown by the distance away from its inside, a negative distance shrinks it.
+    /// The corners are joined like a stroke, the open subpaths have no inside and are kept
+    pub fn offset(&self, distance: f32, join: LineJoin, miter_limit: f32) -> Shape {
+        let style = OffsetStyle {
+            join,
+            miter_limit,
+            ..OffsetStyle::default()
+        };
+        let mut shape = self.clone();
+        let paths = self.paths().map(|path| {
+            let coords: Vec<Coord> = path.iter().map(|c| c.coord).collect();
+            match self.outward(&coords).filter(|_| path_encloses(path)) {
+                Some(outward) => coords_to_path(
+                    &offset_path(&coords, true, outward * distance, &style),
+                    true,
+                ),
+                None => path.clone(),
+            }
+        });
+        let mut paths: Vec<Vec<DbCoord>> = paths.collect();
+        shape.subpaths = paths.split_off(1);
+        shape.path = paths.pop().unwrap_or_default();
+        shape
+    }
+
+    /// Sign of the offset distance that moves a closed subpath away from the inside of the shape,
+    /// found by looking on each side of the middle of its first curve
+    fn outward(&self, coords: &[Coord]) -> Option<f32> {
+        let bounds = self.bounding_box()?;
+        let probe = (bounds.width() + bounds.height()) * 0.0001;
+        coords.windows(4).step_by(3).find_map(|curve| {
+            let [p0, cp0, cp1, p1] = [&curve[0], &curve[1], &curve[2], &curve[3]];
+            if p0 == p1 && p0 == cp0 && p0 == cp1 {
+                return None;
+            }
+            let middle = cubic_bezier(0.5, p0, cp0, cp1, p1);
+            let tangent = tangent_vector(0.5, p0, cp0, cp1, p1);
+            let normal: Coord = Vector::new(-tangent.y, tangent.x).normal().into();
+            match (
+                self.contains(&(middle + normal * probe)),
+                self.contains(&(middle - normal * probe)),
+            ) {
+                (true, false) => Some(-1.0),
+                (false, true) => Some(1.0),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// Subpath of a shape from coordinates, a handle on its anchor is not free.
+/// A closed subpath ends with its first coord
+fn coords_to_path(coords: &[Coord], closed: bool) -> Vec<DbCoord> {
+    let Some(first) = coords.first() else {
+        return Vec::new();
+    };
+    let start = DbCoord::from(*first);
+    let mut path = vec![start];
+    let mut previous = start;
+    let curves_len = (coords.len() - 1) / 3;
+    for (i, curve) in coords[1..].chunks_exact(3).enumerate() {
+        let p1 = match closed && i == curves_len - 1 {
+            true => start,
+            false => DbCoord::from(curve[2]),
+        };
+        let cp0 = match curve[0] == previous.coord {
+            true => previous,
+            false => DbCoord::from(curve[0]),
+        };
+        let cp1 = match curve[1] == p1.coord {
+            true => p1,
+            false => DbCoord::from(curve[1]),
+        };
+        path.extend([cp0, cp1, p1]);
+        previous = p1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Affine, types::Coord, Rgba};
+
+    use crate::{
+        scene::shape::{LineCap, Stroke},
+        DbCoord, FillRule, LineJoin, Paint, Scene, Shape, StrokeAlign,
+    };
+
+    fn square() -> Shape {
+        let mut shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(1.0, 0.0),
+                DbCoord::new(1.0, 1.0),
+                DbCoord::new(0.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        shape.fill = Rgba::black().into();
+        shape.stroke = Stroke::new(0.2, Rgba::red());
+        shape
+    }
+
+    #[test]
+    fn given_stroked_square_when_outline_stroke_then_filled_frame() {
+        let outline = square().outline_stroke().unwrap();
+
+        assert_eq!(outline.fill, Paint::Solid(Rgba::red()));
+        assert_eq!(outline.fill_rule, FillRule::NonZero);
+        assert_eq!(outline.stroke.size, 0.0);
+        assert_eq!(outline.subpaths.len(), 1);
+        assert!(outline.is_closed());
+        assert!(outline.contains(&Coord::new(-0.05, 0.5)));
+        assert!(outline.contains(&Coord::new(0.05, 0.5)));
+        assert!(!outline.contains(&Coord::new(0.5, 0.5)));
+        assert!(!outline.contains(&Coord::new(-0.15, 0.5)));
+        assert!(outline.contains(&Coord::new(-0.09, -0.09)));
+        let mut scene = Scene::new();
+        scene.shape_insert(outline);
+        assert_eq!(scene.validate(), vec![]);
+    }
+
+    #[test]
+    fn given_aligned_strokes_when_outline_stroke_then_one_side_of_the_path() {
+        let mut shape = square();
+        shape.stroke.align = StrokeAlign::Inside;
+        let inside = shape.outline_stroke().unwrap();
+        shape.stroke.align = StrokeAlign::Outside;
+        let outside = shape.outline_stroke().unwrap();
+
+        assert!(inside.contains(&Coord::new(0.15, 0.5)));
+        assert!(!inside.contains(&Coord::new(-0.05, 0.5)));
+        assert!(outside.contains(&Coord::new(-0.15, 0.5)));
+        assert!(!outside.contains(&Coord::new(0.05, 0.5)));
+    }
+
+    #[test]
+    fn given_dashed_open_path_when_outline_stroke_then_one_subpath_per_dash() {
+        let mut shape = Shape::new_polyline(
+            vec![DbCoord::new(0.0, 0.0), DbCoord::new(1.0, 0.0)],
+            Affine::identity(),
+        );
+        shape.stroke = Stroke::new(0.1, Rgba::black());
+        shape.stroke.cap = LineCap::Square;
+        shape.stroke.dashes = vec![0.2, 0.3];
+
+        let outline = shape.outline_stroke().unwrap();
+
+        // The square caps go half of the size over the gaps
+        assert_eq!(outline.paths().count(), 2);
+        assert!(outline.contains(&Coord::new(0.1, 0.0)));
+        assert!(outline.contains(&Coord::new(0.24, 0.0)));
+        assert!(!outline.contains(&Coord::new(0.35, 0.0)));
+        assert!(Shape::new().outline_stroke().is_none());
+    }
+
+    #[test]
+    fn given_square_when_offset_then_grown_or_shrunk() {
+        let grown = square().offset(0.1, LineJoin::Miter, 4.0);
+        let shrunk = square().offset(-0.1, LineJoin::Miter, 4.0);
+
+        assert!(grown.contains(&Coord::new(-0.09, -0.09)));
+        assert!(!grown.contains(&Coord::new(-0.11, 0.5)));
+        assert!(!shrunk.contains(&Coord::new(0.09, 0.5)));
+        assert!(shrunk.contains(&Coord::new(0.11, 0.11)));
+        assert_eq!(grown.stroke, square().stroke);
+    }
+}
@@ -0,0 +1,260 @@
+use common::{
+    pures::Affine,
+    types::{Coord, Rect},
+    Rgba,
+};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{DrawingContext, StrokeStyle};
+
+/// Color of a gradient at an offset between 0.0 and 1.0
+#[derive(Tsify, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct GradientStop {
+    pub offset: f32,
+    pub color: Rgba,
+}
+
+impl GradientStop {
+    pub fn new(offset: f32, color: Rgba) -> Self {
+        GradientStop { offset, color }
+    }
+}
+
+/// Space of the coordinates of a gradient
+#[derive(Tsify, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum GradientUnits {
+    /// 0.0 to 1.0 over the bounding box of the shape, the gradient follows the shape
+    #[default]
+    Shape,
+    /// Coordinates of the scene
+    Scene,
+}
+
+#[derive(Tsify, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct LinearGradient {
+    pub start: Coord,
+    pub end: Coord,
+    pub stops: Vec<GradientStop>,
+    pub units: GradientUnits,
+}
+
+#[derive(Tsify, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub struct RadialGradient {
+    pub center: Coord,
+    /// In shape units, the radius is relative to the geometric mean of the width and height
+    pub radius: f32,
+    pub stops: Vec<GradientStop>,
+    pub units: GradientUnits,
+}
+
+/// How the inside or the stroke of a shape is colored
+#[derive(Tsify, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum Paint {
+    Solid(Rgba),
+    LinearGradient(LinearGradient),
+    RadialGradient(RadialGradient),
+}
+
+impl Default for Paint {
+    fn default() -> Self {
+        Paint::Solid(Rgba::default())
+    }
+}
+
+impl From<Rgba> for Paint {
+    fn from(color: Rgba) -> Self {
+        Paint::Solid(color)
+    }
+}
+
+impl Paint {
+    /// Single color for the places that can't show a gradient, the first stop of a gradient
+    pub fn color(&self) -> Rgba {
+        match self {
+            Paint::Solid(color) => color.clone(),
+            Paint::LinearGradient(LinearGradient { stops, .. })
+            | Paint::RadialGradient(RadialGradient { stops, .. }) => stops
+                .first()
+                .map(|stop| stop.color.clone())
+                .unwrap_or(Rgba::transparent()),
+        }
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        match self {
+            Paint::Solid(color) => color.a == 0,
+            Paint::LinearGradient(LinearGradient { stops, .. })
+            | Paint::RadialGradient(RadialGradient { stops, .. }) => {
+                stops.iter().all(|stop| stop.color.a == 0)
+            }
+        }
+    }
+
+    /// Paint with the alpha of its colors multiplied by the opacity, between 0.0 and 1.0
+    pub fn with_opacity(&self, opacity: f32) -> Paint {
+        let fade = |color: &Rgba| Rgba {
+            a: (color.a as f32 * opacity.clamp(0.0, 1.0)).round() as u8,
+            ..color.clone()
+        };
+        let mut paint = self.clone();
+        match &mut paint {
+            Paint::Solid(color) => *color = fade(color),
+            Paint::LinearGradient(LinearGradient { stops, .. })
+            | Paint::RadialGradient(RadialGradient { stops, .. }) => {
+                for stop in stops {
+                    stop.color = fade(&stop.color);
+                }
+            }
+        }
+        paint
+    }
+
+    /// Move a gradient in scene units with the shape, a gradient in shape units already follows it
+    pub fn transform(&mut self, transform: &Affine) {
+        match self {
+            Paint::LinearGradient(gradient) if gradient.units == GradientUnits::Scene => {
+                gradient.start = *transform * gradient.start;
+                gradient.end = *transform * gradient.end;
+            }
+            Paint::RadialGradient(gradient) if gradient.units == GradientUnits::Scene => {
+                gradient.center = *transform * gradient.center;
+                gradient.radius *= transform.determinant().abs().sqrt();
+            }
+            _ => {}
+        }
+    }
+
+    /// Set the paint as the fill of the renderer, bounds is the bounding box of the shape
+    pub(crate) fn render_fill(
+        &self,
+        renderer: &mut dyn DrawingContext,
+        transform: &Affine,
+        bounds: Option<Rect>,
+    ) -> Result<(), String> {
+        match self {
+            Paint::Solid(color) => renderer.set_fill(color),
+            Paint::LinearGradient(gradient) => {
+                let to_screen = gradient_transform(transform, gradient.units, bounds);
+                renderer.set_fill_linear_gradient(
+                    &(to_screen * gradient.start),
+                    &(to_screen * gradient.end),
+                    &gradient.stops,
+                )
+            }
+            Paint::RadialGradient(gradient) => {
+                let to_screen = gradient_transform(transform, gradient.units, bounds);
+                renderer.set_fill_radial_gradient(
+                    &(to_screen * gradient.center),
+                    gradient.radius * to_screen.determinant().abs().sqrt(),
+                    &gradient.stops,
+                )
+            }
+        }
+    }
+
+    /// Set the paint as the stroke of the renderer, bounds is the bounding box of the shape
+    pub(crate) fn render_stroke(
+        &self,
+        renderer: &mut dyn DrawingContext,
+        transform: &Affine,
+        bounds: Option<Rect>,
+        size: f64,
+        style: &StrokeStyle,
+    ) -> Result<(), String> {
+        match self {
+            Paint::Solid(color) => renderer.set_stroke(color, size, style),
+            Paint::LinearGradient(gradient) => {
+                let to_screen = gradient_transform(transform, gradient.units, bounds);
+                renderer.set_stroke_linear_gradient(
+                    &(to_screen * gradient.start),
+                    &(to_screen * gradient.end),
+                    &gradient.stops,
+                    size,
+                    style,
+                )
+            }
+            Paint::RadialGradient(gradient) => {
+                let to_screen = gradient_transform(transform, gradient.units, bounds);
+                renderer.set_stroke_radial_gradient(
+                    &(to_screen * gradient.center),
+                    gradient.radius * to_screen.determinant().abs().sqrt(),
+                    &gradient.stops,
+                    size,
+                    style,
+                )
+            }
+        }
+    }
+}
+
+/// Transform from the units of the gradient to the output of the transform
+pub(crate) fn gradient_transform(
+    transform: &Affine,
+    units: GradientUnits,
+    bounds: Option<Rect>,
+) -> Affine {
+    match (units, bounds) {
+        (GradientUnits::Shape, Some(bounds)) => {
+            *transform
+                * Affine::from_scale(Coord::new(bounds.width(), bounds.height()))
+                    .translate(bounds.top_left)
+        }
+        _ => *transform,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::Affine,
+        types::{Coord, Rect},
+        Rgba,
+    };
+
+    use super::{gradient_transform, GradientStop, GradientUnits, Paint, RadialGradient};
+
+    #[test]
+    fn given_shape_units_when_gradient_transform_then_unit_square_on_bounds() {
+        let bounds = Rect::new(1.0, 2.0, 3.0, 6.0);
+        let transform = Affine::from_scale(Coord::new(10.0, 10.0));
+
+        let shape = gradient_transform(&transform, GradientUnits::Shape, Some(bounds));
+        let scene = gradient_transform(&transform, GradientUnits::Scene, Some(bounds));
+
+        assert_eq!(shape * Coord::new(0.0, 0.0), Coord::new(10.0, 20.0));
+        assert_eq!(shape * Coord::new(1.0, 1.0), Coord::new(30.0, 60.0));
+        assert_eq!(scene * Coord::new(1.0, 1.0), Coord::new(10.0, 10.0));
+    }
+
+    #[test]
+    fn given_gradients_when_transform_then_only_scene_units_move() {
+        let radial = |units| {
+            Paint::RadialGradient(RadialGradient {
+                center: Coord::new(0.5, 0.5),
+                radius: 0.5,
+                stops: vec![GradientStop::new(0.0, Rgba::red())],
+                units,
+            })
+        };
+        let transform = Affine::from_scale(Coord::new(2.0, 2.0));
+
+        let mut shape = radial(GradientUnits::Shape);
+        shape.transform(&transform);
+        let mut scene = radial(GradientUnits::Scene);
+        scene.transform(&transform);
+
+        assert_eq!(shape, radial(GradientUnits::Shape));
+        let Paint::RadialGradient(gradient) = scene else {
+            panic!("Radial gradient expected");
+        };
+        assert_eq!(gradient.center, Coord::new(1.0, 1.0));
+        assert_eq!(gradient.radius, 1.0);
+        assert_eq!(shape.color(), Rgba::red());
+    }
+}
@@ -0,0 +1,181 @@
+use std::f32::consts::PI;
+
+use common::{
+    pures::{Affine, Vec2},
+    types::{Coord, Length2d, Rect},
+};
+
+use super::{coord::DbCoord, Shape};
+
+/// Length of the handles of a quarter of circle, relative to the radius
+const KAPPA: f32 = 0.552_284_8;
+
+impl Shape {
+    /// Closed path of the corners of the rect, clockwise from the top left
+    pub fn new_rect(rect: Rect) -> Self {
+        let corners = rect_corners(&rect);
+        Shape::new_from_lines(
+            corners.iter().map(|c| DbCoord::from(*c)).collect(),
+            Affine::identity(),
+        )
+    }
+
+    /// Rect with a quarter of circle at each corner, the radii are clockwise from the top left.
+    /// A radius is reduced to half of the smallest side, a corner without radius is sharp
+    pub fn new_rounded_rect(rect: Rect, radii: [f32; 4]) -> Self {
+        let corners = rect_corners(&rect);
+        let max = rect.width().abs().min(rect.height().abs()) / 2.0;
+
+        let mut anchors = Vec::with_capacity(8);
+        for (index, corner) in corners.iter().enumerate() {
+            let radius = radii[index].clamp(0.0, max);
+            if radius == 0.0 {
+                anchors.push((*corner, *corner, *corner));
+                continue;
+            }
+            let previous = corners[(index + 3) % 4];
+            let next = corners[(index + 1) % 4];
+            let start = *corner + (previous - *corner).normal() * radius;
+            let end = *corner + (next - *corner).normal() * radius;
+            anchors.push((start, start, start + (*corner - start) * KAPPA));
+            anchors.push((end + (*corner - end) * KAPPA, end, end));
+        }
+        closed_path(&anchors)
+    }
+
+    /// Ellipse inscribed in the rect
+    pub fn new_ellipse(rect: Rect) -> Self {
+        let radius = Length2d::new(rect.width() / 2.0, rect.height() / 2.0);
+        Shape::new_circle(rect.center(), radius)
+    }
+
+    /// Regular polygon inscribed in the ellipse of the radius, with a vertex at the top.
+    /// At least 3 sides
+    pub fn new_polygon(center: Coord, radius: Length2d, sides: usize) -> Self {
+        let sides = sides.max(3);
+        let vertices = (0..sides)
+            .map(|index| ellipse_point(center, radius, index as f32 / sides as f32))
+            .map(DbCoord::from)
+            .collect();
+        Shape::new_from_lines(vertices, Affine::identity())
+    }
+
+    /// Star with its points on the ellipse of the radius, the first one at the top. The inner
+    /// vertices are on the ellipse of the radius times the ratio, kept in (0, 1] for a star that
+    /// does not cross itself. At least 3 points
+    pub fn new_star(center: Coord, radius: Length2d, points: usize, inner_ratio: f32) -> Self {
+        let points = points.max(3);
+        let inner = radius * inner_ratio.clamp(f32::EPSILON, 1.0);
+        let vertices = (0..points * 2)
+            .map(|index| {
+                let radius = if index % 2 == 0 { radius } else { inner };
+                ellipse_point(center, radius, index as f32 / (points * 2) as f32)
+            })
+            .map(DbCoord::from)
+            .collect();
+        Shape::new_from_lines(vertices, Affine::identity())
+    }
+}
+
+fn rect_corners(rect: &Rect) -> [Coord; 4] {
+    let (top_left, bottom_right) = (rect.top_left, rect.bottom_right);
+    [
+        top_left,
+        Coord::new(bottom_right.x, top_left.y),
+        bottom_right,
+        Coord::new(top_left.x, bottom_right.y),
+    ]
+}
+
+/// Point at a fraction of the turn of the ellipse, clockwise from the top
+fn ellipse_point(center: Coord, radius: Length2d, turn: f32) -> Coord {
+    let angle = turn * 2.0 * PI - PI / 2.0;
+    Coord::new(
+        center.x + radius.x * angle.cos(),
+        center.y + radius.y * angle.sin(),
+    )
+}
+
+/// Closed path of the anchors, each given with its handle from the previous anchor and its
+/// handle toward the next anchor
+fn closed_path(anchors: &[(Coord, Coord, Coord)]) -> Shape {
+    let mut shape = Shape::new();
+    let Some((_, first, _)) = anchors.first() else {
+        return shape;
+    };
+    let start = DbCoord::from(*first);
+    shape.path.push(start);
+    for (index, (_, _, handle_out)) in anchors.iter().enumerate() {
+        let (handle_in, anchor, _) = anchors[(index + 1) % anchors.len()];
+        shape.path.push(DbCoord::from(*handle_out));
+        shape.path.push(DbCoord::from(handle_in));
+        if index + 1 == anchors.len() {
+            shape.path.push(start);
+        } else {
+            shape.path.push(DbCoord::from(anchor));
+        }
+    }
+    shape
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, Length2d, Rect};
+
+    use crate::{scene::shape::curve::path_is_closed, Shape};
+
+    #[test]
+    fn given_radii_when_rounded_rect_then_anchors_on_edges_and_radius_reduced() {
+        let rect = Rect::new(0.0, 0.0, 0.4, 0.2);
+
+        let shape = Shape::new_rounded_rect(rect, [0.05, 0.0, 0.5, 0.05]);
+
+        assert!(path_is_closed(&shape.path));
+        // Two anchors for each round corner and one for the sharp corner
+        let anchors: Vec<Coord> = shape.path.iter().step_by(3).map(|c| c.coord()).collect();
+        assert_eq!(anchors.len(), 8);
+        assert_eq!(anchors[0], Coord::new(0.0, 0.05));
+        assert_eq!(anchors[2], Coord::new(0.4, 0.0));
+        // The radius of the bottom right corner is half the height
+        assert_eq!(anchors[3], Coord::new(0.4, 0.1));
+        assert_eq!(anchors[4], Coord::new(0.3, 0.2));
+        let bounds = shape.bounding_box().unwrap();
+        assert!((bounds.width() - 0.4).abs() < 1e-5 && (bounds.height() - 0.2).abs() < 1e-5);
+
+        let sharp = Shape::new_rounded_rect(rect, [0.0; 4]);
+        assert_eq!(sharp.path.len(), Shape::new_rect(rect).path.len());
+    }
+
+    #[test]
+    fn given_sides_when_polygon_or_star_then_vertices_from_top() {
+        let center = Coord::new(0.0, 0.0);
+        let radius = Length2d::new(0.5, 0.5);
+
+        let hexagon = Shape::new_polygon(center, radius, 6);
+        let star = Shape::new_star(center, radius, 5, 0.4);
+        let triangle = Shape::new_polygon(center, radius, 1);
+
+        assert_eq!(hexagon.path.len(), 6 * 3 + 1);
+        assert_eq!(hexagon.path[0].coord(), Coord::new(0.0, -0.5));
+        assert_eq!(star.path.len(), 10 * 3 + 1);
+        let inner = star.path[3].coord();
+        assert!((inner.x.hypot(inner.y) - 0.2).abs() < 1e-5);
+        assert_eq!(triangle.path.len(), 3 * 3 + 1);
+    }
+
+    #[test]
+    fn given_ratio_out_of_range_when_star_then_inner_vertices_clamped() {
+        let center = Coord::new(0.0, 0.0);
+        let radius = Length2d::new(0.5, 0.5);
+        let inner_distance = |ratio: f32| {
+            let star = Shape::new_star(center, radius, 5, ratio);
+            let inner = star.path[3].coord();
+            inner.x.hypot(inner.y)
+        };
+
+        assert!((inner_distance(2.0) - 0.5).abs() < 1e-5);
+        assert!((inner_distance(1.0) - 0.5).abs() < 1e-5);
+        assert!(inner_distance(0.0) > 0.0);
+        assert!(inner_distance(-1.0) > 0.0 && inner_distance(-1.0) < 1e-5);
+    }
+}
@@ -0,0 +1,815 @@
+use std::collections::{HashMap, HashSet};
+
+use common::{
+    math::point_in_radius,
+    types::{Coord, Length2d, Rect},
+};
+
+use super::{
+    id::LayerId,
+    shape::{coord::DbCoord, Shape},
+    Layer, LayerType, Scene,
+};
+
+/// Number of entries of a node before it is split in two
+const NODE_CAPACITY: usize = 8;
+/// Number of damaged boxes kept until the next take, over it the whole scene is damaged
+const DAMAGE_LIMIT: usize = 256;
+
+/// R-tree of the boxes of items, to find the items with a box near a coord without visiting
+/// every item. The boxes are kept as they are inserted, an item is removed with its box
+#[derive(Debug, Clone)]
+pub(crate) struct BoxTree<T> {
+    root: Node<T>,
+}
+
+#[derive(Debug, Clone)]
+enum Node<T> {
+    Leaf(Vec<(Rect, T)>),
+    Branch(Vec<(Rect, Node<T>)>),
+}
+
+impl<T> Default for BoxTree<T> {
+    fn default() -> Self {
+        Self {
+            root: Node::Leaf(Vec::new()),
+        }
+    }
+}
+
+impl<T: Copy + PartialEq> BoxTree<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, rect: Rect, item: T) {
+        if let Some(sibling) = self.root.insert(rect, item) {
+            let old = std::mem::replace(&mut self.root, Node::Branch(Vec::new()));
+            self.root = Node::Branch(vec![(old.bounds(), old), sibling]);
+        }
+    }
+
+    /// Remove the item inserted with the rect, return false if it is not in the tree
+    pub fn remove(&mut self, rect: &Rect, item: T) -> bool {
+        let removed = self.root.remove(rect, item);
+        // A branch with one child is replaced by its child to keep the tree short
+        while let Node::Branch(children) = &mut self.root {
+            if children.len() != 1 {
+                break;
+            }
+            let (_, child) = children.pop().expect("One child");
+            self.root = child;
+        }
+        removed
+    }
+
+    /// Items with a box intersecting the area
+    pub fn query(&self, area: &Rect) -> Vec<T> {
+        let mut items = Vec::new();
+        self.root.query(area, &mut items);
+        items
+    }
+
+    /// Item with the smallest distance to the coord, with its distance.
+    /// The distance of an item must not be less than the distance from the coord to its box
+    pub fn nearest(&self, coord: &Coord, mut distance: impl FnMut(T) -> f32) -> Option<(T, f32)> {
+        let mut best = None;
+        self.root.nearest(coord, &mut distance, &mut best);
+        best
+    }
+}
+
+impl<T: Copy + PartialEq> Node<T> {
+    fn bounds(&self) -> Rect {
+        match self {
+            Node::Leaf(entries) => entries_bounds(entries),
+            Node::Branch(children) => entries_bounds(children),
+        }
+    }
+
+    /// Insert the item, return the new sibling of the node when it is split
+    fn insert(&mut self, rect: Rect, item: T) -> Option<(Rect, Node<T>)> {
+        match self {
+            Node::Leaf(entries) => {
+                entries.push((rect, item));
+                (entries.len() > NODE_CAPACITY).then(|| {
+                    let sibling = split(entries);
+                    (entries_bounds(&sibling), Node::Leaf(sibling))
+                })
+            }
+            Node::Branch(children) => {
+                let enlargement = |child: &Rect| area(&Rect::max(child, &rect)) - area(child);
+                let (index, _) = children
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, (a, _)), (_, (b, _))| {
+                        enlargement(a)
+                            .total_cmp(&enlargement(b))
+                            .then(area(a).total_cmp(&area(b)))
+                    })
+                    .expect("A branch has children");
+                let (child_rect, child) = &mut children[index];
+                *child_rect = Rect::max(child_rect, &rect);
+                if let Some(sibling) = child.insert(rect, item) {
+                    *child_rect = child.bounds();
+                    children.push(sibling);
+                }
+                (children.len() > NODE_CAPACITY).then(|| {
+                    let sibling = split(children);
+                    (entries_bounds(&sibling), Node::Branch(sibling))
+                })
+            }
+        }
+    }
+
+    fn remove(&mut self, rect: &Rect, item: T) -> bool {
+        match self {
+            Node::Leaf(entries) => match entries.iter().position(|(_, i)| *i == item) {
+                Some(position) => {
+                    entries.remove(position);
+                    true
+                }
+                None => false,
+            },
+            Node::Branch(children) => {
+                for index in 0..children.len() {
+                    let (child_rect, child) = &mut children[index];
+                    if !child_rect.intersect(rect) || !child.remove(rect, item) {
+                        continue;
+                    }
+                    let empty = match child {
+                        Node::Leaf(entries) => entries.is_empty(),
+                        Node::Branch(children) => children.is_empty(),
+                    };
+                    match empty {
+                        true => {
+                            children.remove(index);
+                        }
+                        false => *child_rect = child.bounds(),
+                    }
+                    return true;
+                }
+                false
+            }
+        }
+    }
+
+    fn query(&self, area: &Rect, items: &mut Vec<T>) {
+        match self {
+            Node::Leaf(entries) => items.extend(
+                entries
+                    .iter()
+                    .filter(|(rect, _)| rect.intersect(area))
+                    .map(|(_, item)| *item),
+            ),
+            Node::Branch(children) => {
+                for (rect, child) in children {
+                    if rect.intersect(area) {
+                        child.query(area, items);
+                    }
+                }
+            }
+        }
+    }
+
+    fn nearest(
+        &self,
+        coord: &Coord,
+        distance: &mut impl FnMut(T) -> f32,
+        best: &mut Option<(T, f32)>,
+    ) {
+        let is_better = |d: f32, best: &Option<(T, f32)>| best.is_none_or(|(_, b)| d < b);
+        match self {
+            Node::Leaf(entries) => {
+                for (rect, item) in entries {
+                    if is_better(rect_distance(rect, coord), best) {
+                        let d = distance(*item);
+                        if is_better(d, best) {
+                            *best = Some((*item, d));
+                        }
+                    }
+                }
+            }
+            Node::Branch(children) => {
+                let mut children: Vec<(f32, &Node<T>)> = children
+                    .iter()
+                    .map(|(rect, child)| (rect_distance(rect, coord), child))
+                    .collect();
+                children.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+                for (d, child) in children {
+                    if !is_better(d, best) {
+                        break;
+                    }
+                    child.nearest(coord, distance, best);
+                }
+            }
+        }
+    }
+}
+
+fn entries_bounds<E>(entries: &[(Rect, E)]) -> Rect {
+    entries
+        .iter()
+        .map(|(rect, _)| *rect)
+        .reduce(|a, b| Rect::max(&a, &b))
+        .unwrap_or(Rect::new(0.0, 0.0, 0.0, 0.0))
+}
+
+/// Split the entries in two halves along the longest side of their bounds, return the second half
+fn split<E>(entries: &mut Vec<(Rect, E)>) -> Vec<(Rect, E)> {
+    let bounds = entries_bounds(entries);
+    let horizontal = bounds.width() >= bounds.height();
+    let key = |rect: &Rect| match horizontal {
+        true => rect.center().x,
+        false => rect.center().y,
+    };
+    entries.sort_by(|(a, _), (b, _)| key(a).total_cmp(&key(b)));
+    entries.split_off(entries.len() / 2)
+}
+
+fn area(rect: &Rect) -> f32 {
+    rect.width() * rect.height()
+}
+
+/// Add the states of the shapes of the layers, in the folder of the state
+fn shape_states(layers: &[Layer], folder: &ShapeState, states: &mut HashMap<LayerId, ShapeState>) {
+    for (position, layer) in layers.iter().enumerate() {
+        let mut path = folder.path.clone();
+        path.push(position);
+        let state = ShapeState {
+            visible: folder.visible && layer.visible,
+            locked: folder.locked || layer.locked,
+            opacity: folder.opacity * layer.opacity,
+            path,
+        };
+        match &layer.value {
+            LayerType::Shape(_) => {
+                states.insert(layer.id, state);
+            }
+            LayerType::Folder(children) => shape_states(children, &state, states),
+        }
+    }
+}
+
+/// Distance from the coord to the closest point of the rect, 0.0 inside
+fn rect_distance(rect: &Rect, coord: &Coord) -> f32 {
+    let dx = (rect.top_left.x - coord.x)
+        .max(coord.x - rect.bottom_right.x)
+        .max(0.0);
+    let dy = (rect.top_left.y - coord.y)
+        .max(coord.y - rect.bottom_right.y)
+        .max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+/// Boxes of the shapes of the scene and of the curves of each shape.
+/// It is a cache of the layers, not part of the document. A shape changed since the last
+/// refresh is always a candidate, so the queries are never behind the scene
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SpatialIndex {
+    /// Boxes of the shapes grown by their stroke
+    shapes: BoxTree<LayerId>,
+    /// Box of each shape in `shapes`, to remove it
+    boxes: HashMap<LayerId, Rect>,
+    /// Boxes of the anchors and handles of each curve, by index of the curve in the shape
+    curves: HashMap<LayerId, BoxTree<usize>>,
+    /// Visibility, lock, opacity and place of each shape, with the ones of its folders
+    states: HashMap<LayerId, ShapeState>,
+    dirty: HashSet<LayerId>,
+    /// False until the first refresh, every shape is then a candidate
+    built: bool,
+    /// Boxes where the drawing changed since the last take, before and after the change
+    damage: Vec<Rect>,
+    /// The whole scene changed since the last take, on the first refresh or over the limit
+    damage_all: bool,
+}
+
+/// State of a shape in the tree of the layers at the last refresh
+#[derive(Debug, Clone, PartialEq)]
+struct ShapeState {
+    /// The shape and all its folders are visible
+    visible: bool,
+    /// The shape or one of its folders is locked
+    locked: bool,
+    /// Opacity of the shape times the opacity of its folders
+    opacity: f32,
+    /// Position of each folder of the shape in its parent from the root, then of the shape.
+    /// The order of the paths is the order of the layers from the foreground
+    path: Vec<usize>,
+}
+
+impl Default for ShapeState {
+    /// State of the root of the scene
+    fn default() -> Self {
+        Self {
+            visible: true,
+            locked: false,
+            opacity: 1.0,
+            path: Vec::new(),
+        }
+    }
+}
+
+impl SpatialIndex {
+    /// Changed layer, its boxes are updated at the next refresh
+    pub fn mark(&mut self, id: LayerId) {
+        self.dirty.insert(id);
+    }
+
+    /// Shapes that can be in the area, None when every shape can be
+    pub fn shapes_candidates(&self, area: &Rect) -> Option<HashSet<LayerId>> {
+        if !self.built {
+            return None;
+        }
+        let mut candidates: HashSet<LayerId> = self.shapes.query(area).into_iter().collect();
+        candidates.extend(self.dirty.iter().copied());
+        Some(candidates)
+    }
+
+    /// Visible shapes that can be in the area from the foreground, with their path and their
+    /// opacity. Without the locked shapes if `unlocked_only`. None when a layer changed since
+    /// the last refresh, the states can be behind the scene
+    fn shapes_visible_candidates(
+        &self,
+        unlocked_only: bool,
+        area: &Rect,
+    ) -> Option<Vec<(&[usize], f32)>> {
+        if !self.built || !self.dirty.is_empty() {
+            return None;
+        }
+        let mut shapes: Vec<(&[usize], f32)> = self
+            .shapes
+            .query(area)
+            .into_iter()
+            .filter_map(|id| self.states.get(&id))
+            .filter(|state| state.visible && !(unlocked_only && state.locked))
+            .map(|state| (state.path.as_slice(), state.opacity))
+            .collect();
+        shapes.sort_by_key(|(path, _)| *path);
+        Some(shapes)
+    }
+
+    /// Index of the curves of the shape that can be in the area, in order.
+    /// None when every curve can be
+    pub fn curves_candidates(&self, shape: LayerId, area: &Rect) -> Option<Vec<usize>> {
+        let curves = self.curves_of(shape)?;
+        let mut candidates = curves.query(area);
+        candidates.sort();
+        Some(candidates)
+    }
+
+    /// Tree of the curves of the shape, None if the shape changed since the last refresh
+    fn curves_of(&self, shape: LayerId) -> Option<&BoxTree<usize>> {
+        if !self.built || self.dirty.contains(&shape) {
+            return None;
+        }
+        self.curves.get(&shape)
+    }
+
+    fn update(&mut self, id: LayerId, shape: Option<&Shape>) {
+        if let Some(rect) = self.boxes.remove(&id) {
+            self.shapes.remove(&rect, id);
+            self.damage_add(rect);
+        }
+        self.curves.remove(&id);
+        let Some(shape) = shape else {
+            return;
+        };
+
+        if let Some(rect) = shape.bounding_box_with_stroke() {
+            self.shapes.insert(rect, id);
+            self.boxes.insert(id, rect);
+            self.damage_add(rect);
+        }
+        let mut curves = BoxTree::new();
+        for (index, curve) in shape.curves().enumerate() {
+            curves.insert(curve.quick_bounding_box(), index);
+        }
+        self.curves.insert(id, curves);
+    }
+
+    /// Add a damaged box, the boxes are dropped for the whole scene once over the limit,
+    /// so they don't pile up when nobody takes them
+    fn damage_add(&mut self, rect: Rect) {
+        if self.damage_all {
+            return;
+        }
+        match self.damage.len() < DAMAGE_LIMIT {
+            true => self.damage.push(rect),
+            false => {
+                self.damage.clear();
+                self.damage_all = true;
+            }
+        }
+    }
+}
+
+impl Scene {
+    /// Update the boxes of the layers changed since the last refresh, or of every shape the
+    /// first time. Called after each command, the queries stay right without it
+    pub(crate) fn index_refresh(&mut self) {
+        let mut index = std::mem::take(&mut self.index);
+        let ids: Vec<LayerId> = match index.built {
+            true => index.dirty.drain().collect(),
+            false => {
+                index = SpatialIndex {
+                    built: true,
+                    damage_all: true,
+                    ..SpatialIndex::default()
+                };
+                self.layers_iter()
+                    .filter_map(|layer| match &layer.value {
+                        LayerType::Shape(shape) => Some(shape.id),
+                        LayerType::Folder(_) => None,
+                    })
+                    .collect()
+            }
+        };
+        if !ids.is_empty() {
+            index.states.clear();
+            shape_states(&self.layers, &ShapeState::default(), &mut index.states);
+        }
+        for id in ids {
+            match self.layer_select(id) {
+                Some(LayerType::Shape(shape)) => index.update(id, Some(shape)),
+                // A changed folder changes the drawing of its shapes, their boxes are the same
+                Some(LayerType::Folder(_)) => {
+                    let shapes = self.layer_shapes(id);
+                    let boxes: Vec<Rect> = shapes
+                        .iter()
+                        .filter_map(|id| index.boxes.get(id))
+                        .copied()
+                        .collect();
+                    for rect in boxes {
+                        index.damage_add(rect);
+                    }
+                }
+                None => index.update(id, None),
+            }
+        }
+        self.index = index;
+    }
+
+    /// Same as `shapes_visible` without the shapes outside of the area.
+    /// Only the shapes near the area are visited when no layer changed since the last refresh
+    pub(crate) fn shapes_visible_in(&self, unlocked_only: bool, area: &Rect) -> Vec<(&Shape, f32)> {
+        match self.index.shapes_visible_candidates(unlocked_only, area) {
+            Some(candidates) => candidates
+                .into_iter()
+                .filter_map(|(path, opacity)| Some((self.shape_at_path(path)?, opacity)))
+                .collect(),
+            None => {
+                let mut shapes = self.shapes_visible(unlocked_only);
+                if let Some(candidates) = self.index.shapes_candidates(area) {
+                    shapes.retain(|(shape, _)| candidates.contains(&shape.id));
+                }
+                shapes
+            }
+        }
+    }
+
+    /// Shape at the path of its state in the index
+    fn shape_at_path(&self, path: &[usize]) -> Option<&Shape> {
+        let (last, folders) = path.split_last()?;
+        let mut layers = self.layers.as_slice();
+        for position in folders {
+            match &layers.get(*position)?.value {
+                LayerType::Folder(children) => layers = children,
+                LayerType::Shape(_) => return None,
+            }
+        }
+        match &layers.get(*last)?.value {
+            LayerType::Shape(shape) => Some(shape),
+            LayerType::Folder(_) => None,
+        }
+    }
+
+    /// Boxes of the scene where the drawing changed since the last take, from the layers
+    /// changed by the commands. None when the whole scene must be drawn again
+    pub(crate) fn dirty_regions_take(&mut self) -> Option<Vec<Rect>> {
+        let damage = std::mem::take(&mut self.index.damage);
+        match std::mem::take(&mut self.index.damage_all) {
+            true => None,
+            false => Some(damage),
+        }
+    }
+
+    /// Closest point of the shape to the coord, see `Shape::closest_curve`.
+    /// Only the curves near the coord are visited
+    pub fn shape_closest_curve(
+        &self,
+        id: LayerId,
+        coord: &Coord,
+    ) -> Option<(usize, f32, f32, Coord)> {
+        let shape = self.shape_select(id)?;
+        let Some(curves) = self.index.curves_of(id) else {
+            return Some(shape.closest_curve(coord));
+        };
+        let mut closest = (0, 0.0, f32::MAX, Coord::new(-1000.0, -1000.0));
+        curves.nearest(coord, |index| {
+            let curve = shape.curve_select(index).expect("Indexed curve");
+            let (t, distance, point) = curve.t_closest(coord);
+            if distance < closest.2 {
+                closest = (index, t, distance, point);
+            }
+            distance
+        });
+        Some(closest)
+    }
+
+    /// First coord of the shape in the radius of the coord, anchor or handle.
+    /// Only the coords of the curves near the coord are visited
+    pub fn shape_coord_near(
+        &self,
+        id: LayerId,
+        coord: &Coord,
+        radius: Length2d,
+    ) -> Option<&DbCoord> {
+        let shape = self.shape_select(id)?;
+        let area = Rect::new(
+            coord.x - radius.x,
+            coord.y - radius.y,
+            coord.x + radius.x,
+            coord.y + radius.y,
+        );
+        let in_radius = |db_coord: &&DbCoord| point_in_radius(*coord, db_coord.coord(), radius);
+        match self.index.curves_candidates(id, &area) {
+            // A subpath of a single anchor has no curve
+            Some(curves) if shape.paths().all(|path| path.len() > 1) => curves
+                .into_iter()
+                .filter_map(|index| shape.curve_select(index))
+                .flat_map(|curve| [curve.p0, curve.cp0, curve.cp1, curve.p1])
+                .find(in_radius),
+            _ => shape.paths().flatten().find(in_radius),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::{Affine, Vec2},
+        types::{Coord, Length2d, Rect},
+    };
+
+    use crate::{
+        commands::{
+            ChangeLayerLocked, ChangeLayerOpacity, ChangeLayerVisible, CommandsHandler, Group,
+            LayerDestination, MoveLayer, Pivot, TransformShapes,
+        },
+        DbCoord, LayerId, Scene, Shape,
+    };
+
+    use super::BoxTree;
+
+    fn grid_rect(i: usize) -> Rect {
+        let (x, y) = ((i % 30) as f32, (i / 30) as f32);
+        Rect::new(x, y, x + 0.5, y + 0.5)
+    }
+
+    #[test]
+    fn given_many_boxes_when_query_and_remove_then_same_as_linear_search() {
+        let mut tree = BoxTree::new();
+        for i in 0..300 {
+            tree.insert(grid_rect(i), i);
+        }
+        for i in (0..300).step_by(3) {
+            assert!(tree.remove(&grid_rect(i), i));
+        }
+        assert!(!tree.remove(&grid_rect(0), 0));
+
+        let area = Rect::new(4.2, 2.2, 7.8, 5.1);
+        let mut found = tree.query(&area);
+        found.sort();
+        let expected: Vec<usize> = (0..300)
+            .filter(|i| i % 3 != 0 && grid_rect(*i).intersect(&area))
+            .collect();
+        assert_eq!(found, expected);
+
+        let coord = Coord::new(12.8, 6.9);
+        let (nearest, _) = tree
+            .nearest(&coord, |i| grid_rect(i).center().distance(&coord))
+            .unwrap();
+        assert_eq!(nearest, 7 * 30 + 13);
+
+        for i in (0..300).filter(|i| i % 3 != 0) {
+            assert!(tree.remove(&grid_rect(i), i));
+        }
+        assert!(tree
+            .query(&Rect::new(-100.0, -100.0, 100.0, 100.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn given_moved_shape_when_hit_then_index_follows_commands() {
+        let mut scene = Scene::new();
+        let circles: Vec<LayerId> = (0..50)
+            .map(|i| {
+                let center = Coord::new(i as f32 * 0.1, 0.0);
+                scene.shape_insert(Shape::new_circle(center, Length2d::new(0.04, 0.04)))
+            })
+            .collect();
+        let id = scene.shape_insert(Shape::new_from_lines(
+            vec![
+                DbCoord::new(0.0, 1.0),
+                DbCoord::new(0.2, 1.0),
+                DbCoord::new(0.2, 1.2),
+            ],
+            Affine::identity(),
+        ));
+        let mut command_handler = CommandsHandler::from(scene);
+        command_handler
+            .execute(TransformShapes::boxed(
+                vec![id],
+                Affine::from_translate(Coord::new(1.0, 0.0)),
+                Pivot::Custom(Coord::new(0.0, 0.0)),
+                false,
+            ))
+            .unwrap();
+
+        let scene = command_handler.scene();
+        let inside = Coord::new(1.15, 1.05);
+        assert_eq!(scene.shape_select_hit(&inside, 0.0).unwrap().id, id);
+        assert!(scene
+            .shape_select_contains(&Coord::new(0.15, 1.05))
+            .is_none());
+        assert_eq!(
+            scene
+                .shape_select_contains(&Coord::new(0.3, 0.01))
+                .unwrap()
+                .id,
+            circles[3]
+        );
+        let (curve, _, distance, _) = scene
+            .shape_closest_curve(id, &Coord::new(1.1, 0.9))
+            .unwrap();
+        assert_eq!(curve, 0);
+        assert!((distance - 0.1).abs() < 0.001);
+
+        command_handler.undo().unwrap();
+        let scene = command_handler.scene();
+        assert!(scene.shape_select_hit(&inside, 0.0).is_none());
+        assert_eq!(
+            scene
+                .shape_select_contains(&Coord::new(0.15, 1.05))
+                .unwrap()
+                .id,
+            id
+        );
+    }
+
+    #[test]
+    fn given_shape_changed_without_refresh_when_hit_then_found() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            Length2d::new(0.1, 0.1),
+        ));
+        scene.index_refresh();
+
+        scene
+            .shape_select_mut(id)
+            .unwrap()
+            .transform(&Affine::from_translate(Coord::new(2.0, 0.0)));
+
+        assert!(scene
+            .shape_select_hit(&Coord::new(2.0, 0.01), 0.0)
+            .is_some());
+        assert!(scene
+            .shape_select_hit(&Coord::new(0.0, 0.01), 0.0)
+            .is_none());
+    }
+
+    #[test]
+    fn given_folder_changed_when_shapes_visible_in_then_same_as_tree() {
+        let mut scene = Scene::new();
+        let ids: Vec<LayerId> = (0..4)
+            .map(|i| {
+                let center = Coord::new(i as f32 * 0.1, 0.0);
+                scene.shape_insert(Shape::new_circle(center, Length2d::new(0.04, 0.04)))
+            })
+            .collect();
+        let mut command_handler = CommandsHandler::from(scene);
+        command_handler
+            .execute(Group::boxed(vec![ids[1], ids[2]]))
+            .unwrap();
+        let folder = command_handler.scene().layer_parent(ids[1]).unwrap();
+        command_handler
+            .execute(ChangeLayerOpacity::boxed(folder, 0.5))
+            .unwrap();
+        command_handler
+            .execute(ChangeLayerLocked::boxed(ids[2], true))
+            .unwrap();
+        command_handler
+            .execute(MoveLayer::boxed(ids[3], LayerDestination::Before(ids[0])))
+            .unwrap();
+
+        let area = Rect::new(-0.1, -0.1, 0.22, 0.1);
+        let visible_in = |scene: &Scene, unlocked_only: bool| -> Vec<(LayerId, f32)> {
+            scene
+                .shapes_visible_in(unlocked_only, &area)
+                .into_iter()
+                .map(|(shape, opacity)| (shape.id, opacity))
+                .collect()
+        };
+        let scene = command_handler.scene();
+        assert_eq!(
+            visible_in(scene, false),
+            vec![(ids[0], 1.0), (ids[1], 0.5), (ids[2], 0.5)]
+        );
+        assert_eq!(visible_in(scene, true), vec![(ids[0], 1.0), (ids[1], 0.5)]);
+        let area = Rect::new(-1.0, -1.0, 1.0, 1.0);
+        let all: Vec<(LayerId, f32)> = scene
+            .shapes_visible(false)
+            .into_iter()
+            .map(|(shape, opacity)| (shape.id, opacity))
+            .collect();
+        assert_eq!(all[0].0, ids[3]);
+        assert_eq!(
+            scene
+                .shapes_visible_in(false, &area)
+                .into_iter()
+                .map(|(shape, opacity)| (shape.id, opacity))
+                .collect::<Vec<_>>(),
+            all
+        );
+
+        command_handler
+            .execute(ChangeLayerVisible::boxed(folder, false))
+            .unwrap();
+        assert_eq!(
+            visible_in(command_handler.scene(), false),
+            vec![(ids[0], 1.0)]
+        );
+    }
+
+    #[test]
+    fn given_commands_when_take_dirty_regions_then_boxes_of_changed_shapes() {
+        let mut scene = Scene::new();
+        let radius = Length2d::new(0.1, 0.1);
+        let a = scene.shape_insert(Shape::new_circle(Coord::new(0.0, 0.0), radius));
+        let b = scene.shape_insert(Shape::new_circle(Coord::new(2.0, 0.0), radius));
+        let mut command_handler = CommandsHandler::from(scene);
+        assert_eq!(command_handler.dirty_regions_take(), None);
+        assert_eq!(command_handler.dirty_regions_take(), Some(vec![]));
+
+        command_handler
+            .execute(TransformShapes::boxed(
+                vec![a],
+                Affine::from_translate(Coord::new(0.5, 0.0)),
+                Pivot::Custom(Coord::new(0.0, 0.0)),
+                false,
+            ))
+            .unwrap();
+        let regions = command_handler.dirty_regions_take().unwrap();
+        let touched = |regions: &[Rect], c: Coord| {
+            regions
+                .iter()
+                .any(|r| r.intersect(&Rect::new(c.x, c.y, c.x, c.y)))
+        };
+        assert!(touched(&regions, Coord::new(0.0, 0.0)));
+        assert!(touched(&regions, Coord::new(0.5, 0.0)));
+        assert!(!touched(&regions, Coord::new(2.0, 0.0)));
+
+        command_handler.undo().unwrap();
+        let regions = command_handler.dirty_regions_take().unwrap();
+        assert!(touched(&regions, Coord::new(0.0, 0.0)));
+        assert!(touched(&regions, Coord::new(0.5, 0.0)));
+        command_handler.redo().unwrap();
+        assert!(!command_handler.dirty_regions_take().unwrap().is_empty());
+
+        command_handler.execute(Group::boxed(vec![b])).unwrap();
+        command_handler.dirty_regions_take();
+        let folder = command_handler.scene().layer_parent(b).unwrap();
+        command_handler
+            .execute(ChangeLayerVisible::boxed(folder, false))
+            .unwrap();
+        let regions = command_handler.dirty_regions_take().unwrap();
+        let bounds = command_handler
+            .scene()
+            .shape_select(b)
+            .unwrap()
+            .bounding_box_with_stroke();
+        assert_eq!(regions, bounds.into_iter().collect::<Vec<Rect>>());
+    }
+
+    #[test]
+    fn given_damage_not_taken_when_many_commands_then_whole_scene_damaged() {
+        let mut scene = Scene::new();
+        let id = scene.shape_insert(Shape::new_circle(
+            Coord::new(0.0, 0.0),
+            Length2d::new(0.1, 0.1),
+        ));
+        let mut command_handler = CommandsHandler::from(scene);
+        command_handler.dirty_regions_take();
+
+        for _ in 0..super::DAMAGE_LIMIT {
+            command_handler
+                .execute(ChangeLayerVisible::boxed(id, false))
+                .unwrap();
+        }
+
+        assert_eq!(command_handler.dirty_regions_take(), None);
+        assert_eq!(command_handler.dirty_regions_take(), Some(vec![]));
+    }
+}
@@ -0,0 +1,511 @@
+use std::fmt::Write;
+
+use common::{pures::Affine, types::Rect, Rgba};
+
+use crate::{
+    scene::{
+        shape::{curve::path_encloses, paint::gradient_transform, paths_to_string, Stroke},
+        Layer, LayerType, Scene,
+    },
+    DbCoord, FillRule, GradientStop, LineCap, LineJoin, Paint, Shape, StrokeAlign,
+};
+
+#[derive(Debug, Clone)]
+pub struct SvgExportOption {
+    /// Rectangle of the SVG document where the scene range -1.0 to 1.0 is mapped
+    pub view_box: Rect,
+}
+
+impl Default for SvgExportOption {
+    fn default() -> Self {
+        Self {
+            view_box: Rect::new(0.0, 0.0, 1000.0, 1000.0),
+        }
+    }
+}
+
+impl SvgExportOption {
+    pub fn new(width: f32, height: f32) -> Self {
+        Self {
+            view_box: Rect::new(0.0, 0.0, width, height),
+        }
+    }
+
+    /// Transform from the scene coordinates to the viewBox coordinates
+    pub fn transform(&self) -> Affine {
+        self.view_box.affine_to_normal().inverse()
+    }
+}
+
+impl Scene {
+    /// Serialize the scene in a SVG document
+    /// The visible layers are written in paint order, the background first and the foreground last
+    pub fn to_svg(&self, option: &SvgExportOption) -> String {
+        let transform = option.transform();
+        let view_box = option.view_box;
+
+        let mut svg = String::new();
+        writeln!(
+            &mut svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">"#,
+            view_box.top_left.x,
+            view_box.top_left.y,
+            view_box.width(),
+            view_box.height(),
+            view_box.width(),
+            view_box.height()
+        )
+        .expect("Write should be ok");
+
+        if self.background.a != 0 {
+            writeln!(
+                &mut svg,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                view_box.top_left.x,
+                view_box.top_left.y,
+                view_box.width(),
+                view_box.height(),
+                color_attributes("fill", &self.background)
+            )
+            .expect("Write should be ok");
+        }
+
+        layers_to_svg(&mut svg, &self.layers, &transform, &view_box);
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Write the visible layers from the background. The opacity of a shape is applied to its paints,
+/// a folder with an opacity is a group to be drawn like in the render
+fn layers_to_svg(svg: &mut String, layers: &[Layer], transform: &Affine, view_box: &Rect) {
+    for layer in layers.iter().rev() {
+        if !layer.visible {
+            continue;
+        }
+        match &layer.value {
+            LayerType::Shape(shape) if shape.is_empty() => {}
+            LayerType::Shape(shape) => {
+                let shape = match layer.opacity < 1.0 {
+                    true => &shape.with_opacity(layer.opacity),
+                    false => shape,
+                };
+                svg.push_str(&shape_to_svg(shape, transform, view_box));
+                svg.push('\n');
+            }
+            LayerType::Folder(children) if layer.opacity < 1.0 => {
+                writeln!(svg, r#"<g opacity="{}">"#, layer.opacity).expect("Write should be ok");
+                layers_to_svg(svg, children, transform, view_box);
+                svg.push_str("</g>\n");
+            }
+            LayerType::Folder(children) => layers_to_svg(svg, children, transform, view_box),
+        }
+    }
+}
+
+fn shape_to_svg(shape: &Shape, transform: &Affine, view_box: &Rect) -> String {
+    let mut defs = SvgDefs {
+        shape,
+        transform,
+        defs: String::new(),
+    };
+    let stroke = |defs: &mut SvgDefs, size: f32| {
+        if size == 0.0 {
+            r#"stroke="none""#.to_string()
+        } else {
+            format!(
+                r#"{} stroke-width="{}"{}"#,
+                defs.paint_attributes("stroke", &shape.stroke.paint),
+                transform.scale_length(size),
+                stroke_style_attributes(&shape.stroke, transform)
+            )
+        }
+    };
+
+    let fill_rule = match shape.fill_rule {
+        FillRule::NonZero => "nonzero",
+        FillRule::EvenOdd => "evenodd",
+    };
+
+    // The open subpaths are only stroked, SVG would fill them
+    let (closed, open): (Vec<&Vec<DbCoord>>, Vec<&Vec<DbCoord>>) = shape
+        .paths()
+        .filter(|path| !path.is_empty())
+        .partition(|path| path_encloses(path));
+
+    let mut elements = Vec::new();
+    if !closed.is_empty() {
+        let d = paths_to_string(closed.into_iter(), transform);
+        let fill = defs.paint_attributes("fill", &shape.fill);
+        let aligned = shape.stroke.size != 0.0 && shape.stroke.align != StrokeAlign::Center;
+        if aligned {
+            // SVG has no stroke alignment, a stroke of twice the size is clipped by the path
+            elements.push(format!(
+                r#"<path d="{}" fill-rule="{}" {} stroke="none"/>"#,
+                d, fill_rule, fill
+            ));
+            let clip = defs.clip_attribute(&d, fill_rule, view_box);
+            elements.push(format!(
+                r#"<path d="{}" fill="none" {} {}/>"#,
+                d,
+                stroke(&mut defs, shape.stroke.size * 2.0),
+                clip
+            ));
+        } else {
+            elements.push(format!(
+                r#"<path d="{}" fill-rule="{}" {} {}/>"#,
+                d,
+                fill_rule,
+                fill,
+                stroke(&mut defs, shape.stroke.size)
+            ));
+        }
+    }
+    if !open.is_empty() {
+        elements.push(format!(
+            r#"<path d="{}" fill="none" {}/>"#,
+            paths_to_string(open.into_iter(), transform),
+            stroke(&mut defs, shape.stroke.size)
+        ));
+    }
+    if !defs.defs.is_empty() {
+        elements.insert(0, format!("<defs>{}</defs>", defs.defs));
+    }
+    elements.join("\n")
+}
+
+/// Attributes of the stroke that are not the default of SVG, each with a leading space
+fn stroke_style_attributes(stroke: &Stroke, transform: &Affine) -> String {
+    let mut attributes = String::new();
+    let join = match stroke.join {
+        LineJoin::Miter => None,
+        LineJoin::Round => Some("round"),
+        LineJoin::Bevel => Some("bevel"),
+    };
+    if let Some(join) = join {
+        write!(&mut attributes, r#" stroke-linejoin="{}""#, join).expect("Write should be ok");
+    }
+    let cap = match stroke.cap {
+        LineCap::Butt => None,
+        LineCap::Round => Some("round"),
+        LineCap::Square => Some("square"),
+    };
+    if let Some(cap) = cap {
+        write!(&mut attributes, r#" stroke-linecap="{}""#, cap).expect("Write should be ok");
+    }
+    if stroke.miter_limit != 4.0 {
+        write!(
+            &mut attributes,
+            r#" stroke-miterlimit="{}""#,
+            stroke.miter_limit
+        )
+        .expect("Write should be ok");
+    }
+    if !stroke.dashes.is_empty() {
+        let dashes: Vec<String> = stroke
+            .dashes
+            .iter()
+            .map(|dash| transform.scale_length(*dash).to_string())
+            .collect();
+        write!(
+            &mut attributes,
+            r#" stroke-dasharray="{}""#,
+            dashes.join(",")
+        )
+        .expect("Write should be ok");
+    }
+    if stroke.dash_offset != 0.0 {
+        write!(
+            &mut attributes,
+            r#" stroke-dashoffset="{}""#,
+            transform.scale_length(stroke.dash_offset)
+        )
+        .expect("Write should be ok");
+    }
+    attributes
+}
+
+/// Definitions of a shape, the gradients and the clips, written before its paths
+struct SvgDefs<'a> {
+    shape: &'a Shape,
+    transform: &'a Affine,
+    defs: String,
+}
+
+impl SvgDefs<'_> {
+    /// Attributes of a fill or a stroke, a gradient is added to the defs and referenced by id.
+    /// The gradients are written in the coordinates of the document to be the same as the render.
+    fn paint_attributes(&mut self, name: &str, paint: &Paint) -> String {
+        let id = format!("shape{}-{}", self.shape.id.value(), name);
+        let to_svg = |units| gradient_transform(self.transform, units, self.shape.bounding_box());
+        match paint {
+            Paint::Solid(color) => return color_attributes(name, color),
+            Paint::LinearGradient(gradient) => {
+                let to_svg = to_svg(gradient.units);
+                let start = to_svg * gradient.start;
+                let end = to_svg * gradient.end;
+                write!(
+                    &mut self.defs,
+                    r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}">{}</linearGradient>"#,
+                    id,
+                    start.x,
+                    start.y,
+                    end.x,
+                    end.y,
+                    stops_to_svg(&gradient.stops)
+                )
+            }
+            Paint::RadialGradient(gradient) => {
+                let to_svg = to_svg(gradient.units);
+                let center = to_svg * gradient.center;
+                write!(
+                    &mut self.defs,
+                    r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}">{}</radialGradient>"#,
+                    id,
+                    center.x,
+                    center.y,
+                    gradient.radius * to_svg.determinant().abs().sqrt(),
+                    stops_to_svg(&gradient.stops)
+                )
+            }
+        }
+        .expect("Write should be ok");
+        format!(r#"{}="url(#{})""#, name, id)
+    }
+
+    /// Clip to the inside of the path, or a mask for the outside, for an aligned stroke
+    fn clip_attribute(&mut self, d: &str, fill_rule: &str, view_box: &Rect) -> String {
+        let id = self.shape.id.value();
+        match self.shape.stroke.align {
+            StrokeAlign::Outside => {
+                write!(
+                    &mut self.defs,
+                    r#"<mask id="shape{}-mask" maskUnits="userSpaceOnUse" x="{}" y="{}" width="{}" height="{}"><rect x="{}" y="{}" width="{}" height="{}" fill="white"/><path d="{}" fill-rule="{}" fill="black"/></mask>"#,
+                    id,
+                    view_box.top_left.x,
+                    view_box.top_left.y,
+                    view_box.width(),
+                    view_box.height(),
+                    view_box.top_left.x,
+                    view_box.top_left.y,
+                    view_box.width(),
+                    view_box.height(),
+                    d,
+                    fill_rule
+                )
+                .expect("Write should be ok");
+                format!(r#"mask="url(#shape{}-mask)""#, id)
+            }
+            _ => {
+                write!(
+                    &mut self.defs,
+                    r#"<clipPath id="shape{}-clip"><path d="{}" clip-rule="{}"/></clipPath>"#,
+                    id, d, fill_rule
+                )
+                .expect("Write should be ok");
+                format!(r#"clip-path="url(#shape{}-clip)""#, id)
+            }
+        }
+    }
+}
+
+fn stops_to_svg(stops: &[GradientStop]) -> String {
+    let mut svg = String::new();
+    for stop in stops {
+        write!(
+            &mut svg,
+            r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+            stop.offset,
+            stop.color.to_small_hex_string(),
+            stop.color.a as f32 / 255.0
+        )
+        .expect("Write should be ok");
+    }
+    svg
+}
+
+/// Color and opacity attributes of a fill or a stroke
+fn color_attributes(name: &str, color: &Rgba) -> String {
+    if color.a == 255 {
+        format!(r#"{}="{}""#, name, color.to_small_hex_string())
+    } else {
+        format!(
+            r#"{}="{}" {}-opacity="{}""#,
+            name,
+            color.to_small_hex_string(),
+            name,
+            color.a as f32 / 255.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::Affine,
+        types::{Coord, Length2d},
+        Rgba,
+    };
+
+    use crate::{
+        commands::{ChangeLayerOpacity, CommandsHandler, Group, InsertShape},
+        scene::shape::Stroke,
+        DbCoord, GradientStop, GradientUnits, LayerId, LineJoin, Paint, RadialGradient, Scene,
+        Shape, StrokeAlign,
+    };
+
+    use super::SvgExportOption;
+
+    #[test]
+    fn given_shape_when_to_svg_then_path_in_view_box() {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(-1.0, -1.0),
+                DbCoord::new(1.0, -1.0),
+                DbCoord::new(1.0, 1.0),
+            ],
+            Affine::identity(),
+        );
+        shape.fill = Rgba::new(255, 0, 0, 255).into();
+        shape.stroke = Stroke::new(0.02, Rgba::new(0, 0, 255, 128));
+        scene.shape_insert(shape);
+
+        let svg = scene.to_svg(&SvgExportOption::new(200.0, 100.0));
+
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 200 100""#));
+        assert!(svg.contains(r#"d="M 0 0 C 0 0 200 0 200 0 C 200 0 200 100 200 100 "#));
+        assert!(svg.contains(r##"fill="#ff0000""##));
+        assert!(svg.contains(r##"stroke="#0000ff" stroke-opacity="0.5019608" stroke-width="2""##));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn given_open_path_when_to_svg_then_not_filled_and_not_closed() {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new_polyline(
+            vec![DbCoord::new(-1.0, -1.0), DbCoord::new(1.0, 1.0)],
+            Affine::identity(),
+        );
+        shape.fill = Rgba::new(255, 0, 0, 255).into();
+        shape.stroke = Stroke::new(0.02, Rgba::black());
+        scene.shape_insert(shape);
+
+        let svg = scene.to_svg(&SvgExportOption::new(200.0, 100.0));
+
+        assert!(svg.contains(r#"<path d="M 0 0 C 0 0 200 100 200 100 " fill="none""#));
+        assert!(!svg.contains("Z"));
+    }
+
+    #[test]
+    fn given_radial_gradient_when_to_svg_then_gradient_in_defs() {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(-0.5, -0.5),
+                DbCoord::new(0.5, -0.5),
+                DbCoord::new(0.5, 0.5),
+                DbCoord::new(-0.5, 0.5),
+            ],
+            Affine::identity(),
+        );
+        shape.fill = Paint::RadialGradient(RadialGradient {
+            center: Coord::new(0.5, 0.5),
+            radius: 0.5,
+            stops: vec![
+                GradientStop::new(0.0, Rgba::red()),
+                GradientStop::new(1.0, Rgba::new(0, 0, 255, 0)),
+            ],
+            units: GradientUnits::Shape,
+        });
+        let id = scene.shape_insert(shape);
+
+        let svg = scene.to_svg(&SvgExportOption::new(200.0, 200.0));
+
+        let gradient_id = format!("shape{}-fill", id.value());
+        assert!(svg.contains(&format!(
+            r#"<defs><radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="100" cy="100" r="50">"#,
+            gradient_id
+        )));
+        assert!(svg.contains(r##"<stop offset="1" stop-color="#0000ff" stop-opacity="0"/>"##));
+        assert!(svg.contains(&format!(r#"fill="url(#{})""#, gradient_id)));
+    }
+
+    #[test]
+    fn given_styled_inside_stroke_when_to_svg_then_attributes_and_clip() {
+        let mut scene = Scene::new();
+        let mut shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(-0.5, -0.5),
+                DbCoord::new(0.5, -0.5),
+                DbCoord::new(0.5, 0.5),
+            ],
+            Affine::identity(),
+        );
+        shape.stroke = Stroke::new(0.02, Rgba::black());
+        shape.stroke.join = LineJoin::Round;
+        shape.stroke.dashes = vec![0.04, 0.02];
+        shape.stroke.align = StrokeAlign::Inside;
+        let id = scene.shape_insert(shape);
+
+        let svg = scene.to_svg(&SvgExportOption::new(200.0, 200.0));
+
+        assert!(svg.contains(&format!(r#"<clipPath id="shape{}-clip">"#, id.value())));
+        assert!(svg.contains(r#"stroke-width="4" stroke-linejoin="round" stroke-dasharray="4,2""#));
+        assert!(svg.contains(&format!(r#"clip-path="url(#shape{}-clip)""#, id.value())));
+        assert!(svg.contains(r#"stroke="none""#));
+        assert!(!svg.contains("stroke-linecap"));
+    }
+
+    #[test]
+    fn given_layers_when_to_svg_then_foreground_last() {
+        let mut scene = Scene::new();
+        let mut background = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.5, 0.5));
+        background.fill = Rgba::red().into();
+        scene.shape_insert(background);
+        let mut foreground = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.2, 0.2));
+        foreground.fill = Rgba::blue().into();
+        let id = scene.shape_insert(foreground);
+        scene.layer_move_top(id);
+
+        let svg = scene.to_svg(&SvgExportOption::default());
+
+        let red = svg.find(r##"fill="#ff0000""##).expect("Red shape exported");
+        let blue = svg
+            .find(r##"fill="#0000ff""##)
+            .expect("Blue shape exported");
+        assert!(red < blue);
+        assert!(svg.contains(r#"stroke="none""#));
+    }
+
+    #[test]
+    fn given_half_opaque_folder_when_to_svg_then_group_with_opacity() {
+        let mut command_handler = CommandsHandler::from(Scene::new());
+        for radius in [0.5, 0.2] {
+            let mut shape = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(radius, radius));
+            shape.fill = Rgba::red().into();
+            command_handler.execute(InsertShape::boxed(shape)).unwrap();
+        }
+        let shapes = command_handler
+            .scene()
+            .get_tree_view()
+            .iter()
+            .map(|layer| LayerId::from(layer.layer_id))
+            .collect();
+        command_handler.execute(Group::boxed(shapes)).unwrap();
+        let folder = LayerId::from(command_handler.scene().get_tree_view()[0].layer_id);
+        command_handler
+            .execute(ChangeLayerOpacity::boxed(folder, 0.5))
+            .unwrap();
+
+        let svg = command_handler.scene().to_svg(&SvgExportOption::default());
+
+        let group = svg.find(r#"<g opacity="0.5">"#).expect("Group exported");
+        let end = svg.find("</g>").expect("Group closed");
+        let shapes: Vec<usize> = svg.match_indices("<path").map(|(i, _)| i).collect();
+        assert_eq!(shapes.len(), 2);
+        assert!(shapes.iter().all(|i| group < *i && *i < end));
+        assert!(!svg.contains(r#"fill-opacity="0.5""#));
+    }
+}
@@ -0,0 +1,997 @@
+use std::fmt::{Display, Write};
+
+use common::{
+    pures::{Affine, Vec2},
+    types::{Coord, Rect},
+    Rgba,
+};
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::{scene::shape::Stroke, DbCoord, FillRule, LineCap, LineJoin, Shape};
+
+use super::path_data::{parse_path_data, Parser, Segment, SubPath};
+
+/// Something in the SVG document that could not be converted exactly.
+/// The import continues, the element is skipped or approximated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgImportWarning {
+    /// The element is not supported and was skipped
+    UnsupportedElement { element: String },
+    /// The value of the attribute could not be parsed, the default value is used
+    InvalidAttribute {
+        element: String,
+        attribute: String,
+        value: String,
+    },
+    /// The path data has an error, the path is imported up to the error
+    InvalidPathData { element: String, message: String },
+    /// Paint servers like gradient and pattern are not supported, the fallback color is used
+    UnsupportedPaint { element: String, value: String },
+    /// Open subpaths are only stroked, the fill of an open subpath is ignored
+    OpenPathNotFilled { element: String },
+    /// The element has no geometry to import
+    EmptyGeometry { element: String },
+}
+
+impl Display for SvgImportWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgImportWarning::UnsupportedElement { element } => {
+                write!(f, "{}: element not supported, skipped", element)
+            }
+            SvgImportWarning::InvalidAttribute {
+                element,
+                attribute,
+                value,
+            } => write!(
+                f,
+                "{}: invalid value '{}' for attribute '{}', default used",
+                element, value, attribute
+            ),
+            SvgImportWarning::InvalidPathData { element, message } => {
+                write!(f, "{}: invalid path data, {}", element, message)
+            }
+            SvgImportWarning::UnsupportedPaint { element, value } => {
+                write!(f, "{}: paint '{}' not supported", element, value)
+            }
+            SvgImportWarning::OpenPathNotFilled { element } => {
+                write!(f, "{}: open path not filled", element)
+            }
+            SvgImportWarning::EmptyGeometry { element } => {
+                write!(f, "{}: no geometry, skipped", element)
+            }
+        }
+    }
+}
+
+/// The SVG document could not be imported at all
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgImportError {
+    /// The text is not a valid XML document
+    Xml(String),
+    /// The root element is not `<svg>`
+    NotSvg(String),
+}
+
+impl Display for SvgImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SvgImportError::Xml(message) => write!(f, "Invalid XML: {}", message),
+            SvgImportError::NotSvg(root) => write!(f, "Root element <{}> is not <svg>", root),
+        }
+    }
+}
+
+impl std::error::Error for SvgImportError {}
+
+#[derive(Debug)]
+pub struct SvgImport {
+    /// Shapes in paint order, the first one is the background
+    pub shapes: Vec<Shape>,
+    pub warnings: Vec<SvgImportWarning>,
+    /// Rectangle of the SVG document mapped to the scene range -1.0 to 1.0
+    pub view_box: Rect,
+}
+
+/// Parse a SVG document and convert it to shapes in the scene range.
+pub fn parse_svg(text: &str) -> Result<SvgImport, SvgImportError> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document = Document::parse_with_options(text, options)
+        .map_err(|e| SvgImportError::Xml(e.to_string()))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "svg" {
+        return Err(SvgImportError::NotSvg(root.tag_name().name().to_string()));
+    }
+
+    let mut importer = Importer::default();
+    importer.visit_children(root, &Affine::identity(), &Style::default());
+
+    let view_box = document_rect(root, &mut importer.warnings)
+        .or_else(|| importer.content_bounds())
+        .unwrap_or(Rect::new(-1.0, -1.0, 1.0, 1.0));
+    let normalize = view_box.affine_to_normal();
+
+    let shapes = importer
+        .shapes
+        .into_iter()
+        .map(|mut shape| {
+            for path in shape.paths_mut() {
+                *path = path.iter().map(|c| c.transform(&normalize)).collect();
+            }
+            let stroke = &mut shape.stroke;
+            stroke.size = normalize.scale_length(stroke.size);
+            for dash in stroke.dashes.iter_mut() {
+                *dash = normalize.scale_length(*dash);
+            }
+            stroke.dash_offset = normalize.scale_length(stroke.dash_offset);
+            shape
+        })
+        .collect();
+
+    Ok(SvgImport {
+        shapes,
+        warnings: importer.warnings,
+        view_box,
+    })
+}
+
+#[derive(Debug, Clone)]
+struct Style {
+    fill: Option<Rgba>,
+    stroke: Option<Rgba>,
+    stroke_width: f32,
+    fill_opacity: f32,
+    stroke_opacity: f32,
+    /// Product of the opacity of the element and its ancestors
+    opacity: f32,
+    fill_rule: FillRule,
+    line_join: LineJoin,
+    line_cap: LineCap,
+    miter_limit: f32,
+    dashes: Vec<f32>,
+    dash_offset: f32,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            fill: Some(Rgba::black()),
+            stroke: None,
+            stroke_width: 1.0,
+            fill_opacity: 1.0,
+            stroke_opacity: 1.0,
+            opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            line_join: LineJoin::Miter,
+            line_cap: LineCap::Butt,
+            miter_limit: 4.0,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Importer {
+    /// Shapes in the user space of the document
+    shapes: Vec<Shape>,
+    warnings: Vec<SvgImportWarning>,
+}
+
+impl Importer {
+    fn visit_children(&mut self, node: Node, transform: &Affine, style: &Style) {
+        for child in node.children().filter(|n| n.is_element()) {
+            self.visit(child, transform, style);
+        }
+    }
+
+    fn visit(&mut self, node: Node, parent_transform: &Affine, parent_style: &Style) {
+        let name = node.tag_name().name();
+        if matches!(
+            name,
+            "defs"
+                | "title"
+                | "desc"
+                | "metadata"
+                | "style"
+                | "script"
+                | "linearGradient"
+                | "radialGradient"
+                | "pattern"
+                | "clipPath"
+                | "mask"
+                | "marker"
+                | "symbol"
+        ) {
+            return;
+        }
+
+        if attribute(node, "display") == Some("none")
+            || attribute(node, "visibility") == Some("hidden")
+        {
+            return;
+        }
+
+        let label = element_label(node);
+        let transform = match node.attribute("transform") {
+            Some(value) => match parse_transform(value) {
+                Some(local) => *parent_transform * local,
+                None => {
+                    self.warnings.push(SvgImportWarning::InvalidAttribute {
+                        element: label.clone(),
+                        attribute: "transform".to_string(),
+                        value: value.to_string(),
+                    });
+                    *parent_transform
+                }
+            },
+            None => *parent_transform,
+        };
+        let style = self.style_of(node, &label, parent_style);
+
+        let path_data = match name {
+            "svg" | "g" | "a" | "switch" => {
+                self.visit_children(node, &transform, &style);
+                return;
+            }
+            "path" => node.attribute("d").unwrap_or_default().to_string(),
+            "rect" => self.rect_path(node, &label),
+            "circle" => {
+                let cx = self.length(node, &label, "cx");
+                let cy = self.length(node, &label, "cy");
+                let r = self.length(node, &label, "r");
+                ellipse_path(cx, cy, r, r)
+            }
+            "ellipse" => {
+                let cx = self.length(node, &label, "cx");
+                let cy = self.length(node, &label, "cy");
+                let rx = self.length(node, &label, "rx");
+                let ry = self.length(node, &label, "ry");
+                ellipse_path(cx, cy, rx, ry)
+            }
+            "line" => format!(
+                "M {} {} L {} {}",
+                self.length(node, &label, "x1"),
+                self.length(node, &label, "y1"),
+                self.length(node, &label, "x2"),
+                self.length(node, &label, "y2")
+            ),
+            "polygon" => format!("M {} Z", node.attribute("points").unwrap_or_default()),
+            "polyline" => format!("M {}", node.attribute("points").unwrap_or_default()),
+            _ => {
+                self.warnings
+                    .push(SvgImportWarning::UnsupportedElement { element: label });
+                return;
+            }
+        };
+
+        let data = parse_path_data(&path_data);
+        if let Some(message) = data.error {
+            self.warnings.push(SvgImportWarning::InvalidPathData {
+                element: label.clone(),
+                message,
+            });
+        }
+        if data.subpaths.is_empty() {
+            self.warnings
+                .push(SvgImportWarning::EmptyGeometry { element: label });
+            return;
+        }
+        let color = apply_opacity(&style.fill, style.fill_opacity * style.opacity);
+        // A line is never filled in SVG
+        let filled = color.a != 0 && name != "line";
+        if filled && data.subpaths.iter().any(|s| s.is_open()) {
+            self.warnings.push(SvgImportWarning::OpenPathNotFilled {
+                element: label.clone(),
+            });
+        }
+
+        let mut subpaths = data
+            .subpaths
+            .iter()
+            .map(|subpath| Shape::new_from_path(subpath_to_coords(subpath), transform).path);
+        let mut shape = Shape::new();
+        shape.path = subpaths.next().unwrap_or_default();
+        shape.subpaths = subpaths.collect();
+        shape.fill_rule = style.fill_rule;
+        shape.fill = color.into();
+        shape.stroke = match &style.stroke {
+            Some(color) if style.stroke_width > 0.0 => {
+                let mut stroke = Stroke::new(
+                    transform.scale_length(style.stroke_width),
+                    apply_opacity(&Some(color.clone()), style.stroke_opacity * style.opacity),
+                );
+                stroke.join = style.line_join;
+                stroke.cap = style.line_cap;
+                stroke.miter_limit = style.miter_limit;
+                stroke.dashes = style
+                    .dashes
+                    .iter()
+                    .map(|dash| transform.scale_length(*dash))
+                    .collect();
+                stroke.dash_offset = transform.scale_length(style.dash_offset);
+                stroke
+            }
+            _ => Stroke::new(0.0, Rgba::black()),
+        };
+        self.shapes.push(shape);
+    }
+
+    fn style_of(&mut self, node: Node, label: &str, parent: &Style) -> Style {
+        let mut style = parent.clone();
+
+        let mut properties: Vec<(String, String)> = Vec::new();
+        for name in [
+            "fill",
+            "stroke",
+            "stroke-width",
+            "fill-opacity",
+            "stroke-opacity",
+            "opacity",
+            "fill-rule",
+            "stroke-linejoin",
+            "stroke-linecap",
+            "stroke-miterlimit",
+            "stroke-dasharray",
+            "stroke-dashoffset",
+        ] {
+            if let Some(value) = node.attribute(name) {
+                properties.push((name.to_string(), value.to_string()));
+            }
+        }
+        // The style attribute has priority over the presentation attributes
+        if let Some(declarations) = node.attribute("style") {
+            for declaration in declarations.split(';') {
+                if let Some((name, value)) = declaration.split_once(':') {
+                    properties.push((name.trim().to_string(), value.trim().to_string()));
+                }
+            }
+        }
+
+        for (name, value) in properties {
+            let value = value.trim();
+            match name.as_str() {
+                "fill" => style.fill = self.paint(label, &name, value, parent.fill.clone()),
+                "stroke" => style.stroke = self.paint(label, &name, value, parent.stroke.clone()),
+                "stroke-width" => {
+                    if let Some(width) = self.number(label, &name, value) {
+                        style.stroke_width = width;
+                    }
+                }
+                "fill-opacity" => {
+                    if let Some(opacity) = self.number(label, &name, value) {
+                        style.fill_opacity = opacity.clamp(0.0, 1.0);
+                    }
+                }
+                "stroke-opacity" => {
+                    if let Some(opacity) = self.number(label, &name, value) {
+                        style.stroke_opacity = opacity.clamp(0.0, 1.0);
+                    }
+                }
+                "opacity" => {
+                    if let Some(opacity) = self.number(label, &name, value) {
+                        style.opacity = parent.opacity * opacity.clamp(0.0, 1.0);
+                    }
+                }
+                "fill-rule" => match value {
+                    "nonzero" => style.fill_rule = FillRule::NonZero,
+                    "evenodd" => style.fill_rule = FillRule::EvenOdd,
+                    "inherit" => {}
+                    _ => self.warnings.push(SvgImportWarning::InvalidAttribute {
+                        element: label.to_string(),
+                        attribute: name.clone(),
+                        value: value.to_string(),
+                    }),
+                },
+                "stroke-linejoin" => match value {
+                    "miter" | "miter-clip" | "arcs" => style.line_join = LineJoin::Miter,
+                    "round" => style.line_join = LineJoin::Round,
+                    "bevel" => style.line_join = LineJoin::Bevel,
+                    "inherit" => {}
+                    _ => self.warnings.push(SvgImportWarning::InvalidAttribute {
+                        element: label.to_string(),
+                        attribute: name.clone(),
+                        value: value.to_string(),
+                    }),
+                },
+                "stroke-linecap" => match value {
+                    "butt" => style.line_cap = LineCap::Butt,
+                    "round" => style.line_cap = LineCap::Round,
+                    "square" => style.line_cap = LineCap::Square,
+                    "inherit" => {}
+                    _ => self.warnings.push(SvgImportWarning::InvalidAttribute {
+                        element: label.to_string(),
+                        attribute: name.clone(),
+                        value: value.to_string(),
+                    }),
+                },
+                "stroke-miterlimit" => {
+                    if let Some(limit) = self.number(label, &name, value) {
+                        style.miter_limit = limit.max(1.0);
+                    }
+                }
+                "stroke-dasharray" => match value {
+                    "none" => style.dashes.clear(),
+                    "inherit" => {}
+                    _ => {
+                        let dashes: Option<Vec<f32>> = value
+                            .split(|c: char| c == ',' || c.is_whitespace())
+                            .filter(|dash| !dash.is_empty())
+                            .map(|dash| parse_length(dash).filter(|dash| *dash >= 0.0))
+                            .collect();
+                        match dashes {
+                            // A pattern of zero length is a solid line
+                            Some(dashes) if dashes.iter().sum::<f32>() > 0.0 => {
+                                style.dashes = dashes
+                            }
+                            Some(_) => style.dashes.clear(),
+                            None => self.warnings.push(SvgImportWarning::InvalidAttribute {
+                                element: label.to_string(),
+                                attribute: name.clone(),
+                                value: value.to_string(),
+                            }),
+                        }
+                    }
+                },
+                "stroke-dashoffset" => {
+                    if let Some(offset) = self.number(label, &name, value) {
+                        style.dash_offset = offset;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        style
+    }
+
+    fn paint(
+        &mut self,
+        label: &str,
+        attribute: &str,
+        value: &str,
+        inherited: Option<Rgba>,
+    ) -> Option<Rgba> {
+        if value == "inherit" {
+            return inherited;
+        }
+        if let Some(rest) = value.strip_prefix("url(") {
+            self.warnings.push(SvgImportWarning::UnsupportedPaint {
+                element: label.to_string(),
+                value: value.to_string(),
+            });
+            let fallback = rest.split_once(')').map(|(_, f)| f.trim()).unwrap_or("");
+            return parse_color(fallback).unwrap_or(None);
+        }
+        match parse_color(value) {
+            Some(color) => color,
+            None => {
+                self.warnings.push(SvgImportWarning::InvalidAttribute {
+                    element: label.to_string(),
+                    attribute: attribute.to_string(),
+                    value: value.to_string(),
+                });
+                inherited
+            }
+        }
+    }
+
+    fn number(&mut self, label: &str, attribute: &str, value: &str) -> Option<f32> {
+        let number = parse_length(value);
+        if number.is_none() {
+            self.warnings.push(SvgImportWarning::InvalidAttribute {
+                element: label.to_string(),
+                attribute: attribute.to_string(),
+                value: value.to_string(),
+            });
+        }
+        number
+    }
+
+    fn length(&mut self, node: Node, label: &str, attribute: &str) -> f32 {
+        match node.attribute(attribute) {
+            Some(value) => self.number(label, attribute, value).unwrap_or(0.0),
+            None => 0.0,
+        }
+    }
+
+    fn rect_path(&mut self, node: Node, label: &str) -> String {
+        let x = self.length(node, label, "x");
+        let y = self.length(node, label, "y");
+        let width = self.length(node, label, "width");
+        let height = self.length(node, label, "height");
+        if width <= 0.0 || height <= 0.0 {
+            return String::new();
+        }
+
+        let rx = node.attribute("rx").map(|_| self.length(node, label, "rx"));
+        let ry = node.attribute("ry").map(|_| self.length(node, label, "ry"));
+        let (rx, ry) = match (rx, ry) {
+            (Some(rx), Some(ry)) => (rx, ry),
+            (Some(r), None) | (None, Some(r)) => (r, r),
+            (None, None) => (0.0, 0.0),
+        };
+        let rx = rx.clamp(0.0, width / 2.0);
+        let ry = ry.clamp(0.0, height / 2.0);
+
+        if rx == 0.0 || ry == 0.0 {
+            return format!("M {} {} H {} V {} H {} Z", x, y, x + width, y + height, x);
+        }
+
+        let mut path = String::new();
+        write!(
+            &mut path,
+            "M {} {} H {} A {rx} {ry} 0 0 1 {} {} V {} A {rx} {ry} 0 0 1 {} {} H {} A {rx} {ry} 0 0 1 {} {} V {} A {rx} {ry} 0 0 1 {} {} Z",
+            x + rx,
+            y,
+            x + width - rx,
+            x + width,
+            y + ry,
+            y + height - ry,
+            x + width - rx,
+            y + height,
+            x + rx,
+            x,
+            y + height - ry,
+            y + ry,
+            x + rx,
+            y,
+        )
+        .expect("Write should be ok");
+        path
+    }
+
+    fn content_bounds(&self) -> Option<Rect> {
+        let mut coords = self.shapes.iter().flat_map(|s| s.paths().flatten());
+        let first = coords.next()?.coord();
+        let mut min = first;
+        let mut max = first;
+        for c in coords {
+            min = Coord::min(&min, &c.coord());
+            max = Coord::max(&max, &c.coord());
+        }
+        if min.x == max.x || min.y == max.y {
+            return None;
+        }
+        Some(Rect::new(min.x, min.y, max.x, max.y))
+    }
+}
+
+/// Convert a subpath to the path layout of a shape.
+/// A line has its handles on the same coordinates as its points.
+/// An open subpath stays open, a closed subpath ends with its start point.
+fn subpath_to_coords(subpath: &SubPath) -> Vec<DbCoord> {
+    let start = DbCoord::from(subpath.start);
+    let mut coords = vec![start];
+    let mut previous = start;
+
+    for segment in &subpath.segments {
+        match segment {
+            Segment::Line(p1) => {
+                let p1 = DbCoord::from(*p1);
+                coords.push(previous);
+                coords.push(p1);
+                coords.push(p1);
+                previous = p1;
+            }
+            Segment::Cubic(cp0, cp1, p1) => {
+                let p1 = DbCoord::from(*p1);
+                coords.push(DbCoord::from(*cp0));
+                coords.push(DbCoord::from(*cp1));
+                coords.push(p1);
+                previous = p1;
+            }
+        }
+    }
+
+    // An open subpath ending on its start keeps its own end point, it is not closed
+    if subpath.closed && subpath.end() == subpath.start {
+        // The last point is the start point, they share the same id
+        let last_id = previous.id;
+        for coord in coords.iter_mut().skip(1) {
+            if coord.id == last_id {
+                *coord = start;
+            }
+        }
+    } else if subpath.closed {
+        coords.push(previous);
+        coords.push(start);
+        coords.push(start);
+    }
+
+    coords
+}
+
+fn ellipse_path(cx: f32, cy: f32, rx: f32, ry: f32) -> String {
+    if rx <= 0.0 || ry <= 0.0 {
+        return String::new();
+    }
+    format!(
+        "M {} {} A {rx} {ry} 0 1 0 {} {} A {rx} {ry} 0 1 0 {} {} Z",
+        cx - rx,
+        cy,
+        cx + rx,
+        cy,
+        cx - rx,
+        cy
+    )
+}
+
+fn apply_opacity(color: &Option<Rgba>, opacity: f32) -> Rgba {
+    match color {
+        Some(color) => {
+            let mut color = color.clone();
+            color.a = (color.a as f32 * opacity).round() as u8;
+            color
+        }
+        None => Rgba::transparent(),
+    }
+}
+
+/// The rectangle of the document from the viewBox or the width and height of the root element
+fn document_rect(root: Node, warnings: &mut Vec<SvgImportWarning>) -> Option<Rect> {
+    if let Some(value) = root.attribute("viewBox") {
+        let mut parser = Parser::new(value);
+        let numbers: Result<Vec<f32>, String> = (0..4).map(|_| parser.number()).collect();
+        match numbers {
+            Ok(n) if n[2] > 0.0 && n[3] > 0.0 => {
+                return Some(Rect::new(n[0], n[1], n[0] + n[2], n[1] + n[3]));
+            }
+            _ => warnings.push(SvgImportWarning::InvalidAttribute {
+                element: element_label(root),
+                attribute: "viewBox".to_string(),
+                value: value.to_string(),
+            }),
+        }
+    }
+
+    let width = root.attribute("width").and_then(parse_length)?;
+    let height = root.attribute("height").and_then(parse_length)?;
+    if width > 0.0 && height > 0.0 {
+        Some(Rect::new(0.0, 0.0, width, height))
+    } else {
+        None
+    }
+}
+
+/// Parse a number with an optional absolute unit like `px`. Percentages are not supported.
+fn parse_length(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let number = value.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    if number.ends_with('%') {
+        return None;
+    }
+    number.trim().parse::<f32>().ok()
+}
+
+/// Parse a transform list like `translate(10 20) rotate(45)` in one matrix
+fn parse_transform(value: &str) -> Option<Affine> {
+    let mut result = Affine::identity();
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        let (name, after_name) = rest.split_once('(')?;
+        let (arguments, after) = after_name.split_once(')')?;
+        let mut parser = Parser::new(arguments);
+        let mut values = Vec::new();
+        while !parser.is_end() {
+            values.push(parser.number().ok()?);
+        }
+
+        let transform = match (name.trim(), values.as_slice()) {
+            ("matrix", [a, b, c, d, e, f]) => Affine::new(*a, *b, *c, *d, *e, *f),
+            ("translate", [x]) => Affine::from_translate(Coord::new(*x, 0.0)),
+            ("translate", [x, y]) => Affine::from_translate(Coord::new(*x, *y)),
+            ("scale", [s]) => Affine::from_scale(Coord::new(*s, *s)),
+            ("scale", [x, y]) => Affine::from_scale(Coord::new(*x, *y)),
+            ("rotate", [a]) => Affine::from_rotation(a.to_radians()),
+            ("rotate", [a, x, y]) => Affine::identity()
+                .translate(Coord::new(-x, -y))
+                .rotate(a.to_radians())
+                .translate(Coord::new(*x, *y)),
+            ("skewX", [a]) => Affine::new(1.0, 0.0, a.to_radians().tan(), 1.0, 0.0, 0.0),
+            ("skewY", [a]) => Affine::new(1.0, a.to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+            _ => return None,
+        };
+        // The first transform of the list is the outermost
+        result = result * transform;
+        rest = after.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+    }
+
+    Some(result)
+}
+
+/// Parse a CSS color. `Some(None)` is the `none` paint.
+fn parse_color(value: &str) -> Option<Option<Rgba>> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let lower = value.to_ascii_lowercase();
+
+    if lower == "none" {
+        return Some(None);
+    }
+
+    if let Some(hex) = lower.strip_prefix('#') {
+        let digits: Vec<u8> = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as u8))
+            .collect::<Option<Vec<u8>>>()?;
+        return match digits.as_slice() {
+            [r, g, b] => Some(Some(Rgba::new(r * 17, g * 17, b * 17, 255))),
+            [r, g, b, a] => Some(Some(Rgba::new(r * 17, g * 17, b * 17, a * 17))),
+            [r1, r0, g1, g0, b1, b0] => Some(Some(Rgba::new(
+                r1 * 16 + r0,
+                g1 * 16 + g0,
+                b1 * 16 + b0,
+                255,
+            ))),
+            [r1, r0, g1, g0, b1, b0, a1, a0] => Some(Some(Rgba::new(
+                r1 * 16 + r0,
+                g1 * 16 + g0,
+                b1 * 16 + b0,
+                a1 * 16 + a0,
+            ))),
+            _ => None,
+        };
+    }
+
+    if let Some(arguments) = lower
+        .strip_prefix("rgba(")
+        .or_else(|| lower.strip_prefix("rgb("))
+    {
+        let arguments = arguments.strip_suffix(')')?;
+        let parts: Vec<&str> = arguments
+            .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+            .filter(|p| !p.is_empty())
+            .collect();
+        if parts.len() != 3 && parts.len() != 4 {
+            return None;
+        }
+        let channel = |part: &str| -> Option<u8> {
+            match part.strip_suffix('%') {
+                Some(percent) => Some((percent.parse::<f32>().ok()? * 2.55).round() as u8),
+                None => Some(part.parse::<f32>().ok()?.round().clamp(0.0, 255.0) as u8),
+            }
+        };
+        let alpha = match parts.get(3) {
+            Some(part) => match part.strip_suffix('%') {
+                Some(percent) => percent.parse::<f32>().ok()? / 100.0,
+                None => part.parse::<f32>().ok()?,
+            },
+            None => 1.0,
+        };
+        return Some(Some(Rgba::new(
+            channel(parts[0])?,
+            channel(parts[1])?,
+            channel(parts[2])?,
+            (alpha.clamp(0.0, 1.0) * 255.0).round() as u8,
+        )));
+    }
+
+    let color = match lower.as_str() {
+        "transparent" => Rgba::transparent(),
+        "black" => Rgba::black(),
+        "white" => Rgba::white(),
+        "red" => Rgba::red(),
+        "lime" => Rgba::green(),
+        "green" => Rgba::new(0, 128, 0, 255),
+        "blue" => Rgba::blue(),
+        "yellow" => Rgba::yellow(),
+        "cyan" | "aqua" => Rgba::cyan(),
+        "magenta" | "fuchsia" => Rgba::new(255, 0, 255, 255),
+        "silver" => Rgba::new(192, 192, 192, 255),
+        "gray" | "grey" => Rgba::gray(),
+        "maroon" => Rgba::new(128, 0, 0, 255),
+        "olive" => Rgba::new(128, 128, 0, 255),
+        "navy" => Rgba::new(0, 0, 128, 255),
+        "purple" => Rgba::purple(),
+        "teal" => Rgba::teal(),
+        "orange" => Rgba::orange(),
+        "pink" => Rgba::pink(),
+        "brown" => Rgba::brown(),
+        _ => return None,
+    };
+    Some(Some(color))
+}
+
+fn attribute<'a>(node: Node<'a, 'a>, name: &str) -> Option<&'a str> {
+    if let Some(value) = node.attribute(name) {
+        return Some(value.trim());
+    }
+    let style = node.attribute("style")?;
+    style.split(';').find_map(|declaration| {
+        let (key, value) = declaration.split_once(':')?;
+        (key.trim() == name).then(|| value.trim())
+    })
+}
+
+/// Name used in the warnings, the tag with the id if present like `path#leaf`
+fn element_label(node: Node) -> String {
+    match node.attribute("id") {
+        Some(id) => format!("{}#{}", node.tag_name().name(), id),
+        None => node.tag_name().name().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Vec2, types::Coord, Rgba};
+    use float_cmp::assert_approx_eq;
+
+    use crate::{FillRule, LineCap, LineJoin, SceneUserContext};
+
+    use super::{parse_svg, SvgImportError, SvgImportWarning};
+
+    #[test]
+    fn given_rect_when_import_then_shape_in_scene_range() {
+        let import = parse_svg(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+                <rect x="0" y="0" width="50" height="100" fill="#ff0000" stroke="blue" stroke-width="10"/>
+            </svg>"##,
+        )
+        .unwrap();
+
+        assert!(import.warnings.is_empty());
+        assert_eq!(import.shapes.len(), 1);
+        let shape = &import.shapes[0];
+        assert!(shape.is_closed());
+        assert_eq!(shape.curves_len(), 4);
+        assert_eq!(shape.path[0].coord(), Coord::new(-1.0, -1.0));
+        assert_eq!(shape.path[3].coord(), Coord::new(0.0, -1.0));
+        assert_eq!(shape.fill, Rgba::red().into());
+        assert_eq!(shape.stroke.paint, Rgba::blue().into());
+        assert_approx_eq!(f32, shape.stroke.size, 0.2, epsilon = 0.0001);
+        assert!(shape.curves().all(|c| c.is_straight()));
+    }
+
+    #[test]
+    fn given_stroke_style_when_import_then_stroke_in_scene_units() {
+        let import = parse_svg(
+            r#"<svg viewBox="0 0 100 100">
+                <path d="M 10 10 L 90 10 L 90 90" fill="none" stroke="black" stroke-width="4"
+                    stroke-linejoin="bevel" stroke-linecap="round" stroke-miterlimit="2"
+                    stroke-dasharray="10 5" stroke-dashoffset="5"/>
+            </svg>"#,
+        )
+        .unwrap();
+
+        assert!(import.warnings.is_empty());
+        let stroke = &import.shapes[0].stroke;
+        assert_eq!(stroke.join, LineJoin::Bevel);
+        assert_eq!(stroke.cap, LineCap::Round);
+        assert_eq!(stroke.miter_limit, 2.0);
+        assert_eq!(stroke.dashes.len(), 2);
+        assert_approx_eq!(f32, stroke.dashes[0], 0.2, epsilon = 0.0001);
+        assert_approx_eq!(f32, stroke.dashes[1], 0.1, epsilon = 0.0001);
+        assert_approx_eq!(f32, stroke.dash_offset, 0.1, epsilon = 0.0001);
+    }
+
+    #[test]
+    fn given_group_transform_when_import_then_transform_applied() {
+        let import = parse_svg(
+            r#"<svg viewBox="0 0 100 100">
+                <g transform="translate(50 50)" fill-opacity="0.5">
+                    <circle cx="0" cy="0" r="25"/>
+                </g>
+            </svg>"#,
+        )
+        .unwrap();
+
+        let shape = &import.shapes[0];
+        assert_eq!(shape.fill, Rgba::new(0, 0, 0, 128).into());
+        assert!(shape.contains(&Coord::new(0.0, 0.1)));
+        assert!(shape.contains(&Coord::new(0.45, 0.1)));
+        assert!(!shape.contains(&Coord::new(0.55, 0.1)));
+    }
+
+    #[test]
+    fn given_unsupported_content_when_import_then_warnings() {
+        let import = parse_svg(
+            r#"<svg viewBox="0 0 10 10">
+                <text x="0" y="0">Hello</text>
+                <path id="open" d="M 0 0 L 10 0 L 10 10"/>
+                <path d="M 0 0 L 5 5 M 6 6 L 9 9 L 6 9 Z" fill="url(#gradient)"/>
+            </svg>"#,
+        )
+        .unwrap();
+
+        assert_eq!(import.shapes.len(), 2);
+        assert!(import
+            .warnings
+            .contains(&SvgImportWarning::UnsupportedElement {
+                element: "text".to_string()
+            }));
+        assert!(import
+            .warnings
+            .contains(&SvgImportWarning::OpenPathNotFilled {
+                element: "path#open".to_string()
+            }));
+        assert!(!import.shapes[0].is_closed());
+        assert!(import
+            .warnings
+            .contains(&SvgImportWarning::UnsupportedPaint {
+                element: "path".to_string(),
+                value: "url(#gradient)".to_string()
+            }));
+    }
+
+    #[test]
+    fn given_invalid_document_when_import_then_error() {
+        assert!(matches!(parse_svg("<svg"), Err(SvgImportError::Xml(_))));
+        assert_eq!(
+            parse_svg("<html/>").unwrap_err(),
+            SvgImportError::NotSvg("html".to_string())
+        );
+    }
+
+    #[test]
+    fn given_exported_scene_when_import_then_same_geometry() {
+        let (context, warnings) = SceneUserContext::from_svg(
+            r#"<svg viewBox="0 0 200 100">
+                <polygon points="0,0 200,0 200,100" fill="red"/>
+                <ellipse cx="100" cy="50" rx="50" ry="25" fill="blue"/>
+            </svg>"#,
+        )
+        .unwrap();
+        assert!(warnings.is_empty());
+        let scene = context.scene();
+
+        let svg = scene.to_svg(&crate::SvgExportOption::new(200.0, 100.0));
+        let (context, _) = SceneUserContext::from_svg(&svg).unwrap();
+        let reimported = context.scene();
+
+        let tree = reimported.get_tree_view();
+        assert_eq!(tree.len(), 2);
+        for (a, b) in scene.layers.iter().zip(reimported.layers.iter()) {
+            let a = scene.shape_select(a.id).unwrap();
+            let b = reimported.shape_select(b.id).unwrap();
+            assert_eq!(a.fill, b.fill);
+            assert_eq!(a.path.len(), b.path.len());
+            for (ca, cb) in a.path.iter().zip(b.path.iter()) {
+                assert!((ca.coord() - cb.coord()).norm() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn given_path_ending_on_its_start_when_import_then_closed_only_with_close_command() {
+        let import = parse_svg(
+            r#"<svg viewBox="0 0 100 100">
+                <path d="M 0 0 L 100 0 L 100 100 L 0 0"/>
+                <path d="M 0 0 L 100 0 L 100 100 L 0 0 Z"/>
+            </svg>"#,
+        )
+        .unwrap();
+
+        let (open, closed) = (&import.shapes[0], &import.shapes[1]);
+        assert!(!open.is_closed());
+        assert_eq!(open.path.len(), closed.path.len());
+        let (first, last) = (&open.path[0], &open.path[open.path.len() - 1]);
+        assert_ne!(first.id, last.id);
+        assert_eq!(first.coord(), last.coord());
+        assert!(closed.is_closed());
+    }
+
+    #[test]
+    fn given_path_with_hole_when_import_then_one_compound_shape() {
+        let import = parse_svg(
+            r#"<svg viewBox="0 0 100 100">
+                <path fill-rule="evenodd" d="M 0 0 H 100 V 100 H 0 Z M 25 25 H 75 V 75 H 25 Z"/>
+            </svg>"#,
+        )
+        .unwrap();
+
+        assert!(import.warnings.is_empty());
+        assert_eq!(import.shapes.len(), 1);
+        let shape = &import.shapes[0];
+        assert_eq!(shape.subpaths.len(), 1);
+        assert_eq!(shape.fill_rule, FillRule::EvenOdd);
+        assert!(shape.contains(&Coord::new(-0.75, 0.1)));
+        assert!(!shape.contains(&Coord::new(0.1, 0.1)));
+    }
+}
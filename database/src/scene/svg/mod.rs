@@ -0,0 +1,11 @@
+/*
+Conversion between a Scene and the SVG format.
+The scene range -1.0 to 1.0 is mapped to the viewBox of the SVG document.
+*/
+
+mod export;
+mod import;
+mod path_data;
+
+pub use export::SvgExportOption;
+pub use import::{parse_svg, SvgImportError, SvgImportWarning};
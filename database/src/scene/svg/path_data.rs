@@ -0,0 +1,531 @@
+use std::f64::consts::PI;
+
+use common::types::Coord;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Line(Coord),
+    /// cp0, cp1, p1
+    Cubic(Coord, Coord, Coord),
+}
+
+impl Segment {
+    pub fn end(&self) -> Coord {
+        match self {
+            Segment::Line(p1) => *p1,
+            Segment::Cubic(_, _, p1) => *p1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubPath {
+    pub start: Coord,
+    pub segments: Vec<Segment>,
+    pub closed: bool,
+}
+
+impl SubPath {
+    fn new(start: Coord) -> Self {
+        Self {
+            start,
+            segments: Vec::new(),
+            closed: false,
+        }
+    }
+
+    /// The subpath has no close command and does not come back to its start
+    pub fn is_open(&self) -> bool {
+        !self.closed && self.end() != self.start
+    }
+
+    pub fn end(&self) -> Coord {
+        self.segments.last().map(|s| s.end()).unwrap_or(self.start)
+    }
+}
+
+/// Result of the parsing of a path data attribute.
+/// Like the SVG specification, the path is kept up to the first error.
+#[derive(Debug, Default)]
+pub struct PathData {
+    pub subpaths: Vec<SubPath>,
+    pub error: Option<String>,
+}
+
+/// Parse the `d` attribute of a SVG path.
+/// Every command is converted to absolute lines and cubic curves.
+/// Quadratic curves and elliptical arcs are converted to cubic curves.
+pub fn parse_path_data(d: &str) -> PathData {
+    let mut result = PathData::default();
+    if let Err(error) = parse_into(d, &mut result.subpaths) {
+        result.error = Some(error);
+    }
+    result.subpaths.retain(|s| !s.segments.is_empty());
+    result
+}
+
+fn parse_into(d: &str, subpaths: &mut Vec<SubPath>) -> Result<(), String> {
+    let mut parser = Parser::new(d);
+
+    let mut command: Option<u8> = None;
+    let mut current = Coord::new(0.0, 0.0);
+    // Control point of the last curve, used by the S and T commands
+    let mut last_cubic_cp: Option<Coord> = None;
+    let mut last_quad_cp: Option<Coord> = None;
+
+    loop {
+        parser.skip_separators();
+        let Some(byte) = parser.peek() else {
+            break;
+        };
+
+        if byte.is_ascii_alphabetic() {
+            parser.pos += 1;
+            command = Some(byte);
+        } else {
+            // Implicit repetition of the previous command
+            command = match command {
+                Some(b'M') => Some(b'L'),
+                Some(b'm') => Some(b'l'),
+                Some(b'Z') | Some(b'z') | None => {
+                    return Err(format!("Expected a command at position {}", parser.pos));
+                }
+                other => other,
+            };
+        }
+
+        let cmd = command.expect("Command is set above");
+        let relative = cmd.is_ascii_lowercase();
+        let origin = if relative {
+            current
+        } else {
+            Coord::new(0.0, 0.0)
+        };
+
+        let mut cubic_cp = None;
+        let mut quad_cp = None;
+
+        match cmd.to_ascii_uppercase() {
+            b'M' => {
+                let p = origin + parser.coord()?;
+                subpaths.push(SubPath::new(p));
+                current = p;
+            }
+            b'L' => {
+                let p = origin + parser.coord()?;
+                line_to(subpaths, current, p);
+                current = p;
+            }
+            b'H' => {
+                let x = parser.number()?;
+                let p = Coord::new(if relative { current.x + x } else { x }, current.y);
+                line_to(subpaths, current, p);
+                current = p;
+            }
+            b'V' => {
+                let y = parser.number()?;
+                let p = Coord::new(current.x, if relative { current.y + y } else { y });
+                line_to(subpaths, current, p);
+                current = p;
+            }
+            b'C' => {
+                let cp0 = origin + parser.coord()?;
+                let cp1 = origin + parser.coord()?;
+                let p = origin + parser.coord()?;
+                cubic_to(subpaths, current, cp0, cp1, p);
+                cubic_cp = Some(cp1);
+                current = p;
+            }
+            b'S' => {
+                let cp0 = reflect(last_cubic_cp, current);
+                let cp1 = origin + parser.coord()?;
+                let p = origin + parser.coord()?;
+                cubic_to(subpaths, current, cp0, cp1, p);
+                cubic_cp = Some(cp1);
+                current = p;
+            }
+            b'Q' => {
+                let q = origin + parser.coord()?;
+                let p = origin + parser.coord()?;
+                quad_to(subpaths, current, q, p);
+                quad_cp = Some(q);
+                current = p;
+            }
+            b'T' => {
+                let q = reflect(last_quad_cp, current);
+                let p = origin + parser.coord()?;
+                quad_to(subpaths, current, q, p);
+                quad_cp = Some(q);
+                current = p;
+            }
+            b'A' => {
+                let rx = parser.number()?;
+                let ry = parser.number()?;
+                let x_axis_rotation = parser.number()?;
+                let large_arc = parser.flag()?;
+                let sweep = parser.flag()?;
+                let p = origin + parser.coord()?;
+                for (cp0, cp1, p1) in
+                    arc_to_cubics(current, rx, ry, x_axis_rotation, large_arc, sweep, p)
+                {
+                    cubic_to(subpaths, current, cp0, cp1, p1);
+                    current = p1;
+                }
+                current = p;
+            }
+            b'Z' => {
+                if let Some(subpath) = subpaths.last_mut() {
+                    subpath.closed = true;
+                    current = subpath.start;
+                    // A new command after Z without M start a new subpath at the same point
+                    let start = subpath.start;
+                    if !subpath.segments.is_empty() {
+                        subpaths.push(SubPath::new(start));
+                    }
+                }
+            }
+            _ => return Err(format!("Unknown command '{}'", cmd as char)),
+        }
+
+        last_cubic_cp = cubic_cp;
+        last_quad_cp = quad_cp;
+    }
+
+    Ok(())
+}
+
+fn current_subpath(subpaths: &mut Vec<SubPath>, current: Coord) -> &mut SubPath {
+    if subpaths.last().is_none_or(|s| s.closed) {
+        subpaths.push(SubPath::new(current));
+    }
+    subpaths.last_mut().expect("Subpath pushed above")
+}
+
+fn line_to(subpaths: &mut Vec<SubPath>, current: Coord, p: Coord) {
+    current_subpath(subpaths, current)
+        .segments
+        .push(Segment::Line(p));
+}
+
+fn cubic_to(subpaths: &mut Vec<SubPath>, current: Coord, cp0: Coord, cp1: Coord, p: Coord) {
+    current_subpath(subpaths, current)
+        .segments
+        .push(Segment::Cubic(cp0, cp1, p));
+}
+
+fn quad_to(subpaths: &mut Vec<SubPath>, current: Coord, q: Coord, p: Coord) {
+    let cp0 = current + (q - current) * (2.0 / 3.0);
+    let cp1 = p + (q - p) * (2.0 / 3.0);
+    cubic_to(subpaths, current, cp0, cp1, p);
+}
+
+fn reflect(control_point: Option<Coord>, current: Coord) -> Coord {
+    match control_point {
+        Some(cp) => current * 2.0 - cp,
+        None => current,
+    }
+}
+
+/// Convert an elliptical arc to cubic curves of 90 degrees or less.
+/// See the SVG specification, appendix B.2.4 "Conversion from endpoint to center parameterization"
+pub fn arc_to_cubics(
+    start: Coord,
+    rx: f32,
+    ry: f32,
+    x_axis_rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    end: Coord,
+) -> Vec<(Coord, Coord, Coord)> {
+    if start == end {
+        return vec![];
+    }
+
+    let mut rx = (rx as f64).abs();
+    let mut ry = (ry as f64).abs();
+    if rx == 0.0 || ry == 0.0 {
+        return vec![(start, end, end)];
+    }
+
+    let (x1, y1) = (start.x as f64, start.y as f64);
+    let (x2, y2) = (end.x as f64, end.y as f64);
+    let phi = (x_axis_rotation as f64).to_radians();
+    let (sin_phi, cos_phi) = phi.sin_cos();
+
+    let dx = (x1 - x2) / 2.0;
+    let dy = (y1 - y2) / 2.0;
+    let x1p = cos_phi * dx + sin_phi * dy;
+    let y1p = -sin_phi * dx + cos_phi * dy;
+
+    // Scale up radii that are too small
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+    let denominator = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+    let mut coef = (numerator / denominator).max(0.0).sqrt();
+    if large_arc == sweep {
+        coef = -coef;
+    }
+    let cxp = coef * rx * y1p / ry;
+    let cyp = -coef * ry * x1p / rx;
+
+    let cx = cos_phi * cxp - sin_phi * cyp + (x1 + x2) / 2.0;
+    let cy = sin_phi * cxp + cos_phi * cyp + (y1 + y2) / 2.0;
+
+    let theta1 = angle(1.0, 0.0, (x1p - cxp) / rx, (y1p - cyp) / ry);
+    let mut delta = angle(
+        (x1p - cxp) / rx,
+        (y1p - cyp) / ry,
+        (-x1p - cxp) / rx,
+        (-y1p - cyp) / ry,
+    ) % (2.0 * PI);
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * PI;
+    }
+
+    let segments_count = (delta.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+    let segment_delta = delta / segments_count as f64;
+    let k = 4.0 / 3.0 * (segment_delta / 4.0).tan();
+
+    let point = |t: f64| -> (f64, f64) {
+        let (sin_t, cos_t) = t.sin_cos();
+        (
+            cx + rx * cos_phi * cos_t - ry * sin_phi * sin_t,
+            cy + rx * sin_phi * cos_t + ry * cos_phi * sin_t,
+        )
+    };
+    let derivative = |t: f64| -> (f64, f64) {
+        let (sin_t, cos_t) = t.sin_cos();
+        (
+            -rx * cos_phi * sin_t - ry * sin_phi * cos_t,
+            -rx * sin_phi * sin_t + ry * cos_phi * cos_t,
+        )
+    };
+
+    let mut curves = Vec::with_capacity(segments_count);
+    for i in 0..segments_count {
+        let t0 = theta1 + segment_delta * i as f64;
+        let t1 = t0 + segment_delta;
+        let (p0x, p0y) = point(t0);
+        let (d0x, d0y) = derivative(t0);
+        let (p1x, p1y) = point(t1);
+        let (d1x, d1y) = derivative(t1);
+
+        let cp0 = Coord::new((p0x + k * d0x) as f32, (p0y + k * d0y) as f32);
+        let cp1 = Coord::new((p1x - k * d1x) as f32, (p1y - k * d1y) as f32);
+        let p1 = if i == segments_count - 1 {
+            end
+        } else {
+            Coord::new(p1x as f32, p1y as f32)
+        };
+        curves.push((cp0, cp1, p1));
+    }
+    curves
+}
+
+fn angle(ux: f64, uy: f64, vx: f64, vy: f64) -> f64 {
+    let sign = if ux * vy - uy * vx < 0.0 { -1.0 } else { 1.0 };
+    let dot = ux * vx + uy * vy;
+    let len = (ux * ux + uy * uy).sqrt() * (vx * vx + vy * vy).sqrt();
+    sign * (dot / len).clamp(-1.0, 1.0).acos()
+}
+
+/// Cursor over the numbers of a SVG attribute like `d` or `points`
+pub struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(data: &'a str) -> Self {
+        Self {
+            data: data.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    pub fn is_end(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.data.len()
+    }
+
+    pub fn skip_separators(&mut self) {
+        while let Some(byte) = self.peek() {
+            if byte.is_ascii_whitespace() || byte == b',' {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn number(&mut self) -> Result<f32, String> {
+        self.skip_separators();
+        let start = self.pos;
+
+        if matches!(self.peek(), Some(b'+') | Some(b'-')) {
+            self.pos += 1;
+        }
+        let mut has_digits = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            has_digits |= self.skip_digits();
+        }
+        if !has_digits {
+            return Err(format!("Expected a number at position {}", start));
+        }
+        if matches!(self.peek(), Some(b'e') | Some(b'E')) {
+            let before_exponent = self.pos;
+            self.pos += 1;
+            if matches!(self.peek(), Some(b'+') | Some(b'-')) {
+                self.pos += 1;
+            }
+            if !self.skip_digits() {
+                self.pos = before_exponent;
+            }
+        }
+
+        let text = std::str::from_utf8(&self.data[start..self.pos]).expect("ASCII slice");
+        text.parse::<f32>()
+            .map_err(|e| format!("Invalid number '{}': {}", text, e))
+    }
+
+    fn skip_digits(&mut self) -> bool {
+        let start = self.pos;
+        while matches!(self.peek(), Some(b'0'..=b'9')) {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    pub fn coord(&mut self) -> Result<Coord, String> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(Coord::new(x, y))
+    }
+
+    /// Flags of an arc can be written without separator like `a1 1 0 00 1 1`
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+        match self.peek() {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            _ => Err(format!("Expected a flag at position {}", self.pos)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::Coord;
+    use float_cmp::assert_approx_eq;
+
+    use super::*;
+
+    #[test]
+    fn given_relative_lines_when_parse_then_absolute_lines() {
+        let data = parse_path_data("m 10 10 h 5 v 5 l -5 0 z");
+
+        assert!(data.error.is_none());
+        assert_eq!(data.subpaths.len(), 1);
+        let subpath = &data.subpaths[0];
+        assert!(subpath.closed);
+        assert_eq!(subpath.start, Coord::new(10.0, 10.0));
+        assert_eq!(
+            subpath.segments,
+            vec![
+                Segment::Line(Coord::new(15.0, 10.0)),
+                Segment::Line(Coord::new(15.0, 15.0)),
+                Segment::Line(Coord::new(10.0, 15.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn given_compact_numbers_when_parse_then_all_numbers_read() {
+        let data = parse_path_data("M1.5.5L-2-1e1");
+
+        assert!(data.error.is_none());
+        assert_eq!(data.subpaths[0].start, Coord::new(1.5, 0.5));
+        assert_eq!(
+            data.subpaths[0].segments,
+            vec![Segment::Line(Coord::new(-2.0, -10.0))]
+        );
+    }
+
+    #[test]
+    fn given_quadratic_when_parse_then_cubic() {
+        let data = parse_path_data("M 0 0 Q 3 3 6 0 T 12 0");
+
+        let segments = &data.subpaths[0].segments;
+        assert_eq!(
+            segments[0],
+            Segment::Cubic(
+                Coord::new(2.0, 2.0),
+                Coord::new(4.0, 2.0),
+                Coord::new(6.0, 0.0)
+            )
+        );
+        assert_eq!(
+            segments[1],
+            Segment::Cubic(
+                Coord::new(8.0, -2.0),
+                Coord::new(10.0, -2.0),
+                Coord::new(12.0, 0.0)
+            )
+        );
+    }
+
+    #[test]
+    fn given_smooth_cubic_when_parse_then_reflected_control_point() {
+        let data = parse_path_data("M 0 0 C 0 1 1 1 1 0 S 2 -1 2 0");
+
+        assert_eq!(
+            data.subpaths[0].segments[1],
+            Segment::Cubic(
+                Coord::new(1.0, -1.0),
+                Coord::new(2.0, -1.0),
+                Coord::new(2.0, 0.0)
+            )
+        );
+    }
+
+    #[test]
+    fn given_half_circle_arc_when_parse_then_2_cubics_on_circle() {
+        let data = parse_path_data("M -1 0 A 1 1 0 0 0 1 0");
+
+        let segments = &data.subpaths[0].segments;
+        assert_eq!(segments.len(), 2);
+        let Segment::Cubic(_, _, middle) = segments[0] else {
+            panic!("Arc should be converted to cubic");
+        };
+        assert_approx_eq!(f32, middle.x, 0.0, epsilon = 0.0001);
+        assert_approx_eq!(f32, middle.y, 1.0, epsilon = 0.0001);
+        assert_eq!(segments[1].end(), Coord::new(1.0, 0.0));
+    }
+
+    #[test]
+    fn given_invalid_data_when_parse_then_keep_until_error() {
+        let data = parse_path_data("M 0 0 L 1 1 L 2 X");
+
+        assert!(data.error.is_some());
+        assert_eq!(data.subpaths.len(), 1);
+        assert_eq!(data.subpaths[0].segments.len(), 1);
+    }
+}
@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 use wasm_bindgen::prelude::wasm_bindgen;
 
-use super::{LayerType, Scene};
+use super::{LayerIter, Scene};
 
 #[derive(Tsify, Debug, Serialize, Deserialize)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
@@ -10,32 +10,26 @@ pub struct TreeViewModel {
     pub layer_id: usize,
     pub layer_type: String,
     pub name: String,
+    /// Number of folders containing the layer, 0 at the root of the scene
     pub level: usize,
+    pub visible: bool,
+    pub locked: bool,
+    pub opacity: f32,
 }
 
 impl Scene {
+    /// Layers from the foreground, each folder followed by its layers
     pub fn get_tree_view(&self) -> Vec<TreeViewModel> {
-        let mut result = Vec::new();
-        for layer in &self.layers {
-            match &layer.value {
-                LayerType::Shape(_) => {
-                    result.push(TreeViewModel {
-                        layer_id: layer.id.value(),
-                        name: layer.name.clone(),
-                        layer_type: layer.value.type_string(),
-                        level: 0,
-                    });
-                }
-                LayerType::Folder => {
-                    result.push(TreeViewModel {
-                        layer_id: layer.id.value(),
-                        name: layer.name.clone(),
-                        layer_type: layer.value.type_string(),
-                        level: 0,
-                    });
-                }
-            }
-        }
-        result
+        LayerIter::new(&self.layers)
+            .map(|(level, layer)| TreeViewModel {
+                layer_id: layer.id.value(),
+                name: layer.name.clone(),
+                layer_type: layer.value.type_string(),
+                level,
+                visible: layer.visible,
+                locked: layer.locked,
+                opacity: layer.opacity,
+            })
+            .collect()
     }
 }
@@ -0,0 +1,174 @@
+use std::fmt::Display;
+
+use crate::{CoordId, LayerId};
+
+use super::{shape::curve::path_is_closed, LayerType, Scene};
+
+/// A path of the scene that breaks an invariant of the cubic paths
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathIssue {
+    /// A path has no coord
+    EmptyPath { layer: LayerId, subpath: usize },
+    /// A path must have 1 + 3n coords: the start and p1, cp0, cp1 for each curve
+    InvalidLength {
+        layer: LayerId,
+        subpath: usize,
+        len: usize,
+    },
+    /// The first and last coords share their id to close the path but not their position
+    ClosedEndsApart { layer: LayerId, subpath: usize },
+    /// Two coords share an id but not their position
+    SharedIdApart {
+        layer: LayerId,
+        subpath: usize,
+        id: CoordId,
+    },
+}
+
+impl Display for PathIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathIssue::EmptyPath { layer, subpath } => {
+                write!(f, "layer {} path {}: empty", layer.value(), subpath)
+            }
+            PathIssue::InvalidLength {
+                layer,
+                subpath,
+                len,
+            } => write!(
+                f,
+                "layer {} path {}: {} coords, expected 1 + 3n",
+                layer.value(),
+                subpath,
+                len
+            ),
+            PathIssue::ClosedEndsApart { layer, subpath } => write!(
+                f,
+                "layer {} path {}: closed but the ends are at different positions",
+                layer.value(),
+                subpath
+            ),
+            PathIssue::SharedIdApart { layer, subpath, id } => write!(
+                f,
+                "layer {} path {}: coords with id {:?} at different positions",
+                layer.value(),
+                subpath,
+                id
+            ),
+        }
+    }
+}
+
+impl Scene {
+    /// Check the invariants of every path of the scene, empty if the scene is valid
+    pub fn validate(&self) -> Vec<PathIssue> {
+        let mut issues = Vec::new();
+        for layer in self.layers_iter() {
+            let LayerType::Shape(shape) = &layer.value else {
+                continue;
+            };
+            if shape.path.is_empty() && shape.subpaths.is_empty() {
+                continue;
+            }
+
+            for (subpath, path) in shape.paths().enumerate() {
+                let (Some(first), Some(last)) = (path.first(), path.last()) else {
+                    issues.push(PathIssue::EmptyPath {
+                        layer: layer.id,
+                        subpath,
+                    });
+                    continue;
+                };
+                if (path.len() - 1) % 3 != 0 {
+                    issues.push(PathIssue::InvalidLength {
+                        layer: layer.id,
+                        subpath,
+                        len: path.len(),
+                    });
+                }
+                if path_is_closed(path) && first.coord() != last.coord() {
+                    issues.push(PathIssue::ClosedEndsApart {
+                        layer: layer.id,
+                        subpath,
+                    });
+                }
+
+                // The last coord of a closed path is checked with the first
+                let inner = if path_is_closed(path) {
+                    &path[..path.len() - 1]
+                } else {
+                    &path[..]
+                };
+                for (i, coord) in inner.iter().enumerate() {
+                    let apart = inner[i + 1..]
+                        .iter()
+                        .any(|other| other.id == coord.id && other.coord() != coord.coord());
+                    if apart {
+                        issues.push(PathIssue::SharedIdApart {
+                            layer: layer.id,
+                            subpath,
+                            id: coord.id,
+                        });
+                    }
+                }
+            }
+        }
+        issues
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Affine, types::Coord};
+
+    use crate::{DbCoord, Scene, Shape};
+
+    use super::PathIssue;
+
+    fn triangle() -> Shape {
+        Shape::new_from_lines(
+            vec![
+                DbCoord::new(0.0, 0.0),
+                DbCoord::new(1.0, 0.0),
+                DbCoord::new(1.0, 1.0),
+            ],
+            Affine::identity(),
+        )
+    }
+
+    #[test]
+    fn given_valid_shapes_when_validate_then_no_issue() {
+        let mut scene = Scene::new();
+        scene.shape_insert(triangle());
+        scene.shape_insert(Shape::new());
+
+        assert_eq!(scene.validate(), vec![]);
+    }
+
+    #[test]
+    fn given_broken_paths_when_validate_then_issues() {
+        let mut scene = Scene::new();
+        let mut shape = triangle();
+        let last = shape.path.len() - 1;
+        shape.path[last].coord = Coord::new(0.5, 0.5);
+        shape
+            .subpaths
+            .push(vec![DbCoord::new(0.0, 0.0), DbCoord::new(1.0, 0.0)]);
+        let id = scene.shape_insert(shape);
+
+        assert_eq!(
+            scene.validate(),
+            vec![
+                PathIssue::ClosedEndsApart {
+                    layer: id,
+                    subpath: 0
+                },
+                PathIssue::InvalidLength {
+                    layer: id,
+                    subpath: 1,
+                    len: 2
+                },
+            ]
+        );
+    }
+}
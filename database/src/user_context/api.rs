@@ -1,15 +1,21 @@
+use common::pures::Vec2;
 use common::types::{Coord, ScreenLength2d};
 use common::{dbg_str, Rgba};
 use common::{math::point_in_radius, types::ScreenCoord};
 
 use crate::commands::{
-    AddCoord, ChangeColor, ChangeStrokeColor, ChangeStrokeSize, MoveCoords, RemoveCoord,
-    ToggleHandle,
+    AddCoord, AlignShapes, AlignTarget, Alignment, ChangeColor, ChangeFill, ChangeStrokeColor,
+    ChangeStrokeProperty, ChangeStrokeSize, Command, CommandsHandler, Group, InsertShape,
+    MoveCoords, OutlineStroke, PaintTarget, RemoveCoord, ReplaceShapePath, StrokeProperty,
+    ToggleHandle, Ungroup,
 };
+use crate::file_format::{self, FileFormatError};
 use crate::scene::shape::boolean::ShapeUnion;
+use crate::scene::svg::{parse_svg, SvgImportError, SvgImportWarning};
 use crate::user_context::user_selection::SelectedShape;
-use crate::{LayerId, Shape};
+use crate::{LayerId, LineCap, LineJoin, Paint, RenderOption, Scene, Shape, StrokeAlign};
 
+use super::camera::Camera;
 use super::user_selection::UserSelection;
 use super::SceneUserContext;
 
@@ -23,14 +29,32 @@ impl SceneUserContext {
             log::error!("{:?}", e)
         }
     }
+    /// Move the selected coords by the mouse movement. With snapping, the selected coord
+    /// closest to the start is moved on the target it reaches.
+    /// The alignment guides follow the coords until `drag_end`
     pub fn move_coords_of(
         &mut self,
-        selected: &UserSelection,
+        selected: &mut UserSelection,
         start: ScreenCoord,
         end: ScreenCoord,
     ) {
         let start = self.camera.project(start);
-        let end = self.camera.project(end);
+        let mut end = self.camera.project(end);
+
+        let scene = self.scene();
+        let grabbed = selected
+            .shapes
+            .iter()
+            .filter_map(|s| Some((scene.shape_select(s.shape_id)?, &s.coords)))
+            .flat_map(|(shape, ids)| ids.iter().filter_map(|id| shape.coord_select(*id)))
+            .map(|db_coord| db_coord.coord())
+            .min_by(|a, b| a.distance(&start).total_cmp(&b.distance(&start)));
+        if let Some(grabbed) = grabbed {
+            let moved = grabbed + (end - start);
+            if let Some(snap) = self.snap(moved, &selected.snap, &selected.shapes) {
+                end = start + (snap.coord - grabbed);
+            }
+        }
 
         if let Err(e) = self.command_handler.execute(MoveCoords::boxed(
             selected
@@ -43,6 +67,7 @@ impl SceneUserContext {
         )) {
             log::error!("{:?}", e)
         }
+        self.guides_update(selected);
     }
 
     pub fn add_or_remove_coord(
@@ -75,9 +100,12 @@ impl SceneUserContext {
         let mut min_t = 0.0;
 
         for shape_selected in &selected.shapes {
-            let shape = scene.shape_select(shape_selected.shape_id).unwrap();
-
-            let (curve_index, t, distance, coord) = shape.closest_curve(&pos);
+            // The shape can have no curve or be deleted since its selection
+            let Some((curve_index, t, distance, coord)) =
+                scene.shape_closest_curve(shape_selected.shape_id, &pos)
+            else {
+                continue;
+            };
 
             if distance < min_distance {
                 min_distance = distance;
@@ -112,23 +140,30 @@ impl SceneUserContext {
     }
 
     pub fn draw_shape(&mut self, selected: &mut UserSelection) {
-        let scene = &mut self.command_handler.unsafe_scene_mut();
-        let camera = &mut self.camera;
         if selected.mouse_position.is_none() {
             return;
         }
         let mouse_position = selected.mouse_position.unwrap();
+        let mouse_position = self
+            .snap(mouse_position, &selected.snap, &[])
+            .map_or(mouse_position, |snap| snap.coord);
 
-        let radius = camera.transform_to_length2d_no_scale(ScreenLength2d::new(50.0, 50.0));
+        let radius = self
+            .camera
+            .transform_to_length2d_no_scale(ScreenLength2d::new(50.0, 50.0));
 
         let mut shape = Shape::new_circle(mouse_position, radius);
-        shape.color = selected.color.clone();
-        shape.stroke.color = selected.stroke_color.clone();
+        shape.fill = selected.color.clone().into();
+        shape.stroke.paint = selected.stroke_color.clone().into();
         shape.stroke.size = selected.stroke_size as f32;
 
         if selected.shapes.is_empty() {
-            let id = scene.shape_insert(shape);
-            scene.layer_move_top(id);
+            shape.id = self.command_handler.layer_id_reserve();
+            let id = shape.id;
+            if let Err(e) = self.command_handler.execute(InsertShape::boxed(shape)) {
+                log::error!("{:?}", e);
+                return;
+            }
             selected.shapes.push(SelectedShape::new(id));
             return;
         }
@@ -141,7 +176,8 @@ impl SceneUserContext {
         let shape_selected = &selected.shapes[0];
 
         let result = {
-            let selected_shape = scene
+            let selected_shape = self
+                .scene()
                 .shape_select(shape_selected.shape_id)
                 .expect("Not 404");
             log::debug!(
@@ -155,38 +191,51 @@ impl SceneUserContext {
             selected_shape.union(&shape)
         };
         log::info!("{}", dbg_str!("Union good"));
-        let selected_shape = scene
-            .shape_select_mut(shape_selected.shape_id)
-            .expect("Not 404");
-        match result {
-            ShapeUnion::New(new_shape) => {
-                selected_shape.path = new_shape.path;
-            }
-            ShapeUnion::A => {}
-            ShapeUnion::B => {
-                selected_shape.path = shape.path;
-            }
-            ShapeUnion::None => {}
+        let paths = match result {
+            ShapeUnion::New(new_shape) => new_shape,
+            ShapeUnion::B => shape,
+            ShapeUnion::A | ShapeUnion::None => return,
+        };
+        if let Err(e) = self
+            .command_handler
+            .execute(ReplaceShapePath::boxed(shape_selected.shape_id, paths))
+        {
+            log::error!("{:?}", e)
         }
     }
 }
 
 impl SceneUserContext {
-    pub fn load(vec: Vec<u8>) -> Result<SceneUserContext, String> {
-        let main_slice = vec.as_slice();
-        let scene_user_context_data = postcard::from_bytes::<SceneUserContext>(main_slice);
-        if scene_user_context_data.is_err() {
-            let error = scene_user_context_data.unwrap_err();
-            log::error!("Error: {:?}", error);
-            return Err("Deserialization should be valid".to_string());
-        }
+    pub fn load(vec: Vec<u8>) -> Result<SceneUserContext, FileFormatError> {
+        file_format::decode(vec.as_slice())
+    }
 
-        Ok(scene_user_context_data.unwrap())
+    pub fn save(&self) -> Result<Vec<u8>, FileFormatError> {
+        file_format::encode(self)
     }
 
-    pub fn save(&self) -> Result<Vec<u8>, String> {
-        let vec = postcard::to_allocvec(self).map_err(|_| "Serizalization should be valid")?;
-        Ok(vec)
+    /// Create a new scene from a SVG document, the camera has the size of the document
+    pub fn from_svg(
+        text: &str,
+    ) -> Result<(SceneUserContext, Vec<SvgImportWarning>), SvgImportError> {
+        let import = parse_svg(text)?;
+        let mut scene = Scene::new();
+        for shape in import.shapes {
+            let id = scene.shape_insert(shape);
+            scene.layer_move_top(id);
+        }
+
+        let camera = Camera::new(
+            scene.max_rect().center(),
+            import.view_box.width(),
+            import.view_box.height(),
+        );
+        let scene_context = SceneUserContext {
+            command_handler: CommandsHandler::from(scene),
+            render_options: RenderOption::default(),
+            camera,
+        };
+        Ok((scene_context, import.warnings))
     }
 }
 
@@ -201,6 +250,137 @@ impl SceneUserContext {
         }
     }
 
+    pub fn set_stroke_join_of(&mut self, selected: &UserSelection, join: LineJoin) {
+        let shapes = selected.shapes.iter().map(|s| s.shape_id).collect();
+        let command = ChangeStrokeProperty::boxed(shapes, StrokeProperty::Join(join));
+        self.execute_on_selection(selected, command);
+    }
+
+    pub fn set_stroke_cap_of(&mut self, selected: &UserSelection, cap: LineCap) {
+        let shapes = selected.shapes.iter().map(|s| s.shape_id).collect();
+        let command = ChangeStrokeProperty::boxed(shapes, StrokeProperty::Cap(cap));
+        self.execute_on_selection(selected, command);
+    }
+
+    pub fn set_stroke_miter_limit_of(&mut self, selected: &UserSelection, miter_limit: f64) {
+        let shapes = selected.shapes.iter().map(|s| s.shape_id).collect();
+        let value = StrokeProperty::MiterLimit(miter_limit as f32);
+        let command = ChangeStrokeProperty::boxed(shapes, value);
+        self.execute_on_selection(selected, command);
+    }
+
+    /// Dash the stroke of the selected shapes, an empty dash array for a solid line
+    pub fn set_stroke_dashes_of(
+        &mut self,
+        selected: &UserSelection,
+        dashes: Vec<f32>,
+        offset: f64,
+    ) {
+        let shapes = selected.shapes.iter().map(|s| s.shape_id).collect();
+        let value = StrokeProperty::Dashes(dashes, offset as f32);
+        let command = ChangeStrokeProperty::boxed(shapes, value);
+        self.execute_on_selection(selected, command);
+    }
+
+    pub fn set_stroke_align_of(&mut self, selected: &UserSelection, align: StrokeAlign) {
+        let shapes = selected.shapes.iter().map(|s| s.shape_id).collect();
+        let command = ChangeStrokeProperty::boxed(shapes, StrokeProperty::Align(align));
+        self.execute_on_selection(selected, command);
+    }
+
+    /// Replace the stroke of the selected shapes by filled shapes of its outline
+    pub fn outline_stroke(&mut self, selected: &mut UserSelection) -> Result<(), String> {
+        let shapes = selected.shapes.iter().map(|s| s.shape_id).collect();
+        self.command_handler
+            .execute(OutlineStroke::boxed(shapes))
+            .map_err(|e| e.to_string())?;
+        // A shape without fill has the coords of its outline
+        for shape in selected.shapes.iter_mut() {
+            shape.coords.clear();
+        }
+        Ok(())
+    }
+
+    /// Put the selected shapes and groups in a new group
+    pub fn group_selection(&mut self, selected: &UserSelection) -> Result<(), String> {
+        let layers = self.selected_roots(selected);
+        if layers.is_empty() {
+            return Ok(());
+        }
+        self.command_handler
+            .execute(Group::boxed(layers))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Replace the selected groups by their layers
+    pub fn ungroup_selection(&mut self, selected: &UserSelection) -> Result<(), String> {
+        let scene = self.scene();
+        let folders: Vec<LayerId> = self
+            .selected_roots(selected)
+            .into_iter()
+            .filter(|id| scene.shape_select(*id).is_none())
+            .collect();
+        if folders.is_empty() {
+            return Ok(());
+        }
+        self.command_handler
+            .execute(Ungroup::boxed(folders))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Line up or space evenly the bounding boxes of the selected shapes, in one undo step.
+    /// A group is aligned as a whole, its shapes keep their places in the group
+    pub fn align_shapes_of(
+        &mut self,
+        selected: &UserSelection,
+        alignment: Alignment,
+        target: AlignTarget,
+    ) -> Result<(), String> {
+        let layers = self.selected_roots(selected);
+        if layers.is_empty() {
+            return Ok(());
+        }
+        self.command_handler
+            .execute(AlignShapes::boxed(layers, alignment, target))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Layers at the root of the scene containing the selected shapes
+    pub(super) fn selected_roots(&self, selected: &UserSelection) -> Vec<LayerId> {
+        let mut roots = Vec::new();
+        for shape in &selected.shapes {
+            if let Some(root) = self.scene().layer_root(shape.shape_id) {
+                if !roots.contains(&root) {
+                    roots.push(root);
+                }
+            }
+        }
+        roots
+    }
+
+    fn execute_on_selection(&mut self, selected: &UserSelection, command: Box<dyn Command>) {
+        if selected.shapes.is_empty() {
+            return;
+        }
+        if let Err(e) = self.command_handler.execute(command) {
+            log::error!("{:?}", e)
+        }
+    }
+
+    /// Set a paint, like a gradient, to the fill or the stroke of the selected shapes
+    pub fn set_paint_of(&mut self, selected: &UserSelection, target: PaintTarget, paint: Paint) {
+        if selected.shapes.is_empty() {
+            return;
+        }
+        if let Err(e) = self.command_handler.execute(ChangeFill::boxed(
+            selected.shapes.iter().map(|s| s.shape_id).collect(),
+            target,
+            paint,
+        )) {
+            log::error!("{:?}", e)
+        }
+    }
+
     pub fn set_stroke_color_of(&mut self, selected: &mut UserSelection, fill: Rgba) {
         selected.stroke_color = fill.clone();
         if let Err(e) = self.command_handler.execute(ChangeStrokeColor::boxed(
@@ -211,3 +391,90 @@ impl SceneUserContext {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, Length2d};
+
+    use crate::{
+        commands::InsertShape, user_context::user_selection::SelectedShape, LayerId,
+        SceneUserContext, Shape, UserSelection,
+    };
+
+    #[test]
+    fn given_draw_shape_twice_when_undo_redo_then_scene_follows() {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        let mut selected = UserSelection {
+            mouse_position: Some(Coord::new(0.0, 0.0)),
+            ..Default::default()
+        };
+        let empty = context.scene().clone();
+
+        context.draw_shape(&mut selected);
+        let inserted = context.scene().clone();
+        selected.mouse_position = Some(Coord::new(0.1, 0.0));
+        context.draw_shape(&mut selected);
+        let merged = context.scene().clone();
+        assert_eq!(merged.get_tree_view().len(), 1);
+        assert_ne!(merged, inserted);
+
+        context.command_handler.undo().unwrap();
+        assert_eq!(context.scene(), &inserted);
+        context.command_handler.undo().unwrap();
+        assert_eq!(context.scene(), &empty);
+
+        context.command_handler.redo().unwrap();
+        context.command_handler.redo().unwrap();
+        assert_eq!(context.scene(), &merged);
+    }
+
+    #[test]
+    fn given_grouped_shapes_when_click_one_then_group_selected_until_ungroup() {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        for center in [Coord::new(-0.5, 0.1), Coord::new(0.5, 0.1)] {
+            let circle = Shape::new_circle(center, Length2d::new(0.2, 0.2));
+            context
+                .command_handler
+                .execute(InsertShape::boxed(circle))
+                .unwrap();
+        }
+        let mut selected = UserSelection::default();
+        for layer in context.scene().get_tree_view() {
+            selected
+                .shapes
+                .push(SelectedShape::new(LayerId::from(layer.layer_id)));
+        }
+        context.group_selection(&selected).unwrap();
+        assert_eq!(context.scene().get_tree_view()[1].level, 1);
+
+        let mut selected = UserSelection::default();
+        selected.change_selection(&context, Coord::new(-0.5, 0.15));
+        assert_eq!(selected.shapes.len(), 2);
+
+        context.ungroup_selection(&selected).unwrap();
+        assert_eq!(context.scene().get_tree_view().len(), 2);
+        let mut selected = UserSelection::default();
+        selected.change_selection(&context, Coord::new(-0.5, 0.15));
+        assert_eq!(selected.shapes.len(), 1);
+    }
+    #[test]
+    fn given_deleted_shape_in_selection_when_click_on_path_then_coord_added_to_other_shape() {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        let circle = Shape::new_circle(Coord::new(0.0, 0.0), Length2d::new(0.5, 0.5));
+        let coords = circle.path.len();
+        context
+            .command_handler
+            .execute(InsertShape::boxed(circle))
+            .unwrap();
+        let id = LayerId::from(context.scene().get_tree_view()[0].layer_id);
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(LayerId::null()));
+        selected.shapes.push(SelectedShape::new(id));
+
+        let on_path = context.camera.unproject(Coord::new(0.5, 0.0));
+        context.add_or_remove_coord(&mut selected, on_path);
+
+        let circle = context.scene().shape_select(id).unwrap();
+        assert!(circle.path.len() > coords);
+    }
+}
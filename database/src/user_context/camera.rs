@@ -82,6 +82,26 @@ impl Camera {
         }
     }
 
+    /// Camera with the saved fields of a file, the unsaved ones are the same as after a load
+    pub(crate) fn from_saved(
+        position: Coord,
+        scaling: f32,
+        rotation: f32,
+        reflect: (bool, bool),
+        base_scale: ScreenLength2d,
+    ) -> Self {
+        Self {
+            position,
+            scaling,
+            rotation,
+            reflect_x: reflect.0,
+            reflect_y: reflect.1,
+            base_scale,
+            home: Coord::default(),
+            settings: CameraSettings::default(),
+        }
+    }
+
     pub fn get_zoom(&self) -> f32 {
         self.scaling
     }
@@ -130,6 +150,43 @@ impl Camera {
         Rect::new(pos.x, pos.y, pos.x + length.x, pos.y + length.y)
     }
 
+    /// Rectangle of the scene around the pixel region, it is larger than `region` when the
+    /// camera is rotated
+    pub fn view_bounds(&self) -> Rect {
+        let pixels = self.get_pixel_region();
+        let corners = [
+            pixels.top_left,
+            ScreenCoord::new(pixels.bottom_right.x, pixels.top_left.y),
+            pixels.bottom_right,
+            ScreenCoord::new(pixels.top_left.x, pixels.bottom_right.y),
+        ]
+        .map(|corner| self.project(corner));
+        let (mut min, mut max) = (corners[0], corners[0]);
+        for corner in &corners[1..] {
+            min = Coord::new(min.x.min(corner.x), min.y.min(corner.y));
+            max = Coord::new(max.x.max(corner.x), max.y.max(corner.y));
+        }
+        Rect::new(min.x, min.y, max.x, max.y)
+    }
+
+    /// Pixels around the rectangle of the scene, it is larger than the rectangle when the
+    /// camera is rotated
+    pub fn unproject_bounds(&self, rect: &Rect) -> ScreenRect {
+        let corners = [
+            rect.top_left,
+            Coord::new(rect.bottom_right.x, rect.top_left.y),
+            rect.bottom_right,
+            Coord::new(rect.top_left.x, rect.bottom_right.y),
+        ]
+        .map(|corner| self.unproject(corner));
+        let (mut min, mut max) = (corners[0], corners[0]);
+        for corner in &corners[1..] {
+            min = ScreenCoord::new(min.x.min(corner.x), min.y.min(corner.y));
+            max = ScreenCoord::new(max.x.max(corner.x), max.y.max(corner.y));
+        }
+        ScreenRect::new(min.x, min.y, max.x, max.y)
+    }
+
     /// Return the canvas coordinates of a given pixel point of the apps window.
     /// (0,0) is the top left corner of the window.
     pub fn project(&self, position: ScreenCoord) -> Coord {
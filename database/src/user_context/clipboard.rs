@@ -0,0 +1,365 @@
+use common::{types::Coord, Rgba};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{
+    commands::{CommandsHandler, DeleteLayers, PasteLayers},
+    file_format,
+    scene::{id::IdAllocator, svg::parse_svg, Layer, LayerPlace, LayerType},
+    LayerId, RenderOption, Scene, SelectionModifier, SvgExportOption, SvgImportWarning,
+    UserSelection,
+};
+
+use super::{camera::Camera, SceneUserContext};
+
+/// Copied layers, the data for the editor and the same layers in a SVG document for the
+/// other applications
+#[derive(Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Clipboard {
+    /// A .mvg file of a scene with the copied layers, empty for a clipboard from another
+    /// application
+    pub data: Vec<u8>,
+    pub svg: String,
+}
+
+impl Clipboard {
+    fn from_layers(layers: &[Layer]) -> Result<Self, String> {
+        let scene = Scene::from_layers(Rgba::transparent(), layers.to_vec(), IdAllocator::new());
+        let svg = scene.to_svg(&SvgExportOption::default());
+        let context = SceneUserContext {
+            camera: Camera::new(scene.max_rect().center(), 1.0, 1.0),
+            command_handler: CommandsHandler::from(scene),
+            render_options: RenderOption::default(),
+        };
+        let data = file_format::encode(&context).map_err(|e| e.to_string())?;
+        Ok(Self { data, svg })
+    }
+
+    /// Layers of the data, or the shapes of the SVG document when the data is empty or can't
+    /// be decoded, like from a newer version of the file format
+    fn layers(&self) -> Result<(Vec<Layer>, Vec<SvgImportWarning>), String> {
+        if let Some(layers) = self.data_layers() {
+            return Ok((layers, Vec::new()));
+        }
+        svg_layers(&self.svg)
+    }
+
+    /// The data of an older version is upgraded with the migration chain of the file format
+    fn data_layers(&self) -> Option<Vec<Layer>> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let mut context = file_format::decode(&self.data).ok()?;
+        let scene = std::mem::take(context.command_handler.unsafe_scene_mut());
+        Some(scene.into_layers())
+    }
+}
+
+/// Layers of the shapes of a SVG document from the foreground, without ids
+fn svg_layers(text: &str) -> Result<(Vec<Layer>, Vec<SvgImportWarning>), String> {
+    let import = parse_svg(text).map_err(|e| e.to_string())?;
+    let layers = import
+        .shapes
+        .into_iter()
+        .rev()
+        .map(|shape| Layer::new(LayerId::null(), String::new(), LayerType::Shape(shape)))
+        .collect();
+    Ok((layers, import.warnings))
+}
+
+impl SceneUserContext {
+    /// Copy the layers at the root of the scene containing the selected shapes.
+    /// None if no shape is selected
+    pub fn copy_selection(&self, selected: &UserSelection) -> Result<Option<Clipboard>, String> {
+        let layers = self.selected_layers(selected);
+        if layers.is_empty() {
+            return Ok(None);
+        }
+        Clipboard::from_layers(&layers).map(Some)
+    }
+
+    /// Copy the selection and delete its layers in one undo step
+    pub fn cut_selection(
+        &mut self,
+        selected: &mut UserSelection,
+    ) -> Result<Option<Clipboard>, String> {
+        let Some(clipboard) = self.copy_selection(selected)? else {
+            return Ok(None);
+        };
+        self.command_handler
+            .execute(DeleteLayers::boxed(self.selected_roots(selected)))
+            .map_err(|e| e.to_string())?;
+        selected.shapes.clear();
+        Ok(Some(clipboard))
+    }
+
+    /// Insert the layers of the clipboard above the selection, or on top of the scene without
+    /// selection, with new ids and moved by the offset. The pasted shapes are selected.
+    /// Returns the warnings of the SVG import for a clipboard without data
+    pub fn paste(
+        &mut self,
+        selected: &mut UserSelection,
+        clipboard: &Clipboard,
+        offset: Coord,
+    ) -> Result<Vec<SvgImportWarning>, String> {
+        let (layers, warnings) = clipboard.layers()?;
+        let place = self.paste_place(selected);
+        self.paste_layers(selected, layers, place, offset)?;
+        Ok(warnings)
+    }
+
+    /// Import the shapes of a SVG document on top of the scene in one undo step.
+    /// The imported shapes are selected
+    pub fn import_svg(
+        &mut self,
+        selected: &mut UserSelection,
+        text: &str,
+    ) -> Result<Vec<SvgImportWarning>, String> {
+        let (layers, warnings) = svg_layers(text)?;
+        let top = LayerPlace {
+            parent: None,
+            index: 0,
+        };
+        self.paste_layers(selected, layers, top, Coord::new(0.0, 0.0))?;
+        Ok(warnings)
+    }
+
+    /// Paste a copy of the selection above it, without changing the clipboard
+    pub fn duplicate_selection(
+        &mut self,
+        selected: &mut UserSelection,
+        offset: Coord,
+    ) -> Result<(), String> {
+        let layers = self.selected_layers(selected);
+        let place = self.paste_place(selected);
+        self.paste_layers(selected, layers, place, offset)
+    }
+
+    /// Layers at the root containing the selected shapes, from the foreground
+    fn selected_layers(&self, selected: &UserSelection) -> Vec<Layer> {
+        let roots = self.selected_roots(selected);
+        self.scene()
+            .layers_iter()
+            .filter(|layer| roots.contains(&layer.id))
+            .cloned()
+            .collect()
+    }
+
+    /// Place of the topmost selected layer, or the top of the scene without selection
+    fn paste_place(&self, selected: &UserSelection) -> LayerPlace {
+        let scene = self.scene();
+        self.selected_roots(selected)
+            .into_iter()
+            .filter_map(|id| scene.layer_place(id))
+            .min_by_key(|place| place.index)
+            .unwrap_or(LayerPlace {
+                parent: None,
+                index: 0,
+            })
+    }
+
+    fn paste_layers(
+        &mut self,
+        selected: &mut UserSelection,
+        layers: Vec<Layer>,
+        place: LayerPlace,
+        offset: Coord,
+    ) -> Result<(), String> {
+        if layers.is_empty() {
+            return Ok(());
+        }
+        let count = layers.len();
+        self.command_handler
+            .execute(PasteLayers::boxed(layers, place, offset))
+            .map_err(|e| e.to_string())?;
+
+        let scene = self.scene();
+        let shapes = (place.index..place.index + count)
+            .filter_map(|index| scene.layer_at(LayerPlace { index, ..place }))
+            .flat_map(|id| scene.layer_shapes(id))
+            .collect();
+        selected.select_shapes(shapes, SelectionModifier::Replace);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Affine, types::Coord};
+
+    use crate::{
+        commands::InsertShape, scene::LayerPlace, user_context::user_selection::SelectedShape,
+        DbCoord, LayerId, SceneUserContext, Shape, UserSelection,
+    };
+
+    use super::Clipboard;
+
+    fn context_with_squares(count: usize) -> (SceneUserContext, Vec<LayerId>) {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        let ids = (0..count)
+            .map(|index| {
+                let x = index as f32 * 0.3;
+                let shape = Shape::new_from_lines(
+                    vec![
+                        DbCoord::new(x, 0.0),
+                        DbCoord::new(x + 0.2, 0.0),
+                        DbCoord::new(x + 0.2, 0.2),
+                    ],
+                    Affine::identity(),
+                );
+                context
+                    .command_handler
+                    .execute(InsertShape::boxed(shape))
+                    .unwrap();
+                let top = LayerPlace {
+                    parent: None,
+                    index: 0,
+                };
+                context.scene().layer_at(top).unwrap()
+            })
+            .collect();
+        (context, ids)
+    }
+
+    fn tree(context: &SceneUserContext) -> Vec<LayerId> {
+        let tree = context.scene().get_tree_view();
+        tree.iter().map(|layer| layer.layer_id.into()).collect()
+    }
+
+    #[test]
+    fn given_selection_when_copy_paste_then_new_layer_above_selection_and_selected() {
+        let (mut context, ids) = context_with_squares(3);
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(ids[1]));
+        let expected = context.scene().clone();
+
+        let clipboard = context.copy_selection(&selected).unwrap().unwrap();
+        let offset = Coord::new(0.05, 0.05);
+        let warnings = context.paste(&mut selected, &clipboard, offset).unwrap();
+
+        assert!(warnings.is_empty());
+        let pasted = selected.shapes[0].shape_id;
+        assert_eq!(selected.shapes.len(), 1);
+        assert_eq!(tree(&context), vec![ids[2], pasted, ids[1], ids[0]]);
+        let scene = context.scene();
+        let copy = scene.shape_select(pasted).unwrap();
+        let original = scene.shape_select(ids[1]).unwrap();
+        assert_eq!(copy.path[0].coord(), original.path[0].coord() + offset);
+        assert!(copy
+            .path
+            .iter()
+            .all(|c| original.path.iter().all(|o| o.id != c.id)));
+
+        // Pasted again above the pasted shape
+        context.paste(&mut selected, &clipboard, offset).unwrap();
+        assert_eq!(tree(&context)[1], selected.shapes[0].shape_id);
+
+        context.command_handler.undo().unwrap();
+        context.command_handler.undo().unwrap();
+        assert_eq!(*context.scene(), expected);
+    }
+
+    #[test]
+    fn given_clipboard_without_data_when_paste_then_shapes_of_svg() {
+        let (mut context, ids) = context_with_squares(1);
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(ids[0]));
+        let copied = context.copy_selection(&selected).unwrap().unwrap();
+        let mut selected = UserSelection::default();
+
+        let clipboard = Clipboard {
+            data: Vec::new(),
+            svg: copied.svg.clone(),
+        };
+        context
+            .paste(&mut selected, &clipboard, Coord::new(0.0, 0.0))
+            .unwrap();
+
+        let scene = context.scene();
+        let pasted = scene.shape_select(selected.shapes[0].shape_id).unwrap();
+        let original = scene.shape_select(ids[0]).unwrap();
+        assert_eq!(tree(&context)[0], pasted.id);
+        assert_eq!(pasted.path.len(), original.path.len());
+        for (a, b) in pasted.path.iter().zip(original.path.iter()) {
+            assert!((a.coord().x - b.coord().x).abs() < 1e-4);
+            assert!((a.coord().y - b.coord().y).abs() < 1e-4);
+        }
+
+        let mut other_version = copied.data.clone();
+        other_version[4] = other_version[4].wrapping_add(1);
+        let clipboard = Clipboard {
+            data: other_version,
+            svg: "not svg".to_string(),
+        };
+        assert!(context
+            .paste(&mut selected, &clipboard, Coord::new(0.0, 0.0))
+            .is_err());
+    }
+
+    #[test]
+    fn given_svg_when_import_then_shapes_on_top_in_one_undo_step() {
+        let (mut context, ids) = context_with_squares(2);
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(ids[0]));
+        let expected = context.scene().clone();
+
+        let warnings = context
+            .import_svg(
+                &mut selected,
+                r#"<svg viewBox="0 0 100 100">
+                    <rect width="50" height="50" fill="red"/>
+                    <circle cx="50" cy="50" r="25" fill="blue"/>
+                </svg>"#,
+            )
+            .unwrap();
+
+        assert!(warnings.is_empty());
+        let imported: Vec<LayerId> = selected.shapes.iter().map(|s| s.shape_id).collect();
+        assert_eq!(imported.len(), 2);
+        assert_eq!(
+            tree(&context),
+            vec![imported[0], imported[1], ids[1], ids[0]]
+        );
+        assert!(context.scene().shape_select(imported[0]).is_some());
+
+        context.command_handler.undo().unwrap();
+        assert_eq!(*context.scene(), expected);
+        assert!(context.import_svg(&mut selected, "<html/>").is_err());
+    }
+
+    #[test]
+    fn given_selection_when_cut_or_duplicate_then_one_undo_step() {
+        let (mut context, ids) = context_with_squares(3);
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(ids[0]));
+        selected.shapes.push(SelectedShape::new(ids[2]));
+        let expected = context.scene().clone();
+
+        context
+            .duplicate_selection(&mut selected, Coord::new(0.1, 0.0))
+            .unwrap();
+        let copies: Vec<LayerId> = selected.shapes.iter().map(|s| s.shape_id).collect();
+        assert_eq!(
+            tree(&context),
+            vec![copies[0], copies[1], ids[2], ids[1], ids[0]]
+        );
+        context.command_handler.undo().unwrap();
+        assert_eq!(*context.scene(), expected);
+
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(ids[0]));
+        selected.shapes.push(SelectedShape::new(ids[2]));
+        let clipboard = context.cut_selection(&mut selected).unwrap().unwrap();
+        assert_eq!(tree(&context), vec![ids[1]]);
+        assert!(selected.shapes.is_empty());
+        context.command_handler.undo().unwrap();
+        assert_eq!(*context.scene(), expected);
+
+        context
+            .paste(&mut selected, &clipboard, Coord::new(0.0, 0.0))
+            .unwrap();
+        assert_eq!(tree(&context).len(), 5);
+    }
+}
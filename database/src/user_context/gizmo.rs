@@ -0,0 +1,316 @@
+use common::{
+    pures::{Affine, Vec2},
+    types::{Coord, Rect, ScreenCoord},
+};
+
+use crate::{
+    commands::{Pivot, TransformShapes},
+    user_context::user_selection::{GizmoDrag, GizmoHandle},
+    UserSelection,
+};
+
+use super::SceneUserContext;
+
+/// Distance in pixels to grab a scale handle
+const HANDLE_RADIUS: f32 = 6.0;
+/// Distance in pixels around a corner, outside of the bounds, to grab the rotation
+const ROTATE_RADIUS: f32 = 20.0;
+
+const SCALE_HANDLES: [GizmoHandle; 8] = [
+    GizmoHandle::TopLeft,
+    GizmoHandle::Top,
+    GizmoHandle::TopRight,
+    GizmoHandle::Right,
+    GizmoHandle::BottomRight,
+    GizmoHandle::Bottom,
+    GizmoHandle::BottomLeft,
+    GizmoHandle::Left,
+];
+
+impl GizmoHandle {
+    /// Position of the handle on the bounds, the center for Rotate
+    pub fn coord(&self, bounds: &Rect) -> Coord {
+        let center = bounds.center();
+        let (left, top) = (bounds.top_left.x, bounds.top_left.y);
+        let (right, bottom) = (bounds.bottom_right.x, bounds.bottom_right.y);
+        match self {
+            GizmoHandle::TopLeft => Coord::new(left, top),
+            GizmoHandle::Top => Coord::new(center.x, top),
+            GizmoHandle::TopRight => Coord::new(right, top),
+            GizmoHandle::Right => Coord::new(right, center.y),
+            GizmoHandle::BottomRight => Coord::new(right, bottom),
+            GizmoHandle::Bottom => Coord::new(center.x, bottom),
+            GizmoHandle::BottomLeft => Coord::new(left, bottom),
+            GizmoHandle::Left => Coord::new(left, center.y),
+            GizmoHandle::Rotate => center,
+        }
+    }
+
+    pub fn opposite(&self) -> GizmoHandle {
+        match self {
+            GizmoHandle::TopLeft => GizmoHandle::BottomRight,
+            GizmoHandle::Top => GizmoHandle::Bottom,
+            GizmoHandle::TopRight => GizmoHandle::BottomLeft,
+            GizmoHandle::Right => GizmoHandle::Left,
+            GizmoHandle::BottomRight => GizmoHandle::TopLeft,
+            GizmoHandle::Bottom => GizmoHandle::Top,
+            GizmoHandle::BottomLeft => GizmoHandle::TopRight,
+            GizmoHandle::Left => GizmoHandle::Right,
+            GizmoHandle::Rotate => GizmoHandle::Rotate,
+        }
+    }
+
+    fn is_corner(&self) -> bool {
+        matches!(
+            self,
+            GizmoHandle::TopLeft
+                | GizmoHandle::TopRight
+                | GizmoHandle::BottomRight
+                | GizmoHandle::BottomLeft
+        )
+    }
+}
+
+impl GizmoDrag {
+    /// Transform of the selection from the start of the drag to the position.
+    /// With proportional, the scale is the same on both axis.
+    /// With snap_angle, the rotation is a multiple of the angle in radians.
+    pub fn transform(
+        &self,
+        position: Coord,
+        proportional: bool,
+        snap_angle: Option<f32>,
+    ) -> Affine {
+        let pivot = self.handle.opposite().coord(&self.bounds);
+
+        let affine = match self.handle {
+            GizmoHandle::Rotate => {
+                let from = self.start - pivot;
+                let to = position - pivot;
+                let mut angle = to.y.atan2(to.x) - from.y.atan2(from.x);
+                if let Some(step) = snap_angle.filter(|step| *step > 0.0) {
+                    angle = (angle / step).round() * step;
+                }
+                Affine::from_rotation(angle)
+            }
+            handle => {
+                let from = handle.coord(&self.bounds) - pivot;
+                let to = from + position - self.start;
+                let factor = |to: f32, from: f32| {
+                    if from.abs() < f32::EPSILON {
+                        1.0
+                    } else {
+                        to / from
+                    }
+                };
+                let mut scale = match handle {
+                    GizmoHandle::Top | GizmoHandle::Bottom => Coord::new(1.0, factor(to.y, from.y)),
+                    GizmoHandle::Left | GizmoHandle::Right => Coord::new(factor(to.x, from.x), 1.0),
+                    _ => Coord::new(factor(to.x, from.x), factor(to.y, from.y)),
+                };
+                if proportional {
+                    let uniform = match handle {
+                        GizmoHandle::Top | GizmoHandle::Bottom => scale.y,
+                        GizmoHandle::Left | GizmoHandle::Right => scale.x,
+                        _ if scale.x.abs() > scale.y.abs() => scale.x,
+                        _ => scale.y,
+                    };
+                    scale = Coord::new(uniform, uniform);
+                }
+                Affine::from_scale(scale)
+            }
+        };
+
+        (affine * Affine::from_translate(pivot * -1.0)).translate(pivot)
+    }
+}
+
+impl SceneUserContext {
+    /// Screen position of the scale handles around the selected shapes
+    pub fn gizmo_handles(&self, selected: &UserSelection) -> Vec<(GizmoHandle, ScreenCoord)> {
+        match self.scene().selection_bounds(selected) {
+            Some(bounds) => SCALE_HANDLES
+                .iter()
+                .map(|handle| (*handle, self.camera.unproject(handle.coord(&bounds))))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Gizmo handle under the position, the scale handles are before the rotate zone
+    pub fn gizmo_hit(
+        &self,
+        selected: &UserSelection,
+        position: ScreenCoord,
+    ) -> Option<GizmoHandle> {
+        if selected.pen.is_some() {
+            return None;
+        }
+        let handles = self.gizmo_handles(selected);
+        if let Some((handle, _)) = handles
+            .iter()
+            .find(|(_, coord)| coord.distance(&position) <= HANDLE_RADIUS)
+        {
+            return Some(*handle);
+        }
+
+        let bounds = self.scene().selection_bounds(selected)?;
+        let outside = !bounds.contains(&self.camera.project(position));
+        let near_corner = handles.iter().any(|(handle, coord)| {
+            handle.is_corner() && coord.distance(&position) <= ROTATE_RADIUS
+        });
+        (outside && near_corner).then_some(GizmoHandle::Rotate)
+    }
+
+    /// Start a drag if the position is on a gizmo handle, return true if it started
+    pub fn gizmo_start(&self, selected: &mut UserSelection, position: ScreenCoord) -> bool {
+        let Some(handle) = self.gizmo_hit(selected, position) else {
+            return false;
+        };
+        let Some(bounds) = self.scene().selection_bounds(selected) else {
+            return false;
+        };
+        selected.gizmo = Some(GizmoDrag {
+            handle,
+            bounds,
+            start: self.camera.project(position),
+            applied: Affine::identity(),
+        });
+        true
+    }
+
+    /// Transform the selected shapes to follow the drag, the whole drag is one undo step
+    pub fn gizmo_drag(
+        &mut self,
+        selected: &mut UserSelection,
+        position: ScreenCoord,
+        proportional: bool,
+        snap_angle: Option<f32>,
+    ) {
+        let position = self.camera.project(position);
+        let Some(drag) = selected.gizmo.as_mut() else {
+            return;
+        };
+        let transform = drag.transform(position, proportional, snap_angle);
+        // A flat selection can't be transformed back
+        if transform.determinant().abs() < f32::EPSILON || transform == drag.applied {
+            return;
+        }
+
+        let step = transform * drag.applied.inverse();
+        drag.applied = transform;
+        if let Err(e) = self.command_handler.execute(TransformShapes::boxed(
+            selected.shapes.iter().map(|s| s.shape_id).collect(),
+            step,
+            Pivot::Custom(Coord::new(0.0, 0.0)),
+            false,
+        )) {
+            log::error!("{:?}", e)
+        }
+        self.guides_update(selected);
+    }
+
+    pub fn gizmo_end(&mut self, selected: &mut UserSelection) {
+        selected.gizmo = None;
+        self.drag_end(selected);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::Vec2,
+        types::{Coord, ScreenCoord},
+    };
+
+    use crate::{
+        commands::InsertShape,
+        user_context::user_selection::{GizmoHandle, SelectedShape},
+        SceneUserContext, Shape, UserSelection,
+    };
+
+    fn context_with_square() -> (SceneUserContext, UserSelection) {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        context.camera.set_pixel_region(500.0, 500.0);
+        let shape_id = context.command_handler.layer_id_reserve();
+        let mut shape = Shape::quick_from_string(
+            "M 0 0 C 0 0 0.5 0 0.5 0 C 0.5 0 0.5 0.5 0.5 0.5 \
+            C 0.5 0.5 0 0.5 0 0.5 C 0 0.5 0 0 0 0 Z",
+        );
+        shape.id = shape_id;
+        context
+            .command_handler
+            .execute(InsertShape::boxed(shape))
+            .unwrap();
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(shape_id));
+        (context, selected)
+    }
+
+    fn offset(coord: ScreenCoord, x: f32, y: f32) -> ScreenCoord {
+        ScreenCoord::new(coord.x + x, coord.y + y)
+    }
+
+    #[test]
+    fn given_selection_when_hit_then_handle_or_rotate() {
+        let (context, selected) = context_with_square();
+        let corner = context.camera.unproject(Coord::new(0.5, 0.5));
+        let center = context.camera.unproject(Coord::new(0.25, 0.25));
+
+        assert_eq!(
+            context.gizmo_hit(&selected, offset(corner, 2.0, 2.0)),
+            Some(GizmoHandle::BottomRight)
+        );
+        let outside = context.camera.unproject(Coord::new(0.53, 0.53));
+        assert_eq!(
+            context.gizmo_hit(&selected, outside),
+            Some(GizmoHandle::Rotate)
+        );
+        assert_eq!(context.gizmo_hit(&selected, center), None);
+    }
+
+    #[test]
+    fn given_corner_drag_when_proportional_then_uniform_scale_in_one_undo() {
+        let (mut context, mut selected) = context_with_square();
+        let expected = context.scene().clone();
+        let corner = context.camera.unproject(Coord::new(0.5, 0.5));
+
+        assert!(context.gizmo_start(&mut selected, corner));
+        let middle = context.camera.unproject(Coord::new(0.75, 0.6));
+        context.gizmo_drag(&mut selected, middle, true, None);
+        let end = context.camera.unproject(Coord::new(1.0, 0.7));
+        context.gizmo_drag(&mut selected, end, true, None);
+        context.gizmo_end(&mut selected);
+
+        let bounds = context.scene().selection_bounds(&selected).unwrap();
+        assert!(bounds.top_left.distance(&Coord::new(0.0, 0.0)) < 0.001);
+        assert!(bounds.bottom_right.distance(&Coord::new(1.0, 1.0)) < 0.001);
+
+        context.command_handler.undo().unwrap();
+        assert_eq!(context.scene(), &expected);
+    }
+
+    #[test]
+    fn given_rotate_drag_when_snap_then_angle_is_multiple() {
+        let (mut context, mut selected) = context_with_square();
+        let outside = context.camera.unproject(Coord::new(0.53, 0.53));
+
+        assert!(context.gizmo_start(&mut selected, outside));
+        // 50 degrees from the start around the center
+        let angle = std::f32::consts::FRAC_PI_4 + 50.0_f32.to_radians();
+        let radius = Coord::new(0.28, 0.28).norm();
+        let position = Coord::new(0.25 + radius * angle.cos(), 0.25 + radius * angle.sin());
+        context.gizmo_drag(
+            &mut selected,
+            context.camera.unproject(position),
+            false,
+            Some(15.0_f32.to_radians()),
+        );
+
+        let shape_id = selected.shapes[0].shape_id;
+        let shape = context.scene().shape_select(shape_id).unwrap();
+        // The corner at -135 degrees turned by 45 degrees
+        let rotated = shape.path[0].coord() - Coord::new(0.25, 0.25);
+        assert!((rotated.y.atan2(rotated.x) + std::f32::consts::FRAC_PI_2).abs() < 0.001);
+    }
+}
@@ -0,0 +1,323 @@
+use common::{
+    pures::Vec2,
+    types::{Coord, Rect, ScreenCoord, ScreenLength2d},
+    Rgba,
+};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{DrawingContext, SelectedLevel, StrokeStyle, UserSelection};
+
+use super::SceneUserContext;
+
+/// Half length in pixels of the cross at the ends of a guide
+const GUIDE_MARK: f32 = 3.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuideAxis {
+    /// Line of the same x
+    Vertical,
+    /// Line of the same y
+    Horizontal,
+}
+
+/// Line between a dragged point and a point of another shape with the same x or y
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guide {
+    pub axis: GuideAxis,
+    /// Dragged point moved on the line
+    pub from: Coord,
+    pub to: Coord,
+}
+
+impl Guide {
+    pub fn distance(&self) -> f32 {
+        self.from.distance(&self.to)
+    }
+}
+
+/// Distance of a guide to show at the middle of its line, on the screen
+#[derive(Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GuideLabel {
+    pub position: ScreenCoord,
+    /// Length of the guide in pixels at the zoom of the camera
+    pub distance: f32,
+}
+
+impl SceneUserContext {
+    /// Guides of the dragged points with the anchors and the bounds of the other shapes in the
+    /// view. The dragged points are the selected coords, or the corners, the middle of the
+    /// edges and the center of the bounds of the selected shapes.
+    /// For each line of alignment, the guide is the shortest between a dragged point and an
+    /// aligned point. Two points are aligned under the snapping tolerance
+    pub fn alignment_guides(&self, selected: &UserSelection) -> Vec<Guide> {
+        let scene = self.scene();
+        let level = selected.get_selected_level();
+        let dragged: Vec<Coord> = match level {
+            SelectedLevel::None => return Vec::new(),
+            SelectedLevel::Shape => match scene.selection_bounds(selected) {
+                Some(bounds) => rect_points(&bounds),
+                None => return Vec::new(),
+            },
+            SelectedLevel::Coord => selected
+                .shapes
+                .iter()
+                .filter_map(|s| Some((scene.shape_select(s.shape_id)?, &s.coords)))
+                .flat_map(|(shape, ids)| ids.iter().filter_map(|id| shape.coord_select(*id)))
+                .map(|db_coord| db_coord.coord())
+                .collect(),
+        };
+
+        let mut targets = Vec::new();
+        for (shape, _) in scene.shapes_visible_in(false, &self.camera.view_bounds()) {
+            let moved = selected.shapes.iter().find(|s| s.shape_id == shape.id);
+            if level == SelectedLevel::Shape && moved.is_some() {
+                continue;
+            }
+            let anchors = shape
+                .paths()
+                .flat_map(|path| path.iter().step_by(3))
+                .filter(|c| moved.is_none_or(|s| !s.coords.contains(&c.id)))
+                .map(|c| c.coord());
+            targets.extend(anchors);
+            // The bounds of a shape with dragged coords move with them
+            if let Some(bounds) = shape.bounding_box().filter(|_| moved.is_none()) {
+                targets.extend(rect_points(&bounds));
+            }
+        }
+
+        let tolerance = selected.snap.tolerance;
+        let tolerance = ScreenLength2d::new(tolerance, tolerance);
+        let tolerance = self.camera.transform_to_length2d(tolerance);
+        let mut found: Vec<Guide> = Vec::new();
+        for point in dragged {
+            let vertical = closest_aligned(&targets, |t| {
+                ((t.x - point.x).abs() <= tolerance.x.abs()).then_some((t.y - point.y).abs())
+            });
+            let horizontal = closest_aligned(&targets, |t| {
+                ((t.y - point.y).abs() <= tolerance.y.abs()).then_some((t.x - point.x).abs())
+            });
+            found.extend(vertical.map(|to| Guide {
+                axis: GuideAxis::Vertical,
+                from: Coord::new(to.x, point.y),
+                to,
+            }));
+            found.extend(horizontal.map(|to| Guide {
+                axis: GuideAxis::Horizontal,
+                from: Coord::new(point.x, to.y),
+                to,
+            }));
+        }
+
+        found.sort_by(|a, b| a.distance().total_cmp(&b.distance()));
+        let mut guides: Vec<Guide> = Vec::new();
+        for guide in found {
+            let same_line = guides.iter().any(|other| match (guide.axis, other.axis) {
+                (GuideAxis::Vertical, GuideAxis::Vertical) => {
+                    (guide.to.x - other.to.x).abs() <= tolerance.x.abs()
+                }
+                (GuideAxis::Horizontal, GuideAxis::Horizontal) => {
+                    (guide.to.y - other.to.y).abs() <= tolerance.y.abs()
+                }
+                _ => false,
+            });
+            if !same_line {
+                guides.push(guide);
+            }
+        }
+        guides
+    }
+
+    /// Labels of the guides of the current drag
+    pub fn guide_labels(&self, selected: &UserSelection) -> Vec<GuideLabel> {
+        selected
+            .guides
+            .iter()
+            .map(|guide| GuideLabel {
+                position: self.camera.unproject((guide.from + guide.to) / 2.0),
+                distance: self.guide_pixels(guide),
+            })
+            .collect()
+    }
+
+    /// Length of the guide on the screen in pixels
+    fn guide_pixels(&self, guide: &Guide) -> f32 {
+        let from = self.camera.unproject(guide.from);
+        let to = self.camera.unproject(guide.to);
+        (to.x - from.x).hypot(to.y - from.y)
+    }
+
+    /// Show the guides of the selection until the end of the drag
+    pub(super) fn guides_update(&self, selected: &mut UserSelection) {
+        selected.guides = self.alignment_guides(selected);
+    }
+
+    /// End of a drag of coords or shapes, the guides are hidden
+    pub fn drag_end(&self, selected: &mut UserSelection) {
+        selected.guides.clear();
+    }
+
+    /// Lines of the guides with a cross at both ends and their distance at the middle
+    pub(super) fn draw_guides(
+        &self,
+        selected: &UserSelection,
+        ctx: &mut dyn DrawingContext,
+    ) -> Result<(), String> {
+        if selected.guides.is_empty() {
+            return Ok(());
+        }
+        let t = ctx.get_transform()?;
+        ctx.set_fill(&Rgba::transparent())?;
+        let color = Rgba::new(0xFF, 0x00, 0xFF, 255);
+        ctx.set_stroke(&color, 1.0, &StrokeStyle::default())?;
+        for guide in &selected.guides {
+            let (from, to) = (t * guide.from, t * guide.to);
+            ctx.start_shape(&from)?;
+            ctx.move_line(&to)?;
+            for end in [from, to] {
+                let (x, y, mark) = (end.x, end.y, GUIDE_MARK);
+                ctx.start_subpath(&Coord::new(x - mark, y - mark))?;
+                ctx.move_line(&Coord::new(x + mark, y + mark))?;
+                ctx.start_subpath(&Coord::new(x - mark, y + mark))?;
+                ctx.move_line(&Coord::new(x + mark, y - mark))?;
+            }
+            ctx.end_shape()?;
+        }
+        ctx.set_stroke(&Rgba::transparent(), 0.0, &StrokeStyle::default())?;
+        ctx.set_fill(&color)?;
+        for guide in &selected.guides {
+            let middle = t * ((guide.from + guide.to) / 2.0);
+            let label = format!("{:.0}", self.guide_pixels(guide));
+            ctx.fill_text(
+                &label,
+                &Coord::new(middle.x + GUIDE_MARK, middle.y - GUIDE_MARK),
+            )?;
+        }
+        ctx.set_fill(&Rgba::transparent())?;
+        Ok(())
+    }
+}
+
+/// Corners, middle of the edges and center of the rect
+fn rect_points(rect: &Rect) -> Vec<Coord> {
+    let center = rect.center();
+    let xs = [rect.top_left.x, center.x, rect.bottom_right.x];
+    let ys = [rect.top_left.y, center.y, rect.bottom_right.y];
+    xs.iter()
+        .flat_map(|x| ys.iter().map(|y| Coord::new(*x, *y)))
+        .collect()
+}
+
+/// Target with the smallest distance, the distance is None when the target is not aligned.
+/// A target on the point has no guide
+fn closest_aligned(targets: &[Coord], distance: impl Fn(&Coord) -> Option<f32>) -> Option<Coord> {
+    targets
+        .iter()
+        .filter_map(|target| Some((*target, distance(target)?)))
+        .filter(|(_, distance)| *distance > 0.0)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(target, _)| target)
+}
+
+#[cfg(test)]
+mod test {
+    use common::{pures::Affine, types::Coord};
+
+    use crate::{
+        commands::InsertShape, scene::render::MockDrawingContext,
+        user_context::user_selection::SelectedShape, DbCoord, LayerId, SceneUserContext, Shape,
+        UserSelection,
+    };
+
+    use super::GuideAxis;
+
+    fn insert_square(context: &mut SceneUserContext, corner: Coord, size: f32) -> LayerId {
+        let (x, y) = (corner.x, corner.y);
+        let mut shape = Shape::new_from_lines(
+            vec![
+                DbCoord::new(x, y),
+                DbCoord::new(x + size, y),
+                DbCoord::new(x + size, y + size),
+                DbCoord::new(x, y + size),
+            ],
+            Affine::identity(),
+        );
+        shape.id = context.command_handler.layer_id_reserve();
+        let id = shape.id;
+        context
+            .command_handler
+            .execute(InsertShape::boxed(shape))
+            .unwrap();
+        id
+    }
+
+    fn context_with_squares() -> (SceneUserContext, LayerId) {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        context.camera.set_pixel_region(500.0, 500.0);
+        insert_square(&mut context, Coord::new(0.0, 0.0), 0.2);
+        let moved = insert_square(&mut context, Coord::new(0.5, 0.03), 0.2);
+        (context, moved)
+    }
+
+    #[test]
+    fn given_shape_dragged_in_line_when_translate_then_guides_until_drag_end() {
+        let (mut context, moved) = context_with_squares();
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(moved));
+
+        let start = context.camera.unproject(Coord::new(0.6, 0.1));
+        // The top of the moved square at 0.05 is off the lines of the other square
+        let end = context.camera.unproject(Coord::new(0.6, 0.12));
+        context.translate_shapes_of(&mut selected, start, end);
+        assert!(selected.guides.is_empty());
+
+        let start = end;
+        let end = context.camera.unproject(Coord::new(0.6, 0.07));
+        context.translate_shapes_of(&mut selected, start, end);
+        // The top, the center and the bottom of the squares line up
+        let horizontal: Vec<_> = selected
+            .guides
+            .iter()
+            .filter(|guide| guide.axis == GuideAxis::Horizontal)
+            .collect();
+        assert_eq!(horizontal.len(), 3);
+        assert!(horizontal
+            .iter()
+            .all(|guide| (guide.to.x - 0.2).abs() < 0.001));
+        assert!((horizontal[0].distance() - 0.3).abs() < 0.001);
+        // The camera shows the scene from -1.0 to 1.0 in 500 pixels
+        let labels = context.guide_labels(&selected);
+        assert_eq!(labels.len(), 3);
+        assert!((labels[0].distance - 75.0).abs() < 0.01);
+        context
+            .draw(&selected, &mut MockDrawingContext::default())
+            .unwrap();
+
+        context.drag_end(&mut selected);
+        assert!(selected.guides.is_empty());
+    }
+
+    #[test]
+    fn given_coord_in_line_with_anchor_when_guides_then_to_closest_anchor() {
+        let (context, moved) = context_with_squares();
+        let shape = context.scene().shape_select(moved).unwrap();
+        // The corner at (0.7, 0.03) has the other corners of its shape as targets
+        let corner = shape.path[3].id;
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape {
+            shape_id: moved,
+            coords: vec![corner],
+        });
+
+        let guides = context.alignment_guides(&selected);
+
+        assert!(guides
+            .iter()
+            .any(|guide| guide.axis == GuideAxis::Vertical && guide.to == Coord::new(0.7, 0.23)));
+        assert!(guides
+            .iter()
+            .any(|guide| guide.axis == GuideAxis::Horizontal && guide.to == Coord::new(0.5, 0.03)));
+    }
+}
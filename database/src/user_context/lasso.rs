@@ -0,0 +1,197 @@
+use common::{
+    pures::Affine,
+    types::{Rect, ScreenCoord},
+};
+
+use crate::{
+    user_context::{
+        marquee::curves_points,
+        user_selection::{shapes_selected_by, SelectionModifier},
+    },
+    DbCoord, FillRule, SelectedLevel, Shape, UserSelection,
+};
+
+use super::SceneUserContext;
+
+impl UserSelection {
+    /// Select in the polygon drawn on the screen through the points, closed from the last point
+    /// to the first. Inside is found with the even-odd rule, a lasso that crosses itself has holes.
+    /// At the shape level, the visible and unlocked shapes with every curve inside are selected,
+    /// a group at the root of the scene when all its shapes are inside.
+    /// At the coord level, the coords of the selected shapes inside the lasso are selected
+    pub fn lasso_select(
+        &mut self,
+        canvas_context: &SceneUserContext,
+        points: &[ScreenCoord],
+        level: SelectedLevel,
+        modifier: SelectionModifier,
+    ) {
+        let coords = points
+            .iter()
+            .map(|point| DbCoord::from(canvas_context.camera.project(*point)))
+            .collect();
+        let mut lasso = Shape::new_from_lines(coords, Affine::identity());
+        lasso.fill_rule = FillRule::EvenOdd;
+        // Less than three points has no inside
+        let is_empty = points.len() < 3;
+
+        match level {
+            SelectedLevel::None => {}
+            SelectedLevel::Shape => {
+                let area = lasso
+                    .bounding_box()
+                    .unwrap_or(Rect::new(0.0, 0.0, 0.0, 0.0));
+                let shapes = shapes_selected_by(
+                    canvas_context,
+                    &area,
+                    |shape| {
+                        !is_empty
+                            && shape.curves_len() > 0
+                            && curves_points(shape).flatten().all(|c| lasso.contains(&c))
+                    },
+                    true,
+                );
+                self.select_shapes(shapes, modifier);
+            }
+            SelectedLevel::Coord => {
+                self.select_coords(
+                    canvas_context,
+                    |coord| !is_empty && lasso.contains(coord),
+                    modifier,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, ScreenCoord};
+
+    use crate::{
+        commands::InsertShape, user_context::user_selection::SelectedShape, LayerId,
+        SceneUserContext, SelectedLevel, SelectionModifier, Shape, UserSelection,
+    };
+
+    fn context_with_zigzag() -> (SceneUserContext, LayerId) {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        context.camera.set_pixel_region(500.0, 500.0);
+        let mut shape =
+            Shape::quick_from_string("M 0 0 L 0.1 0.3 L 0.2 0 L 0.3 0.3 L 0.4 0 L 0.5 0.3 L 0.6 0");
+        shape.id = context.command_handler.layer_id_reserve();
+        let id = shape.id;
+        context
+            .command_handler
+            .execute(InsertShape::boxed(shape))
+            .unwrap();
+        (context, id)
+    }
+
+    fn lasso(context: &SceneUserContext, coords: &[(f32, f32)]) -> Vec<ScreenCoord> {
+        coords
+            .iter()
+            .map(|(x, y)| context.camera.unproject(Coord::new(*x, *y)))
+            .collect()
+    }
+
+    fn selected_coords(context: &SceneUserContext, selected: &UserSelection) -> Vec<Coord> {
+        let shape = context
+            .scene()
+            .shape_select(selected.shapes[0].shape_id)
+            .unwrap();
+        let mut coords: Vec<Coord> = shape
+            .paths()
+            .flatten()
+            .filter(|c| selected.shapes[0].coords.contains(&c.id))
+            .map(|c| c.coord())
+            .collect();
+        coords.dedup();
+        coords
+    }
+
+    #[test]
+    fn given_zigzag_when_lasso_bottom_anchors_then_only_they_selected() {
+        let (context, id) = context_with_zigzag();
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(id));
+        // Concave lasso around the anchors at y = 0.3, between the anchors at y = 0
+        let points = lasso(
+            &context,
+            &[
+                (0.05, 0.35),
+                (0.05, 0.25),
+                (0.25, 0.25),
+                (0.25, 0.35),
+                (0.45, 0.35),
+                (0.45, 0.25),
+                (0.55, 0.25),
+                (0.55, 0.4),
+                (0.05, 0.4),
+            ],
+        );
+
+        selected.lasso_select(
+            &context,
+            &points,
+            SelectedLevel::Coord,
+            SelectionModifier::Replace,
+        );
+
+        assert_eq!(
+            selected_coords(&context, &selected),
+            vec![Coord::new(0.1, 0.3), Coord::new(0.5, 0.3)]
+        );
+    }
+
+    #[test]
+    fn given_selected_coords_when_lasso_add_or_subtract_then_combined() {
+        let (context, id) = context_with_zigzag();
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(id));
+        let first = lasso(
+            &context,
+            &[(-0.05, -0.05), (0.15, -0.05), (0.15, 0.35), (-0.05, 0.35)],
+        );
+        let last = lasso(
+            &context,
+            &[(0.45, 0.35), (0.55, -0.05), (0.65, -0.05), (0.65, 0.35)],
+        );
+        let (level, modifier) = (SelectedLevel::Coord, SelectionModifier::Replace);
+
+        selected.lasso_select(&context, &first, level, modifier);
+        assert_eq!(
+            selected_coords(&context, &selected),
+            vec![Coord::new(0.0, 0.0), Coord::new(0.1, 0.3)]
+        );
+
+        selected.lasso_select(&context, &last, level, SelectionModifier::Add);
+        assert_eq!(selected_coords(&context, &selected).len(), 4);
+
+        selected.lasso_select(&context, &first, level, SelectionModifier::Subtract);
+        assert_eq!(
+            selected_coords(&context, &selected),
+            vec![Coord::new(0.5, 0.3), Coord::new(0.6, 0.0)]
+        );
+    }
+
+    #[test]
+    fn given_shape_when_lasso_around_or_across_then_selected_only_inside() {
+        let (context, id) = context_with_zigzag();
+        let mut selected = UserSelection::default();
+        let around = lasso(
+            &context,
+            &[(-0.1, -0.1), (0.7, -0.1), (0.7, 0.4), (-0.1, 0.4)],
+        );
+        let across = lasso(
+            &context,
+            &[(-0.1, -0.1), (0.3, -0.1), (0.3, 0.4), (-0.1, 0.4)],
+        );
+
+        let modifier = SelectionModifier::Add;
+        selected.lasso_select(&context, &across, SelectedLevel::Shape, modifier);
+        assert!(selected.shapes.is_empty());
+
+        selected.lasso_select(&context, &around, SelectedLevel::Shape, modifier);
+        assert_eq!(selected.shapes[0].shape_id, id);
+    }
+}
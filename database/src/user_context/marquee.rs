@@ -0,0 +1,411 @@
+use common::types::{Coord, Rect, ScreenCoord};
+
+use crate::{
+    user_context::user_selection::{shapes_selected_by, MarqueeMode, SelectionModifier},
+    SelectedLevel, Shape, UserSelection,
+};
+
+use super::{camera::Camera, SceneUserContext};
+
+/// Points taken on each curve to find if it crosses the marquee
+const CURVE_SAMPLES: usize = 16;
+
+/// Rectangle dragged on the screen, in the scene it is a quad when the camera is rotated
+#[derive(Debug, Clone, Copy)]
+pub struct Marquee {
+    corners: [Coord; 4],
+}
+
+impl Marquee {
+    pub fn new(camera: &Camera, start: ScreenCoord, end: ScreenCoord) -> Self {
+        Self {
+            corners: [
+                camera.project(start),
+                camera.project(ScreenCoord::new(end.x, start.y)),
+                camera.project(end),
+                camera.project(ScreenCoord::new(start.x, end.y)),
+            ],
+        }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Coord, Coord)> + '_ {
+        (0..4).map(|i| (self.corners[i], self.corners[(i + 1) % 4]))
+    }
+
+    fn bounds(&self) -> Rect {
+        let first = Rect {
+            top_left: self.corners[0],
+            bottom_right: self.corners[0],
+        };
+        self.corners.iter().fold(first, |rect, corner| {
+            Rect::max(
+                &rect,
+                &Rect {
+                    top_left: *corner,
+                    bottom_right: *corner,
+                },
+            )
+        })
+    }
+
+    /// Return true if the coord is inside the marquee or on its edges.
+    /// A marquee without area contains nothing
+    pub fn contains(&self, coord: &Coord) -> bool {
+        let area: f32 = self
+            .edges()
+            .map(|(a, b)| cross(&a, &b, &Coord::new(0.0, 0.0)))
+            .sum();
+        if area.abs() < f32::EPSILON {
+            return false;
+        }
+        // The sign of the area keeps the side of the edges right when the camera is reflected
+        self.edges()
+            .all(|(a, b)| cross(&a, &b, coord) * area.signum() >= 0.0)
+    }
+
+    /// Return true if every curve of the shape is inside the marquee.
+    /// The points along the curves are checked, the handles can be outside, like for the lasso
+    pub fn encloses(&self, shape: &Shape) -> bool {
+        shape.curves_len() > 0
+            && curves_points(shape)
+                .flatten()
+                .all(|point| self.contains(&point))
+    }
+
+    /// Return true if a curve of the shape is inside or crosses the marquee,
+    /// or if the marquee is inside the shape
+    pub fn touches(&self, shape: &Shape) -> bool {
+        match shape.quick_bounding_box() {
+            Some(bounds) if bounds.intersect(&self.bounds()) => {}
+            _ => return false,
+        }
+
+        for points in curves_points(shape) {
+            if points.iter().any(|point| self.contains(point)) {
+                return true;
+            }
+            let crosses = points.windows(2).any(|segment| {
+                self.edges()
+                    .any(|(a, b)| segments_cross(&segment[0], &segment[1], &a, &b))
+            });
+            if crosses {
+                return true;
+            }
+        }
+
+        shape.contains(&self.corners[0])
+    }
+
+    fn select(&self, shape: &Shape, mode: MarqueeMode) -> bool {
+        match mode {
+            MarqueeMode::Touch => self.touches(shape),
+            MarqueeMode::Enclose => self.encloses(shape),
+        }
+    }
+}
+
+/// Points along each curve of the shape, from its start to its end
+pub(super) fn curves_points(shape: &Shape) -> impl Iterator<Item = Vec<Coord>> + '_ {
+    shape.curves().map(|curve| {
+        (0..=CURVE_SAMPLES)
+            .map(|i| curve.cubic_bezier(i as f32 / CURVE_SAMPLES as f32))
+            .collect()
+    })
+}
+
+/// Cross product of ab and ac, positive when c is on the left of ab
+fn cross(a: &Coord, b: &Coord, c: &Coord) -> f32 {
+    (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+/// Return true if the segment pq crosses or touches the segment ab
+fn segments_cross(p: &Coord, q: &Coord, a: &Coord, b: &Coord) -> bool {
+    let (d1, d2) = (cross(a, b, p), cross(a, b, q));
+    let (d3, d4) = (cross(p, q, a), cross(p, q, b));
+    d1 * d2 <= 0.0 && d3 * d4 <= 0.0 && (d1 != d2 || d3 != d4)
+}
+
+impl SceneUserContext {
+    /// Select in the rectangle dragged on the screen from start to end.
+    /// At the shape level, the visible and unlocked shapes that touch or are enclosed by the
+    /// marquee are selected, a group at the root of the scene is selected as a unit.
+    /// At the coord level, the coords of the selected shapes inside the marquee are selected
+    pub fn marquee_select(
+        &self,
+        selected: &mut UserSelection,
+        start: ScreenCoord,
+        end: ScreenCoord,
+        level: SelectedLevel,
+        mode: MarqueeMode,
+        modifier: SelectionModifier,
+    ) {
+        let marquee = Marquee::new(&self.camera, start, end);
+        match level {
+            SelectedLevel::None => {}
+            SelectedLevel::Shape => {
+                let shapes = shapes_selected_by(
+                    self,
+                    &marquee.bounds(),
+                    |shape| marquee.select(shape, mode),
+                    mode == MarqueeMode::Enclose,
+                );
+                selected.select_shapes(shapes, modifier);
+            }
+            SelectedLevel::Coord => {
+                selected.select_coords(self, |coord| marquee.contains(coord), modifier);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::Vec2,
+        types::{Coord, ScreenCoord, ScreenLength2d},
+    };
+
+    use crate::{
+        commands::{Group, InsertShape},
+        user_context::user_selection::SelectedShape,
+        LayerId, MarqueeMode, SceneUserContext, SelectedLevel, SelectionModifier, Shape,
+        UserSelection,
+    };
+
+    fn square(x: f32, y: f32) -> Shape {
+        Shape::quick_from_string(&format!(
+            "M {x} {y} C {x} {y} {x1} {y} {x1} {y} C {x1} {y} {x1} {y1} {x1} {y1} \
+            C {x1} {y1} {x} {y1} {x} {y1} C {x} {y1} {x} {y} {x} {y} Z",
+            x1 = x + 0.2,
+            y1 = y + 0.2
+        ))
+    }
+
+    fn context_with_squares(positions: &[(f32, f32)]) -> (SceneUserContext, Vec<LayerId>) {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        context.camera.set_pixel_region(500.0, 500.0);
+        let mut ids = Vec::new();
+        for (x, y) in positions {
+            let mut shape = square(*x, *y);
+            shape.id = context.command_handler.layer_id_reserve();
+            ids.push(shape.id);
+            context
+                .command_handler
+                .execute(InsertShape::boxed(shape))
+                .unwrap();
+        }
+        (context, ids)
+    }
+
+    /// Select the shapes in the marquee between two coords of the scene
+    fn marquee(
+        context: &SceneUserContext,
+        selected: &mut UserSelection,
+        (from, to): (Coord, Coord),
+        mode: MarqueeMode,
+        modifier: SelectionModifier,
+    ) {
+        let start = context.camera.unproject(from);
+        let end = context.camera.unproject(to);
+        context.marquee_select(selected, start, end, SelectedLevel::Shape, mode, modifier);
+    }
+
+    fn selected_ids(selected: &UserSelection) -> Vec<LayerId> {
+        let mut ids: Vec<LayerId> = selected.shapes.iter().map(|s| s.shape_id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn given_squares_when_marquee_touch_or_enclose_then_shapes_selected() {
+        let (context, ids) = context_with_squares(&[(0.0, 0.0), (0.5, 0.0)]);
+        let mut selected = UserSelection::default();
+        let over_tops = (Coord::new(-0.1, -0.1), Coord::new(0.6, 0.1));
+        let around_first = (Coord::new(-0.1, -0.1), Coord::new(0.3, 0.3));
+        let replace = SelectionModifier::Replace;
+
+        marquee(
+            &context,
+            &mut selected,
+            over_tops,
+            MarqueeMode::Touch,
+            replace,
+        );
+        assert_eq!(selected_ids(&selected), ids);
+
+        marquee(
+            &context,
+            &mut selected,
+            over_tops,
+            MarqueeMode::Enclose,
+            replace,
+        );
+        assert!(selected.shapes.is_empty());
+
+        marquee(
+            &context,
+            &mut selected,
+            around_first,
+            MarqueeMode::Enclose,
+            replace,
+        );
+        assert_eq!(selected_ids(&selected), vec![ids[0]]);
+    }
+
+    #[test]
+    fn given_marquee_inside_shape_when_touch_then_shape_selected() {
+        let (context, ids) = context_with_squares(&[(0.0, 0.0)]);
+        let mut selected = UserSelection::default();
+        let inside = (Coord::new(0.05, 0.05), Coord::new(0.15, 0.15));
+
+        marquee(
+            &context,
+            &mut selected,
+            inside,
+            MarqueeMode::Touch,
+            SelectionModifier::Replace,
+        );
+
+        assert_eq!(selected_ids(&selected), ids);
+    }
+
+    #[test]
+    fn given_selection_when_marquee_add_or_subtract_then_combined() {
+        let (context, ids) = context_with_squares(&[(0.0, 0.0), (0.5, 0.0), (1.0, 0.0)]);
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(ids[0]));
+        let last_two = (Coord::new(0.4, -0.1), Coord::new(1.3, 0.3));
+        let first_two = (Coord::new(-0.1, -0.1), Coord::new(0.8, 0.3));
+        let mode = MarqueeMode::Enclose;
+
+        marquee(
+            &context,
+            &mut selected,
+            last_two,
+            mode,
+            SelectionModifier::Add,
+        );
+        assert_eq!(selected_ids(&selected), ids);
+
+        marquee(
+            &context,
+            &mut selected,
+            first_two,
+            mode,
+            SelectionModifier::Subtract,
+        );
+        assert_eq!(selected_ids(&selected), vec![ids[2]]);
+    }
+
+    #[test]
+    fn given_rotated_and_reflected_camera_when_marquee_coords_then_coords_inside() {
+        let (mut context, ids) = context_with_squares(&[(0.0, 0.0)]);
+        context.camera.set_rotation(std::f32::consts::FRAC_PI_4);
+        context.camera.set_reflect_x(true);
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(ids[0]));
+
+        // Screen square around the first anchor, smaller than the square in the scene
+        let scale = context
+            .camera
+            .transform_to_length2d(ScreenLength2d::new(1.0, 1.0))
+            .x;
+        let radius = 0.05 / scale;
+        let center = context.camera.unproject(Coord::new(0.0, 0.0));
+        let start = ScreenCoord::new(center.x - radius, center.y - radius);
+        let end = ScreenCoord::new(center.x + radius, center.y + radius);
+        let (level, mode) = (SelectedLevel::Coord, MarqueeMode::Touch);
+        context.marquee_select(
+            &mut selected,
+            start,
+            end,
+            level,
+            mode,
+            SelectionModifier::Replace,
+        );
+
+        let shape = context.scene().shape_select(ids[0]).unwrap();
+        let coords = selected.shapes[0].coords.clone();
+        assert!(!coords.is_empty());
+        for coord in shape.paths().flatten() {
+            let at_origin = coord.coord().distance(&Coord::new(0.0, 0.0)) < 0.001;
+            assert_eq!(coords.contains(&coord.id), at_origin);
+        }
+        assert_eq!(selected.get_selected_level(), SelectedLevel::Coord);
+
+        context.marquee_select(
+            &mut selected,
+            start,
+            end,
+            level,
+            mode,
+            SelectionModifier::Subtract,
+        );
+        assert_eq!(selected.get_selected_level(), SelectedLevel::Shape);
+    }
+
+    #[test]
+    fn given_group_when_marquee_touch_one_then_whole_group_selected() {
+        let (mut context, ids) = context_with_squares(&[(0.0, 0.0), (0.5, 0.0)]);
+        context
+            .command_handler
+            .execute(Group::boxed(ids.clone()))
+            .unwrap();
+        let mut selected = UserSelection::default();
+        let around_first = (Coord::new(-0.1, -0.1), Coord::new(0.3, 0.3));
+        let replace = SelectionModifier::Replace;
+
+        marquee(
+            &context,
+            &mut selected,
+            around_first,
+            MarqueeMode::Enclose,
+            replace,
+        );
+        assert!(selected.shapes.is_empty());
+
+        marquee(
+            &context,
+            &mut selected,
+            around_first,
+            MarqueeMode::Touch,
+            replace,
+        );
+        assert_eq!(selected_ids(&selected), ids);
+    }
+
+    #[test]
+    fn given_handles_outside_when_marquee_enclose_curve_then_shape_selected() {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        context.camera.set_pixel_region(500.0, 500.0);
+        // The curve goes up to y = -0.225, its handles are at y = -0.3
+        let mut shape = Shape::quick_from_string("M 0 0 C 0 -0.3 0.2 -0.3 0.2 0 C 0.2 0 0 0 0 0 Z");
+        shape.id = context.command_handler.layer_id_reserve();
+        let id = shape.id;
+        context
+            .command_handler
+            .execute(InsertShape::boxed(shape))
+            .unwrap();
+        let mut selected = UserSelection::default();
+        let around_curve = (Coord::new(-0.1, -0.25), Coord::new(0.3, 0.1));
+        let cut_curve = (Coord::new(-0.1, -0.2), Coord::new(0.3, 0.1));
+
+        marquee(
+            &context,
+            &mut selected,
+            around_curve,
+            MarqueeMode::Enclose,
+            SelectionModifier::Replace,
+        );
+        assert_eq!(selected_ids(&selected), vec![id]);
+
+        marquee(
+            &context,
+            &mut selected,
+            cut_curve,
+            MarqueeMode::Enclose,
+            SelectionModifier::Replace,
+        );
+        assert!(selected.shapes.is_empty());
+    }
+}
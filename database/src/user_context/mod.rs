@@ -3,13 +3,22 @@ use camera::Camera;
 use common::{pures::Affine, Rgba};
 use serde::{Deserialize, Serialize};
 
-use crate::{DbCoord, DrawingContext, RenderOption, Scene, Shape};
+use crate::{DbCoord, DrawingContext, RenderOption, RenderStats, Scene, Shape};
 
 pub mod api;
 pub mod camera;
+pub mod clipboard;
+pub mod guides;
 
 mod ui;
 mod boolean;
+mod gizmo;
+mod lasso;
+mod marquee;
+mod pen;
+pub mod primitive;
+pub mod snap;
+mod transform;
 pub mod user_selection;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -35,9 +44,15 @@ impl SceneUserContext {
         &self.command_handler.scene()
     }
 
-    pub fn scene_render<T: DrawingContext>(&self, drawing_context: &mut T) -> Result<(), String> {
-        self.scene()
-            .render_with_options(drawing_context, self.render_options.clone())
+    /// Render the scene with the render options, the shapes outside of the camera are skipped
+    /// if the options have no view
+    pub fn scene_render<T: DrawingContext>(
+        &self,
+        drawing_context: &mut T,
+    ) -> Result<RenderStats, String> {
+        let mut options = self.render_options.clone();
+        options.view.get_or_insert(self.camera.view_bounds());
+        self.scene().render_with_options(drawing_context, options)
     }
 }
 
@@ -52,7 +67,7 @@ impl Default for SceneUserContext {
             ],
             Affine::identity(),
         );
-        shape1.color = Rgba::new(128, 0, 0, 255);
+        shape1.fill = Rgba::new(128, 0, 0, 255).into();
         shape1.stroke = Stroke::new(0.02, Rgba::new(0, 255, 0, 255));
         scene.shape_insert(shape1);
 
@@ -64,7 +79,7 @@ impl Default for SceneUserContext {
             ],
             Affine::identity(),
         );
-        shape2.color = Rgba::new(0, 0, 0, 255);
+        shape2.fill = Rgba::new(0, 0, 0, 255).into();
         scene.shape_insert(shape2);
 
         let camera = Camera::new(scene.max_rect().center(), 750.0, 500.0);
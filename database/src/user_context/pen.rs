@@ -0,0 +1,253 @@
+use common::{
+    math::point_in_radius,
+    types::{Coord, ScreenCoord, ScreenLength2d},
+};
+
+use crate::{
+    commands::{AppendAnchor, ClosePath, StartPath},
+    scene::shape::Stroke,
+    user_context::user_selection::{PenPath, SelectedShape},
+    DbCoord, PathEnd, UserSelection,
+};
+
+use super::SceneUserContext;
+
+/*
+The pen draws open paths anchor by anchor.
+Each anchor is placed with a press and released after a drag. The drag gives the handle of the
+anchor toward the next anchor, the handle toward the previous anchor is its mirror.
+A click without drag makes a corner.
+*/
+impl SceneUserContext {
+    /// Start a new open path at the position, the drag is where the mouse was released
+    pub fn pen_start(
+        &mut self,
+        selected: &mut UserSelection,
+        position: ScreenCoord,
+        drag: ScreenCoord,
+    ) -> Result<(), String> {
+        let anchor = self.camera.project(position);
+        let drag = self.camera.project(drag);
+        let shape_id = self.command_handler.layer_id_reserve();
+        let stroke = Stroke::new(selected.stroke_size, selected.stroke_color.clone());
+
+        self.command_handler
+            .execute(StartPath::boxed(
+                shape_id,
+                anchor,
+                selected.color.clone(),
+                stroke,
+            ))
+            .map_err(|e| e.to_string())?;
+
+        selected.shapes = vec![SelectedShape::new(shape_id)];
+        selected.pen = Some(PenPath {
+            shape_id,
+            subpath: 0,
+            end: PathEnd::End,
+            handle: drag_handle(anchor, drag),
+        });
+        Ok(())
+    }
+
+    /// Continue the open path with an end under the position, the anchors are added at this end.
+    /// Return false if no end of an open path is under the position.
+    pub fn pen_continue(&mut self, selected: &mut UserSelection, position: ScreenCoord) -> bool {
+        let coord = self.camera.project(position);
+        let radius = self
+            .camera
+            .transform_to_length2d(ScreenLength2d::new(10.0, 10.0));
+        let Some((shape_id, subpath, end)) = self.scene().shape_select_open_end(&coord, radius)
+        else {
+            return false;
+        };
+
+        let shape = self.scene().shape_select(shape_id).expect("Not 404");
+        let path = shape.subpath(subpath);
+        let len = path.len();
+        // The next curve continues the curve at the end without a corner
+        let handle = match end {
+            _ if len == 1 => None,
+            PathEnd::End => smooth_handle(&path[len - 1], &path[len - 2]),
+            PathEnd::Start => smooth_handle(&path[0], &path[1]),
+        };
+
+        selected.shapes = vec![SelectedShape::new(shape_id)];
+        selected.pen = Some(PenPath {
+            shape_id,
+            subpath,
+            end,
+            handle,
+        });
+        true
+    }
+
+    /// Add an anchor at the position to the path of the pen, the drag is where the mouse was
+    /// released. On the anchor at the other end of the path, the path is closed.
+    pub fn pen_append(
+        &mut self,
+        selected: &mut UserSelection,
+        position: ScreenCoord,
+        drag: ScreenCoord,
+    ) -> Result<(), String> {
+        let pen = selected.pen.as_ref().ok_or("No path drawn with the pen")?;
+        let anchor = self.camera.project(position);
+        let drag = self.camera.project(drag);
+
+        let shape = self
+            .scene()
+            .shape_select(pen.shape_id)
+            .ok_or("Shape not found")?;
+        let path = shape.paths().nth(pen.subpath).ok_or("Subpath not found")?;
+        let other_end = match pen.end {
+            PathEnd::End => path[0],
+            PathEnd::Start => path[path.len() - 1],
+        };
+        let radius = self
+            .camera
+            .transform_to_length2d(ScreenLength2d::new(10.0, 10.0));
+        if path.len() > 1 && point_in_radius(anchor, other_end.coord(), radius) {
+            return self.pen_close(selected);
+        }
+
+        let handle = drag_handle(anchor, drag);
+        self.command_handler
+            .execute(AppendAnchor::boxed(
+                pen.shape_id,
+                pen.subpath,
+                pen.end,
+                pen.handle,
+                handle.map(|handle| mirror(anchor, handle)),
+                anchor,
+            ))
+            .map_err(|e| e.to_string())?;
+
+        if let Some(pen) = selected.pen.as_mut() {
+            pen.handle = handle;
+        }
+        Ok(())
+    }
+
+    /// Close the path of the pen, the pen is done with the path
+    pub fn pen_close(&mut self, selected: &mut UserSelection) -> Result<(), String> {
+        let pen = selected.pen.clone().ok_or("No path drawn with the pen")?;
+        let shape = self
+            .scene()
+            .shape_select(pen.shape_id)
+            .ok_or("Shape not found")?;
+        let path = shape.paths().nth(pen.subpath).ok_or("Subpath not found")?;
+        let len = path.len();
+        if len < 4 {
+            return Err("A path needs two anchors to be closed".to_string());
+        }
+
+        // The handle at the anchor not under the pen continues its curve without a corner
+        let (handle_from, handle_to) = match pen.end {
+            PathEnd::End => (pen.handle, smooth_handle(&path[0], &path[1])),
+            PathEnd::Start => (smooth_handle(&path[len - 1], &path[len - 2]), pen.handle),
+        };
+
+        self.command_handler
+            .execute(ClosePath::boxed(
+                pen.shape_id,
+                pen.subpath,
+                handle_from,
+                handle_to,
+            ))
+            .map_err(|e| e.to_string())?;
+        selected.pen = None;
+        Ok(())
+    }
+
+    /// Stop drawing with the pen, the path stays open
+    pub fn pen_end(&mut self, selected: &mut UserSelection) {
+        selected.pen = None;
+    }
+}
+
+/// Handle from the drag out of the anchor, none for a click
+fn drag_handle(anchor: Coord, drag: Coord) -> Option<Coord> {
+    if anchor == drag {
+        None
+    } else {
+        Some(drag)
+    }
+}
+
+/// Handle on the other side of the anchor, in line with the handle
+fn smooth_handle(anchor: &DbCoord, handle: &DbCoord) -> Option<Coord> {
+    if anchor.id == handle.id {
+        None
+    } else {
+        Some(mirror(anchor.coord(), handle.coord()))
+    }
+}
+
+fn mirror(anchor: Coord, handle: Coord) -> Coord {
+    anchor * 2.0 - handle
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::ScreenCoord;
+
+    use crate::{SceneUserContext, UserSelection};
+
+    #[test]
+    fn given_pen_when_three_anchors_and_close_then_closed_shape_with_curve() {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        let mut selected = UserSelection {
+            stroke_size: 0.01,
+            ..Default::default()
+        };
+
+        let a = ScreenCoord::new(100.0, 100.0);
+        let b = ScreenCoord::new(400.0, 100.0);
+        let c = ScreenCoord::new(250.0, 400.0);
+        context.pen_start(&mut selected, a, a).unwrap();
+        context
+            .pen_append(&mut selected, b, ScreenCoord::new(450.0, 150.0))
+            .unwrap();
+        context.pen_append(&mut selected, c, c).unwrap();
+
+        let shape_id = selected.pen.as_ref().unwrap().shape_id;
+        let shape = context.scene().shape_select(shape_id).unwrap();
+        assert_eq!(shape.curves_len(), 2);
+        assert!(!shape.is_closed());
+        assert!(!shape.curve_select(0).unwrap().is_straight());
+        assert!(!shape.curve_select(1).unwrap().is_straight());
+
+        context.pen_append(&mut selected, a, a).unwrap();
+
+        let shape = context.scene().shape_select(shape_id).unwrap();
+        assert!(selected.pen.is_none());
+        assert!(shape.is_closed());
+        assert_eq!(shape.curves_len(), 3);
+
+        context.command_handler.undo().unwrap();
+        let shape = context.scene().shape_select(shape_id).unwrap();
+        assert!(!shape.is_closed());
+    }
+
+    #[test]
+    fn given_open_path_when_continue_from_start_then_anchor_added_before() {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        let mut selected = UserSelection::default();
+
+        let a = ScreenCoord::new(100.0, 100.0);
+        let b = ScreenCoord::new(400.0, 100.0);
+        context.pen_start(&mut selected, a, a).unwrap();
+        context.pen_append(&mut selected, b, b).unwrap();
+        let shape_id = selected.pen.as_ref().unwrap().shape_id;
+        context.pen_end(&mut selected);
+
+        assert!(context.pen_continue(&mut selected, ScreenCoord::new(102.0, 101.0)));
+        let c = ScreenCoord::new(100.0, 400.0);
+        context.pen_append(&mut selected, c, c).unwrap();
+
+        let shape = context.scene().shape_select(shape_id).unwrap();
+        assert_eq!(shape.curves_len(), 2);
+        assert_eq!(shape.path[0].coord(), context.camera.project(c));
+        assert!(!context.pen_continue(&mut selected, ScreenCoord::new(250.0, 250.0)));
+    }
+}
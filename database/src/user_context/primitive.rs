@@ -0,0 +1,201 @@
+use common::types::{Coord, Length2d, Rect, ScreenCoord, ScreenLength2d};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use crate::{
+    commands::InsertShape, user_context::user_selection::SelectedShape, DrawingContext, Shape,
+    UserSelection,
+};
+
+use super::{ui::draw_border, SceneUserContext};
+
+/// Shape inserted by a drag, sized to the rect of the drag
+#[derive(Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Primitive {
+    Rectangle,
+    /// Radii in pixels of the screen, clockwise from the top left corner
+    RoundedRectangle {
+        radii: [f32; 4],
+    },
+    Ellipse,
+    /// Regular polygon inscribed in the ellipse of the rect
+    Polygon {
+        sides: usize,
+    },
+    /// Star inscribed in the ellipse of the rect, the inner vertices at the ratio of the radius
+    Star {
+        points: usize,
+        inner_ratio: f32,
+    },
+}
+
+/*
+A primitive is inserted with a drag from a corner of its rect to the opposite corner.
+The shape follows the drag until its end, where it is inserted as one undo step.
+*/
+impl SceneUserContext {
+    /// Size the primitive to the drag from the start to the end. With `constrain` the width and
+    /// the height are the same, with `from_center` the start is the center of the shape.
+    /// The start and the end are snapped with the settings of the selection
+    pub fn primitive_drag(
+        &self,
+        selected: &mut UserSelection,
+        primitive: Primitive,
+        start: ScreenCoord,
+        end: ScreenCoord,
+        constrain: bool,
+        from_center: bool,
+    ) {
+        let [start, end] = [start, end].map(|position| {
+            let coord = self.camera.project(position);
+            self.snap(coord, &selected.snap, &[])
+                .map_or(coord, |snap| snap.coord)
+        });
+        let rect = drag_rect(start, end, constrain, from_center);
+        if rect.width() == 0.0 || rect.height() == 0.0 {
+            selected.primitive = None;
+            return;
+        }
+
+        let mut shape = self.primitive_shape(primitive, rect);
+        shape.fill = selected.color.clone().into();
+        shape.stroke.paint = selected.stroke_color.clone().into();
+        shape.stroke.size = selected.stroke_size;
+        selected.primitive = Some(shape);
+    }
+
+    /// Insert the shape of the drag and select it, nothing for a drag without size
+    pub fn primitive_end(&mut self, selected: &mut UserSelection) -> Result<(), String> {
+        let Some(mut shape) = selected.primitive.take() else {
+            return Ok(());
+        };
+        shape.id = self.command_handler.layer_id_reserve();
+        let id = shape.id;
+        self.command_handler
+            .execute(InsertShape::boxed(shape))
+            .map_err(|e| e.to_string())?;
+        selected.shapes = vec![SelectedShape::new(id)];
+        Ok(())
+    }
+
+    fn primitive_shape(&self, primitive: Primitive, rect: Rect) -> Shape {
+        let radius = Length2d::new(rect.width() / 2.0, rect.height() / 2.0);
+        match primitive {
+            Primitive::Rectangle => Shape::new_rect(rect),
+            Primitive::RoundedRectangle { radii } => {
+                let radii = radii.map(|radius| {
+                    let radius = ScreenLength2d::new(radius, radius);
+                    self.camera.transform_to_length2d(radius).x.abs()
+                });
+                Shape::new_rounded_rect(rect, radii)
+            }
+            Primitive::Ellipse => Shape::new_ellipse(rect),
+            Primitive::Polygon { sides } => Shape::new_polygon(rect.center(), radius, sides),
+            Primitive::Star {
+                points,
+                inner_ratio,
+            } => Shape::new_star(rect.center(), radius, points, inner_ratio),
+        }
+    }
+
+    /// Shape of the drag with its border, even without fill and stroke
+    pub(super) fn draw_primitive(
+        &self,
+        selected: &UserSelection,
+        ctx: &mut dyn DrawingContext,
+    ) -> Result<(), String> {
+        let Some(shape) = &selected.primitive else {
+            return Ok(());
+        };
+        shape.render(ctx)?;
+        draw_border(shape, ctx)
+    }
+}
+
+/// Rect of the drag, the end is moved on the diagonal to constrain it to a square
+fn drag_rect(start: Coord, end: Coord, constrain: bool, from_center: bool) -> Rect {
+    let mut delta = end - start;
+    if constrain {
+        let side = delta.x.abs().max(delta.y.abs());
+        delta = Coord::new(side.copysign(delta.x), side.copysign(delta.y));
+    }
+    let (a, b) = match from_center {
+        true => (start - delta, start + delta),
+        false => (start, start + delta),
+    };
+    Rect::new(a.x.min(b.x), a.y.min(b.y), a.x.max(b.x), a.y.max(b.y))
+}
+
+#[cfg(test)]
+mod test {
+    use common::types::{Coord, Rect};
+
+    use crate::{scene::render::MockDrawingContext, SceneUserContext, UserSelection};
+
+    use super::Primitive;
+
+    fn context() -> SceneUserContext {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        context.camera.set_pixel_region(500.0, 500.0);
+        context
+    }
+
+    fn assert_rect_near(a: Rect, b: Rect) {
+        let near = |a: Coord, b: Coord| (a.x - b.x).abs() < 1e-4 && (a.y - b.y).abs() < 1e-4;
+        assert!(
+            near(a.top_left, b.top_left) && near(a.bottom_right, b.bottom_right),
+            "{:?} != {:?}",
+            a,
+            b
+        );
+    }
+
+    #[test]
+    fn given_drag_when_primitive_end_then_shape_in_rect_inserted_and_selected() {
+        let mut context = context();
+        let mut selected = UserSelection::default();
+        let start = context.camera.unproject(Coord::new(0.1, 0.1));
+        let end = context.camera.unproject(Coord::new(0.5, 0.3));
+
+        context.primitive_drag(
+            &mut selected,
+            Primitive::Rectangle,
+            start,
+            end,
+            false,
+            false,
+        );
+        let preview = selected.primitive.as_ref().unwrap().bounding_box().unwrap();
+        assert_rect_near(preview, Rect::new(0.1, 0.1, 0.5, 0.3));
+        context
+            .draw(&selected, &mut MockDrawingContext::default())
+            .unwrap();
+
+        context.primitive_end(&mut selected).unwrap();
+        assert!(selected.primitive.is_none());
+        let id = selected.shapes[0].shape_id;
+        let shape = context.scene().shape_select(id).unwrap();
+        assert_rect_near(shape.bounding_box().unwrap(), preview);
+
+        context.command_handler.undo().unwrap();
+        assert!(context.scene().shape_select(id).is_none());
+    }
+
+    #[test]
+    fn given_modifiers_when_primitive_drag_then_square_from_center() {
+        let context = context();
+        let mut selected = UserSelection::default();
+        let start = context.camera.unproject(Coord::new(0.0, 0.0));
+        let end = context.camera.unproject(Coord::new(0.2, -0.1));
+
+        context.primitive_drag(&mut selected, Primitive::Ellipse, start, end, true, true);
+        let bounds = selected.primitive.as_ref().unwrap().bounding_box().unwrap();
+        assert_rect_near(bounds, Rect::new(-0.2, -0.2, 0.2, 0.2));
+
+        // A click has no size, nothing is inserted
+        context.primitive_drag(&mut selected, Primitive::Ellipse, start, start, true, true);
+        assert!(selected.primitive.is_none());
+    }
+}
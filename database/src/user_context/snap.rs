@@ -0,0 +1,334 @@
+use common::{
+    math::point_in_radius,
+    pures::Vec2,
+    types::{Coord, Rect, ScreenLength2d},
+};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+
+use super::{user_selection::SelectedShape, SceneUserContext};
+
+/// Targets and tolerance of the snapping of the coords moved or drawn with the mouse
+#[derive(Tsify)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    /// Distance in pixels of the screen under which a target catches the coord
+    pub tolerance: f32,
+    /// Distance between the lines of the document grid, from the origin of the scene
+    pub grid_spacing: f32,
+    pub to_grid: bool,
+    /// Anchors and handles of the shapes, the coords that move are not targets
+    pub to_coords: bool,
+    pub to_midpoints: bool,
+    /// Closest point on the curves of the shapes
+    pub to_curves: bool,
+    /// Edges and centers of the bounding boxes of the shapes
+    pub to_bounds: bool,
+    /// Edges of the scene, from -1 to 1
+    pub to_border: bool,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tolerance: 8.0,
+            grid_spacing: 0.1,
+            to_grid: true,
+            to_coords: true,
+            to_midpoints: true,
+            to_curves: true,
+            to_bounds: true,
+            to_border: true,
+        }
+    }
+}
+
+/// Target caught by a snapped coord
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapReason {
+    /// Intersection of the lines of the grid
+    Grid,
+    Anchor,
+    Handle,
+    /// Middle of a curve
+    Midpoint,
+    /// Closest point on a curve
+    Curve,
+    BoundsEdge,
+    BoundsCenter,
+    /// Edge of the scene
+    Border,
+}
+
+impl SnapReason {
+    /// A point target is taken before a line target in the tolerance, even if farther
+    fn is_point(&self) -> bool {
+        !matches!(
+            self,
+            SnapReason::Curve | SnapReason::BoundsEdge | SnapReason::Border
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snap {
+    pub coord: Coord,
+    pub reason: SnapReason,
+}
+
+impl SceneUserContext {
+    /// Closest target of the settings in the tolerance of the coord.
+    /// The coords of `moving` are not targets, nor the curves and the bounds of their shapes
+    /// as they change with them. None if snapping is disabled or no target is close enough
+    pub fn snap(
+        &self,
+        coord: Coord,
+        settings: &SnapSettings,
+        moving: &[SelectedShape],
+    ) -> Option<Snap> {
+        if !settings.enabled {
+            return None;
+        }
+        let tolerance = ScreenLength2d::new(settings.tolerance, settings.tolerance);
+        let radius = self.camera.transform_to_length2d(tolerance);
+        let (radius_x, radius_y) = (radius.x.abs(), radius.y.abs());
+
+        let mut best: Option<(Snap, f32)> = None;
+        let mut offer = |target: Coord, reason: SnapReason| {
+            if !point_in_radius(coord, target, radius) {
+                return;
+            }
+            let distance = coord.distance(&target);
+            let better = best.is_none_or(|(snap, best_distance)| {
+                (!reason.is_point(), distance) < (!snap.reason.is_point(), best_distance)
+            });
+            if better {
+                best = Some((
+                    Snap {
+                        coord: target,
+                        reason,
+                    },
+                    distance,
+                ));
+            }
+        };
+
+        if settings.to_grid && settings.grid_spacing > 0.0 {
+            let spacing = settings.grid_spacing;
+            let target = Coord::new(
+                (coord.x / spacing).round() * spacing,
+                (coord.y / spacing).round() * spacing,
+            );
+            offer(target, SnapReason::Grid);
+        }
+        if settings.to_border {
+            offer(
+                rect_edge_closest(&self.scene().max_rect(), &coord),
+                SnapReason::Border,
+            );
+        }
+
+        let area = Rect::new(
+            coord.x - radius_x,
+            coord.y - radius_y,
+            coord.x + radius_x,
+            coord.y + radius_y,
+        );
+        for (shape, _) in self.scene().shapes_visible_in(false, &area) {
+            let moved = moving
+                .iter()
+                .find(|selected| selected.shape_id == shape.id && !selected.coords.is_empty());
+            if settings.to_coords {
+                // A subpath is an anchor followed by the two handles and the anchor of each curve
+                let coords = shape
+                    .paths()
+                    .flat_map(|path| path.iter().enumerate())
+                    .filter(|(_, c)| moved.is_none_or(|s| !s.coords.contains(&c.id)));
+                for (index, db_coord) in coords {
+                    let reason = match index % 3 == 0 {
+                        true => SnapReason::Anchor,
+                        false => SnapReason::Handle,
+                    };
+                    offer(db_coord.coord(), reason);
+                }
+            }
+            if moved.is_some() {
+                continue;
+            }
+
+            if settings.to_midpoints {
+                for curve in shape.curves() {
+                    offer(curve.cubic_bezier(0.5), SnapReason::Midpoint);
+                }
+            }
+            if settings.to_curves && shape.curves_len() > 0 {
+                if let Some((_, _, _, point)) = self.scene().shape_closest_curve(shape.id, &coord) {
+                    offer(point, SnapReason::Curve);
+                }
+            }
+            if settings.to_bounds {
+                if let Some(bounds) = shape.bounding_box() {
+                    offer(bounds.center(), SnapReason::BoundsCenter);
+                    offer(rect_edge_closest(&bounds, &coord), SnapReason::BoundsEdge);
+                }
+            }
+        }
+
+        best.map(|(snap, _)| snap)
+    }
+}
+
+/// Closest point of the edges of the rect, a coord inside goes to the nearest edge
+fn rect_edge_closest(rect: &Rect, coord: &Coord) -> Coord {
+    let (left, top) = (rect.top_left.x, rect.top_left.y);
+    let (right, bottom) = (rect.bottom_right.x, rect.bottom_right.y);
+    let x = coord.x.clamp(left, right);
+    let y = coord.y.clamp(top, bottom);
+    if x != coord.x || y != coord.y {
+        return Coord::new(x, y);
+    }
+
+    let edges = [
+        (coord.x - left, Coord::new(left, y)),
+        (right - coord.x, Coord::new(right, y)),
+        (coord.y - top, Coord::new(x, top)),
+        (bottom - coord.y, Coord::new(x, bottom)),
+    ];
+    edges
+        .into_iter()
+        .min_by(|(a, _), (b, _)| a.total_cmp(b))
+        .map(|(_, edge)| edge)
+        .expect("Four edges")
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::Affine,
+        types::{Coord, ScreenLength2d},
+    };
+
+    use crate::{
+        commands::InsertShape, user_context::user_selection::SelectedShape, DbCoord, LayerId,
+        SceneUserContext, Shape, UserSelection,
+    };
+
+    use super::{SnapReason, SnapSettings};
+
+    fn context_with_square(corner: f32, size: f32) -> (SceneUserContext, LayerId) {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        context.camera.set_pixel_region(500.0, 500.0);
+        let mut square = Shape::new_from_lines(
+            vec![
+                DbCoord::new(corner, corner),
+                DbCoord::new(corner + size, corner),
+                DbCoord::new(corner + size, corner + size),
+                DbCoord::new(corner, corner + size),
+            ],
+            Affine::identity(),
+        );
+        square.id = context.command_handler.layer_id_reserve();
+        let id = square.id;
+        context
+            .command_handler
+            .execute(InsertShape::boxed(square))
+            .unwrap();
+        (context, id)
+    }
+
+    fn settings() -> SnapSettings {
+        SnapSettings {
+            enabled: true,
+            to_grid: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn given_targets_in_tolerance_when_snap_then_points_before_lines() {
+        let (context, _) = context_with_square(0.05, 0.3);
+        let radius = context
+            .camera
+            .transform_to_length2d(ScreenLength2d::new(8.0, 8.0));
+        let near = radius.x / 2.0;
+
+        let snap = context.snap(Coord::new(0.05 + near, 0.06), &settings(), &[]);
+        assert_eq!(snap.unwrap().reason, SnapReason::Anchor);
+        assert_eq!(snap.unwrap().coord, Coord::new(0.05, 0.05));
+
+        let snap = context.snap(Coord::new(0.05 + near, 0.13), &settings(), &[]);
+        assert_eq!(snap.unwrap().reason, SnapReason::Curve);
+        assert_eq!(snap.unwrap().coord, Coord::new(0.05, 0.13));
+
+        let snap = context.snap(Coord::new(0.2, 0.2 + near), &settings(), &[]);
+        assert_eq!(snap.unwrap().reason, SnapReason::BoundsCenter);
+
+        let grid = SnapSettings {
+            to_grid: true,
+            grid_spacing: 0.25,
+            ..settings()
+        };
+        let snap = context.snap(Coord::new(-0.5 + near, 0.75), &grid, &[]);
+        assert_eq!(snap.unwrap().coord, Coord::new(-0.5, 0.75));
+        assert_eq!(snap.unwrap().reason, SnapReason::Grid);
+
+        let snap = context.snap(Coord::new(1.0 - near, 0.6), &settings(), &[]);
+        assert_eq!(snap.unwrap().reason, SnapReason::Border);
+
+        assert!(context
+            .snap(Coord::new(-0.5, 0.6), &settings(), &[])
+            .is_none());
+        let disabled = SnapSettings::default();
+        assert!(context
+            .snap(Coord::new(0.05 + near, 0.06), &disabled, &[])
+            .is_none());
+    }
+
+    #[test]
+    fn given_snapping_when_move_coord_near_anchor_then_moved_on_it() {
+        let (mut context, _) = context_with_square(0.05, 0.3);
+        let (id, corner) = {
+            let mut other = Shape::new_from_lines(
+                vec![
+                    DbCoord::new(-0.5, -0.5),
+                    DbCoord::new(-0.2, -0.5),
+                    DbCoord::new(-0.2, -0.2),
+                ],
+                Affine::identity(),
+            );
+            other.id = context.command_handler.layer_id_reserve();
+            let id = other.id;
+            context
+                .command_handler
+                .execute(InsertShape::boxed(other))
+                .unwrap();
+            let shape = context.scene().shape_select(id).unwrap();
+            (id, shape.path[0].id)
+        };
+        let radius = context
+            .camera
+            .transform_to_length2d(ScreenLength2d::new(8.0, 8.0));
+        let mut selected = UserSelection {
+            snap: settings(),
+            ..Default::default()
+        };
+        selected.shapes.push(SelectedShape {
+            shape_id: id,
+            coords: vec![corner],
+        });
+
+        // The anchor is dropped a bit off the corner of the square
+        let start = context.camera.unproject(Coord::new(-0.5, -0.5));
+        let end = context
+            .camera
+            .unproject(Coord::new(0.05 + radius.x / 3.0, 0.05 - radius.y / 3.0));
+        context.move_coords_of(&mut selected, start, end);
+
+        let shape = context.scene().shape_select(id).unwrap();
+        let moved = shape.coord_select(corner).unwrap().coord();
+        assert!((moved.x - 0.05).abs() < 1e-5 && (moved.y - 0.05).abs() < 1e-5);
+    }
+}
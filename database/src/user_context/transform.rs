@@ -0,0 +1,123 @@
+use common::{
+    pures::Affine,
+    types::{Coord, ScreenCoord},
+};
+
+use crate::{
+    commands::{Pivot, TransformShapes},
+    UserSelection,
+};
+
+use super::SceneUserContext;
+
+impl SceneUserContext {
+    /// Move the shapes by the mouse movement, the alignment guides follow them until
+    /// `drag_end`
+    pub fn translate_shapes_of(
+        &mut self,
+        selected: &mut UserSelection,
+        start: ScreenCoord,
+        end: ScreenCoord,
+    ) {
+        let movement = self.camera.project(end) - self.camera.project(start);
+        self.transform_shapes_of(
+            selected,
+            Affine::from_translate(movement),
+            Pivot::Center,
+            false,
+        );
+        self.guides_update(selected);
+    }
+
+    pub fn scale_shapes_of(
+        &mut self,
+        selected: &UserSelection,
+        scale: Coord,
+        pivot: Pivot,
+        scale_stroke: bool,
+    ) {
+        self.transform_shapes_of(selected, Affine::from_scale(scale), pivot, scale_stroke);
+    }
+
+    /// Rotate by the angle in radians
+    pub fn rotate_shapes_of(&mut self, selected: &UserSelection, angle: f32, pivot: Pivot) {
+        self.transform_shapes_of(selected, Affine::from_rotation(angle), pivot, false);
+    }
+
+    /// Mirror the shapes left to right around the centre of the selection
+    pub fn flip_horizontal_of(&mut self, selected: &UserSelection) {
+        self.transform_shapes_of(selected, Affine::from_reflect_y(), Pivot::Center, false);
+    }
+
+    /// Mirror the shapes top to bottom around the centre of the selection
+    pub fn flip_vertical_of(&mut self, selected: &UserSelection) {
+        self.transform_shapes_of(selected, Affine::from_reflect_x(), Pivot::Center, false);
+    }
+
+    fn transform_shapes_of(
+        &mut self,
+        selected: &UserSelection,
+        transform: Affine,
+        pivot: Pivot,
+        scale_stroke: bool,
+    ) {
+        if selected.shapes.is_empty() {
+            return;
+        }
+        if let Err(e) = self.command_handler.execute(TransformShapes::boxed(
+            selected.shapes.iter().map(|s| s.shape_id).collect(),
+            transform,
+            pivot,
+            scale_stroke,
+        )) {
+            log::error!("{:?}", e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use common::{
+        pures::Vec2,
+        types::{Coord, Length2d},
+    };
+
+    use crate::{
+        commands::{InsertShape, Pivot},
+        user_context::user_selection::SelectedShape,
+        SceneUserContext, Shape, UserSelection,
+    };
+
+    #[test]
+    fn given_selected_circle_when_flip_and_scale_then_bounds_follow() {
+        let mut context = SceneUserContext::new(500.0, 500.0);
+        let shape_id = context.command_handler.layer_id_reserve();
+        let mut shape = Shape::new_circle(Coord::new(0.5, 0.0), Length2d::new(0.25, 0.25));
+        shape.id = shape_id;
+        context
+            .command_handler
+            .execute(InsertShape::boxed(shape))
+            .unwrap();
+        let mut selected = UserSelection::default();
+        selected.shapes.push(SelectedShape::new(shape_id));
+
+        context.flip_horizontal_of(&selected);
+        let rect = context
+            .scene()
+            .shape_select(shape_id)
+            .unwrap()
+            .bounding_box()
+            .unwrap();
+        assert!(rect.center().distance(&Coord::new(0.5, 0.0)) < 0.001);
+
+        context.scale_shapes_of(&selected, Coord::new(2.0, 2.0), Pivot::TopLeft, false);
+        let rect = context
+            .scene()
+            .shape_select(shape_id)
+            .unwrap()
+            .bounding_box()
+            .unwrap();
+        assert!(rect.top_left.distance(&Coord::new(0.25, -0.25)) < 0.001);
+        assert!(rect.bottom_right.distance(&Coord::new(1.25, 0.75)) < 0.001);
+    }
+}
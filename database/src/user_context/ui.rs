@@ -5,7 +5,8 @@ use common::{
     Rgba,
 };
 
-use crate::{DrawingContext, SceneUserContext, Shape, UserSelection};
+use crate::scene::shape::curve::path_encloses;
+use crate::{DrawingContext, SceneUserContext, Shape, StrokeStyle, UserSelection};
 
 use super::user_selection::CoordState;
 
@@ -24,7 +25,11 @@ impl SceneUserContext {
 
             let t = ctx.get_transform()?;
 
-            ctx.set_stroke(&Rgba::new(0x3A, 0xD1, 0xEF, 255), 2.0)?;
+            ctx.set_stroke(
+                &Rgba::new(0x3A, 0xD1, 0xEF, 255),
+                2.0,
+                &StrokeStyle::default(),
+            )?;
 
             //Draw line between cp and p
             for curve in shape.curves() {
@@ -34,7 +39,7 @@ impl SceneUserContext {
                     &(t * curve.cp0.coord()),
                     &(t * curve.cp0.coord()),
                 )?;
-                ctx.close_shape()?;
+                ctx.end_shape()?;
 
                 ctx.start_shape(&(t * curve.cp1.coord()))?;
                 ctx.move_curve(
@@ -42,12 +47,12 @@ impl SceneUserContext {
                     &(t * curve.p1.coord()),
                     &(t * curve.p1.coord()),
                 )?;
-                ctx.close_shape()?;
+                ctx.end_shape()?;
             }
-            ctx.set_stroke(&Rgba::transparent(), 0.0)?;
+            ctx.set_stroke(&Rgba::transparent(), 0.0, &StrokeStyle::default())?;
 
             //Draw coord
-            for db_coord in shape.path.iter() {
+            for db_coord in shape.paths().flatten() {
                 let coord_state = shape_selected.coord_state(selected, db_coord.id);
                 let color = match coord_state {
                     CoordState::Hover => Rgba::new(0x0E, 0x90, 0xAA, 255),
@@ -60,35 +65,25 @@ impl SceneUserContext {
                 //TODO Don't mix buisness logic with rendering Shape with database id and rendering
                 let mut circle =
                     Shape::new_circle(db_coord.coord(), self.camera.transform_to_length2d(radius));
-                circle.color = color;
+                circle.fill = color.into();
                 circle.render(ctx)?;
             }
 
             //Draw shape selection border
-            ctx.set_fill(&Rgba::transparent())?;
-            ctx.set_stroke(&Rgba::new(0x3A, 0xD1, 0xEF, 0x80), 1.0)?;
-            ctx.start_shape(&(t * shape.path.first().unwrap().coord()))?;
-
-            for curve in shape.curves() {
-                ctx.move_curve(
-                    &(t * curve.cp0.coord()),
-                    &(t * curve.cp1.coord()),
-                    &(t * curve.p1.coord()),
-                )?;
-            }
-            ctx.close_shape()?;
-            ctx.set_stroke(&Rgba::transparent(), 0.0)?;
+            draw_border(shape, ctx)?;
         }
+        self.draw_guides(selected, ctx)?;
+        self.draw_primitive(selected, ctx)?;
 
         let t = ctx.get_transform()?;
-        ctx.set_stroke(&Rgba::black(), 1.0)?;
+        ctx.set_stroke(&Rgba::black(), 1.0, &StrokeStyle::default())?;
         ctx.set_fill(&Rgba::transparent())?;
         ctx.start_shape(&(t * Coord::new(-1.0, -1.0)))?;
         ctx.move_line(&(t * Coord::new(1.0, -1.0)))?;
         ctx.move_line(&(t * Coord::new(1.0, 1.0)))?;
         ctx.move_line(&(t * Coord::new(-1.0, 1.0)))?;
         ctx.close_shape()?;
-        ctx.set_stroke(&Rgba::transparent(), 0.0)?;
+        ctx.set_stroke(&Rgba::transparent(), 0.0, &StrokeStyle::default())?;
 
         Ok(())
     }
@@ -108,13 +103,11 @@ impl SceneUserContext {
         let pos = pos.unwrap();
 
         for shape_selected in &selected.shapes {
-            let shape = self
+            let (_, _, distance, coord) = self
                 .scene()
-                .shape_select(shape_selected.shape_id)
+                .shape_closest_curve(shape_selected.shape_id, &pos)
                 .expect("not 404");
 
-            let (_, _, distance, coord) = shape.closest_curve(&pos);
-
             if distance < min_distance {
                 min_distance = distance;
                 min_coord = coord;
@@ -136,8 +129,39 @@ impl SceneUserContext {
         let radius = ScreenLength2d::new(3.0, 3.0);
         let radius = self.camera.transform_to_length2d(radius);
         let mut circle = Shape::new_circle(center, radius);
-        circle.color = color;
+        circle.fill = color.into();
         circle.render(ctx)?;
         Ok(())
     }
 }
+
+/// Outline of the paths of the shape, like the border of a selected shape
+pub(super) fn draw_border(shape: &Shape, ctx: &mut dyn DrawingContext) -> Result<(), String> {
+    let t = ctx.get_transform()?;
+    ctx.set_fill(&Rgba::transparent())?;
+    ctx.set_stroke(
+        &Rgba::new(0x3A, 0xD1, 0xEF, 0x80),
+        1.0,
+        &StrokeStyle::default(),
+    )?;
+    for (index, path) in shape.paths().filter(|p| !p.is_empty()).enumerate() {
+        if index == 0 {
+            ctx.start_shape(&(t * path[0].coord()))?;
+        } else {
+            ctx.start_subpath(&(t * path[0].coord()))?;
+        }
+        for i in (1..(path.len() - 1)).step_by(3) {
+            ctx.move_curve(
+                &(t * path[i].coord()),
+                &(t * path[i + 1].coord()),
+                &(t * path[i + 2].coord()),
+            )?;
+        }
+        if path_encloses(path) {
+            ctx.close_subpath()?;
+        }
+    }
+    ctx.end_shape()?;
+    ctx.set_stroke(&Rgba::transparent(), 0.0, &StrokeStyle::default())?;
+    Ok(())
+}
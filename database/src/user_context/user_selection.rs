@@ -1,10 +1,14 @@
-use common::types::{Coord, ScreenLength2d};
+use std::collections::HashSet;
+
+use common::pures::Affine;
+use common::types::{Coord, Length2d, Rect, ScreenLength2d};
 use common::Rgba;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
 
-use crate::{CoordId, LayerId};
-use common::math::point_in_radius;
+use crate::{CoordId, LayerId, PathEnd, Shape};
 
-use crate::user_context::SceneUserContext;
+use crate::user_context::{guides::Guide, snap::SnapSettings, SceneUserContext};
 
 #[derive(Debug, Default)]
 pub struct UserSelection {
@@ -14,6 +18,13 @@ pub struct UserSelection {
     pub color: Rgba,
     pub stroke_size: f32,
     pub stroke_color: Rgba,
+    pub pen: Option<PenPath>,
+    pub gizmo: Option<GizmoDrag>,
+    pub snap: SnapSettings,
+    /// Alignment guides of the current drag
+    pub guides: Vec<Guide>,
+    /// Shape sized by the current drag, inserted at the end of the drag
+    pub primitive: Option<Shape>,
 }
 
 #[derive(Debug, Default)]
@@ -22,6 +33,64 @@ pub struct SelectedShape {
     pub coords: Vec<CoordId>,
 }
 
+/// Open path drawn with the pen tool
+#[derive(Debug, Clone)]
+pub struct PenPath {
+    pub shape_id: LayerId,
+    pub subpath: usize,
+    /// End of the subpath where the next anchor is added
+    pub end: PathEnd,
+    /// Handle of the end anchor toward the next anchor, from the drag out of the anchor
+    pub handle: Option<Coord>,
+}
+
+/// Handle of the transform gizmo around the selected shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GizmoHandle {
+    TopLeft,
+    Top,
+    TopRight,
+    Right,
+    BottomRight,
+    Bottom,
+    BottomLeft,
+    Left,
+    /// Zone outside of a corner of the bounds
+    Rotate,
+}
+
+/// Drag of a gizmo handle in progress
+#[derive(Debug, Clone)]
+pub struct GizmoDrag {
+    pub handle: GizmoHandle,
+    /// Bounds of the selection when the drag started
+    pub bounds: Rect,
+    pub start: Coord,
+    /// Transform applied to the selection since the drag started
+    pub applied: Affine,
+}
+
+/// Shapes selected by a marquee
+#[derive(Tsify, Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum MarqueeMode {
+    /// Any part of the shape is in the marquee
+    #[default]
+    Touch,
+    /// The whole shape is in the marquee
+    Enclose,
+}
+
+/// How a new selection is combined with the current one
+#[derive(Tsify, Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+pub enum SelectionModifier {
+    #[default]
+    Replace,
+    Add,
+    Subtract,
+}
+
 #[derive(Debug, Clone)]
 pub struct HoverCoord {
     pub shape_index: LayerId,
@@ -109,10 +178,11 @@ impl UserSelection {
                 None => continue,
             };
 
-            if colors.contains(&shape.color) {
+            let color = shape.fill.color();
+            if colors.contains(&color) {
                 continue;
             }
-            colors.push(shape.color.clone());
+            colors.push(color);
         }
 
         colors
@@ -154,32 +224,19 @@ impl UserSelection {
                 None => continue,
             };
 
-            stroke_colors.push(shape.stroke.color.clone());
+            stroke_colors.push(shape.stroke.paint.color());
         }
 
         stroke_colors
     }
 
     pub fn change_hover(&mut self, canvas_context: &SceneUserContext, cursor_position: Coord) {
-        'shape_loop: for shape_selected in &mut self.shapes {
-            let shape = canvas_context
+        let radius = coord_radius(canvas_context);
+        for shape_selected in &mut self.shapes {
+            self.hover_coord = canvas_context
                 .scene()
-                .shape_select(shape_selected.shape_id)
-                .unwrap();
-            let db_coords = &shape.path;
-            for db_coord in db_coords {
-                if point_in_radius(
-                    cursor_position,
-                    db_coord.coord(),
-                    canvas_context
-                        .camera
-                        .transform_to_length2d(ScreenLength2d::new(12.0, 12.0)),
-                ) {
-                    self.hover_coord = Some(HoverCoord::new(shape_selected.shape_id, db_coord.id));
-                    continue 'shape_loop;
-                }
-            }
-            self.hover_coord = None;
+                .shape_coord_near(shape_selected.shape_id, &cursor_position, radius)
+                .map(|db_coord| HoverCoord::new(shape_selected.shape_id, db_coord.id));
         }
     }
 
@@ -188,22 +245,16 @@ impl UserSelection {
 
         if selected_shapes.is_empty() {
             //Add shape
-            let closest_shapes = canvas_context.scene().shape_select_contains(&start_press);
+            let closest_shapes = canvas_context
+                .scene()
+                .shape_select_hit(&start_press, hit_tolerance(canvas_context));
 
             if let Some(shape) = closest_shapes {
-                let pos = selected_shapes
-                    .iter()
-                    .position(|shape_selected| shape_selected.shape_id == shape.id);
-
-                match pos {
-                    Some(index) => {
-                        let elment = selected_shapes.remove(index);
-                        selected_shapes.clear();
-                        selected_shapes.push(elment);
-                    }
-                    None => {
-                        selected_shapes.clear();
-                        selected_shapes.push(SelectedShape::new(shape.id));
+                let group = shape_group(canvas_context, shape.id);
+                selected_shapes.retain(|shape_selected| group.contains(&shape_selected.shape_id));
+                for shape_id in group {
+                    if !selected_shapes.iter().any(|s| s.shape_id == shape_id) {
+                        selected_shapes.push(SelectedShape::new(shape_id));
                     }
                 }
             }
@@ -214,24 +265,15 @@ impl UserSelection {
                 selected_shape.coords.clear();
             }
 
+            let radius = coord_radius(canvas_context);
             for selected_shape in selected_shapes {
-                let shape = canvas_context
-                    .scene()
-                    .shape_select(selected_shape.shape_id)
-                    .unwrap();
-                let coords = &shape.path;
-                for db_coord in coords {
-                    let coord = db_coord.coord();
-                    if point_in_radius(
-                        start_press,
-                        coord,
-                        canvas_context
-                            .camera
-                            .transform_to_length2d(ScreenLength2d::new(12.0, 12.0)),
-                    ) {
-                        selected_shape.coords.push(db_coord.id);
-                        return;
-                    }
+                if let Some(db_coord) = canvas_context.scene().shape_coord_near(
+                    selected_shape.shape_id,
+                    &start_press,
+                    radius,
+                ) {
+                    selected_shape.coords.push(db_coord.id);
+                    return;
                 }
             }
         }
@@ -239,42 +281,36 @@ impl UserSelection {
 
     pub fn add_selection(&mut self, canvas_context: &SceneUserContext, start_press: Coord) {
         //Coord
+        let radius = coord_radius(canvas_context);
         for shape_selected in &mut self.shapes {
-            let shape = canvas_context
-                .scene()
-                .shape_select(shape_selected.shape_id)
-                .unwrap();
-            let db_coords = &shape.path;
-            for db_coord in db_coords {
-                let coord = db_coord.coord();
-                if point_in_radius(
-                    start_press,
-                    coord,
-                    canvas_context
-                        .camera
-                        .transform_to_length2d(ScreenLength2d::new(12.0, 12.0)),
-                ) {
-                    let pos = shape_selected
-                        .coords
-                        .iter()
-                        .position(|coord_id| *coord_id == db_coord.id);
-                    match pos {
-                        Some(index) => {
-                            shape_selected.coords.swap_remove(index);
-                        }
-                        None => {
-                            shape_selected.coords.push(db_coord.id);
-                        }
+            if let Some(db_coord) = canvas_context.scene().shape_coord_near(
+                shape_selected.shape_id,
+                &start_press,
+                radius,
+            ) {
+                let pos = shape_selected
+                    .coords
+                    .iter()
+                    .position(|coord_id| *coord_id == db_coord.id);
+                match pos {
+                    Some(index) => {
+                        shape_selected.coords.swap_remove(index);
+                    }
+                    None => {
+                        shape_selected.coords.push(db_coord.id);
                     }
-                    return;
                 }
+                return;
             }
         }
 
-        let shape = canvas_context.scene().shape_select_contains(&start_press);
+        let shape = canvas_context
+            .scene()
+            .shape_select_hit(&start_press, hit_tolerance(canvas_context));
 
         if let Some(shape) = shape {
             let shapes = &mut self.shapes;
+            let group = shape_group(canvas_context, shape.id);
             let pos = shapes
                 .iter()
                 .position(|shape_selected| shape_selected.shape_id == shape.id);
@@ -287,11 +323,18 @@ impl UserSelection {
                         .coords
                         .is_empty()
                     {
-                        shapes.swap_remove(index);
+                        shapes.retain(|shape_selected| {
+                            !group.contains(&shape_selected.shape_id)
+                                || !shape_selected.coords.is_empty()
+                        });
                     }
                 }
                 None => {
-                    shapes.push(SelectedShape::new(shape.id));
+                    for shape_id in group {
+                        if !shapes.iter().any(|s| s.shape_id == shape_id) {
+                            shapes.push(SelectedShape::new(shape_id));
+                        }
+                    }
                 }
             }
         }
@@ -309,6 +352,127 @@ impl UserSelection {
     }
 }
 
+impl UserSelection {
+    /// Combine the shapes with the selected shapes
+    pub(super) fn select_shapes(&mut self, shapes: Vec<LayerId>, modifier: SelectionModifier) {
+        match modifier {
+            SelectionModifier::Replace => {
+                self.shapes = shapes.into_iter().map(SelectedShape::new).collect();
+            }
+            SelectionModifier::Add => {
+                for shape_id in shapes {
+                    if !self.shapes.iter().any(|s| s.shape_id == shape_id) {
+                        self.shapes.push(SelectedShape::new(shape_id));
+                    }
+                }
+            }
+            SelectionModifier::Subtract => {
+                self.shapes.retain(|s| !shapes.contains(&s.shape_id));
+            }
+        }
+    }
+
+    /// Combine the coords of the selected shapes that are inside with their selected coords
+    pub(super) fn select_coords(
+        &mut self,
+        canvas_context: &SceneUserContext,
+        inside: impl Fn(&Coord) -> bool,
+        modifier: SelectionModifier,
+    ) {
+        for selected_shape in &mut self.shapes {
+            let Some(shape) = canvas_context.scene().shape_select(selected_shape.shape_id) else {
+                continue;
+            };
+            let mut coords: Vec<CoordId> = Vec::new();
+            for db_coord in shape.paths().flatten() {
+                if inside(&db_coord.coord()) && !coords.contains(&db_coord.id) {
+                    coords.push(db_coord.id);
+                }
+            }
+            match modifier {
+                SelectionModifier::Replace => selected_shape.coords = coords,
+                SelectionModifier::Add => {
+                    coords.retain(|id| !selected_shape.coords.contains(id));
+                    selected_shape.coords.extend(coords);
+                }
+                SelectionModifier::Subtract => {
+                    selected_shape.coords.retain(|id| !coords.contains(id));
+                }
+            }
+        }
+    }
+}
+
+/// Visible and unlocked shapes in the area selected by the predicate, in the order of the layers
+/// from the foreground. A group at the root of the scene is selected as a unit, with whole_group
+/// only when all its visible and unlocked shapes are selected by the predicate
+pub(super) fn shapes_selected_by(
+    canvas_context: &SceneUserContext,
+    area: &Rect,
+    select: impl Fn(&Shape) -> bool,
+    whole_group: bool,
+) -> Vec<LayerId> {
+    let scene = canvas_context.scene();
+    let hits: Vec<LayerId> = scene
+        .shapes_visible_in(true, area)
+        .into_iter()
+        .filter(|(shape, _)| select(shape))
+        .map(|(shape, _)| shape.id)
+        .collect();
+    let hit_ids: HashSet<LayerId> = hits.iter().copied().collect();
+    let selectable: HashSet<LayerId> = match whole_group {
+        true => scene
+            .shapes_visible(true)
+            .into_iter()
+            .map(|(shape, _)| shape.id)
+            .collect(),
+        false => HashSet::new(),
+    };
+
+    let mut shapes = Vec::new();
+    let mut added = HashSet::new();
+    for hit in &hits {
+        let group = shape_group(canvas_context, *hit);
+        let selected = !whole_group
+            || group
+                .iter()
+                .all(|id| hit_ids.contains(id) || !selectable.contains(id));
+        if !selected {
+            continue;
+        }
+        for shape_id in group {
+            if added.insert(shape_id) {
+                shapes.push(shape_id);
+            }
+        }
+    }
+    shapes
+}
+
+/// Shapes selected together with the shape, a group at the root of the scene is selected as a unit
+fn shape_group(canvas_context: &SceneUserContext, shape: LayerId) -> Vec<LayerId> {
+    let scene = canvas_context.scene();
+    match scene.layer_root(shape) {
+        Some(root) => scene.layer_shapes(root),
+        None => vec![shape],
+    }
+}
+
+/// Distance in the scene to hover or select a coord
+fn coord_radius(canvas_context: &SceneUserContext) -> Length2d {
+    canvas_context
+        .camera
+        .transform_to_length2d(ScreenLength2d::new(12.0, 12.0))
+}
+
+/// Distance in the scene to select an open path by clicking next to its stroke
+fn hit_tolerance(canvas_context: &SceneUserContext) -> f32 {
+    canvas_context
+        .camera
+        .transform_to_length2d(ScreenLength2d::new(6.0, 6.0))
+        .x
+}
+
 pub enum CoordState {
     Hover,
     Selected,
@@ -54,6 +54,9 @@ export class MoveCoordService extends Functionality {
 
         const endPress = this.eventsService.mouseUp$.subscribe(() => {
             this.startPress = undefined;
+            this.scenesService.currentSceneNow((scene) => {
+                scene.sceneClient.drag_end(this.selectionService.selection);
+            });
         });
 
         const selecShape = this.eventsService.mouseDown$
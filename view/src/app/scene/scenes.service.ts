@@ -87,7 +87,13 @@ export class ScenesService {
                 const reader = new FileReader();
                 reader.onload = () => {
                     const buffer = reader.result as ArrayBuffer;
-                    const canvasContent = SceneClient.load(new Uint8Array(buffer));
+                    let canvasContent: SceneClient;
+                    try {
+                        canvasContent = SceneClient.load(new Uint8Array(buffer));
+                    } catch (error) {
+                        console.error("Failed to load " + file.name + ": " + error);
+                        return;
+                    }
                     const scene = new Scene(canvasContent);
                     let filename = file.name;
                     if (filename.endsWith(".mvg")) {
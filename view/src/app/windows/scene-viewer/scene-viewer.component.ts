@@ -29,8 +29,6 @@ import { LayerThumbnailComponent } from "./layer-thumbnail/layer-thumbnail.compo
 export interface LocalTreeViewModel {
     expandable?: boolean;
     isExpanded?: boolean;
-    lockEdit?: boolean;
-    hideLayer?: boolean;
 }
 
 export type MergedTreeViewModel = TreeViewModel & LocalTreeViewModel;
@@ -134,14 +132,20 @@ export class SceneViewerComponent {
     }
 
     protected toggleShowLayer(node: MergedTreeViewModel) {
-        node.hideLayer = node.hideLayer == undefined ? true : !node.hideLayer;
-
         this.scenes.currentSceneNow((scene) => {
-            if (node.hideLayer) {
+            if (node.visible) {
                 scene.sceneClient.hide_layer(node.layer_id);
             } else {
                 scene.sceneClient.show_layer(node.layer_id);
             }
+            this.treeData.next(scene.sceneClient.get_tree_view());
+        });
+    }
+
+    protected toggleLockLayer(node: MergedTreeViewModel) {
+        this.scenes.currentSceneNow((scene) => {
+            scene.sceneClient.set_layer_locked(node.layer_id, !node.locked);
+            this.treeData.next(scene.sceneClient.get_tree_view());
         });
     }
 }
@@ -1,9 +1,19 @@
-use common::{types::ScreenCoord, Rgba};
-use database::{LayerId, SceneUserContext, TreeViewModel};
+use common::{
+    types::{Coord, ScreenCoord},
+    Rgba,
+};
+use database::{
+    commands::{
+        AlignTarget, Alignment, ChangeLayerLocked, ChangeLayerOpacity, ChangeLayerVisible,
+        LayerDestination, PaintTarget, Pivot, RenameLayer,
+    },
+    Clipboard, GuideLabel, LineCap, LineJoin, MarqueeMode, Paint, Primitive, SceneUserContext,
+    SelectionModifier, StrokeAlign, SvgExportOption, TreeViewModel,
+};
 use js_sys::Uint8Array;
 use wasm_bindgen::prelude::wasm_bindgen;
 
-use crate::{SceneClient, UserSelectionClient};
+use crate::{SceneClient, SelectedLevelClient, UserSelectionClient};
 
 #[wasm_bindgen]
 impl SceneClient {
@@ -17,6 +27,88 @@ impl SceneClient {
             .set_stroke_size_of(&mut selected.selection, size);
     }
 
+    pub fn set_paint_of(
+        &mut self,
+        selected: &UserSelectionClient,
+        target: PaintTarget,
+        paint: Paint,
+    ) {
+        self.scene_context
+            .set_paint_of(&selected.selection, target, paint);
+    }
+
+    pub fn set_stroke_join_of(&mut self, selected: &UserSelectionClient, join: LineJoin) {
+        self.scene_context
+            .set_stroke_join_of(&selected.selection, join);
+    }
+
+    pub fn set_stroke_cap_of(&mut self, selected: &UserSelectionClient, cap: LineCap) {
+        self.scene_context
+            .set_stroke_cap_of(&selected.selection, cap);
+    }
+
+    pub fn set_stroke_miter_limit_of(&mut self, selected: &UserSelectionClient, miter_limit: f64) {
+        self.scene_context
+            .set_stroke_miter_limit_of(&selected.selection, miter_limit);
+    }
+
+    pub fn set_stroke_dashes_of(
+        &mut self,
+        selected: &UserSelectionClient,
+        dashes: Vec<f32>,
+        offset: f64,
+    ) {
+        self.scene_context
+            .set_stroke_dashes_of(&selected.selection, dashes, offset);
+    }
+
+    pub fn set_stroke_align_of(&mut self, selected: &UserSelectionClient, align: StrokeAlign) {
+        self.scene_context
+            .set_stroke_align_of(&selected.selection, align);
+    }
+
+    pub fn outline_stroke(&mut self, selected: &mut UserSelectionClient) -> Result<(), String> {
+        self.scene_context.outline_stroke(&mut selected.selection)
+    }
+
+    pub fn group_selection(&mut self, selected: &UserSelectionClient) -> Result<(), String> {
+        self.scene_context.group_selection(&selected.selection)
+    }
+
+    pub fn ungroup_selection(&mut self, selected: &UserSelectionClient) -> Result<(), String> {
+        self.scene_context.ungroup_selection(&selected.selection)
+    }
+
+    pub fn align_shapes_of(
+        &mut self,
+        selected: &UserSelectionClient,
+        alignment: Alignment,
+    ) -> Result<(), String> {
+        self.scene_context
+            .align_shapes_of(&selected.selection, alignment, AlignTarget::Selection)
+    }
+
+    /// Align to the bounds of the key shape, which does not move
+    pub fn align_shapes_to_key(
+        &mut self,
+        selected: &UserSelectionClient,
+        alignment: Alignment,
+        key: usize,
+    ) -> Result<(), String> {
+        let target = AlignTarget::Key(key.into());
+        self.scene_context
+            .align_shapes_of(&selected.selection, alignment, target)
+    }
+
+    pub fn align_shapes_to_scene(
+        &mut self,
+        selected: &UserSelectionClient,
+        alignment: Alignment,
+    ) -> Result<(), String> {
+        self.scene_context
+            .align_shapes_of(&selected.selection, alignment, AlignTarget::Scene)
+    }
+
     pub fn set_stroke_color_of(&mut self, selected: &mut UserSelectionClient, fill: Rgba) {
         self.scene_context
             .set_stroke_color_of(&mut selected.selection, fill);
@@ -24,12 +116,20 @@ impl SceneClient {
 
     pub fn move_coords_of(
         &mut self,
-        selected: &UserSelectionClient,
+        selected: &mut UserSelectionClient,
         start: ScreenCoord,
         end: ScreenCoord,
     ) {
         self.scene_context
-            .move_coords_of(&selected.selection, start, end);
+            .move_coords_of(&mut selected.selection, start, end);
+    }
+
+    pub fn drag_end(&self, selected: &mut UserSelectionClient) {
+        self.scene_context.drag_end(&mut selected.selection);
+    }
+
+    pub fn guide_labels(&self, selected: &UserSelectionClient) -> Vec<GuideLabel> {
+        self.scene_context.guide_labels(&selected.selection)
     }
 
     pub fn add_or_remove_coord(
@@ -53,9 +153,94 @@ impl SceneClient {
         self.scene_context.save().expect("failed to save")
     }
 
-    pub fn load(data: Uint8Array) -> Self {
-        let scene_context = SceneUserContext::load(data.to_vec()).expect("failed to load");
-        Self { scene_context }
+    pub fn load(data: Uint8Array) -> Result<SceneClient, String> {
+        let scene_context = SceneUserContext::load(data.to_vec()).map_err(|e| e.to_string())?;
+        Ok(Self::from_context(scene_context))
+    }
+
+    pub fn load_svg(text: &str) -> Result<SceneClient, String> {
+        let (scene_context, warnings) =
+            SceneUserContext::from_svg(text).map_err(|e| e.to_string())?;
+        for warning in warnings {
+            log::warn!("SVG import: {}", warning);
+        }
+        Ok(Self::from_context(scene_context))
+    }
+
+    /// None if no shape is selected
+    pub fn copy_selection(
+        &self,
+        selected: &UserSelectionClient,
+    ) -> Result<Option<Clipboard>, String> {
+        self.scene_context.copy_selection(&selected.selection)
+    }
+
+    pub fn cut_selection(
+        &mut self,
+        selected: &mut UserSelectionClient,
+    ) -> Result<Option<Clipboard>, String> {
+        self.scene_context.cut_selection(&mut selected.selection)
+    }
+
+    /// Paste the clipboard of a tab of the editor, its SVG is used if the data is from a newer
+    /// version
+    pub fn paste(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        clipboard: Clipboard,
+        offset: Coord,
+    ) -> Result<(), String> {
+        let warnings = self
+            .scene_context
+            .paste(&mut selected.selection, &clipboard, offset)?;
+        for warning in warnings {
+            log::warn!("SVG import: {}", warning);
+        }
+        Ok(())
+    }
+
+    /// Paste a SVG document copied from another application
+    pub fn paste_svg(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        text: String,
+        offset: Coord,
+    ) -> Result<(), String> {
+        let clipboard = Clipboard {
+            data: Vec::new(),
+            svg: text,
+        };
+        self.paste(selected, clipboard, offset)
+    }
+
+    /// Import the shapes of a SVG document on top of the scene, undone in one step
+    pub fn import_svg(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        text: String,
+    ) -> Result<(), String> {
+        let warnings = self
+            .scene_context
+            .import_svg(&mut selected.selection, &text)?;
+        for warning in warnings {
+            log::warn!("SVG import: {}", warning);
+        }
+        Ok(())
+    }
+
+    pub fn duplicate_selection(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        offset: Coord,
+    ) -> Result<(), String> {
+        self.scene_context
+            .duplicate_selection(&mut selected.selection, offset)
+    }
+
+    pub fn export_svg(&self, width: f32, height: f32) -> String {
+        self.scene_context
+            .scene()
+            .to_svg(&SvgExportOption::new(width, height))
     }
 
     pub fn get_tree_view(&self) -> Vec<TreeViewModel> {
@@ -67,25 +252,60 @@ impl SceneClient {
             .command_handler
             .execute(database::commands::MoveLayer::boxed(
                 id_to_move.into(),
-                id_position.into(),
+                LayerDestination::Before(id_position.into()),
+            ))
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+
+    pub fn move_layer_into(&mut self, id_to_move: usize, id_folder: usize) -> Result<(), String> {
+        self.scene_context
+            .command_handler
+            .execute(database::commands::MoveLayer::boxed(
+                id_to_move.into(),
+                LayerDestination::Into(id_folder.into()),
             ))
             .map_err(|e| format!("{:?}", e))?;
         Ok(())
     }
 
     pub fn hide_layer(&mut self, id_to_hide: usize) -> Result<(), String> {
-        let skip_layers = &mut self.scene_context.render_options.skip_layers;
-        if !skip_layers.iter().any(|x| *x == LayerId::from(id_to_hide)) {
-            skip_layers.push(id_to_hide.into());
-        }
+        self.scene_context
+            .command_handler
+            .execute(ChangeLayerVisible::boxed(id_to_hide.into(), false))
+            .map_err(|e| format!("{:?}", e))?;
         Ok(())
     }
 
     pub fn show_layer(&mut self, id_to_show: usize) -> Result<(), String> {
         self.scene_context
-            .render_options
-            .skip_layers
-            .retain(|&x| x != id_to_show.into());
+            .command_handler
+            .execute(ChangeLayerVisible::boxed(id_to_show.into(), true))
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+
+    pub fn set_layer_locked(&mut self, layer_id: usize, locked: bool) -> Result<(), String> {
+        self.scene_context
+            .command_handler
+            .execute(ChangeLayerLocked::boxed(layer_id.into(), locked))
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+
+    pub fn set_layer_opacity(&mut self, layer_id: usize, opacity: f32) -> Result<(), String> {
+        self.scene_context
+            .command_handler
+            .execute(ChangeLayerOpacity::boxed(layer_id.into(), opacity))
+            .map_err(|e| format!("{:?}", e))?;
+        Ok(())
+    }
+
+    pub fn rename_layer(&mut self, layer_id: usize, name: String) -> Result<(), String> {
+        self.scene_context
+            .command_handler
+            .execute(RenameLayer::boxed(layer_id.into(), name))
+            .map_err(|e| format!("{:?}", e))?;
         Ok(())
     }
 
@@ -123,3 +343,145 @@ impl SceneClient {
         Ok(())
     }
 }
+
+#[wasm_bindgen]
+impl SceneClient {
+    pub fn pen_start(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        position: ScreenCoord,
+        drag: ScreenCoord,
+    ) -> Result<(), String> {
+        self.scene_context
+            .pen_start(&mut selected.selection, position, drag)
+    }
+
+    pub fn pen_continue(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        position: ScreenCoord,
+    ) -> bool {
+        self.scene_context
+            .pen_continue(&mut selected.selection, position)
+    }
+
+    pub fn pen_append(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        position: ScreenCoord,
+        drag: ScreenCoord,
+    ) -> Result<(), String> {
+        self.scene_context
+            .pen_append(&mut selected.selection, position, drag)
+    }
+
+    pub fn pen_close(&mut self, selected: &mut UserSelectionClient) -> Result<(), String> {
+        self.scene_context.pen_close(&mut selected.selection)
+    }
+
+    pub fn pen_end(&mut self, selected: &mut UserSelectionClient) {
+        self.scene_context.pen_end(&mut selected.selection);
+    }
+}
+
+#[wasm_bindgen]
+impl SceneClient {
+    pub fn translate_shapes_of(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        start: ScreenCoord,
+        end: ScreenCoord,
+    ) {
+        self.scene_context
+            .translate_shapes_of(&mut selected.selection, start, end);
+    }
+
+    pub fn scale_shapes_of(
+        &mut self,
+        selected: &UserSelectionClient,
+        scale: Coord,
+        pivot: Pivot,
+        scale_stroke: bool,
+    ) {
+        self.scene_context
+            .scale_shapes_of(&selected.selection, scale, pivot, scale_stroke);
+    }
+
+    pub fn rotate_shapes_of(&mut self, selected: &UserSelectionClient, angle: f32, pivot: Pivot) {
+        self.scene_context
+            .rotate_shapes_of(&selected.selection, angle, pivot);
+    }
+
+    pub fn flip_horizontal_of(&mut self, selected: &UserSelectionClient) {
+        self.scene_context.flip_horizontal_of(&selected.selection);
+    }
+
+    pub fn flip_vertical_of(&mut self, selected: &UserSelectionClient) {
+        self.scene_context.flip_vertical_of(&selected.selection);
+    }
+}
+
+#[wasm_bindgen]
+impl SceneClient {
+    pub fn gizmo_start(&self, selected: &mut UserSelectionClient, position: ScreenCoord) -> bool {
+        self.scene_context
+            .gizmo_start(&mut selected.selection, position)
+    }
+
+    pub fn gizmo_drag(
+        &mut self,
+        selected: &mut UserSelectionClient,
+        position: ScreenCoord,
+        proportional: bool,
+        snap_angle: Option<f32>,
+    ) {
+        self.scene_context
+            .gizmo_drag(&mut selected.selection, position, proportional, snap_angle);
+    }
+
+    pub fn gizmo_end(&mut self, selected: &mut UserSelectionClient) {
+        self.scene_context.gizmo_end(&mut selected.selection);
+    }
+
+    pub fn primitive_drag(
+        &self,
+        selected: &mut UserSelectionClient,
+        primitive: Primitive,
+        start: ScreenCoord,
+        end: ScreenCoord,
+        constrain: bool,
+        from_center: bool,
+    ) {
+        self.scene_context.primitive_drag(
+            &mut selected.selection,
+            primitive,
+            start,
+            end,
+            constrain,
+            from_center,
+        );
+    }
+
+    pub fn primitive_end(&mut self, selected: &mut UserSelectionClient) -> Result<(), String> {
+        self.scene_context.primitive_end(&mut selected.selection)
+    }
+
+    pub fn marquee_select(
+        &self,
+        selected: &mut UserSelectionClient,
+        start: ScreenCoord,
+        end: ScreenCoord,
+        level: SelectedLevelClient,
+        mode: MarqueeMode,
+        modifier: SelectionModifier,
+    ) {
+        self.scene_context.marquee_select(
+            &mut selected.selection,
+            start,
+            end,
+            level.into(),
+            mode,
+            modifier,
+        );
+    }
+}
@@ -1,13 +1,24 @@
 use common::types::Coord;
 use common::Rgba;
 use common::{pures::Affine, types::ScreenRect};
-use database::DrawingContext;
-use web_sys::{CanvasRenderingContext2d, CanvasWindingRule};
+use database::{
+    DrawingContext, FillRule, GradientStop, LineCap, LineJoin, StrokeAlign, StrokeStyle,
+};
+use js_sys::Array;
+use wasm_bindgen::JsValue;
+use web_sys::{CanvasGradient, CanvasRenderingContext2d, CanvasWindingRule, HtmlCanvasElement};
+
+use crate::rendering::offscreen_canvas;
 
 pub struct CanvasContext2DRender<'a> {
     context: &'a CanvasRenderingContext2d,
+    /// Canvases of the open groups with the opacity of the groups, the shapes go to the last one
+    groups: Vec<(HtmlCanvasElement, CanvasRenderingContext2d, f32)>,
     transform: Affine,
     max_view: ScreenRect,
+    fill_rule: CanvasWindingRule,
+    stroke_size: f64,
+    stroke_align: StrokeAlign,
 }
 
 impl<'a> CanvasContext2DRender<'a> {
@@ -18,19 +29,134 @@ impl<'a> CanvasContext2DRender<'a> {
     ) -> Self {
         Self {
             context,
+            groups: Vec::new(),
             transform,
             max_view,
+            fill_rule: CanvasWindingRule::Evenodd,
+            stroke_size: 0.0,
+            stroke_align: StrokeAlign::Center,
+        }
+    }
+
+    /// Context of the last open group, or of the canvas
+    fn ctx(&self) -> &CanvasRenderingContext2d {
+        match self.groups.last() {
+            Some((_, ctx, _)) => ctx,
+            None => self.context,
+        }
+    }
+
+    fn set_stroke_style(&mut self, size: f64, style: &StrokeStyle) -> Result<(), String> {
+        self.ctx().set_line_width(size);
+        self.ctx().set_line_join(match style.join {
+            LineJoin::Miter => "miter",
+            LineJoin::Round => "round",
+            LineJoin::Bevel => "bevel",
+        });
+        self.ctx().set_line_cap(match style.cap {
+            LineCap::Butt => "butt",
+            LineCap::Round => "round",
+            LineCap::Square => "square",
+        });
+        self.ctx().set_miter_limit(style.miter_limit as f64);
+        let dashes: Array = style.dashes.iter().map(|d| JsValue::from_f64(*d)).collect();
+        self.ctx()
+            .set_line_dash(&dashes)
+            .map_err(|e| format!("{:?}", e))?;
+        self.ctx().set_line_dash_offset(style.dash_offset);
+        self.stroke_size = size;
+        self.stroke_align = style.align;
+        Ok(())
+    }
+
+    /// Fill and stroke the current path.
+    /// The inside and outside strokes are centered strokes of twice the size, clipped by the path.
+    fn draw_path(&self) {
+        self.ctx().fill_with_canvas_winding_rule(self.fill_rule);
+        match self.stroke_align {
+            StrokeAlign::Center => self.ctx().stroke(),
+            align => {
+                self.ctx().save();
+                if align == StrokeAlign::Inside {
+                    self.ctx().clip_with_canvas_winding_rule(self.fill_rule);
+                } else {
+                    // A rectangle around the view turns the clip inside out, its own stroke is
+                    // out of the view
+                    let margin = self.stroke_size * 2.0;
+                    let view = self.max_view;
+                    let left = view.top_left.x.min(view.bottom_right.x) as f64 - margin;
+                    let top = view.top_left.y.min(view.bottom_right.y) as f64 - margin;
+                    self.ctx().rect(
+                        left,
+                        top,
+                        view.width().abs() as f64 + margin * 2.0,
+                        view.height().abs() as f64 + margin * 2.0,
+                    );
+                    self.ctx()
+                        .clip_with_canvas_winding_rule(CanvasWindingRule::Evenodd);
+                }
+                self.ctx().set_line_width(self.stroke_size * 2.0);
+                self.ctx().stroke();
+                self.ctx().restore();
+            }
         }
     }
+
+    fn linear_gradient(
+        &self,
+        start: &Coord,
+        end: &Coord,
+        stops: &[GradientStop],
+    ) -> Result<CanvasGradient, String> {
+        let gradient = self.ctx().create_linear_gradient(
+            start.x as f64,
+            start.y as f64,
+            end.x as f64,
+            end.y as f64,
+        );
+        add_color_stops(&gradient, stops)?;
+        Ok(gradient)
+    }
+
+    fn radial_gradient(
+        &self,
+        center: &Coord,
+        radius: f32,
+        stops: &[GradientStop],
+    ) -> Result<CanvasGradient, String> {
+        let gradient = self
+            .context
+            .create_radial_gradient(
+                center.x as f64,
+                center.y as f64,
+                0.0,
+                center.x as f64,
+                center.y as f64,
+                radius.max(0.0) as f64,
+            )
+            .map_err(|e| format!("{:?}", e))?;
+        add_color_stops(&gradient, stops)?;
+        Ok(gradient)
+    }
+}
+
+/// The offsets are clamped, the canvas throws outside of 0.0 to 1.0
+fn add_color_stops(gradient: &CanvasGradient, stops: &[GradientStop]) -> Result<(), String> {
+    for stop in stops {
+        gradient
+            .add_color_stop(stop.offset.clamp(0.0, 1.0), &stop.color.to_css_string())
+            .map_err(|e| format!("{:?}", e))?;
+    }
+    Ok(())
 }
 
 impl<'a> DrawingContext for CanvasContext2DRender<'a> {
     fn create(&mut self) -> Result<(), String> {
-        self.context
+        self.ctx()
             .set_fill_style(&String::from("rgba(0, 0, 0, 0)").into());
-        self.context
+        self.ctx()
             .set_stroke_style(&String::from("rgba(0, 0, 0, 0)").into());
-        self.context.set_line_width(0.0);
+        self.ctx().set_line_width(0.0);
         Ok(())
     }
 
@@ -43,44 +169,105 @@ impl<'a> DrawingContext for CanvasContext2DRender<'a> {
     }
 
     fn fill_background(&mut self, color: &Rgba) -> Result<(), String> {
-        self.context.set_fill_style(&color.to_css_string().into());
+        self.ctx().set_fill_style(&color.to_css_string().into());
 
         let corner0 = self.transform * Coord::new(-1.0, -1.0);
         let corner1 = self.transform * Coord::new(-1.0, 1.0);
         let corner2 = self.transform * Coord::new(1.0, 1.0);
         let corner3 = self.transform * Coord::new(1.0, -1.0);
 
-        self.context.begin_path();
-        self.context.move_to(corner0.x as f64, corner0.y as f64);
-        self.context.line_to(corner1.x as f64, corner1.y as f64);
-        self.context.line_to(corner2.x as f64, corner2.y as f64);
-        self.context.line_to(corner3.x as f64, corner3.y as f64);
-        self.context.close_path();
-        self.context.fill();
+        self.ctx().begin_path();
+        self.ctx().move_to(corner0.x as f64, corner0.y as f64);
+        self.ctx().line_to(corner1.x as f64, corner1.y as f64);
+        self.ctx().line_to(corner2.x as f64, corner2.y as f64);
+        self.ctx().line_to(corner3.x as f64, corner3.y as f64);
+        self.ctx().close_path();
+        self.ctx().fill();
 
         Ok(())
     }
 
     fn set_fill(&mut self, color: &Rgba) -> Result<(), String> {
-        self.context.set_fill_style(&color.to_css_string().into());
+        self.ctx().set_fill_style(&color.to_css_string().into());
         Ok(())
     }
 
-    fn set_stroke(&mut self, color: &Rgba, size: f64) -> Result<(), String> {
-        self.context.set_stroke_style(&color.to_css_string().into());
-        self.context.set_line_width(size);
+    fn set_stroke(&mut self, color: &Rgba, size: f64, style: &StrokeStyle) -> Result<(), String> {
+        self.ctx().set_stroke_style(&color.to_css_string().into());
+        self.set_stroke_style(size, style)
+    }
+
+    fn set_fill_linear_gradient(
+        &mut self,
+        start: &Coord,
+        end: &Coord,
+        stops: &[GradientStop],
+    ) -> Result<(), String> {
+        let gradient = self.linear_gradient(start, end, stops)?;
+        self.ctx().set_fill_style(&gradient);
+        Ok(())
+    }
+
+    fn set_fill_radial_gradient(
+        &mut self,
+        center: &Coord,
+        radius: f32,
+        stops: &[GradientStop],
+    ) -> Result<(), String> {
+        let gradient = self.radial_gradient(center, radius, stops)?;
+        self.ctx().set_fill_style(&gradient);
+        Ok(())
+    }
+
+    fn set_stroke_linear_gradient(
+        &mut self,
+        start: &Coord,
+        end: &Coord,
+        stops: &[GradientStop],
+        size: f64,
+        style: &StrokeStyle,
+    ) -> Result<(), String> {
+        let gradient = self.linear_gradient(start, end, stops)?;
+        self.ctx().set_stroke_style(&gradient);
+        self.set_stroke_style(size, style)
+    }
+
+    fn set_stroke_radial_gradient(
+        &mut self,
+        center: &Coord,
+        radius: f32,
+        stops: &[GradientStop],
+        size: f64,
+        style: &StrokeStyle,
+    ) -> Result<(), String> {
+        let gradient = self.radial_gradient(center, radius, stops)?;
+        self.ctx().set_stroke_style(&gradient);
+        self.set_stroke_style(size, style)
+    }
+
+    fn set_fill_rule(&mut self, fill_rule: FillRule) -> Result<(), String> {
+        self.fill_rule = match fill_rule {
+            FillRule::NonZero => CanvasWindingRule::Nonzero,
+            FillRule::EvenOdd => CanvasWindingRule::Evenodd,
+        };
         Ok(())
     }
 
     fn start_shape(&mut self, start_point: &Coord) -> Result<(), String> {
-        self.context.begin_path();
-        self.context
+        self.ctx().begin_path();
+        self.ctx()
+            .move_to(start_point.x as f64, start_point.y as f64);
+        Ok(())
+    }
+
+    fn start_subpath(&mut self, start_point: &Coord) -> Result<(), String> {
+        self.ctx()
             .move_to(start_point.x as f64, start_point.y as f64);
         Ok(())
     }
 
     fn move_curve(&mut self, cp0: &Coord, cp1: &Coord, p1: &Coord) -> Result<(), String> {
-        self.context.bezier_curve_to(
+        self.ctx().bezier_curve_to(
             cp0.x as f64,
             cp0.y as f64,
             cp1.x as f64,
@@ -92,18 +279,50 @@ impl<'a> DrawingContext for CanvasContext2DRender<'a> {
     }
 
     fn move_line(&mut self, p: &Coord) -> Result<(), String> {
-        self.context.line_to(p.x as f64, p.y as f64);
+        self.ctx().line_to(p.x as f64, p.y as f64);
+        Ok(())
+    }
+
+    fn close_subpath(&mut self) -> Result<(), String> {
+        self.ctx().close_path();
         Ok(())
     }
 
     fn close_shape(&mut self) -> Result<(), String> {
-        self.context.close_path();
-        self.context
-            .fill_with_canvas_winding_rule(CanvasWindingRule::Evenodd);
-        self.context.stroke();
+        self.ctx().close_path();
+        self.draw_path();
+        Ok(())
+    }
+
+    fn end_shape(&mut self) -> Result<(), String> {
+        self.draw_path();
         Ok(())
     }
 
+    fn fill_text(&mut self, text: &str, position: &Coord) -> Result<(), String> {
+        self.ctx()
+            .fill_text(text, position.x as f64, position.y as f64)
+            .map_err(|e| format!("{:?}", e))
+    }
+
+    fn begin_group(&mut self, opacity: f32) -> Result<(), String> {
+        let canvas = self.ctx().canvas().ok_or("No canvas")?;
+        let (group, ctx) =
+            offscreen_canvas(canvas.width(), canvas.height()).map_err(|e| format!("{:?}", e))?;
+        self.groups.push((group, ctx, opacity));
+        Ok(())
+    }
+
+    fn end_group(&mut self) -> Result<(), String> {
+        let (group, _, opacity) = self.groups.pop().ok_or("No group started")?;
+        let ctx = self.ctx();
+        ctx.save();
+        ctx.set_global_alpha(opacity as f64);
+        let drawn = ctx.draw_image_with_html_canvas_element(&group, 0.0, 0.0);
+        ctx.restore();
+        drawn.map_err(|e| format!("{:?}", e))
+    }
+
     fn end(&mut self) -> Result<(), String> {
         Ok(())
     }
@@ -5,18 +5,21 @@ mod rendering;
 
 use common::{
     dbg_str,
-    types::{Coord, ScreenRect},
+    types::{Coord, ScreenCoord, ScreenRect},
     Rgba,
 };
-use database::{SceneUserContext, SelectedLevel, UserSelection};
+use database::{SceneUserContext, SelectedLevel, SelectionModifier, SnapSettings, UserSelection};
 use log::{info, warn};
 use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 use wasm_bindgen::prelude::wasm_bindgen;
 
+use rendering::SceneCache;
+
 #[wasm_bindgen]
 pub struct SceneClient {
     scene_context: SceneUserContext,
+    scene_cache: SceneCache,
 }
 
 #[macro_export]
@@ -37,8 +40,7 @@ macro_rules! generate_child_methods {
 impl SceneClient {
     #[wasm_bindgen(constructor)]
     pub fn new(width: f32, height: f32) -> SceneClient {
-        let scene_context = SceneUserContext::new(width, height);
-        Self { scene_context }
+        Self::from_context(SceneUserContext::new(width, height))
     }
 
     pub fn get_render_rect(&self) -> ScreenRect {
@@ -48,8 +50,7 @@ impl SceneClient {
     }
 
     pub fn default_call() -> SceneClient {
-        let scene_context = SceneUserContext::default();
-        Self { scene_context }
+        Self::from_context(SceneUserContext::default())
     }
 
     pub fn debug_string(&self) -> String {
@@ -57,6 +58,15 @@ impl SceneClient {
     }
 }
 
+impl SceneClient {
+    fn from_context(scene_context: SceneUserContext) -> Self {
+        Self {
+            scene_context,
+            scene_cache: SceneCache::default(),
+        }
+    }
+}
+
 #[wasm_bindgen]
 #[derive(Default)]
 pub struct UserSelectionClient {
@@ -152,9 +162,32 @@ impl UserSelectionClient {
             .add_selection(&canvas_context.scene_context, cursor_position)
     }
 
+    pub fn lasso_select(
+        &mut self,
+        canvas_context: &SceneClient,
+        points: Vec<ScreenCoord>,
+        level: SelectedLevelClient,
+        modifier: SelectionModifier,
+    ) {
+        self.selection.lasso_select(
+            &canvas_context.scene_context,
+            &points,
+            level.into(),
+            modifier,
+        )
+    }
+
     pub fn set_mouse_position(&mut self, position: Option<Coord>) {
         self.selection.mouse_position = position
     }
+
+    pub fn get_snap_settings(&self) -> SnapSettings {
+        self.selection.snap.clone()
+    }
+
+    pub fn set_snap_settings(&mut self, settings: SnapSettings) {
+        self.selection.snap = settings
+    }
 }
 //------------------------------------------------------------------------------
 // Utilities
@@ -1,34 +1,39 @@
 use common::pures::{Affine, Vec2};
-use common::types::{Length2d, ScreenLength2d, ScreenRect};
-use database::RenderOption;
+use common::types::{Length2d, Rect, ScreenLength2d, ScreenRect};
+use database::{RenderOption, RenderStats, SceneUserContext};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsValue;
-use web_sys::CanvasRenderingContext2d;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
 
 use crate::{canvas_context_2d_render::CanvasContext2DRender, SceneClient, UserSelectionClient};
 
 #[wasm_bindgen]
 impl SceneClient {
+    /// Draw the scene in the view of the camera with the selection over it.
+    /// The scene is kept in a canvas of its own, only the regions changed by the commands are
+    /// drawn again in it. Return the count of the shapes drawn again and skipped outside of
+    /// the regions
     pub fn render_main(
-        &self,
+        &mut self,
         user_selection: &UserSelectionClient,
         ctx: &CanvasRenderingContext2d,
-    ) -> Result<(), JsValue> {
+    ) -> Result<RenderStats, JsValue> {
         let transform = self.scene_context.camera.get_transform();
         let pixel_region = self.scene_context.camera.get_pixel_region();
-        let mut render = CanvasContext2DRender::new(ctx, transform, pixel_region);
+        let regions = self.scene_context.command_handler.dirty_regions_take();
 
+        let stats = self.scene_cache.update(&self.scene_context, regions)?;
         ctx.clear_rect(
             pixel_region.top_left.x as f64,
             pixel_region.top_left.y as f64,
             pixel_region.width() as f64,
             pixel_region.height() as f64,
         );
+        if let Some((canvas, _)) = &self.scene_cache.canvas {
+            ctx.draw_image_with_html_canvas_element(canvas, 0.0, 0.0)?;
+        }
 
-        self.scene_context
-            .scene_render(&mut render)
-            .map_err(|e| JsValue::from_str(&e))?;
-
+        let mut render = CanvasContext2DRender::new(ctx, transform, pixel_region);
         self.scene_context
             .draw(&user_selection.selection, &mut render)
             .map_err(|e| JsValue::from_str(&e))?;
@@ -36,7 +41,7 @@ impl SceneClient {
             .draw_closest_pt(&user_selection.selection, &mut render)
             .map_err(|e| JsValue::from_str(&e))?;
 
-        Ok(())
+        Ok(stats)
     }
 
     pub fn render_cover(
@@ -58,9 +63,16 @@ impl SceneClient {
             ScreenRect::new(0.0, 0.0, width, height),
         );
 
+        // The cover shows the whole scene, not the view of the camera
+        let option = RenderOption {
+            view: None,
+            ..self.scene_context.render_options.clone()
+        };
         self.scene_context
-            .scene_render(&mut ctx_2d_renderer)
-            .map_err(|e| JsValue::from_str(&e))
+            .scene()
+            .render_with_options(&mut ctx_2d_renderer, option)
+            .map_err(|e| JsValue::from_str(&e))?;
+        Ok(())
     }
 
     pub fn image_layer(&self, layer_id: usize, width: f32, height: f32) -> Result<String, JsValue> {
@@ -115,3 +127,98 @@ fn contain(mut ratio: ScreenLength2d, size: (f32, f32)) -> (f32, f32) {
         (height * ratio.x / ratio.y, height)
     }
 }
+
+/// Drawing of the scene without the selection, from the last render
+#[derive(Default)]
+pub(crate) struct SceneCache {
+    canvas: Option<(HtmlCanvasElement, CanvasRenderingContext2d)>,
+    /// Transform and pixel region of the camera of the drawing
+    view: Option<(Affine, ScreenRect)>,
+}
+
+impl SceneCache {
+    /// Draw again the regions of the scene, or all of it when the regions are None or the
+    /// camera moved since the last update
+    fn update(
+        &mut self,
+        scene_context: &SceneUserContext,
+        regions: Option<Vec<Rect>>,
+    ) -> Result<RenderStats, JsValue> {
+        let camera = &scene_context.camera;
+        let view = (camera.get_transform(), camera.get_pixel_region());
+        let (width, height) = (view.1.width().ceil() as u32, view.1.height().ceil() as u32);
+        let resized = match &self.canvas {
+            Some((canvas, _)) => canvas.width() != width || canvas.height() != height,
+            None => true,
+        };
+        if resized {
+            self.canvas = Some(offscreen_canvas(width, height)?);
+        }
+        let (_, ctx) = self.canvas.as_ref().expect("Created above");
+        let mut render = CanvasContext2DRender::new(ctx, view.0, view.1);
+
+        let regions = match regions {
+            Some(regions) if !resized && self.view == Some(view) => regions,
+            _ => {
+                self.view = Some(view);
+                ctx.clear_rect(0.0, 0.0, width as f64, height as f64);
+                return scene_context
+                    .scene_render(&mut render)
+                    .map_err(|e| JsValue::from_str(&e));
+            }
+        };
+
+        let view_bounds = camera.view_bounds();
+        let mut stats = RenderStats::default();
+        for region in regions
+            .iter()
+            .filter(|region| region.intersect(&view_bounds))
+        {
+            let pixels = camera.unproject_bounds(region);
+            // Whole pixels around the region, the antialiasing of its edges is drawn again
+            let (x, y) = (
+                pixels.top_left.x.floor() - 1.0,
+                pixels.top_left.y.floor() - 1.0,
+            );
+            let (w, h) = (
+                pixels.bottom_right.x.ceil() + 1.0 - x,
+                pixels.bottom_right.y.ceil() + 1.0 - y,
+            );
+            ctx.save();
+            ctx.begin_path();
+            ctx.rect(x as f64, y as f64, w as f64, h as f64);
+            ctx.clip();
+            ctx.clear_rect(x as f64, y as f64, w as f64, h as f64);
+            let options = RenderOption {
+                view: Some(*region),
+                ..scene_context.render_options.clone()
+            };
+            let region_stats = scene_context
+                .scene()
+                .render_with_options(&mut render, options);
+            ctx.restore();
+            let region_stats = region_stats.map_err(|e| JsValue::from_str(&e))?;
+            stats.rendered += region_stats.rendered;
+            stats.culled += region_stats.culled;
+        }
+        Ok(stats)
+    }
+}
+
+/// Canvas out of the document to draw the scene or a group before drawing it on another canvas
+pub(crate) fn offscreen_canvas(
+    width: u32,
+    height: u32,
+) -> Result<(HtmlCanvasElement, CanvasRenderingContext2d), JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or("No document")?;
+    let canvas: HtmlCanvasElement = document.create_element("canvas")?.dyn_into()?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+    let ctx = canvas
+        .get_context("2d")?
+        .ok_or("No 2d context")?
+        .dyn_into::<CanvasRenderingContext2d>()?;
+    Ok((canvas, ctx))
+}